@@ -0,0 +1,214 @@
+#!/usr/bin/env node
+// Minimal ACP agent speaking newline-delimited JSON-RPC 2.0 over stdio.
+// Used to exercise AcpClient/SessionManager by hand without a real agent:
+//
+//   node scripts/mock-acp-agent.mjs
+//
+// Supports initialize, session/new, session/set_mode, session/prompt and the
+// session/cancel notification; session/load too with MOCK_AGENT_LOAD_SESSION=1 and
+// session/fork with MOCK_AGENT_FORK=1. Each prompt streams an echo of the text, then a
+// tool call that asks for permission via session/request_permission, and ends
+// with end_turn (or cancelled). MOCK_AGENT_DELAY_MS slows streaming so cancel
+// can be tried. With MOCK_AGENT_FAIL_ONCE=1 the first prompt streams a partial
+// reply and then fails with an internal error.
+//
+// Prompts that start with a directive call back into the client instead:
+//   read <path>            fs/read_text_file; replies with the content
+//   write <path> <text>    fs/write_text_file
+//   terminal <cmd> [args]  runs a terminal to completion; replies with its output and exit code
+//   spawn <cmd> [args]     terminal/create only; replies with the terminal id
+//   output <terminalId>    terminal/output for an earlier spawn
+
+import { createInterface } from 'node:readline'
+
+const DELAY_MS = Number(process.env.MOCK_AGENT_DELAY_MS || 50)
+const LOAD_SESSION = process.env.MOCK_AGENT_LOAD_SESSION === '1'
+const FORK_SESSION = process.env.MOCK_AGENT_FORK === '1'
+let failNextPrompt = process.env.MOCK_AGENT_FAIL_ONCE === '1'
+
+let nextSessionId = 1
+let nextRequestId = 1
+const pendingClientRequests = new Map()
+const cancelledSessions = new Set()
+
+function send(message) {
+  process.stdout.write(JSON.stringify({ jsonrpc: '2.0', ...message }) + '\n')
+}
+
+function respond(id, result) {
+  send({ id, result })
+}
+
+function respondError(id, code, message) {
+  send({ id, error: { code, message } })
+}
+
+function notify(sessionId, update) {
+  send({ method: 'session/update', params: { sessionId, update } })
+}
+
+function requestClient(method, params) {
+  const id = `mock-${nextRequestId++}`
+  send({ id, method, params })
+  return new Promise((resolve) => pendingClientRequests.set(id, resolve))
+}
+
+const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms))
+
+/** Run a client method named by the prompt; returns the reply text, or null for a plain echo prompt */
+async function runDirective(sessionId, text) {
+  const [directive, target, ...rest] = text.split(' ')
+  let response
+  switch (directive) {
+    case 'read':
+      response = await requestClient('fs/read_text_file', { sessionId, path: target })
+      return response.error ? `Error: ${response.error.message}` : response.result.content
+    case 'write':
+      response = await requestClient('fs/write_text_file', { sessionId, path: target, content: rest.join(' ') })
+      return response.error ? `Error: ${response.error.message}` : `Wrote ${target}`
+    case 'terminal': {
+      response = await requestClient('terminal/create', { sessionId, command: target, args: rest })
+      if (response.error) return `Error: ${response.error.message}`
+      const { terminalId } = response.result
+      const exit = await requestClient('terminal/wait_for_exit', { sessionId, terminalId })
+      const output = await requestClient('terminal/output', { sessionId, terminalId })
+      await requestClient('terminal/release', { sessionId, terminalId })
+      return `${output.result.output.trim()} (exit ${exit.result.exitCode})`
+    }
+    case 'spawn':
+      response = await requestClient('terminal/create', { sessionId, command: target, args: rest })
+      return response.error ? `Error: ${response.error.message}` : response.result.terminalId
+    case 'output':
+      response = await requestClient('terminal/output', { sessionId, terminalId: target })
+      return response.error ? `Error: ${response.error.message}` : response.result.output
+    default:
+      return null
+  }
+}
+
+async function handlePrompt(id, params) {
+  const { sessionId, prompt = [] } = params
+  cancelledSessions.delete(sessionId)
+  const text = prompt
+    .filter((block) => block.type === 'text')
+    .map((block) => block.text)
+    .join('\n')
+
+  if (failNextPrompt) {
+    failNextPrompt = false
+    notify(sessionId, { sessionUpdate: 'agent_message_chunk', content: { type: 'text', text: 'Partial reply' } })
+    await sleep(DELAY_MS)
+    return respondError(id, -32603, 'Simulated failure')
+  }
+
+  const reply = await runDirective(sessionId, text)
+  if (reply !== null) {
+    notify(sessionId, { sessionUpdate: 'agent_message_chunk', content: { type: 'text', text: reply } })
+    return respond(id, { stopReason: 'end_turn' })
+  }
+
+  for (const word of `Echo: ${text}`.split(/(?<=\s)/)) {
+    if (cancelledSessions.has(sessionId)) return respond(id, { stopReason: 'cancelled' })
+    notify(sessionId, { sessionUpdate: 'agent_message_chunk', content: { type: 'text', text: word } })
+    await sleep(DELAY_MS)
+  }
+
+  const toolCallId = `tool-${id}`
+  const toolCall = { toolCallId, title: 'Write mock.txt', kind: 'edit', locations: [{ path: 'mock.txt', line: 1 }] }
+  notify(sessionId, { sessionUpdate: 'tool_call', status: 'pending', ...toolCall })
+
+  // The full JSON-RPC response is echoed back as rawOutput so tests can check its framing
+  const response = await requestClient('session/request_permission', {
+    sessionId,
+    toolCall,
+    options: [
+      { optionId: 'allow', name: 'Allow once', kind: 'allow_once' },
+      { optionId: 'reject', name: 'Reject', kind: 'reject_once' }
+    ]
+  })
+  const permission = response?.result
+  if (cancelledSessions.has(sessionId) || permission?.outcome?.outcome === 'cancelled') {
+    notify(sessionId, { sessionUpdate: 'tool_call_update', toolCallId, status: 'failed', rawOutput: response })
+    return respond(id, { stopReason: 'cancelled' })
+  }
+
+  const allowed = permission?.outcome?.optionId === 'allow'
+  notify(sessionId, {
+    sessionUpdate: 'tool_call_update',
+    toolCallId,
+    status: allowed ? 'completed' : 'failed',
+    rawOutput: response,
+    content: [{ type: 'content', content: { type: 'text', text: allowed ? 'Wrote mock.txt' : 'Permission denied' } }]
+  })
+  respond(id, { stopReason: 'end_turn' })
+}
+
+function handleRequest({ id, method, params = {} }) {
+  switch (method) {
+    case 'initialize':
+      return respond(id, {
+        protocolVersion: params.protocolVersion ?? 1,
+        agentInfo: { name: 'mock-agent', title: 'Mock Agent', version: '0.0.0' },
+        agentCapabilities: {
+          loadSession: LOAD_SESSION,
+          promptCapabilities: { image: false, audio: false, embeddedContext: true },
+          ...(FORK_SESSION ? { sessionCapabilities: { fork: {} } } : {})
+        },
+        authMethods: []
+      })
+    case 'session/new':
+      return respond(id, {
+        sessionId: `mock-session-${nextSessionId++}`,
+        modes: {
+          currentModeId: 'default',
+          availableModes: [
+            { id: 'default', name: 'Default' },
+            { id: 'plan', name: 'Plan', description: 'Describe changes without making them' }
+          ]
+        }
+      })
+    case 'session/load':
+      if (!LOAD_SESSION) return respondError(id, -32601, `Method not found: ${method}`)
+      // Stand-in history: the mock keeps no transcripts
+      notify(params.sessionId, { sessionUpdate: 'user_message_chunk', content: { type: 'text', text: 'earlier prompt' } })
+      notify(params.sessionId, { sessionUpdate: 'agent_message_chunk', content: { type: 'text', text: `Loaded ${params.sessionId}` } })
+      return respond(id, {})
+    case 'session/fork':
+      if (!FORK_SESSION) return respondError(id, -32601, `Method not found: ${method}`)
+      return respond(id, { sessionId: `mock-session-${nextSessionId++}` })
+    case 'session/set_mode':
+      notify(params.sessionId, { sessionUpdate: 'current_mode_update', currentModeId: params.modeId })
+      return respond(id, {})
+    case 'session/prompt':
+      return void handlePrompt(id, params).catch((err) => respondError(id, -32603, String(err)))
+    default:
+      return respondError(id, -32601, `Method not found: ${method}`)
+  }
+}
+
+const input = createInterface({ input: process.stdin })
+input.on('line', (line) => {
+  if (!line.trim()) return
+  let message
+  try {
+    message = JSON.parse(line)
+  } catch {
+    return respondError(null, -32700, 'Parse error')
+  }
+
+  // Response to one of our requests (permission)
+  if (message.id !== undefined && !message.method) {
+    const resolve = pendingClientRequests.get(message.id)
+    pendingClientRequests.delete(message.id)
+    resolve?.(message)
+    return
+  }
+
+  if (message.method === 'session/cancel') {
+    cancelledSessions.add(message.params?.sessionId)
+    return
+  }
+
+  if (message.id !== undefined) handleRequest(message)
+})
+input.on('close', () => process.exit(0))
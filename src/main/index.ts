@@ -1,4 +1,6 @@
-import { app, BrowserWindow } from 'electron'
+import { app, BrowserWindow, dialog } from 'electron'
+// Must be evaluated before any service creates its electron-store
+import { getDataDirStatus } from './util/data-dir'
 import { createMainWindow } from './window'
 import { registerAllIpcHandlers } from './ipc'
 import { agentManager } from './services/agent-manager'
@@ -7,8 +9,12 @@ import { terminalService } from './services/terminal-service'
 import { threadStore } from './services/thread-store'
 import { folderThreadStore } from './services/folder-thread-store'
 import { workspaceService } from './services/workspace-service'
+import { settingsService } from './services/settings-service'
+import { downloadService } from './services/download-service'
 import { startInternalApi, stopInternalApi } from './mcp/internal-api'
-import { logger } from './util/logger'
+import { initLogFile, logger, setLogLevel } from './util/logger'
+import { getLogsDir } from './util/paths'
+import { acquireDataDirLock, releaseDataDirLock } from './util/instance-lock'
 
 // Prevent multiple instances
 const gotTheLock = app.requestSingleInstanceLock()
@@ -25,8 +31,25 @@ if (!gotTheLock) {
 }
 
 app.whenReady().then(() => {
+  initLogFile(getLogsDir())
+  const { logLevel } = settingsService.get().general
+  if (logLevel) setLogLevel(logLevel)
   logger.info('AgentManager starting...')
 
+  // Guard the data directory against another instance (e.g. a different build
+  // sharing userData) that the single-instance lock above doesn't cover.
+  const lockOwnerPid = acquireDataDirLock()
+  if (lockOwnerPid !== null) {
+    logger.error(`Data directory is in use by another process (pid ${lockOwnerPid})`)
+    dialog.showErrorBox(
+      'AgentManager is already running',
+      `Another AgentManager process (pid ${lockOwnerPid}) is using the data directory.\n` +
+        'Close it before starting a new instance to avoid corrupting settings and threads.'
+    )
+    app.quit()
+    return
+  }
+
   // Set app user model id for Windows
   if (process.platform === 'win32') {
     app.setAppUserModelId('com.agentmanager')
@@ -49,18 +72,52 @@ app.whenReady().then(() => {
     threadStore.setMigrationComplete()
   }
 
-  // Rebuild thread cache from .agent/ folders across all workspaces
-  const workspaces = workspaceService.list().map((w) => ({ path: w.path, id: w.id }))
-  threadStore.rebuildCacheFromFolders(workspaces)
+  // Clear out leftovers from interrupted agent downloads
+  if (settingsService.get().general.autoPruneDownloads) {
+    try {
+      downloadService.pruneDownloads()
+    } catch (err) {
+      logger.warn('Failed to prune downloads directory:', err)
+    }
+  }
 
   // Create the main window
   const mainWindow = createMainWindow()
 
+  const dataDir = getDataDirStatus()
+  if (dataDir.isFallback) {
+    void dialog.showMessageBox(mainWindow, {
+      type: 'warning',
+      title: 'Using a temporary data directory',
+      message: 'The data directory is not writable, so AgentManager is storing its data in a temporary directory.',
+      detail: `${dataDir.error}\n\nData is stored in ${dataDir.path} and may be lost when the system cleans temporary files.`
+    })
+  }
+
   // Pass window reference to services
   agentManager.setMainWindow(mainWindow)
   sessionManager.setMainWindow(mainWindow)
   terminalService.setMainWindow(mainWindow)
 
+  // Rebuild thread cache from .agent/ folders once the window is up, so startup isn't blocked
+  mainWindow.webContents.once('did-finish-load', () => {
+    const workspaces = workspaceService.list().map((w) => ({ path: w.path, id: w.id }))
+    threadStore
+      .rebuildCacheFromFolders(
+        workspaces,
+        (event) => {
+          if (event.phase === 'done') {
+            sessionManager.syncWithStore()
+          }
+          if (!mainWindow.isDestroyed()) {
+            mainWindow.webContents.send('thread-cache:progress', event)
+          }
+        },
+        { incremental: true }
+      )
+      .catch((err) => logger.error('Failed to rebuild thread cache:', err))
+  })
+
   app.on('activate', () => {
     if (BrowserWindow.getAllWindows().length === 0) {
       const window = createMainWindow()
@@ -89,3 +146,7 @@ app.on('before-quit', () => {
   terminalService.killAll()
   stopInternalApi()
 })
+
+app.on('will-quit', () => {
+  releaseDataDirLock()
+})
@@ -1,52 +1,117 @@
-import { ipcMain } from 'electron'
+import { handle } from './handle'
 import { registryService } from '../services/registry-service'
 import { agentManager } from '../services/agent-manager'
+import type { InstalledAgent, RegistrySearchFilters } from '@shared/types/agent'
 
 export function registerAgentHandlers(): void {
   // --- Registry ---
-  ipcMain.handle('registry:fetch', async () => {
-    return registryService.fetch()
+  handle('registry:fetch', async (_event, options?: { force?: boolean }) => {
+    return registryService.fetch(options?.force)
   })
 
-  ipcMain.handle('registry:get-cached', () => {
+  handle(
+    'registry:search',
+    (_event, { query, filters }: { query: string; filters?: RegistrySearchFilters }) => {
+      return registryService.search(query, filters)
+    }
+  )
+
+  handle('registry:clear-icon-cache', () => {
+    registryService.clearIconCache()
+  })
+
+  handle('registry:refresh', async () => {
+    return registryService.fetch(true)
+  })
+
+  handle('registry:set-sources', async (_event, { sources }: { sources: string[] }) => {
+    return registryService.setSources(sources)
+  })
+
+  handle('registry:get-sources', () => {
+    return registryService.getSources()
+  })
+
+  handle('registry:get-cached', () => {
     return registryService.getCached()
   })
 
-  ipcMain.handle('registry:get-icon-svg', async (_event, { agentId, icon }: { agentId: string; icon?: string }) => {
+  handle('registry:get-icon-svg', async (_event, { agentId, icon }: { agentId: string; icon?: string }) => {
     return registryService.fetchRegistryIconSvg(agentId, icon)
   })
 
+  handle('registry:list-extensions', async () => {
+    return registryService.listExtensions()
+  })
+
+  handle('registry:get-agent', async (_event, { agentId }: { agentId: string }) => {
+    return registryService.getAgent(agentId)
+  })
+
+  handle('registry:agent-installable', async (_event, { agentId }: { agentId: string }) => {
+    return registryService.checkInstallable(agentId)
+  })
+
   // --- Agent Management ---
-  ipcMain.handle('agent:install', async (_event, { agentId }: { agentId: string }) => {
+  handle('agent:install', async (_event, { agentId }: { agentId: string }) => {
     return agentManager.install(agentId)
   })
 
-  ipcMain.handle('agent:uninstall', async (_event, { agentId }: { agentId: string }) => {
+  handle(
+    'agent:install-extension',
+    async (_event, { agentId, extensionId }: { agentId: string; extensionId: string }) => {
+      return agentManager.installExtension(agentId, extensionId)
+    }
+  )
+
+  handle(
+    'agent:uninstall-extension',
+    (_event, { agentId, extensionId }: { agentId: string; extensionId: string }) => {
+      return agentManager.uninstallExtension(agentId, extensionId)
+    }
+  )
+
+  handle('agent:uninstall', async (_event, { agentId }: { agentId: string }) => {
     agentManager.uninstall(agentId)
     return { success: true }
   })
 
-  ipcMain.handle('agent:list-installed', () => {
+  handle(
+    'agent:switch-distribution',
+    async (_event, { agentId, distributionType }: { agentId: string; distributionType: InstalledAgent['distributionType'] }) => {
+      return agentManager.switchDistribution(agentId, distributionType)
+    }
+  )
+
+  handle('agent:list-installed', () => {
     return agentManager.listInstalled()
   })
 
-  ipcMain.handle(
+  handle('agent:check-updates', () => {
+    return agentManager.checkUpdates()
+  })
+
+  handle('agent:update', async (_event, { agentId }: { agentId: string }) => {
+    return agentManager.update(agentId)
+  })
+
+  handle(
     'agent:launch',
     async (_event, { agentId, projectPath, extraEnv }: { agentId: string; projectPath: string; extraEnv?: Record<string, string> }) => {
       return agentManager.launch(agentId, projectPath, extraEnv)
     }
   )
 
-  ipcMain.handle('agent:check-auth', async (_event, { agentId, projectPath }: { agentId: string; projectPath?: string }) => {
+  handle('agent:check-auth', async (_event, { agentId, projectPath }: { agentId: string; projectPath?: string }) => {
     return agentManager.checkAuthentication(agentId, projectPath)
   })
 
-  ipcMain.handle('agent:terminate', async (_event, { connectionId }: { connectionId: string }) => {
+  handle('agent:terminate', async (_event, { connectionId }: { connectionId: string }) => {
     agentManager.terminate(connectionId)
     return { success: true }
   })
 
-  ipcMain.handle(
+  handle(
     'agent:authenticate',
     async (
       _event,
@@ -60,22 +125,30 @@ export function registerAgentHandlers(): void {
     }
   )
 
-  ipcMain.handle('agent:logout', async (_event, { connectionId }: { connectionId: string }) => {
+  handle('agent:logout', async (_event, { connectionId }: { connectionId: string }) => {
     await agentManager.logout(connectionId)
   })
 
-  ipcMain.handle('agent:list-connections', () => {
+  handle('agent:list-connections', () => {
     return agentManager.listConnections()
   })
 
-  ipcMain.handle(
+  handle('agent:get-process-info', (_event, { connectionId }: { connectionId: string }) => {
+    return agentManager.getProcessInfo(connectionId)
+  })
+
+  handle('agent:get-resource-usage', (_event, { connectionId }: { connectionId: string }) => {
+    return agentManager.getResourceUsage(connectionId)
+  })
+
+  handle(
     'agent:get-models',
     async (_event, { agentId, projectPath, forceRefresh }: { agentId: string; projectPath: string; forceRefresh?: boolean }) => {
       return agentManager.getModels(agentId, projectPath, forceRefresh)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'agent:get-modes',
     async (_event, { agentId, projectPath }: { agentId: string; projectPath: string }) => {
       return agentManager.getModes(agentId, projectPath)
@@ -83,7 +156,7 @@ export function registerAgentHandlers(): void {
   )
 
   // --- CLI Detection ---
-  ipcMain.handle('agent:detect-cli', async (_event, { commands }: { commands: string[] }) => {
+  handle('agent:detect-cli', async (_event, { commands }: { commands: string[] }) => {
     return agentManager.detectCliCommands(commands)
   })
 }
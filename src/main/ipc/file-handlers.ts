@@ -1,8 +1,11 @@
 import path from 'path'
-import { ipcMain, dialog, BrowserWindow } from 'electron'
+import { dialog, BrowserWindow } from 'electron'
+import { handle } from './handle'
 import { fileService } from '../services/file-service'
 import { gitService } from '../services/git-service'
 import { workspaceService } from '../services/workspace-service'
+import { searchFiles } from '../util/text-search'
+import type { FileSearchDoneEvent, FileSearchOptions } from '@shared/types/project'
 
 /**
  * Check if a file path is within any allowed directory (workspace paths + worktree paths).
@@ -22,24 +25,87 @@ function isPathAllowed(filePath: string): boolean {
 }
 
 export function registerFileHandlers(): void {
-  ipcMain.handle(
+  handle(
     'file:read-tree',
-    async (_event, { dirPath, depth }: { dirPath: string; depth?: number }) => {
+    async (
+      _event,
+      {
+        dirPath,
+        depth,
+        lazy,
+        includeMetadata
+      }: { dirPath: string; depth?: number; lazy?: boolean; includeMetadata?: boolean }
+    ) => {
       if (!isPathAllowed(dirPath)) {
         throw new Error('Access denied: path is outside allowed directories')
       }
-      return fileService.readTree(dirPath, depth)
+      return fileService.readTree(dirPath, depth, lazy, includeMetadata)
     }
   )
 
-  ipcMain.handle('file:read', async (_event, { filePath }: { filePath: string }) => {
+  handle(
+    'file:read-dir',
+    async (_event, { dirPath, includeMetadata }: { dirPath: string; includeMetadata?: boolean }) => {
+      if (!isPathAllowed(dirPath)) {
+        throw new Error('Access denied: path is outside allowed directories')
+      }
+      return fileService.readDir(dirPath, includeMetadata)
+    }
+  )
+
+  handle('file:read', async (_event, { filePath }: { filePath: string }) => {
     if (!isPathAllowed(filePath)) {
       throw new Error('Access denied: path is outside allowed directories')
     }
     return fileService.readFile(filePath)
   })
 
-  ipcMain.handle(
+  handle(
+    'file:write',
+    async (
+      _event,
+      { filePath, content, createDirs, root }: { filePath: string; content: string; createDirs?: boolean; root?: string }
+    ) => {
+      const target = root ? path.resolve(root, filePath) : filePath
+      if (!isPathAllowed(target)) {
+        throw new Error('Access denied: path is outside allowed directories')
+      }
+      fileService.writeFile(filePath, content, createDirs, root)
+    }
+  )
+
+  // Matches stream as file:search-result events; the final summary is both returned and sent as file:search-done
+  handle(
+    'file:search',
+    async (
+      event,
+      { searchId, root, query, options }: { searchId: string; root: string; query: string; options?: FileSearchOptions }
+    ) => {
+      if (!isPathAllowed(root)) {
+        throw new Error('Access denied: path is outside allowed directories')
+      }
+      const sender = event.sender
+      let done: FileSearchDoneEvent
+      try {
+        const result = await searchFiles(
+          root,
+          query,
+          options ?? {},
+          (matches) => {
+            if (!sender.isDestroyed()) sender.send('file:search-result', { searchId, matches })
+          },
+          () => sender.isDestroyed()
+        )
+        done = { searchId, ...result }
+      } catch (error) {
+        done = { searchId, matchCount: 0, truncated: false, error: (error as Error).message }
+      }
+      if (!sender.isDestroyed()) sender.send('file:search-done', done)
+      return done
+    }
+  )
+
+  handle(
     'file:get-changes',
     async (_event, { workingDir }: { workingDir: string }) => {
       if (!isPathAllowed(workingDir)) {
@@ -49,7 +115,7 @@ export function registerFileHandlers(): void {
     }
   )
 
-  ipcMain.handle('project:open', async (_event, { path: projectPath }: { path: string }) => {
+  handle('project:open', async (_event, { path: projectPath }: { path: string }) => {
     const isGitRepo = await gitService.isGitRepo(projectPath)
     const gitBranch = isGitRepo ? await gitService.getBranch(projectPath) : undefined
     const name = projectPath.split(/[/\\]/).pop() || projectPath
@@ -62,7 +128,7 @@ export function registerFileHandlers(): void {
     }
   })
 
-  ipcMain.handle('project:select-directory', async (event) => {
+  handle('project:select-directory', async (event) => {
     const window = BrowserWindow.fromWebContents(event.sender)
     if (!window) return null
 
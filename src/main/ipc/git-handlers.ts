@@ -1,15 +1,18 @@
-import { ipcMain } from 'electron'
+import { handle } from './handle'
 import { gitService } from '../services/git-service'
+import { sessionManager } from '../services/session-manager'
+import { threadStore } from '../services/thread-store'
+import type { MergeStrategy } from '@shared/types/git'
 
 export function registerGitHandlers(): void {
-  ipcMain.handle(
+  handle(
     'git:status',
     async (_event, { projectPath }: { projectPath: string }) => {
       return gitService.getStatus(projectPath)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'git:create-worktree',
     async (
       _event,
@@ -19,7 +22,7 @@ export function registerGitHandlers(): void {
     }
   )
 
-  ipcMain.handle(
+  handle(
     'git:remove-worktree',
     async (
       _event,
@@ -29,38 +32,158 @@ export function registerGitHandlers(): void {
     }
   )
 
-  ipcMain.handle(
+  handle(
     'git:list-worktrees',
     async (_event, { projectPath }: { projectPath: string }) => {
       return gitService.listWorktrees(projectPath)
     }
   )
 
-  ipcMain.handle(
+  handle('git:prune-worktrees', async (_event, { projectPath }: { projectPath: string }) => {
+    // Persisted threads count too: their worktrees are reused when they reconnect
+    const keepPaths = [...sessionManager.listSessions(), ...threadStore.loadAll()]
+      .map((thread) => thread.worktreePath)
+      .filter((p): p is string => !!p)
+    return gitService.pruneOrphanWorktrees(projectPath, keepPaths)
+  })
+
+  handle(
     'git:list-branches',
     async (_event, { projectPath }: { projectPath: string }) => {
       return gitService.listBranches(projectPath)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'git:commit',
     async (
       _event,
-      { worktreePath, message, files }: { worktreePath: string; message: string; files: string[] }
+      {
+        worktreePath,
+        message,
+        files,
+        coAuthors
+      }: { worktreePath: string; message: string; files: string[]; coAuthors?: string[] }
+    ) => {
+      return gitService.commit(worktreePath, message, files, coAuthors)
+    }
+  )
+
+  handle(
+    'git:amend',
+    async (
+      _event,
+      { worktreePath, message, files, force }: { worktreePath: string; message?: string; files?: string[]; force?: boolean }
+    ) => {
+      return gitService.amend(worktreePath, message, files ?? [], force)
+    }
+  )
+
+  handle('git:suggest-commit', async (_event, { worktreePath }: { worktreePath: string }) => {
+    return gitService.suggestCommitMessage(worktreePath)
+  })
+
+  handle(
+    'git:log',
+    async (_event, { workingDir, limit, skip }: { workingDir: string; limit?: number; skip?: number }) => {
+      return gitService.log(workingDir, limit, skip)
+    }
+  )
+
+  handle(
+    'git:stash-save',
+    async (
+      _event,
+      { workingDir, message, includeUntracked }: { workingDir: string; message?: string; includeUntracked?: boolean }
+    ) => {
+      return gitService.stashSave(workingDir, message, includeUntracked)
+    }
+  )
+
+  handle('git:stash-pop', async (_event, { workingDir, index }: { workingDir: string; index?: number }) => {
+    return gitService.stashPop(workingDir, index)
+  })
+
+  handle('git:stash-list', async (_event, { workingDir }: { workingDir: string }) => {
+    return gitService.stashList(workingDir)
+  })
+
+  handle('git:stage', async (_event, { workingDir, files }: { workingDir: string; files: string[] }) => {
+    return gitService.stage(workingDir, files)
+  })
+
+  handle('git:unstage', async (_event, { workingDir, files }: { workingDir: string; files: string[] }) => {
+    return gitService.unstage(workingDir, files)
+  })
+
+  handle(
+    'git:discard',
+    async (
+      _event,
+      { workingDir, files, recursive }: { workingDir: string; files: string[]; recursive?: boolean }
     ) => {
-      return gitService.commit(worktreePath, message, files)
+      return gitService.discard(workingDir, files, recursive)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'git:diff',
-    async (_event, { worktreePath, filePath }: { worktreePath: string; filePath?: string }) => {
-      return gitService.getDiff(worktreePath, filePath)
+    async (
+      _event,
+      { worktreePath, filePath, includeContent }: { worktreePath: string; filePath?: string; includeContent?: boolean }
+    ) => {
+      return gitService.getDiff(worktreePath, filePath, includeContent)
+    }
+  )
+
+  handle('git:fetch', async (_event, { workingDir, remote }: { workingDir: string; remote?: string }) => {
+    return gitService.fetch(workingDir, remote)
+  })
+
+  handle(
+    'git:pull',
+    async (_event, { workingDir, remote, rebase }: { workingDir: string; remote?: string; rebase?: boolean }) => {
+      return gitService.pull(workingDir, remote, rebase)
+    }
+  )
+
+  handle(
+    'git:merge-worktree',
+    async (
+      _event,
+      { projectPath, branch, into, strategy }: { projectPath: string; branch: string; into: string; strategy?: MergeStrategy }
+    ) => {
+      return gitService.mergeWorktree(projectPath, branch, into, strategy)
+    }
+  )
+
+  handle(
+    'git:push',
+    async (
+      _event,
+      { workingDir, remote, setUpstream }: { workingDir: string; remote?: string; setUpstream?: boolean }
+    ) => {
+      return gitService.push(workingDir, remote, setUpstream)
+    }
+  )
+
+  handle(
+    'git:create-branch',
+    async (
+      _event,
+      {
+        workingDir,
+        name,
+        base,
+        checkout,
+        force
+      }: { workingDir: string; name: string; base?: string; checkout?: boolean; force?: boolean }
+    ) => {
+      return gitService.createBranch(workingDir, name, base, checkout, force)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'git:rename-branch',
     async (_event, { worktreePath, newBranch }: { worktreePath: string; newBranch: string }) => {
       return gitService.renameBranch(worktreePath, newBranch)
@@ -0,0 +1,20 @@
+import { ipcMain } from 'electron'
+import type { IpcMainInvokeEvent } from 'electron'
+import { toIpcError } from '../util/app-error'
+import { logger } from '../util/logger'
+
+/**
+ * Register an invoke handler whose failures reach the renderer as structured
+ * errors. The original error is logged first, since only the encoded form
+ * (code and message, no stack) crosses the IPC boundary.
+ */
+export function handle(channel: string, listener: (event: IpcMainInvokeEvent, ...args: any[]) => unknown): void {
+  ipcMain.handle(channel, async (event, ...args) => {
+    try {
+      return await listener(event, ...args)
+    } catch (error) {
+      logger.error(`IPC handler for ${channel} failed:`, error)
+      throw toIpcError(error)
+    }
+  })
+}
@@ -1,87 +1,194 @@
-import { ipcMain } from 'electron'
+import { handle } from './handle'
 import { sessionManager } from '../services/session-manager'
 import { threadStore } from '../services/thread-store'
 import { gitService } from '../services/git-service'
 import { workspaceService } from '../services/workspace-service'
 import type { CreateSessionRequest, PermissionResponse, InteractionMode, ContentBlock } from '@shared/types/session'
+import type { MergeStrategy } from '@shared/types/git'
+import { AppError } from '../util/app-error'
 
 export function registerSessionHandlers(): void {
-  ipcMain.handle('session:create', async (_event, request: CreateSessionRequest) => {
+  handle('session:create', async (_event, request: CreateSessionRequest) => {
     return sessionManager.createSession(request)
   })
 
-  ipcMain.handle(
+  handle(
     'session:prompt',
-    async (_event, { sessionId, content, mode }: { sessionId: string; content: ContentBlock[]; mode?: InteractionMode }) => {
-      return sessionManager.prompt(sessionId, content, mode)
+    async (
+      _event,
+      {
+        sessionId,
+        content,
+        mode,
+        attachments
+      }: { sessionId: string; content: ContentBlock[]; mode?: InteractionMode; attachments?: string[] }
+    ) => {
+      return sessionManager.prompt(sessionId, content, mode, attachments)
     }
   )
 
-  ipcMain.handle('session:cancel', async (_event, { sessionId }: { sessionId: string }) => {
+  handle('session:retry', async (_event, { sessionId }: { sessionId: string }) => {
+    return sessionManager.retryLast(sessionId)
+  })
+
+  handle('session:get-commands', (_event, { sessionId }: { sessionId: string }) => {
+    return sessionManager.getCommands(sessionId)
+  })
+
+  handle(
+    'session:run-command',
+    async (_event, { sessionId, name, args }: { sessionId: string; name: string; args?: string }) => {
+      return sessionManager.runCommand(sessionId, name, args)
+    }
+  )
+
+  handle('session:cancel', async (_event, { sessionId }: { sessionId: string }) => {
     await sessionManager.cancel(sessionId)
   })
 
-  ipcMain.handle('session:list', () => {
+  handle('session:cancel-all', async () => {
+    return sessionManager.cancelAll()
+  })
+
+  handle('session:list-active-prompts', () => {
+    return sessionManager.listActivePrompts()
+  })
+
+  handle('session:get-usage', (_event, { sessionId }: { sessionId: string }) => {
+    return sessionManager.getUsage(sessionId)
+  })
+
+  handle('session:clear-queue', (_event, { sessionId }: { sessionId: string }) => {
+    return sessionManager.clearQueue(sessionId)
+  })
+
+  handle(
+    'session:set-system-prompt',
+    (_event, { sessionId, systemPrompt }: { sessionId: string; systemPrompt: string | null }) => {
+      sessionManager.setSystemPrompt(sessionId, systemPrompt)
+    }
+  )
+
+  handle(
+    'session:edit-message',
+    (_event, { sessionId, messageId, content }: { sessionId: string; messageId: string; content: ContentBlock[] }) => {
+      return sessionManager.editMessage(sessionId, messageId, content)
+    }
+  )
+
+  handle(
+    'session:delete-message',
+    (_event, { sessionId, messageId, rewind }: { sessionId: string; messageId: string; rewind?: boolean }) => {
+      return sessionManager.deleteMessage(sessionId, messageId, rewind)
+    }
+  )
+
+  handle('session:list', () => {
     return sessionManager.listSessions()
   })
 
-  ipcMain.handle('session:list-persisted', () => {
+  handle('session:get', (_event, { sessionId }: { sessionId: string }) => {
+    return sessionManager.getSession(sessionId) ?? null
+  })
+
+  handle('session:sync', () => {
+    return sessionManager.syncWithStore()
+  })
+
+  handle('session:get-metadata', (_event, { sessionId }: { sessionId: string }) => {
+    return sessionManager.getMetadata(sessionId)
+  })
+
+  handle(
+    'session:set-metadata',
+    (_event, { sessionId, metadata }: { sessionId: string; metadata: Record<string, unknown> }) => {
+      return sessionManager.setMetadata(sessionId, metadata)
+    }
+  )
+
+  handle('session:list-persisted', () => {
     return threadStore.loadAll()
   })
 
-  ipcMain.handle(
+  handle(
     'session:remove',
-    async (_event, { sessionId, cleanupWorktree }: { sessionId: string; cleanupWorktree: boolean }) => {
-      await sessionManager.removeSession(sessionId, cleanupWorktree)
+    async (
+      _event,
+      {
+        sessionId,
+        cleanupWorktree,
+        mergeInto
+      }: { sessionId: string; cleanupWorktree: boolean; mergeInto?: { branch: string; strategy?: MergeStrategy } }
+    ) => {
+      await sessionManager.removeSession(sessionId, cleanupWorktree, mergeInto)
     }
   )
 
-  ipcMain.handle('session:permission-response', async (_event, response: PermissionResponse) => {
+  handle(
+    'session:close',
+    async (_event, { sessionId, terminateConnection }: { sessionId: string; terminateConnection: boolean }) => {
+      await sessionManager.closeSession(sessionId, terminateConnection)
+    }
+  )
+
+  handle('session:permission-response', async (_event, response: PermissionResponse) => {
     sessionManager.resolvePermission(response)
   })
 
-  ipcMain.handle(
+  handle(
     'session:rename',
     async (_event, { sessionId, title }: { sessionId: string; title: string }) => {
-      threadStore.rename(sessionId, title)
+      sessionManager.renameSession(sessionId, title)
     }
   )
 
-  ipcMain.handle('session:rebuild-cache', () => {
+  handle('session:rebuild-cache', async (event, request?: { workspaceId?: string }) => {
+    // Rescan just one workspace when asked; otherwise do a full rebuild
+    if (request?.workspaceId) {
+      const workspace = workspaceService.get(request.workspaceId)
+      if (!workspace) throw new Error(`Workspace not found: ${request.workspaceId}`)
+      threadStore.syncWorkspaceToCache(workspace.path, workspace.id)
+      return { threadCount: threadStore.loadAll().filter((t) => t.workspaceId === workspace.id).length }
+    }
+
     const workspaces = workspaceService.list().map((w) => ({ path: w.path, id: w.id }))
-    threadStore.rebuildCacheFromFolders(workspaces)
-    return { threadCount: threadStore.loadAll().length }
+    const threadCount = await threadStore.rebuildCacheFromFolders(workspaces, (progress) => {
+      if (!event.sender.isDestroyed()) {
+        event.sender.send('thread-cache:progress', progress)
+      }
+    })
+    return { threadCount }
   })
 
-  ipcMain.handle(
+  handle(
     'session:set-mode',
     async (_event, { sessionId, modeId }: { sessionId: string; modeId: string }) => {
       await sessionManager.setMode(sessionId, modeId)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:set-interaction-mode',
     async (_event, { sessionId, mode }: { sessionId: string; mode: InteractionMode }) => {
       await sessionManager.setInteractionMode(sessionId, mode)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:set-model',
     async (_event, { sessionId, modelId }: { sessionId: string; modelId: string }) => {
       await sessionManager.setModel(sessionId, modelId)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:set-config-option',
     async (_event, { sessionId, configId, value }: { sessionId: string; configId: string; value: string }) => {
       return sessionManager.setConfigOption(sessionId, configId, value)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:generate-title',
     async (_event, { sessionId }: { sessionId: string }) => {
       console.log('[session:generate-title] Received request for sessionId:', sessionId)
@@ -91,7 +198,7 @@ export function registerSessionHandlers(): void {
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:fork',
     async (_event, { sessionId, title }: { sessionId: string; title?: string }) => {
       const result = await sessionManager.forkSession(sessionId, title)
@@ -100,19 +207,19 @@ export function registerSessionHandlers(): void {
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:ensure-connected',
     async (_event, { sessionId }: { sessionId: string }) => {
       return sessionManager.ensureConnected(sessionId)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'session:rename-branch',
     async (_event, { sessionId, newBranch }: { sessionId: string; newBranch: string }) => {
       // Find the session's worktree path from persisted data
       const thread = threadStore.loadAll().find((t) => t.sessionId === sessionId)
-      if (!thread) throw new Error(`Session not found: ${sessionId}`)
+      if (!thread) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
       if (!thread.worktreePath) throw new Error('Session does not have a worktree')
 
       // Rename the git branch
@@ -1,17 +1,21 @@
-import { ipcMain } from 'electron'
+import { handle } from './handle'
 import { settingsService } from '../services/settings-service'
-import type { AppSettings } from '@shared/types/settings'
+import { setLogLevel } from '../util/logger'
+import type { AppSettings, McpServerConfig } from '@shared/types/settings'
 
 export function registerSettingsHandlers(): void {
-  ipcMain.handle('settings:get', () => {
+  handle('settings:get', () => {
     return settingsService.get()
   })
 
-  ipcMain.handle('settings:set', async (_event, partial: Partial<AppSettings>) => {
+  handle('settings:set', async (_event, partial: Partial<AppSettings>) => {
     settingsService.set(partial)
+    if (partial.general?.logLevel) {
+      setLogLevel(partial.general.logLevel)
+    }
   })
 
-  ipcMain.handle(
+  handle(
     'settings:set-agent',
     async (
       _event,
@@ -20,4 +24,26 @@ export function registerSettingsHandlers(): void {
       settingsService.setAgentSettings(agentId, settings)
     }
   )
+
+  handle(
+    'mcp:add-server',
+    (_event, server: Omit<McpServerConfig, 'id' | 'enabled'> & Partial<Pick<McpServerConfig, 'id' | 'enabled'>>) => {
+      return settingsService.addMcpServer(server)
+    }
+  )
+
+  handle('mcp:remove-server', (_event, { serverId }: { serverId: string }) => {
+    return settingsService.removeMcpServer(serverId)
+  })
+
+  handle(
+    'mcp:update-server',
+    (_event, { serverId, updates }: { serverId: string; updates: Partial<McpServerConfig> }) => {
+      return settingsService.updateMcpServer(serverId, updates)
+    }
+  )
+
+  handle('mcp:toggle-server', (_event, { serverId, enabled }: { serverId: string; enabled: boolean }) => {
+    return settingsService.toggleMcpServer(serverId, enabled)
+  })
 }
@@ -1,10 +1,57 @@
-import { ipcMain } from 'electron'
+import { shell } from 'electron'
+import { handle } from './handle'
 import { isWslAvailable, getWslDistributions } from '../util/platform'
+import { downloadService } from '../services/download-service'
+import { backupService } from '../services/backup-service'
+import { settingsService } from '../services/settings-service'
+import { getDataDirStatus } from '../util/data-dir'
+import { getLogFilePath, logger, readLogTail, setLogLevel } from '../util/logger'
+import type { LogLevel } from '@shared/types/settings'
+
+const DEFAULT_LOG_TAIL_LINES = 200
 
 export function registerSystemHandlers(): void {
-  ipcMain.handle('system:wsl-info', () => {
+  handle('system:wsl-info', () => {
     const available = isWslAvailable()
     const distributions = available ? getWslDistributions() : []
     return { available, distributions }
   })
+
+  handle('system:get-data-dir', () => {
+    return getDataDirStatus()
+  })
+
+  handle('system:set-log-level', (_event, { level }: { level: LogLevel }) => {
+    setLogLevel(level)
+    settingsService.set({ general: { ...settingsService.get().general, logLevel: level } })
+    logger.info(`Log level set to ${level}`)
+  })
+
+  handle('system:get-app-log', (_event, { maxLines }: { maxLines?: number }) => {
+    return readLogTail(maxLines ?? DEFAULT_LOG_TAIL_LINES)
+  })
+
+  handle('system:open-log-file', async () => {
+    const logFile = getLogFilePath()
+    if (!logFile) throw new Error('Application log file is not available')
+    const openError = await shell.openPath(logFile)
+    if (openError) {
+      throw new Error(openError)
+    }
+  })
+
+  handle('system:prune-downloads', (_event, { maxAgeDays }: { maxAgeDays?: number }) => {
+    return downloadService.pruneDownloads(maxAgeDays)
+  })
+
+  handle(
+    'system:export-backup',
+    async (_event, { path, includeCache }: { path: string; includeCache?: boolean }) => {
+      return backupService.exportBackup(path, { includeCache })
+    }
+  )
+
+  handle('system:import-backup', async (_event, { path }: { path: string }) => {
+    return backupService.importBackup(path)
+  })
 }
@@ -1,34 +1,69 @@
-import { ipcMain } from 'electron'
+import { handle } from './handle'
 import { terminalService } from '../services/terminal-service'
+import { sessionManager } from '../services/session-manager'
+import { agentManager } from '../services/agent-manager'
+
+/** Keep PTY dimensions within sane bounds */
+function clampSize(cols: number, rows: number): { cols: number; rows: number } {
+  return {
+    cols: Math.max(1, Math.min(500, Math.floor(cols))),
+    rows: Math.max(1, Math.min(200, Math.floor(rows)))
+  }
+}
 
 export function registerTerminalHandlers(): void {
-  ipcMain.handle(
+  handle(
     'terminal:create',
-    async (_event, { cwd, sessionId }: { cwd: string; sessionId: string }) => {
-      return terminalService.create(cwd, sessionId)
+    async (
+      _event,
+      {
+        cwd,
+        sessionId,
+        env,
+        cols,
+        rows
+      }: { cwd: string; sessionId: string; env?: Record<string, string>; cols?: number; rows?: number }
+    ) => {
+      // The session's agent env applies first so explicitly passed env wins
+      const agentId = sessionManager.getSession(sessionId)?.agentId
+      const agentEnv = agentId ? agentManager.getAgentEnv(agentId) : {}
+      // Open at the renderer's real size when known, to avoid an initial reflow
+      const size =
+        Number.isFinite(cols) && Number.isFinite(rows) ? clampSize(cols!, rows!) : undefined
+      return terminalService.create(cwd, sessionId, { ...agentEnv, ...env }, size)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'terminal:write',
     async (_event, { terminalId, data }: { terminalId: string; data: string }) => {
       terminalService.write(terminalId, data)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'terminal:resize',
     async (
       _event,
       { terminalId, cols, rows }: { terminalId: string; cols: number; rows: number }
     ) => {
-      const safeCols = Math.max(1, Math.min(500, Math.floor(cols)))
-      const safeRows = Math.max(1, Math.min(200, Math.floor(rows)))
-      terminalService.resize(terminalId, safeCols, safeRows)
+      const size = clampSize(cols, rows)
+      terminalService.resize(terminalId, size.cols, size.rows)
     }
   )
 
-  ipcMain.handle('terminal:kill', async (_event, { terminalId }: { terminalId: string }) => {
+  handle('terminal:kill', async (_event, { terminalId }: { terminalId: string }) => {
     terminalService.kill(terminalId)
   })
+
+  handle('terminal:list', async (_event, data?: { sessionId?: string }) => {
+    return terminalService.list(data?.sessionId)
+  })
+
+  handle(
+    'terminal:get-scrollback',
+    async (_event, { terminalId }: { terminalId: string }) => {
+      return terminalService.getScrollback(terminalId)
+    }
+  )
 }
@@ -0,0 +1,90 @@
+import { BrowserWindow, ipcMain } from 'electron'
+import Store from 'electron-store'
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+import { registerWindowHandlers } from './window-handlers'
+import { settingsService } from '../services/settings-service'
+import { clampZoom } from '../window'
+
+type Handler = (event: unknown, ...args: unknown[]) => Promise<unknown>
+
+/** Same backing data as settingsService */
+const settings = new Store({ name: 'settings' })
+
+let handlers: Map<string, Handler>
+let zoomFactor: number
+
+beforeEach(() => {
+  handlers = new Map()
+  vi.spyOn(ipcMain, 'handle').mockImplementation(((channel: string, handler: Handler) => {
+    handlers.set(channel, handler)
+  }) as typeof ipcMain.handle)
+  registerWindowHandlers()
+  zoomFactor = 1
+})
+
+afterEach(() => {
+  vi.restoreAllMocks()
+  settings.clear()
+})
+
+/** Route handlers to a window whose zoom factor lives in `zoomFactor` */
+function withWindow(): void {
+  const webContents = {
+    getZoomFactor: () => zoomFactor,
+    setZoomFactor: (factor: number) => {
+      zoomFactor = factor
+    }
+  }
+  vi.spyOn(BrowserWindow, 'fromWebContents').mockReturnValue({ webContents } as unknown as BrowserWindow)
+}
+
+function invoke(channel: string, ...args: unknown[]): Promise<unknown> {
+  return handlers.get(channel)!({ sender: {} }, ...args)
+}
+
+describe('zoom', () => {
+  it('round-trips the zoom level through settings', async () => {
+    withWindow()
+
+    expect(await invoke('window:set-zoom', { level: 1.5 })).toBe(1.5)
+
+    expect(zoomFactor).toBe(1.5)
+    expect(settingsService.get().general.zoomLevel).toBe(1.5)
+    expect(await invoke('window:get-zoom')).toBe(1.5)
+  })
+
+  it('clamps to the supported range', async () => {
+    withWindow()
+
+    expect(await invoke('window:set-zoom', { level: 10 })).toBe(3)
+    expect(await invoke('window:set-zoom', { level: 0.1 })).toBe(0.5)
+    expect(settingsService.get().general.zoomLevel).toBe(0.5)
+  })
+
+  it('persists zooming in, out and resetting', async () => {
+    withWindow()
+
+    await invoke('window:zoom-in')
+    await invoke('window:zoom-in')
+    expect(zoomFactor).toBeCloseTo(1.2)
+    expect(settingsService.get().general.zoomLevel).toBeCloseTo(1.2)
+
+    await invoke('window:zoom-out')
+    expect(zoomFactor).toBeCloseTo(Math.sqrt(1.2))
+
+    await invoke('window:reset-zoom')
+    expect(settingsService.get().general.zoomLevel).toBe(1)
+  })
+
+  it('reports the stored level without a window', async () => {
+    settingsService.set({ general: { ...settingsService.get().general, zoomLevel: 1.25 } })
+
+    expect(await invoke('window:get-zoom')).toBe(1.25)
+  })
+
+  it('treats unusable stored values as 100%', () => {
+    expect(clampZoom(undefined)).toBe(1)
+    expect(clampZoom(Number.NaN)).toBe(1)
+    expect(clampZoom(Number.POSITIVE_INFINITY)).toBe(1)
+  })
+})
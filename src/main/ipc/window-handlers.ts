@@ -1,43 +1,73 @@
-import { ipcMain, BrowserWindow, app } from 'electron'
+import { BrowserWindow, app } from 'electron'
+import { handle } from './handle'
+import type { WebContents } from 'electron'
+import { settingsService } from '../services/settings-service'
+import { clampZoom, resetWindowGeometry } from '../window'
+
+/** One zoom-in/out step: half an Electron zoom level (factor = 1.2^level) */
+const ZOOM_STEP = Math.pow(1.2, 0.5)
+
+/** Apply a clamped zoom factor and persist it for the next launch */
+function setZoom(wc: WebContents, factor: number): number {
+  const zoom = clampZoom(factor)
+  wc.setZoomFactor(zoom)
+  settingsService.set({ general: { ...settingsService.get().general, zoomLevel: zoom } })
+  return zoom
+}
 
 export function registerWindowHandlers(): void {
-  ipcMain.handle('window:reload', (event) => {
+  handle('window:reload', (event) => {
     BrowserWindow.fromWebContents(event.sender)?.reload()
   })
 
-  ipcMain.handle('window:toggle-devtools', (event) => {
+  handle('window:toggle-devtools', (event) => {
     BrowserWindow.fromWebContents(event.sender)?.webContents.toggleDevTools()
   })
 
-  ipcMain.handle('window:reset-zoom', (event) => {
+  handle('window:reset-zoom', (event) => {
+    const wc = BrowserWindow.fromWebContents(event.sender)?.webContents
+    if (wc) setZoom(wc, 1)
+  })
+
+  handle('window:zoom-in', (event) => {
     const wc = BrowserWindow.fromWebContents(event.sender)?.webContents
-    if (wc) wc.setZoomLevel(0)
+    if (wc) setZoom(wc, wc.getZoomFactor() * ZOOM_STEP)
   })
 
-  ipcMain.handle('window:zoom-in', (event) => {
+  handle('window:zoom-out', (event) => {
     const wc = BrowserWindow.fromWebContents(event.sender)?.webContents
-    if (wc) wc.setZoomLevel(wc.getZoomLevel() + 0.5)
+    if (wc) setZoom(wc, wc.getZoomFactor() / ZOOM_STEP)
   })
 
-  ipcMain.handle('window:zoom-out', (event) => {
+  handle('window:get-zoom', (event) => {
     const wc = BrowserWindow.fromWebContents(event.sender)?.webContents
-    if (wc) wc.setZoomLevel(wc.getZoomLevel() - 0.5)
+    return wc ? wc.getZoomFactor() : clampZoom(settingsService.get().general.zoomLevel)
+  })
+
+  handle('window:set-zoom', (event, { level }: { level: number }) => {
+    const wc = BrowserWindow.fromWebContents(event.sender)?.webContents
+    return wc ? setZoom(wc, level) : clampZoom(level)
+  })
+
+  handle('window:reset-geometry', (event) => {
+    const win = BrowserWindow.fromWebContents(event.sender)
+    if (win) resetWindowGeometry(win)
   })
 
-  ipcMain.handle('window:toggle-fullscreen', (event) => {
+  handle('window:toggle-fullscreen', (event) => {
     const win = BrowserWindow.fromWebContents(event.sender)
     if (win) win.setFullScreen(!win.isFullScreen())
   })
 
-  ipcMain.handle('window:minimize', (event) => {
+  handle('window:minimize', (event) => {
     BrowserWindow.fromWebContents(event.sender)?.minimize()
   })
 
-  ipcMain.handle('window:close', (event) => {
+  handle('window:close', (event) => {
     BrowserWindow.fromWebContents(event.sender)?.close()
   })
 
-  ipcMain.handle('window:quit', () => {
+  handle('window:quit', () => {
     app.quit()
   })
 }
@@ -1,4 +1,5 @@
-import { ipcMain, dialog, BrowserWindow, shell } from 'electron'
+import { dialog, BrowserWindow, shell } from 'electron'
+import { handle } from './handle'
 import { execFile } from 'child_process'
 import { access } from 'node:fs/promises'
 import { constants as fsConstants } from 'node:fs'
@@ -79,18 +80,18 @@ async function openInVSCode(workspacePath: string): Promise<void> {
 }
 
 export function registerWorkspaceHandlers(): void {
-  ipcMain.handle('workspace:list', () => {
+  handle('workspace:list', () => {
     return workspaceService.list()
   })
 
-  ipcMain.handle(
+  handle(
     'workspace:create',
     async (_event, { path, name }: { path: string; name?: string }) => {
       return workspaceService.create(path, name)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'workspace:remove',
     async (_event, { id, cleanupWorktrees = false }: { id: string; cleanupWorktrees?: boolean }) => {
       const workspaceThreads = threadStore.loadAll().filter((thread) => thread.workspaceId === id)
@@ -104,7 +105,7 @@ export function registerWorkspaceHandlers(): void {
     }
   )
 
-  ipcMain.handle(
+  handle(
     'workspace:update',
     async (
       _event,
@@ -122,7 +123,14 @@ export function registerWorkspaceHandlers(): void {
     }
   )
 
-  ipcMain.handle('workspace:select-directory', async (event) => {
+  handle(
+    'workspace:resume-sessions',
+    async (_event, { workspaceId, max }: { workspaceId: string; max: number }) => {
+      return sessionManager.resumeWorkspaceSessions(workspaceId, max)
+    }
+  )
+
+  handle('workspace:select-directory', async (event) => {
     const window = BrowserWindow.fromWebContents(event.sender)
     if (!window) return null
 
@@ -135,7 +143,7 @@ export function registerWorkspaceHandlers(): void {
     return result.filePaths[0]
   })
 
-  ipcMain.handle('workspace:open-in-vscode', async (_event, { path }: { path: string }) => {
+  handle('workspace:open-in-vscode', async (_event, { path }: { path: string }) => {
     try {
       await openInVSCode(path)
     } catch (error) {
@@ -144,7 +152,7 @@ export function registerWorkspaceHandlers(): void {
     }
   })
 
-  ipcMain.handle('workspace:open-directory', async (_event, { path }: { path: string }) => {
+  handle('workspace:open-directory', async (_event, { path }: { path: string }) => {
     try {
       const openError = await shell.openPath(path)
       if (openError) {
@@ -156,14 +164,14 @@ export function registerWorkspaceHandlers(): void {
     }
   })
 
-  ipcMain.handle(
+  handle(
     'workspace:get-config',
     async (_event, { workspacePath }: { workspacePath: string }) => {
       return worktreeHookService.readConfig(workspacePath)
     }
   )
 
-  ipcMain.handle(
+  handle(
     'workspace:set-config',
     async (
       _event,
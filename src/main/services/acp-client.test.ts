@@ -0,0 +1,378 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import { fileURLToPath } from 'url'
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+import type { BrowserWindow } from 'electron'
+import type { PermissionRequestEvent, SessionUpdate, SessionUpdateEvent } from '@shared/types/session'
+import { AcpError } from '../util/acp-error'
+import { AcpClient, resolveInitialModeId } from './acp-client'
+
+// AcpClient only needs these for the renderer and saved permission rules
+vi.mock('electron', () => ({ BrowserWindow: class {} }))
+vi.mock('./permission-rule-service', () => ({
+  permissionRuleService: { findMatchingRule: () => undefined }
+}))
+
+const MOCK_AGENT = fileURLToPath(new URL('../../../scripts/mock-acp-agent.mjs', import.meta.url))
+
+const clients: AcpClient[] = []
+
+async function startClient(delayMs = 1): Promise<AcpClient> {
+  const client = new AcpClient(
+    'mock-agent',
+    process.execPath,
+    [MOCK_AGENT],
+    { MOCK_AGENT_DELAY_MS: String(delayMs) },
+    process.cwd()
+  )
+  clients.push(client)
+  await client.start()
+  await client.initialize(5000)
+  return client
+}
+
+/** Session updates the client emits for one session, in order */
+function collectUpdates(client: AcpClient, sessionId: string): SessionUpdate[] {
+  const updates: SessionUpdate[] = []
+  client.on('session-update', (event: SessionUpdateEvent) => {
+    if (event.sessionId === sessionId) updates.push(event.update)
+  })
+  return updates
+}
+
+/** Answer every permission request with `optionId`; returns the requests seen */
+function answerPermissions(client: AcpClient, optionId: string): PermissionRequestEvent[] {
+  const requests: PermissionRequestEvent[] = []
+  client.on('permission-request', (event: PermissionRequestEvent) => {
+    requests.push(event)
+    client.resolvePermission({ requestId: event.requestId, optionId })
+  })
+  return requests
+}
+
+function messageText(updates: SessionUpdate[]): string {
+  return updates.map((u) => (u.type === 'text_chunk' ? u.text : '')).join('')
+}
+
+/** Stand-in for the renderer window; records every event the client sends it */
+function recordingWindow(sent: Array<{ channel: string; payload: unknown }>): BrowserWindow {
+  return {
+    isDestroyed: () => false,
+    webContents: { send: (channel: string, payload: unknown) => sent.push({ channel, payload }) }
+  } as unknown as BrowserWindow
+}
+
+/** Our permission response as the agent received it; the mock echoes it back as the tool call's rawOutput */
+function permissionResponse(updates: SessionUpdate[]): unknown {
+  const update = updates.find((u) => u.type === 'tool_call_update')
+  return update?.type === 'tool_call_update' && update.output ? JSON.parse(update.output) : undefined
+}
+
+afterEach(async () => {
+  await Promise.all(clients.splice(0).map((client) => client.terminate(500)))
+})
+
+describe('AcpClient against the mock agent', () => {
+  it('initializes and records the agent capabilities', async () => {
+    const client = await startClient()
+
+    expect(client.agentName).toBe('mock-agent')
+    expect(client.agentVersion).toBe('0.0.0')
+    expect(client.authMethods).toEqual([])
+    expect(client.capabilities?.promptCapabilities).toEqual({ image: false, audio: false, embeddedContext: true })
+  })
+
+  it('rejects with the structured code once both logout methods are missing', async () => {
+    const client = await startClient()
+
+    const error = await client.logout().catch((err: unknown) => err)
+
+    expect(error).toBeInstanceOf(AcpError)
+    expect(error).toMatchObject({ code: -32601, rpcMessage: 'Method not found: logout' })
+  })
+
+  it('maps a new session to the agent-assigned id', async () => {
+    const client = await startClient()
+
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+
+    expect(sessionId).toBe('internal-1')
+    expect(client.getRemoteSessionId('internal-1')).toBe('mock-session-1')
+  })
+
+  it('streams a prompt reply and completes the tool call once permission is granted', async () => {
+    const client = await startClient()
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+    const updates = collectUpdates(client, sessionId)
+    const requests = answerPermissions(client, 'allow')
+
+    const result = await client.prompt(sessionId, 'hello there')
+
+    expect(result.stopReason).toBe('end_turn')
+    expect(messageText(updates)).toBe('Echo: hello there')
+    expect(requests).toHaveLength(1)
+    expect(requests[0].sessionId).toBe(sessionId)
+    expect(requests[0].options.map((o) => o.optionId)).toEqual(['allow', 'reject'])
+    expect(updates.find((u) => u.type === 'tool_call_update')).toMatchObject({ status: 'completed' })
+  })
+
+  it('fails the tool call when permission is rejected', async () => {
+    const client = await startClient()
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+    const updates = collectUpdates(client, sessionId)
+    answerPermissions(client, 'reject')
+
+    const result = await client.prompt(sessionId, 'hello')
+
+    expect(result.stopReason).toBe('end_turn')
+    expect(updates.find((u) => u.type === 'tool_call_update')).toMatchObject({ status: 'failed' })
+  })
+
+  it('ends a streaming prompt as cancelled', async () => {
+    const client = await startClient(100)
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+    const updates = collectUpdates(client, sessionId)
+    const requests = answerPermissions(client, 'allow')
+    const cancelOnFirstChunk = (event: SessionUpdateEvent): void => {
+      if (event.update.type !== 'text_chunk') return
+      client.off('session-update', cancelOnFirstChunk)
+      client.cancel(sessionId)
+    }
+    client.on('session-update', cancelOnFirstChunk)
+
+    const result = await client.prompt(sessionId, 'one two three four five six')
+
+    expect(result.stopReason).toBe('cancelled')
+    expect(messageText(updates).length).toBeLessThan('Echo: one two three four five six'.length)
+    expect(requests).toHaveLength(0)
+  })
+})
+
+describe('permission responses', () => {
+  it('writes the selected option back to the agent as the JSON-RPC result', async () => {
+    const client = await startClient()
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+    const updates = collectUpdates(client, sessionId)
+    answerPermissions(client, 'reject')
+
+    await client.prompt(sessionId, 'hello')
+
+    expect(permissionResponse(updates)).toEqual({
+      jsonrpc: '2.0',
+      id: 'mock-1',
+      result: { outcome: { outcome: 'selected', optionId: 'reject' } }
+    })
+  })
+
+  it('answers a cancelled request with the cancelled outcome', async () => {
+    const client = await startClient()
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+    const updates = collectUpdates(client, sessionId)
+    answerPermissions(client, '__cancelled__')
+
+    const result = await client.prompt(sessionId, 'hello')
+
+    expect(result.stopReason).toBe('cancelled')
+    expect(permissionResponse(updates)).toEqual({
+      jsonrpc: '2.0',
+      id: 'mock-1',
+      result: { outcome: { outcome: 'cancelled' } }
+    })
+  })
+})
+
+describe('fs requests from the agent', () => {
+  let root: string
+  let outside: string
+
+  beforeEach(() => {
+    root = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'acp-fs-')))
+    outside = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'acp-fs-outside-')))
+    fs.writeFileSync(path.join(root, 'notes.txt'), 'inside')
+    fs.writeFileSync(path.join(outside, 'secret.txt'), 'outside')
+  })
+
+  afterEach(() => {
+    fs.rmSync(root, { recursive: true, force: true })
+    fs.rmSync(outside, { recursive: true, force: true })
+  })
+
+  /** Client with a session rooted at `root`; `reply` runs one directive prompt and returns the agent's text */
+  async function startSession() {
+    const client = await startClient()
+    const sent: Array<{ channel: string; payload: unknown }> = []
+    client.setMainWindow(recordingWindow(sent))
+    const sessionId = await client.newSession(root, [], 'internal-1')
+    const reply = async (prompt: string): Promise<string> => {
+      const updates = collectUpdates(client, sessionId)
+      await client.prompt(sessionId, prompt)
+      return messageText(updates)
+    }
+    const fileAccess = () => sent.filter((e) => e.channel === 'session:file-access').map((e) => e.payload)
+    return { sessionId, reply, fileAccess }
+  }
+
+  it('reads a file relative to the session directory', async () => {
+    const { sessionId, reply, fileAccess } = await startSession()
+
+    expect(await reply('read notes.txt')).toBe('inside')
+    expect(fileAccess()).toEqual([{ sessionId, path: path.join(root, 'notes.txt'), operation: 'read' }])
+  })
+
+  it('writes a file, creating missing directories', async () => {
+    const { sessionId, reply, fileAccess } = await startSession()
+
+    expect(await reply('write sub/out.txt hello world')).toBe('Wrote sub/out.txt')
+    expect(fs.readFileSync(path.join(root, 'sub', 'out.txt'), 'utf-8')).toBe('hello world')
+    expect(fileAccess()).toEqual([{ sessionId, path: path.join(root, 'sub', 'out.txt'), operation: 'write' }])
+  })
+
+  it('rejects paths that climb out of the session directory', async () => {
+    const { reply, fileAccess } = await startSession()
+    const escape = `../${path.basename(outside)}/secret.txt`
+
+    expect(await reply(`read ${escape}`)).toMatch(/^Error: Path is outside the session working directory/)
+    expect(await reply(`write ../${path.basename(outside)}/new.txt x`)).toMatch(/^Error: Path is outside/)
+    expect(fs.existsSync(path.join(outside, 'new.txt'))).toBe(false)
+    expect(fileAccess()).toEqual([])
+  })
+
+  it.skipIf(process.platform === 'win32')('rejects symlinks that point outside the session directory', async () => {
+    fs.symlinkSync(outside, path.join(root, 'link'))
+    const { reply, fileAccess } = await startSession()
+
+    expect(await reply('read link/secret.txt')).toMatch(/^Error: Path is outside/)
+    expect(await reply('write link/new.txt x')).toMatch(/^Error: Path is outside/)
+    expect(fs.existsSync(path.join(outside, 'new.txt'))).toBe(false)
+    expect(fileAccess()).toEqual([])
+  })
+})
+
+describe('terminal requests from the agent', () => {
+  async function startSession() {
+    const client = await startClient()
+    const sessionId = await client.newSession(process.cwd(), [], 'internal-1')
+    const reply = async (prompt: string): Promise<string> => {
+      const updates = collectUpdates(client, sessionId)
+      await client.prompt(sessionId, prompt)
+      return messageText(updates)
+    }
+    return { client, sessionId, reply }
+  }
+
+  it('runs a command and reports its output and exit code', async () => {
+    const { reply } = await startSession()
+
+    expect(await reply('terminal echo hi')).toBe('hi (exit 0)')
+  })
+
+  it.skipIf(process.platform === 'win32')('kills and forgets the terminals of a released session', async () => {
+    const { client, sessionId, reply } = await startSession()
+    const terminalId = await reply('spawn sleep 30')
+
+    expect(await reply(`output ${terminalId}`)).toBe('')
+    client.releaseSession(sessionId)
+
+    expect(await reply(`output ${terminalId}`)).toBe(`Error: Terminal not found: ${terminalId}`)
+  })
+})
+
+describe('initial session mode', () => {
+  const modes = [{ id: 'default' }, { id: 'plan' }, { id: 'edit' }]
+
+  it('prefers the requested mode, then the agent current mode, then the first one', () => {
+    expect(resolveInitialModeId(modes, 'edit', 'plan')).toBe('plan')
+    expect(resolveInitialModeId(modes, 'edit', 'missing')).toBe('edit')
+    expect(resolveInitialModeId(modes, 'edit')).toBe('edit')
+    expect(resolveInitialModeId(modes, undefined, 'missing')).toBe('default')
+    expect(resolveInitialModeId([], undefined, 'plan')).toBeUndefined()
+  })
+
+  it.each([
+    ['plan', 'plan'],
+    [undefined, 'default'],
+    ['missing', 'default']
+  ])('reports mode %s from session/new as %s', async (preferredModeId, expected) => {
+    const client = await startClient()
+    const updates = collectUpdates(client, 'internal-1')
+
+    await client.newSession(process.cwd(), [], 'internal-1', { preferredModeId })
+
+    expect(updates.filter((u) => u.type === 'current_mode_update')).toEqual([
+      { type: 'current_mode_update', modeId: expected }
+    ])
+    expect(client.getModeCatalog().currentModeId).toBe('default')
+  })
+})
+
+describe('tool calls without a toolCallId', () => {
+  interface Transformer {
+    transformSessionUpdate(raw: Record<string, unknown>, sessionId: string): SessionUpdate
+  }
+
+  it('resolves later updates to the id generated for the call', () => {
+    const client = new AcpClient('mock-agent', process.execPath, [MOCK_AGENT], {}, process.cwd())
+    const transform = (raw: Record<string, unknown>): SessionUpdate =>
+      (client as unknown as Transformer).transformSessionUpdate(raw, 'internal-1')
+
+    const start = transform({ sessionUpdate: 'tool_call', toolCallId: '', messageId: 'm1', title: 'Read' })
+    const running = transform({ sessionUpdate: 'tool_call_update', toolCallId: '', messageId: 'm1', status: 'in_progress' })
+    const done = transform({ sessionUpdate: 'tool_call_update', toolCallId: '', messageId: 'm1', status: 'completed' })
+    // A second id-less call in another message gets its own id
+    const other = transform({ sessionUpdate: 'tool_call', messageId: 'm2', title: 'Write' })
+
+    const id = start.type === 'tool_call_start' ? start.toolCall.toolCallId : undefined
+    expect(id).toBeTruthy()
+    expect(running).toMatchObject({ type: 'tool_call_update', toolCallId: id, status: 'in_progress' })
+    expect(done).toMatchObject({ type: 'tool_call_update', toolCallId: id, status: 'completed' })
+    expect(other.type === 'tool_call_start' ? other.toolCall.toolCallId : undefined).not.toBe(id)
+  })
+})
+
+describe.skipIf(process.platform === 'win32')('terminating the agent process', () => {
+  interface Child {
+    childProcess: { exitCode: number | null; signalCode: NodeJS.Signals | null }
+  }
+
+  /** Spawn `script` as a stand-in agent that never answers requests */
+  async function startScript(script: string, env: Record<string, string> = {}): Promise<AcpClient> {
+    const client = new AcpClient('fake-agent', process.execPath, ['-e', script], env, process.cwd())
+    await client.start()
+    return client
+  }
+
+  it('lets an agent that exits on stdin close flush state first', async () => {
+    const marker = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'acp-terminate-')), 'flushed')
+    const client = await startScript(
+      "process.stdin.resume(); process.stdin.on('end', () => { require('fs').writeFileSync(process.env.MARKER, 'ok'); process.exit(0) })",
+      { MARKER: marker }
+    )
+
+    const started = Date.now()
+    await client.terminate(2000)
+
+    expect(Date.now() - started).toBeLessThan(2000)
+    expect((client as unknown as Child).childProcess).toMatchObject({ exitCode: 0, signalCode: null })
+    expect(fs.readFileSync(marker, 'utf-8')).toBe('ok')
+    fs.rmSync(path.dirname(marker), { recursive: true, force: true })
+  })
+
+  it('sends SIGTERM once the grace period runs out', async () => {
+    const client = await startScript('setInterval(() => {}, 1000)')
+
+    await client.terminate(100)
+
+    expect((client as unknown as Child).childProcess.signalCode).toBe('SIGTERM')
+  })
+
+  it('force-kills an agent that ignores SIGTERM and rejects pending requests', async () => {
+    const client = await startScript("process.on('SIGTERM', () => {}); setInterval(() => {}, 1000)")
+    const pending = expect(client.initialize(10000)).rejects.toThrow('Agent terminated')
+
+    await client.terminate(100)
+
+    await pending
+    expect((client as unknown as Child).childProcess.signalCode).toBe('SIGKILL')
+  })
+})
@@ -23,9 +23,14 @@ import type {
   ToolCallLocation,
   ToolCallStatus,
   ContentBlock,
-  StopReason
+  StopReason,
+  FileAccessEvent,
+  SlashCommand
 } from '@shared/types/session'
 import { logger } from '../util/logger'
+import { toSpawnError } from '../util/spawn-error'
+import { AppError } from '../util/app-error'
+import { ACP_METHOD_NOT_FOUND, AcpError, parseAcpError } from '../util/acp-error'
 import { permissionRuleService } from './permission-rule-service'
 
 // ============================================================
@@ -66,6 +71,8 @@ type PermissionResolver = (response: PermissionResponse) => void
 
 interface TerminalProcess {
   process: ChildProcess
+  /** Internal session id that created the terminal, if known */
+  sessionId?: string
   output: string
   truncated: boolean
   exitCode: number | null
@@ -75,6 +82,37 @@ interface TerminalProcess {
   waitResolvers: Array<() => void>
 }
 
+/** How long an agent gets to exit after stdin is closed before it is signalled */
+const TERMINATE_GRACE_MS = 2000
+/** Delay between SIGTERM and SIGKILL during termination */
+const FORCE_KILL_DELAY_MS = 3000
+
+/** Cap on session/update notifications held for a session we don't know yet */
+const MAX_BUFFERED_UPDATES_PER_SESSION = 200
+
+/** Pick the initial mode: the preferred mode if offered, then the agent's current mode, then the first one */
+export function resolveInitialModeId(
+  availableModes: Array<{ id: string }>,
+  currentModeId?: string,
+  preferredModeId?: string
+): string | undefined {
+  if (preferredModeId && availableModes.some((mode) => mode.id === preferredModeId)) {
+    return preferredModeId
+  }
+  return currentModeId || availableModes[0]?.id
+}
+
+/** Real path of `p`; for a file that doesn't exist yet, the real path of its nearest existing ancestor plus the rest */
+function realpathExisting(p: string): string {
+  try {
+    return fs.realpathSync(p)
+  } catch {
+    const parent = path.dirname(p)
+    if (parent === p) return p
+    return path.join(realpathExisting(parent), path.basename(p))
+  }
+}
+
 export class AcpClient extends EventEmitter {
   readonly connectionId: string
   private childProcess: ChildProcess | null = null
@@ -94,11 +132,34 @@ export class AcpClient extends EventEmitter {
   // Session context: internalSessionId -> { workspaceId }
   private sessionContext = new Map<string, { workspaceId: string }>()
 
+  // session/update notifications for sessions not (yet) known: remoteId -> raw updates
+  private unknownSessionUpdates = new Map<string, Array<Record<string, unknown>>>()
+
+  // Generated tool call ids for calls the agent sent without one: key -> generated id
+  private generatedToolCallIds = new Map<string, string>()
+
+  // Session working directories: internalSessionId -> cwd (root for fs/* requests)
+  private sessionCwds = new Map<string, string>()
+
+  // Latest available_commands_update per session: internalSessionId -> commands.
+  // Agents often send it right after session/new, before the session is registered upstream.
+  private availableCommands = new Map<string, SlashCommand[]>()
+
   // Public state
   capabilities: AgentCapabilities | null = null
   authMethods: AuthMethod[] = []
   agentName = 'Unknown Agent'
   agentVersion = ''
+  /** ISO timestamp of when the agent process was spawned (empty until start()) */
+  startedAt = ''
+  /** Number of times the process was respawned after an unexpected exit */
+  restartCount = 0
+  private terminating = false
+  /** Set while restart() runs; a replacement dying mid-initialize fails the attempt, not another crash */
+  private restarting = false
+  // Agent notifications/requests received before initialize resolved, replayed in order afterwards
+  private initialized = false
+  private preInitMessages: JsonRpcResponse[] = []
   private modelCatalog: AgentModelCatalog = { availableModels: [] }
   private modeCatalog: AgentModeCatalog = { availableModes: [] }
 
@@ -126,8 +187,11 @@ export class AcpClient extends EventEmitter {
   /** Spawn the agent and connect via stdio */
   async start(): Promise<void> {
     logger.info(`Spawning agent: ${this.spawnCommand} ${this.spawnArgs.join(' ')}`)
+    this.startedAt = new Date().toISOString()
+    this.initialized = false
+    this.preInitMessages = []
 
-    this.childProcess = spawn(this.spawnCommand, this.spawnArgs, {
+    const child = spawn(this.spawnCommand, this.spawnArgs, {
       stdio: ['pipe', 'pipe', 'pipe'],
       cwd: this.cwd,
       env: { ...process.env, ...this.spawnEnv },
@@ -135,6 +199,7 @@ export class AcpClient extends EventEmitter {
       // Native Windows: use shell so .cmd files resolve correctly
       shell: process.platform === 'win32' && !this.useWsl
     })
+    this.childProcess = child
 
     // Handle stdout (JSON-RPC messages from agent)
     this.childProcess.stdout!.on('data', (data: Buffer) => {
@@ -161,14 +226,49 @@ export class AcpClient extends EventEmitter {
         ? `Agent process exited: code=${code}\n${stderr}`
         : `Agent process exited: code=${code}`
       this.rejectAllPending(new Error(msg))
+      // Only the current process dying on its own counts as a crash
+      if (!this.terminating && !this.restarting && child === this.childProcess) {
+        this.emit('crashed', msg)
+      }
     })
 
     this.childProcess.on('error', (err) => {
       logger.error(`Agent ${this.agentId} spawn error:`, err)
-      this.rejectAllPending(err)
+      this.rejectAllPending(toSpawnError(err, this.agentId, this.spawnCommand, this.useWsl))
     })
   }
 
+  /**
+   * Respawn the agent with the original command/args/env and re-run initialize.
+   * The new process knows none of the old sessions, so their mappings are
+   * dropped and `restarted` is emitted with their internal ids to re-establish.
+   */
+  async restart(): Promise<void> {
+    this.restartCount++
+    this.restarting = true
+    this.stdoutBuffer = ''
+    this.stderrBuffer = ''
+    try {
+      await this.start()
+      await this.initialize()
+    } catch (error) {
+      // Detach the failed process so its exit is not reported as another crash
+      const failed = this.childProcess
+      this.childProcess = null
+      failed?.kill('SIGKILL')
+      throw error
+    } finally {
+      this.restarting = false
+    }
+
+    const sessionIds = Array.from(this.internalToRemote.keys())
+    this.remoteToInternal.clear()
+    this.internalToRemote.clear()
+    this.unknownSessionUpdates.clear()
+    this.generatedToolCallIds.clear()
+    this.emit('restarted', sessionIds)
+  }
+
   /** Initialize the ACP connection */
   async initialize(timeoutMs: number = 30000): Promise<{
     capabilities: AgentCapabilities
@@ -207,6 +307,16 @@ export class AcpClient extends EventEmitter {
       `auth methods: ${this.authMethods.map((a) => a.id).join(', ') || 'none'}`
     )
 
+    this.initialized = true
+    const early = this.preInitMessages
+    this.preInitMessages = []
+    if (early.length > 0) {
+      logger.debug(`[${this.agentId}] Replaying ${early.length} message(s) received before initialize completed`)
+    }
+    for (const msg of early) {
+      this.handleAgentMethodCall(msg)
+    }
+
     return {
       capabilities: this.capabilities || {},
       authMethods: this.authMethods,
@@ -264,21 +374,21 @@ export class AcpClient extends EventEmitter {
     this.updateModelCatalogFromSessionNewResult(result)
     this.updateModeCatalogFromSessionNewResult(result)
 
+    const sessionId = internalSessionId || remoteId
+    this.sessionCwds.set(sessionId, cwd)
+
     if (internalSessionId) {
       this.registerSessionMapping(remoteId, internalSessionId)
+    } else {
+      this.flushBufferedUpdates(remoteId)
     }
 
-    const sessionId = internalSessionId || remoteId
-
     // Forward initial modes and configOptions from session/new response as updates
     if (!suppressInitialUpdates && result.modes) {
       const modes = result.modes
       // Build configOptions from legacy modes field for backward compat
       if (modes.availableModes && modes.availableModes.length > 0) {
-        const resolvedModeId =
-          preferredModeId && modes.availableModes.some((mode) => mode.id === preferredModeId)
-            ? preferredModeId
-            : modes.currentModeId || modes.availableModes[0].id
+        const resolvedModeId = resolveInitialModeId(modes.availableModes, modes.currentModeId, preferredModeId)
         const modeConfigOption = {
           id: '_mode',
           name: 'Mode',
@@ -501,11 +611,13 @@ export class AcpClient extends EventEmitter {
     const newRemoteId = result.sessionId
 
     if (internalSessionId) {
-      this.remoteToInternal.set(newRemoteId, internalSessionId)
-      this.internalToRemote.set(internalSessionId, newRemoteId)
+      this.sessionCwds.set(internalSessionId, cwd)
+      this.registerSessionMapping(newRemoteId, internalSessionId)
       return internalSessionId
     }
 
+    this.sessionCwds.set(newRemoteId, cwd)
+    this.flushBufferedUpdates(newRemoteId)
     return newRemoteId
   }
 
@@ -568,9 +680,26 @@ export class AcpClient extends EventEmitter {
     })
   }
 
+  /** Slash commands the agent last advertised for a session; empty until it does */
+  getAvailableCommands(sessionId: string): SlashCommand[] {
+    return this.availableCommands.get(sessionId) ?? []
+  }
+
+  /** Agent-side id for an internal session, if one has been mapped */
+  getRemoteSessionId(internalSessionId: string): string | undefined {
+    return this.internalToRemote.get(internalSessionId)
+  }
+
   /** Load an existing session (spec: session/load) */
-  async loadSession(sessionId: string, cwd: string, mcpServers: unknown[] = []): Promise<void> {
-    const remoteId = this.internalToRemote.get(sessionId) || sessionId
+  async loadSession(
+    sessionId: string,
+    cwd: string,
+    mcpServers: unknown[] = [],
+    remoteSessionId?: string
+  ): Promise<void> {
+    const remoteId = remoteSessionId || this.internalToRemote.get(sessionId) || sessionId
+    this.sessionCwds.set(sessionId, cwd)
+    this.registerSessionMapping(remoteId, sessionId)
     await this.sendRequest('session/load', {
       sessionId: remoteId,
       cwd,
@@ -579,8 +708,15 @@ export class AcpClient extends EventEmitter {
   }
 
   /** Resume an existing session (experimental: session/resume) */
-  async resumeSession(sessionId: string, cwd: string, mcpServers: unknown[] = []): Promise<void> {
-    const remoteId = this.internalToRemote.get(sessionId) || sessionId
+  async resumeSession(
+    sessionId: string,
+    cwd: string,
+    mcpServers: unknown[] = [],
+    remoteSessionId?: string
+  ): Promise<void> {
+    const remoteId = remoteSessionId || this.internalToRemote.get(sessionId) || sessionId
+    this.sessionCwds.set(sessionId, cwd)
+    this.registerSessionMapping(remoteId, sessionId)
     await this.sendRequest('session/resume', {
       sessionId: remoteId,
       cwd,
@@ -641,17 +777,40 @@ export class AcpClient extends EventEmitter {
     }
   }
 
-  /** Terminate the agent process */
-  terminate(): void {
-    if (this.childProcess) {
-      this.childProcess.kill('SIGTERM')
-      setTimeout(() => {
-        if (this.childProcess && !this.childProcess.killed) {
-          this.childProcess.kill('SIGKILL')
-        }
-      }, 5000)
-    }
+  /**
+   * Terminate the agent process. Closing stdin is the stdio transport's shutdown
+   * signal, so the agent gets `gracePeriodMs` to flush state and exit on its own
+   * before it is killed. Resolves once the process has exited.
+   */
+  terminate(gracePeriodMs: number = TERMINATE_GRACE_MS): Promise<void> {
+    this.terminating = true
     this.rejectAllPending(new Error('Agent terminated'))
+    this.unknownSessionUpdates.clear()
+
+    const child = this.childProcess
+    if (!child || child.exitCode !== null || child.signalCode !== null) {
+      return Promise.resolve()
+    }
+
+    return new Promise((resolve) => {
+      const timers: NodeJS.Timeout[] = []
+      child.once('exit', () => {
+        timers.forEach(clearTimeout)
+        resolve()
+      })
+
+      child.stdin?.end()
+      timers.push(
+        setTimeout(() => {
+          logger.info(`Agent ${this.agentId} did not exit within ${gracePeriodMs}ms, sending SIGTERM`)
+          child.kill('SIGTERM')
+        }, gracePeriodMs),
+        setTimeout(() => {
+          logger.warn(`Agent ${this.agentId} ignored SIGTERM, killing`)
+          child.kill('SIGKILL')
+        }, gracePeriodMs + FORCE_KILL_DELAY_MS)
+      )
+    })
   }
 
   get pid(): number | undefined {
@@ -713,7 +872,7 @@ export class AcpClient extends EventEmitter {
       const timeout = setTimeout(() => {
         this.pendingRequests.delete(id)
         this.requestMetadata.delete(id)
-        reject(new Error(`Request '${method}' timed out after ${timeoutMs}ms`))
+        reject(new AppError('TIMEOUT', `Request '${method}' timed out after ${timeoutMs}ms`, { method }, { timeoutMs }))
       }, timeoutMs)
 
       this.pendingRequests.set(id, {
@@ -813,7 +972,7 @@ export class AcpClient extends EventEmitter {
         this.pendingRequests.delete(responseId)
         this.requestMetadata.delete(responseId)
         if (msg.error) {
-          pending.reject(new Error(`ACP error ${msg.error.code}: ${msg.error.message}${msg.error.data ? ' | data: ' + JSON.stringify(msg.error.data) : ''}`))
+          pending.reject(parseAcpError(msg.error))
         } else {
           pending.resolve(msg.result)
         }
@@ -823,6 +982,11 @@ export class AcpClient extends EventEmitter {
 
     // Notification from agent (no id) or request from agent (has id + method)
     if (msg.method) {
+      if (!this.initialized) {
+        logger.debug(`[${this.agentId}] Holding ${msg.method} until initialize completes`)
+        this.preInitMessages.push(msg)
+        return
+      }
       this.handleAgentMethodCall(msg)
     }
   }
@@ -900,7 +1064,6 @@ export class AcpClient extends EventEmitter {
 
   private handleSessionUpdate(params: Record<string, unknown>): void {
     const remoteId = params.sessionId as string
-    const internalId = this.remoteToInternal.get(remoteId) || remoteId
 
     // ACP session/update notification structure:
     // { sessionId, update: { sessionUpdate: "agent_message_chunk", content: {...}, ... } }
@@ -911,14 +1074,53 @@ export class AcpClient extends EventEmitter {
       return
     }
 
+    // Updates for a session we haven't mapped yet (e.g. sent before session/new
+    // returned, or after a restart before resume) are held until it is known.
+    if (!this.isKnownSession(remoteId)) {
+      const buffered = this.unknownSessionUpdates.get(remoteId) || []
+      if (buffered.length >= MAX_BUFFERED_UPDATES_PER_SESSION) {
+        logger.debug(`[${this.agentId}] Dropping update for unknown session ${remoteId}: buffer full`)
+        return
+      }
+      logger.debug(`[${this.agentId}] Buffering update for unknown session ${remoteId}`)
+      buffered.push(update)
+      this.unknownSessionUpdates.set(remoteId, buffered)
+      return
+    }
+
+    this.dispatchSessionUpdate(remoteId, update)
+  }
+
+  private isKnownSession(remoteId: string): boolean {
+    return this.remoteToInternal.has(remoteId) || this.sessionCwds.has(remoteId)
+  }
+
+  /** Deliver updates that arrived before the session was known */
+  private flushBufferedUpdates(remoteId: string): void {
+    const buffered = this.unknownSessionUpdates.get(remoteId)
+    if (!buffered) return
+    this.unknownSessionUpdates.delete(remoteId)
+    logger.debug(`[${this.agentId}] Flushing ${buffered.length} buffered update(s) for session ${remoteId}`)
+    for (const update of buffered) {
+      this.dispatchSessionUpdate(remoteId, update)
+    }
+  }
+
+  private dispatchSessionUpdate(remoteId: string, update: Record<string, unknown>): void {
+    const internalId = this.remoteToInternal.get(remoteId) || remoteId
+
     try {
       // Transform to our SessionUpdate format
-      const sessionUpdate = this.transformSessionUpdate(update)
+      const sessionUpdate = this.transformSessionUpdate(update, internalId)
       const event: SessionUpdateEvent = {
         sessionId: internalId,
         update: sessionUpdate
       }
 
+      if (sessionUpdate.type === 'available_commands_update') {
+        this.availableCommands.set(internalId, sessionUpdate.commands)
+      }
+
       // Emit for SessionManagerService
       this.emit('session-update', event)
 
@@ -931,6 +1133,62 @@ export class AcpClient extends EventEmitter {
     }
   }
 
+  /**
+   * Normalize agent-provided tool call locations to { path, line, column, endLine, endColumn },
+   * accepting common range shapes and resolving relative paths against the session cwd.
+   */
+  private normalizeLocations(raw: unknown, sessionId: string): ToolCallLocation[] | undefined {
+    if (!Array.isArray(raw)) return undefined
+    const cwd = this.sessionCwds.get(sessionId) || this.cwd
+    const toNumber = (value: unknown): number | undefined =>
+      typeof value === 'number' && Number.isFinite(value) ? value : undefined
+
+    const locations: ToolCallLocation[] = []
+    for (const entry of raw) {
+      if (!entry || typeof entry !== 'object') continue
+      const loc = entry as Record<string, unknown>
+      const rawPath = (loc.path as string) || (loc.uri as string) || (loc.file as string)
+      if (!rawPath) continue
+
+      const filePath = rawPath.startsWith('file://') ? decodeURIComponent(rawPath.slice('file://'.length)) : rawPath
+      const range = loc.range as Record<string, Record<string, unknown>> | undefined
+      locations.push({
+        ...loc,
+        path: path.isAbsolute(filePath) ? filePath : path.resolve(cwd, filePath),
+        line: toNumber(loc.line) ?? toNumber(range?.start?.line),
+        column: toNumber(loc.column) ?? toNumber(range?.start?.column) ?? toNumber(range?.start?.character),
+        endLine: toNumber(loc.endLine) ?? toNumber(range?.end?.line),
+        endColumn: toNumber(loc.endColumn) ?? toNumber(range?.end?.column) ?? toNumber(range?.end?.character)
+      })
+    }
+    return locations
+  }
+
+  private toolCallKey(sessionId: string, raw: Record<string, unknown>, agentToolCallId: string): string {
+    return `${sessionId}\u0000${(raw.messageId as string) || 'current'}\u0000${agentToolCallId}`
+  }
+
+  /**
+   * Agents may send tool calls without a usable toolCallId. We generate one on
+   * tool_call and remember it under the agent's (possibly empty) id + messageId,
+   * so later tool_call_updates carrying that same id resolve to the same call.
+   */
+  private resolveToolCallId(
+    sessionId: string,
+    raw: Record<string, unknown>,
+    agentToolCallId: string,
+    isStart: boolean
+  ): string {
+    const key = this.toolCallKey(sessionId, raw, agentToolCallId)
+    if (isStart) {
+      if (agentToolCallId) return agentToolCallId
+      const generated = uuid()
+      this.generatedToolCallIds.set(key, generated)
+      return generated
+    }
+    return this.generatedToolCallIds.get(key) || agentToolCallId
+  }
+
   private extractText(raw: Record<string, unknown>): string {
     // ACP agents send text content in various shapes; try common patterns
     const content = raw.content as Record<string, unknown> | string | undefined
@@ -946,7 +1204,7 @@ export class AcpClient extends EventEmitter {
     return ''
   }
 
-  private transformSessionUpdate(raw: Record<string, unknown>): SessionUpdateEvent['update'] {
+  private transformSessionUpdate(raw: Record<string, unknown>, sessionId: string): SessionUpdateEvent['update'] {
     // ACP uses 'sessionUpdate' as the discriminator field, not 'type'
     const updateType = raw.sessionUpdate as string
 
@@ -989,7 +1247,7 @@ export class AcpClient extends EventEmitter {
       }
 
       case 'tool_call': {
-        const toolCallId = (raw.toolCallId as string) || uuid()
+        const toolCallId = this.resolveToolCallId(sessionId, raw, (raw.toolCallId as string) || '', true)
         const rawInput = raw.rawInput || raw.input
         const contentArr = raw.content as Array<Record<string, unknown>> | undefined
         // Extract diff from content array if present
@@ -1012,7 +1270,7 @@ export class AcpClient extends EventEmitter {
         const kind = (raw.kind as ToolCallKind) || undefined
 
         // ACP spec: locations for file-following
-        const locations = raw.locations as ToolCallLocation[] | undefined
+        const locations = this.normalizeLocations(raw.locations, sessionId)
 
         return {
           type: 'tool_call_start',
@@ -1033,13 +1291,15 @@ export class AcpClient extends EventEmitter {
 
       case 'tool_call_update': {
         const rawOutput = raw.rawOutput || raw.output
-        const locations = raw.locations as ToolCallLocation[] | undefined
+        const locations = this.normalizeLocations(raw.locations, sessionId)
         const toolCallObj = raw.toolCall as Record<string, unknown> | undefined
-        const resolvedToolCallId =
-          (raw.toolCallId as string) ||
-          (toolCallObj?.toolCallId as string) ||
-          (raw.id as string) ||
-          ''
+        const agentToolCallId =
+          (raw.toolCallId as string) || (toolCallObj?.toolCallId as string) || (raw.id as string) || ''
+        const resolvedToolCallId = this.resolveToolCallId(sessionId, raw, agentToolCallId, false)
+        // A finished call receives no further updates
+        if (raw.status === 'completed' || raw.status === 'failed') {
+          this.generatedToolCallIds.delete(this.toolCallKey(sessionId, raw, agentToolCallId))
+        }
         return {
           type: 'tool_call_update',
           toolCallId: resolvedToolCallId,
@@ -1208,16 +1468,18 @@ export class AcpClient extends EventEmitter {
       // Setup resolver BEFORE sending to renderer to avoid race conditions
       const responsePromise = new Promise<PermissionResponse>((resolve) => {
         let settled = false
+        let timeout: ReturnType<typeof setTimeout> | undefined
         const safeResolve = (response: PermissionResponse): void => {
           if (settled) return
           settled = true
+          if (timeout) clearTimeout(timeout)
           this.permissionResolvers.delete(requestId)
           resolve(response)
         }
         this.permissionResolvers.set(requestId, safeResolve)
 
         // Timeout after 5 minutes - cancel by default
-        setTimeout(() => {
+        timeout = setTimeout(() => {
           logger.warn(`[${this.agentId}] Permission request ${requestId} timed out.`)
           safeResolve({ requestId, optionId: '__cancelled__' })
         }, 5 * 60 * 1000)
@@ -1260,13 +1522,45 @@ export class AcpClient extends EventEmitter {
     }
   }
 
+  /**
+   * Resolve an fs/* path against the requesting session's working directory.
+   * Symlinks are followed so a link inside the directory cannot point outside it.
+   * Throws if the resolved path escapes that directory.
+   */
+  private resolveSessionPath(params: Record<string, unknown>): { sessionId: string; resolvedPath: string } {
+    const remoteId = params.sessionId as string
+    const sessionId = this.remoteToInternal.get(remoteId) || remoteId
+    const root = realpathExisting(path.resolve(this.sessionCwds.get(sessionId) || this.cwd))
+    const resolvedPath = realpathExisting(path.resolve(root, params.path as string))
+    const relative = path.relative(root, resolvedPath)
+    if (relative === '..' || relative.startsWith('..' + path.sep) || path.isAbsolute(relative)) {
+      throw new Error(`Path is outside the session working directory: ${params.path}`)
+    }
+    return { sessionId, resolvedPath }
+  }
+
+  private emitFileAccess(event: FileAccessEvent): void {
+    this.emit('file-access', event)
+    if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+      this.mainWindow.webContents.send('session:file-access', event)
+    }
+  }
+
   private handleReadFile(id: number, params: Record<string, unknown>): void {
     const filePath = params.path as string
     const line = params.line as number | undefined    // 1-based start line
     const limit = params.limit as number | undefined  // max lines to read
+
+    let target: { sessionId: string; resolvedPath: string }
     try {
-      const resolvedPath = path.resolve(this.cwd, filePath)
-      let content = fs.readFileSync(resolvedPath, 'utf-8')
+      target = this.resolveSessionPath(params)
+    } catch (err) {
+      this.sendError(id, -32602, (err as Error).message)
+      return
+    }
+
+    try {
+      let content = fs.readFileSync(target.resolvedPath, 'utf-8')
 
       // ACP spec: optional line/limit for partial reads
       if (line !== undefined || limit !== undefined) {
@@ -1277,19 +1571,28 @@ export class AcpClient extends EventEmitter {
       }
 
       this.sendResponse(id, { content })
+      this.emitFileAccess({ sessionId: target.sessionId, path: target.resolvedPath, operation: 'read' })
     } catch {
       this.sendError(id, -32002, `File not found: ${filePath}`)
     }
   }
 
   private handleWriteFile(id: number, params: Record<string, unknown>): void {
-    const filePath = params.path as string
     const content = (params.content || params.text) as string
+
+    let target: { sessionId: string; resolvedPath: string }
     try {
-      const resolvedPath = path.resolve(this.cwd, filePath)
-      fs.mkdirSync(path.dirname(resolvedPath), { recursive: true })
-      fs.writeFileSync(resolvedPath, content, 'utf-8')
+      target = this.resolveSessionPath(params)
+    } catch (err) {
+      this.sendError(id, -32602, (err as Error).message)
+      return
+    }
+
+    try {
+      fs.mkdirSync(path.dirname(target.resolvedPath), { recursive: true })
+      fs.writeFileSync(target.resolvedPath, content, 'utf-8')
       this.sendResponse(id, {})
+      this.emitFileAccess({ sessionId: target.sessionId, path: target.resolvedPath, operation: 'write' })
     } catch (err) {
       this.sendError(id, -32000, `Write failed: ${(err as Error).message}`)
     }
@@ -1320,8 +1623,10 @@ export class AcpClient extends EventEmitter {
         stdio: ['pipe', 'pipe', 'pipe']
       })
 
+      const remoteSessionId = params.sessionId as string | undefined
       const terminal: TerminalProcess = {
         process: proc,
+        sessionId: remoteSessionId ? this.remoteToInternal.get(remoteSessionId) || remoteSessionId : undefined,
         output: '',
         truncated: false,
         exitCode: null,
@@ -1431,10 +1736,29 @@ export class AcpClient extends EventEmitter {
     this.sendResponse(id, {})
   }
 
+  /** Kill and forget all terminals created for a session and drop its cached commands, e.g. when it is closed */
+  releaseSession(internalSessionId: string): void {
+    this.availableCommands.delete(internalSessionId)
+    for (const [terminalId, terminal] of Array.from(this.terminals)) {
+      if (terminal.sessionId !== internalSessionId) continue
+      if (!terminal.exited) {
+        terminal.process.kill('SIGKILL')
+      }
+      for (const r of terminal.waitResolvers) r()
+      terminal.waitResolvers = []
+      this.terminals.delete(terminalId)
+      logger.info(`Released terminal ${terminalId} for session ${internalSessionId}`)
+    }
+  }
+
   private rejectAllPending(error: Error): void {
     for (const [, pending] of this.pendingRequests) {
       pending.reject(error)
     }
+    // Settle outstanding permission prompts as cancelled so their handlers finish
+    for (const [requestId, resolver] of Array.from(this.permissionResolvers)) {
+      resolver({ requestId, optionId: '__cancelled__' })
+    }
     this.pendingRequests.clear()
     this.requestMetadata.clear()
     // Clean up terminals
@@ -1449,14 +1773,12 @@ export class AcpClient extends EventEmitter {
   private registerSessionMapping(remoteId: string, internalSessionId: string): void {
     this.remoteToInternal.set(remoteId, internalSessionId)
     this.internalToRemote.set(internalSessionId, remoteId)
+    this.flushBufferedUpdates(remoteId)
   }
 
   private isMethodNotFoundError(error: unknown): boolean {
-    if (!(error instanceof Error)) return false
-    return (
-      /ACP error -32601/i.test(error.message) ||
-      /Method not found/i.test(error.message) ||
-      /Method not implemented/i.test(error.message)
-    )
+    if (!(error instanceof AcpError)) return false
+    // Some agents report unimplemented methods under a generic code
+    return error.code === ACP_METHOD_NOT_FOUND || /Method not implemented/i.test(error.rpcMessage)
   }
 }
@@ -0,0 +1,428 @@
+import { spawn } from 'child_process'
+import { EventEmitter } from 'events'
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+import type { AcpRegistry, AcpRegistryAgent, AgentStatus, BinaryDistribution, InstalledAgent } from '@shared/types/agent'
+import type { BrowserWindow } from 'electron'
+import Store from 'electron-store'
+import type { AcpClient } from './acp-client'
+import { AgentManagerService, agentManager } from './agent-manager'
+import { downloadService } from './download-service'
+import { registryService } from './registry-service'
+import { settingsService } from './settings-service'
+import { getCurrentPlatformTarget } from '../util/platform'
+import { DISTRIBUTION_DEPENDENCIES, MissingDependencyError } from '../util/spawn-error'
+import { connectMockAgent, disconnectMockAgents } from '../test/mock-agent'
+
+/** The restart loop normally runs off a client's `crashed` event; tests drive it directly */
+interface Supervisor {
+  connections: Map<string, AcpClient>
+  superviseRestart(client: AcpClient, emitStatus: (status: AgentStatus) => void, reason: string): Promise<void>
+}
+
+type FakeClient = AcpClient & { restart: ReturnType<typeof vi.fn> }
+
+/** A crashed client whose restart attempts succeed or fail in the given order */
+function crashedClient(outcomes: boolean[]): FakeClient {
+  const client = Object.assign(new EventEmitter(), {
+    agentId: 'mock-agent',
+    connectionId: 'conn-1',
+    pid: undefined,
+    isRunning: false,
+    restart: vi.fn(async () => {
+      if (!outcomes.shift()) throw new Error('spawn failed')
+    }),
+    terminate: vi.fn(async () => {})
+  })
+  return client as unknown as FakeClient
+}
+
+interface Installed {
+  installed: Map<string, InstalledAgent>
+  connections: Map<string, AcpClient>
+}
+
+type InstalledFields = Partial<InstalledAgent> & Pick<InstalledAgent, 'registryId' | 'version' | 'distributionType'>
+
+function installAgent(manager: AgentManagerService, agent: InstalledFields): void {
+  ;(manager as unknown as Installed).installed.set(agent.registryId, {
+    name: agent.registryId,
+    description: '',
+    installedAt: new Date(0).toISOString(),
+    icon: '',
+    authors: [],
+    license: 'MIT',
+    ...agent
+  })
+}
+
+function registryAgent(id: string, version: string, distribution: AcpRegistryAgent['distribution']): AcpRegistryAgent {
+  return { id, name: id, version, description: '', authors: [], license: 'MIT', icon: '', distribution }
+}
+
+/** Serve `agents` (and `extensions`) as both the cached and the freshly fetched registry */
+function stubRegistry(agents: AcpRegistryAgent[], extensions: unknown[] = []): void {
+  const registry: AcpRegistry = { version: '1', agents, extensions }
+  vi.spyOn(registryService, 'getCached').mockReturnValue(registry)
+  vi.spyOn(registryService, 'fetch').mockResolvedValue(registry)
+}
+
+describe('restart supervision', () => {
+  let manager: AgentManagerService
+  let supervisor: Supervisor
+  let statuses: AgentStatus[]
+
+  beforeEach(() => {
+    vi.useFakeTimers()
+    manager = new AgentManagerService()
+    supervisor = manager as unknown as Supervisor
+    statuses = []
+  })
+
+  afterEach(() => {
+    manager.terminate('conn-1')
+    vi.useRealTimers()
+  })
+
+  function supervise(client: FakeClient): Promise<void> {
+    supervisor.connections.set(client.connectionId, client)
+    return supervisor.superviseRestart(client, (status) => statuses.push(status), 'exit code 1')
+  }
+
+  it('retries with exponential backoff until a restart succeeds', async () => {
+    const client = crashedClient([false, false, true])
+    const done = supervise(client)
+
+    await vi.advanceTimersByTimeAsync(999)
+    expect(client.restart).toHaveBeenCalledTimes(0)
+    await vi.advanceTimersByTimeAsync(1)
+    expect(client.restart).toHaveBeenCalledTimes(1)
+    await vi.advanceTimersByTimeAsync(1999)
+    expect(client.restart).toHaveBeenCalledTimes(1)
+    await vi.advanceTimersByTimeAsync(1)
+    expect(client.restart).toHaveBeenCalledTimes(2)
+    await vi.advanceTimersByTimeAsync(4000)
+    await done
+
+    expect(client.restart).toHaveBeenCalledTimes(3)
+    expect(statuses).toEqual(['crashed', 'connected'])
+    expect(manager.getClient('conn-1')).toBe(client)
+  })
+
+  it('gives up after three attempts and drops the connection', async () => {
+    const client = crashedClient([false, false, false])
+    const failed = vi.fn()
+    client.on('failed', failed)
+    const done = supervise(client)
+
+    await vi.advanceTimersByTimeAsync(7000)
+    await done
+
+    expect(client.restart).toHaveBeenCalledTimes(3)
+    expect(statuses).toEqual(['crashed', 'failed'])
+    expect(manager.getClient('conn-1')).toBeUndefined()
+    expect(manager.listConnections()).toEqual([])
+    expect(failed).toHaveBeenCalledWith('Agent did not recover after 3 restart attempts')
+  })
+
+  it('stops retrying once the connection is terminated', async () => {
+    const client = crashedClient([true])
+    const done = supervise(client)
+
+    manager.terminate('conn-1')
+    await vi.advanceTimersByTimeAsync(1000)
+    await done
+
+    expect(client.restart).not.toHaveBeenCalled()
+    expect(statuses).toEqual(['crashed'])
+  })
+})
+
+describe('process info', () => {
+  afterEach(async () => {
+    await disconnectMockAgents()
+  })
+
+  it('reports the pid and start time of a launched agent', async () => {
+    const client = await connectMockAgent()
+
+    const info = agentManager.getProcessInfo(client.connectionId)
+
+    expect(info?.pid).toBeGreaterThan(0)
+    // Signal 0 only checks that the process exists
+    expect(() => process.kill(info!.pid!, 0)).not.toThrow()
+    expect(info?.running).toBe(true)
+    expect(Date.parse(info!.startedAt)).not.toBeNaN()
+    expect(info?.uptimeMs).toBeGreaterThanOrEqual(0)
+    expect(agentManager.listConnections()).toContainEqual(
+      expect.objectContaining({ connectionId: client.connectionId, pid: info?.pid, startedAt: info?.startedAt })
+    )
+  })
+
+  it('returns null for an unknown connection', () => {
+    expect(agentManager.getProcessInfo('missing')).toBeNull()
+  })
+})
+
+describe('resource sampling', () => {
+  interface Sampler {
+    connections: Map<string, AcpClient>
+    startResourceSampling(client: AcpClient): void
+    sampleResources(client: AcpClient, sampler: unknown): Promise<void>
+  }
+
+  let manager: AgentManagerService
+  let sampler: Sampler
+  let child: ReturnType<typeof spawn>
+
+  beforeEach(() => {
+    // Only the sampling interval is faked; ps still runs for real
+    vi.useFakeTimers({ toFake: ['setInterval', 'clearInterval'] })
+    manager = new AgentManagerService()
+    sampler = manager as unknown as Sampler
+    child = spawn('sleep', ['30'], { stdio: 'ignore' })
+  })
+
+  afterEach(() => {
+    child.kill()
+    vi.useRealTimers()
+  })
+
+  /** A connection backed by the sleep process, sampled by the manager */
+  function sampledClient(): AcpClient {
+    const client = Object.assign(new EventEmitter(), {
+      agentId: 'sleepy-agent',
+      connectionId: 'conn-sleep',
+      pid: child.pid,
+      isRunning: true,
+      terminate: vi.fn(async () => {
+        child.kill()
+      })
+    }) as unknown as AcpClient
+    sampler.connections.set(client.connectionId, client)
+    sampler.startResourceSampling(client)
+    return client
+  }
+
+  async function firstSample(): Promise<void> {
+    vi.advanceTimersByTime(5000)
+    await vi.waitFor(() => expect(manager.getResourceUsage('conn-sleep')).not.toBeNull(), { timeout: 5000 })
+  }
+
+  it.skipIf(process.platform === 'win32')('samples the process until the connection is terminated', async () => {
+    sampledClient()
+    const samples = vi.spyOn(sampler, 'sampleResources')
+
+    await firstSample()
+    expect(manager.getResourceUsage('conn-sleep')).toMatchObject({ connectionId: 'conn-sleep', pid: child.pid })
+    expect(manager.getResourceUsage('conn-sleep')!.memoryBytes).toBeGreaterThan(0)
+
+    manager.terminate('conn-sleep')
+    const taken = samples.mock.calls.length
+    vi.advanceTimersByTime(20000)
+
+    expect(samples.mock.calls.length).toBe(taken)
+    expect(manager.getResourceUsage('conn-sleep')).toBeNull()
+    expect(vi.getTimerCount()).toBe(0)
+  })
+
+  it.skipIf(process.platform === 'win32')('stops sampling when the agent is uninstalled', async () => {
+    const client = sampledClient()
+
+    await firstSample()
+    manager.uninstall('sleepy-agent')
+
+    expect(client.terminate).toHaveBeenCalled()
+    expect(manager.getClient('conn-sleep')).toBeUndefined()
+    expect(manager.getResourceUsage('conn-sleep')).toBeNull()
+    expect(vi.getTimerCount()).toBe(0)
+  })
+})
+
+describe('agent updates', () => {
+  let manager: AgentManagerService
+
+  beforeEach(() => {
+    manager = new AgentManagerService()
+  })
+
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('reports which installed agents have a different registry version', () => {
+    installAgent(manager, { registryId: 'npx-agent', version: '1.0.0', distributionType: 'npx', npxPackage: 'npx-agent@1.0.0' })
+    installAgent(manager, { registryId: 'rolled-back', version: '2.0.0', distributionType: 'npx', npxPackage: 'rolled-back@2.0.0' })
+    installAgent(manager, { registryId: 'old-binary', version: '1.9.0', distributionType: 'binary' })
+    installAgent(manager, { registryId: 'new-binary', version: '1.10.0', distributionType: 'binary' })
+    installAgent(manager, { registryId: 'delisted', version: '1.0.0', distributionType: 'npx' })
+    stubRegistry([
+      registryAgent('npx-agent', '1.1.0', { npx: { package: 'npx-agent@1.1.0' } }),
+      registryAgent('rolled-back', '1.5.0', { npx: { package: 'rolled-back@1.5.0' } }),
+      registryAgent('old-binary', '1.10.0', { binary: {} }),
+      registryAgent('new-binary', '1.9.0', { binary: {} })
+    ])
+
+    expect(manager.checkUpdates()).toEqual([
+      { agentId: 'npx-agent', installedVersion: '1.0.0', latestVersion: '1.1.0', updateAvailable: true },
+      // The registry is authoritative for npx/uvx, even when it goes back
+      { agentId: 'rolled-back', installedVersion: '2.0.0', latestVersion: '1.5.0', updateAvailable: true },
+      { agentId: 'old-binary', installedVersion: '1.9.0', latestVersion: '1.10.0', updateAvailable: true },
+      { agentId: 'new-binary', installedVersion: '1.10.0', latestVersion: '1.9.0', updateAvailable: false }
+    ])
+  })
+
+  it('reinstalls an npx agent at the bumped version and terminates its connections', async () => {
+    installAgent(manager, { registryId: 'npx-agent', version: '1.0.0', distributionType: 'npx', npxPackage: 'npx-agent@1.0.0' })
+    stubRegistry([registryAgent('npx-agent', '1.1.0', { npx: { package: 'npx-agent@1.1.0' } })])
+    const client = Object.assign(new EventEmitter(), {
+      agentId: 'npx-agent',
+      connectionId: 'conn-npx',
+      terminate: vi.fn(async () => {})
+    }) as unknown as AcpClient
+    ;(manager as unknown as Installed).connections.set(client.connectionId, client)
+
+    const updated = await manager.update('npx-agent')
+
+    expect(updated).toMatchObject({ version: '1.1.0', npxPackage: 'npx-agent@1.1.0' })
+    expect(manager.listInstalled()).toEqual([updated])
+    expect(client.terminate).toHaveBeenCalled()
+    expect(manager.getClient('conn-npx')).toBeUndefined()
+  })
+
+  it('downloads a newer binary and prunes the old version', async () => {
+    const platform = getCurrentPlatformTarget()
+    if (!platform) return
+    installAgent(manager, { registryId: 'bin-agent', version: '1.0.0', distributionType: 'binary', executablePath: '/old/agent' })
+    const binary: BinaryDistribution = {}
+    binary[platform] = { archive: 'https://example.invalid/a.tar.gz', cmd: 'agent' }
+    stubRegistry([registryAgent('bin-agent', '1.1.0', { binary })])
+    const download = vi.spyOn(downloadService, 'downloadAndExtract').mockResolvedValue('/new/agent')
+    const prune = vi.spyOn(downloadService, 'pruneOldVersions').mockReturnValue(0)
+
+    const updated = await manager.update('bin-agent')
+
+    expect(download).toHaveBeenCalledWith('bin-agent', '1.1.0', expect.objectContaining({ cmd: 'agent' }), expect.any(Function))
+    expect(updated).toMatchObject({ version: '1.1.0', executablePath: '/new/agent' })
+    expect(prune).toHaveBeenCalledWith('bin-agent', '1.1.0', '/new/agent')
+  })
+
+  it('leaves an up-to-date agent alone', async () => {
+    installAgent(manager, { registryId: 'bin-agent', version: '1.1.0', distributionType: 'binary', executablePath: '/current/agent' })
+    stubRegistry([registryAgent('bin-agent', '1.1.0', { binary: {} })])
+    const download = vi.spyOn(downloadService, 'downloadAndExtract')
+
+    const result = await manager.update('bin-agent')
+
+    expect(result.executablePath).toBe('/current/agent')
+    expect(download).not.toHaveBeenCalled()
+  })
+})
+
+describe('launch dependency check', () => {
+  let manager: AgentManagerService
+
+  beforeEach(() => {
+    manager = new AgentManagerService()
+    stubRegistry([])
+  })
+
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  /** Stand-in main window recording every status change */
+  function watchStatus(): unknown[] {
+    const sent: unknown[] = []
+    manager.setMainWindow({
+      isDestroyed: () => false,
+      webContents: { send: (channel: string, payload: unknown) => channel === 'agent:status-change' && sent.push(payload) }
+    } as unknown as BrowserWindow)
+    return sent
+  }
+
+  it.each([
+    ['npx', 'node', { node: false, npx: false }],
+    ['npx', 'npx', { node: true, npx: false }],
+    ['uvx', 'uv', { uv: false, uvx: true }]
+  ] as const)('refuses to launch a %s agent without %s', async (distributionType, tool, found) => {
+    installAgent(manager, { registryId: 'dep-agent', version: '1.0.0', distributionType, npxPackage: 'pkg', uvxPackage: 'pkg' })
+    const lookups = vi.spyOn(manager, 'detectCliCommands').mockReturnValue({ ...found })
+    const statuses = watchStatus()
+
+    const launch = manager.launch('dep-agent', process.cwd())
+
+    await expect(launch).rejects.toBeInstanceOf(MissingDependencyError)
+    await expect(launch).rejects.toMatchObject({ agentId: 'dep-agent', tool })
+    expect(lookups).toHaveBeenCalledWith(DISTRIBUTION_DEPENDENCIES[distributionType].map((d) => d.tool))
+    expect(statuses).toEqual([expect.objectContaining({ agentId: 'dep-agent', status: 'missing-dependency' })])
+    expect(manager.listConnections()).toEqual([])
+  })
+
+  it('skips the check for binary agents', async () => {
+    installAgent(manager, { registryId: 'bin-agent', version: '1.0.0', distributionType: 'binary', executablePath: '/missing/agent' })
+    const lookups = vi.spyOn(manager, 'detectCliCommands')
+
+    await expect(manager.launch('bin-agent', process.cwd())).rejects.not.toBeInstanceOf(MissingDependencyError)
+    expect(lookups).not.toHaveBeenCalled()
+  })
+})
+
+describe('registry extensions', () => {
+  const TOOLS = {
+    id: 'tool-pack',
+    name: 'Tool Pack',
+    description: 'Extra tools',
+    agents: ['npx-agent'],
+    env: { TOOL_PACK: '1', SHARED: 'extension' },
+    args: ['--tools'],
+    mcpServers: [{ name: 'tools', command: 'tools-mcp' }]
+  }
+  let manager: AgentManagerService
+
+  beforeEach(() => {
+    manager = new AgentManagerService()
+    installAgent(manager, { registryId: 'npx-agent', version: '1.0.0', distributionType: 'npx', npxPackage: 'npx-agent' })
+    installAgent(manager, { registryId: 'other-agent', version: '1.0.0', distributionType: 'npx', npxPackage: 'other-agent' })
+    stubRegistry([], [TOOLS, { id: 'anywhere', name: 'Anywhere', env: { ANYWHERE: '1' } }, { description: 'no id or name' }])
+  })
+
+  afterEach(() => {
+    vi.restoreAllMocks()
+    new Store({ name: 'settings' }).clear()
+  })
+
+  it('lists typed extensions, skipping entries without an id', async () => {
+    const extensions = await registryService.listExtensions()
+
+    expect(extensions.map((ext) => ext.id)).toEqual(['tool-pack', 'anywhere'])
+    expect(extensions[1]).toMatchObject({ name: 'Anywhere', description: '', agents: undefined })
+  })
+
+  it('enables an extension once and persists it in the agent settings', async () => {
+    expect(await manager.installExtension('npx-agent', 'tool-pack')).toEqual(['tool-pack'])
+    expect(await manager.installExtension('npx-agent', 'tool-pack')).toEqual(['tool-pack'])
+    expect(await manager.installExtension('npx-agent', 'anywhere')).toEqual(['tool-pack', 'anywhere'])
+
+    expect(settingsService.getAgentSettings('npx-agent')?.extensions).toEqual(['tool-pack', 'anywhere'])
+    expect(manager.uninstallExtension('npx-agent', 'tool-pack')).toEqual(['anywhere'])
+  })
+
+  it('refuses an uninstalled agent, an unknown extension and an unsupported agent', async () => {
+    await expect(manager.installExtension('missing-agent', 'tool-pack')).rejects.toThrow('Agent not installed: missing-agent')
+    await expect(manager.installExtension('npx-agent', 'missing')).rejects.toThrow('Extension not found in registry: missing')
+    await expect(manager.installExtension('other-agent', 'tool-pack')).rejects.toThrow(
+      "Extension 'Tool Pack' does not support agent other-agent (supports: npx-agent)"
+    )
+    expect(settingsService.getAgentSettings('other-agent')?.extensions).toBeUndefined()
+  })
+
+  it('adds enabled extensions to the launch env, below the user env', async () => {
+    settingsService.setAgentSettings('npx-agent', { customEnv: { SHARED: 'user' } })
+    expect(manager.getAgentEnv('npx-agent')).toEqual({ SHARED: 'user' })
+
+    await manager.installExtension('npx-agent', 'tool-pack')
+
+    expect(manager.getAgentEnv('npx-agent')).toEqual({ TOOL_PACK: '1', SHARED: 'user' })
+    expect(manager.getAgentEnv('other-agent')).toEqual({})
+  })
+})
@@ -1,7 +1,8 @@
 import { BrowserWindow } from 'electron'
 import { execSync } from 'child_process'
-import { existsSync } from 'fs'
+import { existsSync, rmSync } from 'fs'
 import { homedir } from 'os'
+import path from 'path'
 import { v4 as uuid } from 'uuid'
 import type {
   AcpRegistryAgent,
@@ -11,16 +12,57 @@ import type {
   AgentStatus,
   BinaryDistribution,
   AuthMethod,
+  AgentCapabilities,
   AgentModelCatalog,
-  AgentModeCatalog
+  AgentModeCatalog,
+  AgentProcessInfo,
+  AgentResourceUsage,
+  AgentUpdateInfo
 } from '@shared/types/agent'
 import { getApiKeyEnvVarsForAgent, getModelArgForAgent, getModelEnvVarsForAgent } from '@shared/config/agent-env'
 import { registryService } from './registry-service'
 import { settingsService } from './settings-service'
 import { downloadService } from './download-service'
 import { AcpClient } from './acp-client'
-import { getCurrentPlatformTarget, getNpxCommand, getUvxCommand, toWslPath } from '../util/platform'
+import { getAgentsDir } from '../util/paths'
+import { buildWslSpawn, getCurrentPlatformTarget, getNpxCommand, getUvxCommand } from '../util/platform'
+import { readProcessStats } from '../util/process-stats'
+import type { ProcessStats } from '../util/process-stats'
+import { AgentSpawnError, DISTRIBUTION_DEPENDENCIES, MissingDependencyError } from '../util/spawn-error'
 import { logger } from '../util/logger'
+import { AppError } from '../util/app-error'
+
+const MAX_RESTART_ATTEMPTS = 3
+const RESTART_BASE_DELAY_MS = 1000
+const RESOURCE_SAMPLE_INTERVAL_MS = 5000
+
+/**
+ * Compare dotted versions numerically (1.10.0 > 1.9.2), ignoring a leading
+ * "v"; a pre-release sorts before its release. Returns <0, 0 or >0.
+ */
+function compareVersions(a: string, b: string): number {
+  const parse = (v: string): { parts: number[]; pre: string } => {
+    const [core, pre = ''] = v.trim().replace(/^v/i, '').split('-', 2)
+    return { parts: core.split('.').map((p) => parseInt(p, 10) || 0), pre }
+  }
+  const left = parse(a)
+  const right = parse(b)
+  for (let i = 0; i < Math.max(left.parts.length, right.parts.length); i++) {
+    const diff = (left.parts[i] ?? 0) - (right.parts[i] ?? 0)
+    if (diff !== 0) return diff
+  }
+  if (left.pre === right.pre) return 0
+  if (!left.pre) return 1
+  if (!right.pre) return -1
+  return left.pre < right.pre ? -1 : 1
+}
+
+/** Periodic CPU/memory sampling state for one connection */
+interface ResourceSampler {
+  timer: ReturnType<typeof setInterval>
+  previous?: ProcessStats & { at: number }
+  latest?: AgentResourceUsage
+}
 
 /**
  * AgentManager handles the full agent lifecycle:
@@ -33,6 +75,9 @@ import { logger } from '../util/logger'
 export class AgentManagerService {
   private installed = new Map<string, InstalledAgent>()
   private connections = new Map<string, AcpClient>()
+  private resourceSamplers = new Map<string, ResourceSampler>()
+  /** Running restart loop per connection; a crash while one runs is part of it */
+  private supervisors = new Map<string, Promise<void>>()
   private mainWindow: BrowserWindow | null = null
 
   constructor() {
@@ -55,7 +100,7 @@ export class AgentManagerService {
     const registry = await registryService.fetch()
     const agent = registry.agents.find((a) => a.id === agentId)
     if (!agent) {
-      throw new Error(`Agent not found in registry: ${agentId}`)
+      throw new AppError('AGENT_NOT_FOUND', `Agent not found in registry: ${agentId}`, { agentId })
     }
 
     let installed: InstalledAgent
@@ -70,13 +115,78 @@ export class AgentManagerService {
       throw new Error(`No supported distribution method for agent: ${agentId}`)
     }
 
-    this.installed.set(agentId, installed)
-    this.saveInstalled()
-
+    this.storeInstalled(installed)
+    if (installed.distributionType === 'binary') {
+      downloadService.pruneOldVersions(agentId, installed.version, installed.executablePath)
+    }
     logger.info(`Agent installed: ${installed.name} (${installed.distributionType})`)
     return installed
   }
 
+  /**
+   * Switch an installed agent to another distribution the registry offers
+   * (e.g. npx -> binary). Downloads the binary when switching to it, and removes
+   * downloaded binaries when switching away. Running connections keep the old
+   * command until relaunched.
+   */
+  async switchDistribution(
+    agentId: string,
+    distributionType: InstalledAgent['distributionType']
+  ): Promise<InstalledAgent> {
+    const current = this.installed.get(agentId)
+    if (!current) {
+      throw new Error(`Agent not installed: ${agentId}`)
+    }
+    if (current.distributionType === distributionType) {
+      return current
+    }
+
+    const registry = await registryService.fetch()
+    const agent = registry.agents.find((a) => a.id === agentId)
+    if (!agent) {
+      throw new AppError('AGENT_NOT_FOUND', `Agent not found in registry: ${agentId}`, { agentId })
+    }
+
+    let installed: InstalledAgent
+    if (distributionType === 'npx') {
+      if (!agent.distribution.npx) throw new Error(`${agent.name} is not distributed via npx`)
+      installed = this.installNpx(agent)
+    } else if (distributionType === 'uvx') {
+      if (!agent.distribution.uvx) throw new Error(`${agent.name} is not distributed via uvx`)
+      installed = this.installUvx(agent)
+    } else {
+      if (!agent.distribution.binary) throw new Error(`${agent.name} is not distributed as a binary`)
+      installed = await this.installBinary(agent)
+    }
+
+    this.storeInstalled(installed)
+
+    if (current.distributionType === 'binary') {
+      try {
+        rmSync(path.join(getAgentsDir(), agentId), { recursive: true, force: true })
+      } catch (err) {
+        logger.warn(`Failed to remove binaries for ${agentId}:`, err)
+      }
+    }
+
+    logger.info(`Agent ${agentId} switched from ${current.distributionType} to ${distributionType}`)
+    return installed
+  }
+
+  private storeInstalled(installed: InstalledAgent): void {
+    // Keep cached capabilities only if the version didn't change; otherwise they
+    // are refreshed on the next launch.
+    const previous = this.installed.get(installed.registryId)
+    if (previous?.capabilities && previous.version === installed.version) {
+      installed.capabilities = previous.capabilities
+      installed.authMethods = previous.authMethods
+      installed.capabilitiesUpdatedAt = previous.capabilitiesUpdatedAt
+    }
+
+    this.installed.set(installed.registryId, installed)
+    this.saveInstalled()
+  }
+
   private installNpx(agent: AcpRegistryAgent): InstalledAgent {
     return {
       registryId: agent.id,
@@ -122,7 +232,16 @@ export class AgentManagerService {
     const executablePath = await downloadService.downloadAndExtract(
       agent.id,
       agent.version,
-      target
+      target,
+      (downloadedBytes, totalBytes) => {
+        if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+          this.mainWindow.webContents.send('agent:download-progress', {
+            agentId: agent.id,
+            downloadedBytes,
+            totalBytes
+          })
+        }
+      }
     )
 
     return {
@@ -143,8 +262,7 @@ export class AgentManagerService {
     // Terminate any active connections first
     for (const [connId, client] of this.connections) {
       if (client.agentId === agentId) {
-        client.terminate()
-        this.connections.delete(connId)
+        this.terminate(connId)
       }
     }
 
@@ -153,10 +271,110 @@ export class AgentManagerService {
     logger.info(`Agent uninstalled: ${agentId}`)
   }
 
+  /**
+   * Enable a registry extension for an installed agent. It takes effect on the
+   * next launch (env/args) and the next session (MCP servers).
+   */
+  async installExtension(agentId: string, extensionId: string): Promise<string[]> {
+    if (!this.installed.has(agentId)) {
+      throw new Error(`Agent not installed: ${agentId}`)
+    }
+    const extension = (await registryService.listExtensions()).find((ext) => ext.id === extensionId)
+    if (!extension) {
+      throw new Error(`Extension not found in registry: ${extensionId}`)
+    }
+    if (extension.agents && !extension.agents.includes(agentId)) {
+      throw new Error(`Extension '${extension.name}' does not support agent ${agentId} (supports: ${extension.agents.join(', ')})`)
+    }
+
+    const current = settingsService.getAgentSettings(agentId)?.extensions ?? []
+    const extensions = current.includes(extensionId) ? current : [...current, extensionId]
+    settingsService.setAgentSettings(agentId, { extensions })
+    logger.info(`Extension ${extensionId} enabled for ${agentId}`)
+    return extensions
+  }
+
+  /** Disable a previously enabled extension */
+  uninstallExtension(agentId: string, extensionId: string): string[] {
+    const current = settingsService.getAgentSettings(agentId)?.extensions ?? []
+    const extensions = current.filter((id) => id !== extensionId)
+    settingsService.setAgentSettings(agentId, { extensions })
+    logger.info(`Extension ${extensionId} disabled for ${agentId}`)
+    return extensions
+  }
+
   listInstalled(): InstalledAgent[] {
     return Array.from(this.installed.values())
   }
 
+  /**
+   * Compare installed agents against the cached registry. npx/uvx agents run
+   * whatever the registry names, so any version difference counts as an
+   * update; binaries only update to a newer version.
+   */
+  checkUpdates(): AgentUpdateInfo[] {
+    const registry = registryService.getCached()
+    if (!registry) return []
+
+    const updates: AgentUpdateInfo[] = []
+    for (const installed of this.installed.values()) {
+      const agent = registry.agents.find((a) => a.id === installed.registryId)
+      if (!agent) continue
+      const diff = compareVersions(agent.version, installed.version)
+      updates.push({
+        agentId: installed.registryId,
+        installedVersion: installed.version,
+        latestVersion: agent.version,
+        updateAvailable: installed.distributionType === 'binary' ? diff > 0 : diff !== 0
+      })
+    }
+    return updates
+  }
+
+  /**
+   * Reinstall an agent from its current registry entry, keeping its
+   * distribution type. Running connections are terminated since they use the
+   * old version. Returns the agent unchanged when it is already up to date.
+   */
+  async update(agentId: string): Promise<InstalledAgent> {
+    const current = this.installed.get(agentId)
+    if (!current) {
+      throw new Error(`Agent not installed: ${agentId}`)
+    }
+
+    const registry = await registryService.fetch()
+    const agent = registry.agents.find((a) => a.id === agentId)
+    if (!agent) {
+      throw new AppError('AGENT_NOT_FOUND', `Agent not found in registry: ${agentId}`, { agentId })
+    }
+    const diff = compareVersions(agent.version, current.version)
+    if (current.distributionType === 'binary' ? diff <= 0 : diff === 0) {
+      return current
+    }
+
+    let installed: InstalledAgent
+    if (current.distributionType === 'npx' && agent.distribution.npx) {
+      installed = this.installNpx(agent)
+    } else if (current.distributionType === 'uvx' && agent.distribution.uvx) {
+      installed = this.installUvx(agent)
+    } else if (current.distributionType === 'binary' && agent.distribution.binary) {
+      installed = await this.installBinary(agent)
+    } else {
+      throw new Error(`${agent.name} is no longer distributed via ${current.distributionType}`)
+    }
+
+    for (const [connectionId, client] of this.connections) {
+      if (client.agentId === agentId) this.terminate(connectionId)
+    }
+
+    this.storeInstalled(installed)
+    if (installed.distributionType === 'binary') {
+      downloadService.pruneOldVersions(agentId, installed.version, installed.executablePath)
+    }
+    logger.info(`Agent ${agentId} updated from ${current.version} to ${installed.version}`)
+    return installed
+  }
+
   // ============================
   // Launching & Connection
   // ============================
@@ -183,27 +401,8 @@ export class AgentManagerService {
 
     // Get agent-specific settings
     const agentSettings = settingsService.getAgentSettings(agentId)
-    const finalEnv: Record<string, string> = { ...env }
-
-    // Add mapped API key env vars for this agent.
-    for (const envVarName of getApiKeyEnvVarsForAgent(agentId)) {
-      const mappedValue = this.resolveMappedApiKeyValue(agentId, agentSettings, envVarName)
-      if (mappedValue) {
-        finalEnv[envVarName] = mappedValue
-      }
-    }
-
-    // Add mapped model env vars for this agent.
-    if (agentSettings?.model) {
-      for (const envVarName of getModelEnvVarsForAgent(agentId)) {
-        finalEnv[envVarName] = agentSettings.model
-      }
-    }
-
-    // Merge custom env
-    if (agentSettings?.customEnv) {
-      Object.assign(finalEnv, agentSettings.customEnv)
-    }
+    const extensions = registryService.getCachedExtensions(agentSettings?.extensions ?? [])
+    const finalEnv: Record<string, string> = { ...env, ...this.getAgentEnv(agentId) }
 
     // Merge extra env (e.g. from env_var auth method) with blocklist
     if (extraEnv) {
@@ -222,7 +421,7 @@ export class AgentManagerService {
     }
 
     // Add custom args
-    let finalArgs = [...args, ...(agentSettings?.customArgs || [])]
+    let finalArgs = [...args, ...extensions.flatMap((ext) => ext.args ?? []), ...(agentSettings?.customArgs || [])]
 
     // Add mapped model CLI arg for agents that require startup model selection.
     if (agentSettings?.model) {
@@ -235,51 +434,57 @@ export class AgentManagerService {
     // Determine spawn parameters (potentially wrapped for WSL)
     let spawnCommand = command
     let spawnArgs = finalArgs
-    let spawnCwd = projectPath
+    let spawnEnv = finalEnv
+    // wsl.exe itself runs on Windows, so its cwd stays the Windows project path
+    const spawnCwd = projectPath
     let useWsl = false
 
     if (process.platform === 'win32' && agentSettings?.runInWsl) {
       useWsl = true
-      const wslCwd = toWslPath(projectPath)
+      const wsl = buildWslSpawn(command, finalArgs, projectPath, finalEnv, agentSettings.wslDistribution)
+      spawnCommand = wsl.command
+      spawnArgs = wsl.args
+      spawnEnv = wsl.env
 
-      // Use wsl.exe --env flags to pass env vars safely (no shell interpolation)
-      const wslArgs: string[] = []
-      if (agentSettings.wslDistribution) {
-        wslArgs.push('--distribution', agentSettings.wslDistribution)
-      }
-      for (const [k, v] of Object.entries(finalEnv)) {
-        // Validate key is alphanumeric + underscore only
-        if (!/^[A-Za-z_][A-Za-z0-9_]*$/.test(k)) {
-          logger.warn(`Skipping invalid environment variable name: ${k}`)
-          continue
+      logger.info(`WSL spawn: wsl ${spawnArgs.join(' ')} (WSLENV=${spawnEnv.WSLENV || ''})`)
+    }
+
+    // npx/uvx agents fail deep inside spawn when node/uv is missing; check up front.
+    // No connection exists yet, so the status is reported against the agent.
+    if (!useWsl) {
+      const missing = this.findMissingDependency(agent.distributionType)
+      if (missing) {
+        const error = new MissingDependencyError(agentId, missing.tool, missing.hint)
+        logger.warn(error.message)
+        if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+          this.mainWindow.webContents.send('agent:status-change', {
+            agentId,
+            status: 'missing-dependency',
+            error: error.message,
+            hint: missing.hint
+          })
         }
-        wslArgs.push('--env', `${k}=${v}`)
+        throw error
       }
-      wslArgs.push('--cd', wslCwd, '--', command, ...finalArgs)
-
-      spawnCommand = 'wsl'
-      spawnArgs = wslArgs
-      // cwd doesn't matter for wsl.exe, but keep a valid Windows dir
-      spawnCwd = projectPath
-
-      logger.info(`WSL spawn: wsl ${spawnArgs.join(' ')}`)
     }
 
     logger.info(`Launching ${agentId} with command: ${spawnCommand} ${spawnArgs.join(' ')}`)
 
     // Create ACP client
-    const client = new AcpClient(agentId, spawnCommand, spawnArgs, useWsl ? {} : finalEnv, spawnCwd, useWsl)
+    const client = new AcpClient(agentId, spawnCommand, spawnArgs, spawnEnv, spawnCwd, useWsl)
     if (this.mainWindow) {
       client.setMainWindow(this.mainWindow)
     }
 
     // Update status
-    const emitStatus = (status: AgentStatus, error?: string) => {
+    const emitStatus = (status: AgentStatus, error?: string, hint?: string) => {
       if (this.mainWindow && !this.mainWindow.isDestroyed()) {
         this.mainWindow.webContents.send('agent:status-change', {
+          agentId,
           connectionId: client.connectionId,
           status,
-          error
+          error,
+          hint
         })
       }
     }
@@ -294,6 +499,7 @@ export class AgentManagerService {
       const initResult = await client.initialize()
 
       this.connections.set(client.connectionId, client)
+      this.cacheCapabilities(agentId, initResult.capabilities, initResult.authMethods)
 
       // Auto-authenticate if env_var auth method is available and API key was provided
       await this.autoAuthenticateIfNeeded(client, initResult.authMethods, agentSettings, emitStatus)
@@ -302,23 +508,82 @@ export class AgentManagerService {
       // The agent may still accept sessions/prompts; auth errors surface at prompt time.
       emitStatus('connected')
 
+      client.on('crashed', (reason: string) => {
+        if (this.supervisors.has(client.connectionId)) return
+        const supervisor = this.superviseRestart(client, emitStatus, reason).finally(() => {
+          this.supervisors.delete(client.connectionId)
+        })
+        this.supervisors.set(client.connectionId, supervisor)
+      })
+      this.startResourceSampling(client)
+
       return {
         connectionId: client.connectionId,
         agentId,
         agentName: initResult.agentName,
         status: 'connected',
         pid: client.pid,
-        startedAt: new Date().toISOString(),
+        startedAt: client.startedAt,
         capabilities: initResult.capabilities,
         authMethods: initResult.authMethods
       }
     } catch (error) {
-      client.terminate()
-      emitStatus('error', (error as Error).message)
+      void client.terminate()
+      emitStatus('error', (error as Error).message, error instanceof AgentSpawnError ? error.hint : undefined)
       throw error
     }
   }
 
+  /**
+   * Respawn a crashed agent with exponential backoff. After MAX_RESTART_ATTEMPTS
+   * the connection is dropped and the client emits `failed`.
+   */
+  private async superviseRestart(
+    client: AcpClient,
+    emitStatus: (status: AgentStatus, error?: string) => void,
+    reason: string
+  ): Promise<void> {
+    logger.warn(`Agent ${client.agentId} (${client.connectionId}) crashed: ${reason}`)
+    emitStatus('crashed', reason)
+    // Nothing to sample while the process is down; a restart starts a fresh sampler for the new pid
+    this.stopResourceSampling(client.connectionId)
+
+    for (let attempt = 1; attempt <= MAX_RESTART_ATTEMPTS; attempt++) {
+      await new Promise((resolve) => setTimeout(resolve, RESTART_BASE_DELAY_MS * 2 ** (attempt - 1)))
+      // Terminated by the user while we were waiting
+      if (!this.connections.has(client.connectionId)) return
+
+      try {
+        logger.info(`Restarting ${client.agentId} (attempt ${attempt}/${MAX_RESTART_ATTEMPTS})`)
+        await client.restart()
+        emitStatus('connected')
+        this.startResourceSampling(client)
+        return
+      } catch (error) {
+        logger.warn(`Restart attempt ${attempt} for ${client.agentId} failed:`, error)
+      }
+    }
+
+    const message = `Agent did not recover after ${MAX_RESTART_ATTEMPTS} restart attempts`
+    emitStatus('failed', message)
+    // The process is gone for good: stop routing to it so its sessions relaunch on next use
+    this.connections.delete(client.connectionId)
+    client.emit('failed', message)
+  }
+
+  /** Persist what the agent reported on initialize so the UI can show it offline. */
+  private cacheCapabilities(agentId: string, capabilities: AgentCapabilities, authMethods: AuthMethod[]): void {
+    const agent = this.installed.get(agentId)
+    if (!agent) return
+    this.installed.set(agentId, {
+      ...agent,
+      capabilities,
+      authMethods,
+      capabilitiesUpdatedAt: new Date().toISOString()
+    })
+    this.saveInstalled()
+  }
+
   private async autoAuthenticateIfNeeded(
     client: AcpClient,
     authMethods: AuthMethod[],
@@ -347,6 +612,41 @@ export class AgentManagerService {
     }
   }
 
+  /**
+   * Env the user configured for an agent: mapped API keys and model, enabled
+   * extensions' env, then custom env (later entries win).
+   */
+  getAgentEnv(agentId: string): Record<string, string> {
+    const agentSettings = settingsService.getAgentSettings(agentId)
+    const agentEnv: Record<string, string> = {}
+
+    // Add mapped API key env vars for this agent.
+    for (const envVarName of getApiKeyEnvVarsForAgent(agentId)) {
+      const mappedValue = this.resolveMappedApiKeyValue(agentId, agentSettings, envVarName)
+      if (mappedValue) {
+        agentEnv[envVarName] = mappedValue
+      }
+    }
+
+    // Add mapped model env vars for this agent.
+    if (agentSettings?.model) {
+      for (const envVarName of getModelEnvVarsForAgent(agentId)) {
+        agentEnv[envVarName] = agentSettings.model
+      }
+    }
+
+    // Enabled registry extensions contribute env; the user's own settings win
+    for (const ext of registryService.getCachedExtensions(agentSettings?.extensions ?? [])) {
+      if (ext.env) Object.assign(agentEnv, ext.env)
+    }
+
+    // Merge custom env
+    if (agentSettings?.customEnv) {
+      Object.assign(agentEnv, agentSettings.customEnv)
+    }
+    return agentEnv
+  }
+
   private resolveMappedApiKeyValue(
     agentId: string,
     agentSettings: ReturnType<typeof settingsService.getAgentSettings>,
@@ -369,12 +669,13 @@ export class AgentManagerService {
     credentials?: Record<string, string>
   ): Promise<void> {
     const client = this.connections.get(connectionId)
-    if (!client) throw new Error(`Connection not found: ${connectionId}`)
+    if (!client) throw new AppError('NOT_CONNECTED', `Connection not found: ${connectionId}`, { connectionId })
     await client.authenticate(method, credentials)
 
     // Emit connected status after successful authentication
     if (this.mainWindow && !this.mainWindow.isDestroyed()) {
       this.mainWindow.webContents.send('agent:status-change', {
+        agentId: client.agentId,
         connectionId,
         status: 'connected'
       })
@@ -387,7 +688,7 @@ export class AgentManagerService {
    */
   async authenticateConnectionForSession(connectionId: string): Promise<void> {
     const client = this.connections.get(connectionId)
-    if (!client) throw new Error(`Connection not found: ${connectionId}`)
+    if (!client) throw new AppError('NOT_CONNECTED', `Connection not found: ${connectionId}`, { connectionId })
 
     const authMethods = client.authMethods || []
     if (authMethods.length === 0) return
@@ -413,7 +714,9 @@ export class AgentManagerService {
       if (selectedMethod.type === 'env_var' && selectedMethod.varName) {
         const apiKey = this.resolveMappedApiKeyValue(client.agentId, agentSettings, selectedMethod.varName)
         if (!apiKey) {
-          throw new Error(`Authentication required: missing ${selectedMethod.varName}`)
+          throw new AppError('NOT_AUTHENTICATED', `Authentication required: missing ${selectedMethod.varName}`, {
+            agentId: client.agentId
+          })
         }
         logger.info(`Authenticating ${client.agentId} with ${selectedMethod.id} (${selectedMethod.varName})`)
         await client.authenticate(selectedMethod.id, { [selectedMethod.varName]: apiKey })
@@ -424,20 +727,21 @@ export class AgentManagerService {
     } catch (error) {
       const message = error instanceof Error ? error.message : String(error)
       logger.warn(`Authentication failed for ${client.agentId} using ${selectedMethod.id}:`, error)
-      throw new Error(`Authentication required: ${message}`)
+      throw new AppError('NOT_AUTHENTICATED', `Authentication required: ${message}`, { agentId: client.agentId })
     }
   }
 
   async logout(connectionId: string): Promise<void> {
     const client = this.connections.get(connectionId)
-    if (!client) throw new Error(`Connection not found: ${connectionId}`)
+    if (!client) throw new AppError('NOT_CONNECTED', `Connection not found: ${connectionId}`, { connectionId })
     await client.logout()
   }
 
   terminate(connectionId: string): void {
+    this.stopResourceSampling(connectionId)
     const client = this.connections.get(connectionId)
     if (client) {
-      client.terminate()
+      void client.terminate()
       this.connections.delete(connectionId)
     }
   }
@@ -450,6 +754,67 @@ export class AgentManagerService {
     return Array.from(this.connections.values()).map((client) => this.toAgentConnection(client))
   }
 
+  /** Latest CPU/memory sample for a connection, if one has been taken */
+  getResourceUsage(connectionId: string): AgentResourceUsage | null {
+    return this.resourceSamplers.get(connectionId)?.latest ?? null
+  }
+
+  private startResourceSampling(client: AcpClient): void {
+    if (this.resourceSamplers.has(client.connectionId)) return
+    const sampler: ResourceSampler = {
+      timer: setInterval(() => void this.sampleResources(client, sampler), RESOURCE_SAMPLE_INTERVAL_MS)
+    }
+    this.resourceSamplers.set(client.connectionId, sampler)
+  }
+
+  private stopResourceSampling(connectionId: string): void {
+    const sampler = this.resourceSamplers.get(connectionId)
+    if (!sampler) return
+    clearInterval(sampler.timer)
+    this.resourceSamplers.delete(connectionId)
+  }
+
+  private async sampleResources(client: AcpClient, sampler: ResourceSampler): Promise<void> {
+    const pid = client.pid
+    if (!pid || !client.isRunning) return
+
+    const stats = await readProcessStats(pid)
+    // Connection may have been terminated while ps was running
+    if (!stats || this.resourceSamplers.get(client.connectionId) !== sampler) return
+
+    const now = Date.now()
+    const previous = sampler.previous
+    // CPU time is cumulative; a restart (new pid) resets it, so skip negative deltas
+    const cpuDelta = previous ? stats.cpuSeconds - previous.cpuSeconds : 0
+    const cpuPercent = previous && cpuDelta >= 0 ? (cpuDelta / ((now - previous.at) / 1000)) * 100 : 0
+    sampler.previous = { ...stats, at: now }
+
+    const usage: AgentResourceUsage = {
+      connectionId: client.connectionId,
+      pid,
+      cpuPercent: Math.round(cpuPercent * 10) / 10,
+      memoryBytes: stats.rssBytes,
+      sampledAt: new Date(now).toISOString()
+    }
+    sampler.latest = usage
+    if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+      this.mainWindow.webContents.send('agent:resource-usage', usage)
+    }
+  }
+
+  getProcessInfo(connectionId: string): AgentProcessInfo | null {
+    const client = this.connections.get(connectionId)
+    if (!client) return null
+    const startedMs = client.startedAt ? Date.parse(client.startedAt) : NaN
+    return {
+      connectionId,
+      pid: client.pid,
+      startedAt: client.startedAt,
+      uptimeMs: Number.isNaN(startedMs) ? 0 : Date.now() - startedMs,
+      running: client.isRunning
+    }
+  }
+
   async checkAuthentication(agentId: string, projectPath?: string): Promise<AgentAuthCheckResult> {
     const resolvedProjectPath = projectPath || this.resolveOnboardingProjectPath()
     const checkedAt = new Date().toISOString()
@@ -675,6 +1040,16 @@ export class AgentManagerService {
   // CLI Detection
   // ============================
 
+  /** First host tool required by the distribution type that isn't on PATH */
+  private findMissingDependency(
+    distributionType: InstalledAgent['distributionType']
+  ): { tool: string; hint: string } | null {
+    const dependencies = DISTRIBUTION_DEPENDENCIES[distributionType] || []
+    if (dependencies.length === 0) return null
+    const found = this.detectCliCommands(dependencies.map((d) => d.tool))
+    return dependencies.find((d) => !found[d.tool]) || null
+  }
+
   detectCliCommands(commands: string[]): Record<string, boolean> {
     const results: Record<string, boolean> = {}
     const whichCmd = process.platform === 'win32' ? 'where' : 'which'
@@ -698,9 +1073,10 @@ export class AgentManagerService {
       agentName: client.agentName,
       status: client.isRunning ? 'connected' : 'terminated',
       pid: client.pid,
-      startedAt: '',
+      startedAt: client.startedAt,
       capabilities: client.capabilities || undefined,
-      authMethods: client.authMethods
+      authMethods: client.authMethods,
+      restartCount: client.restartCount
     }
   }
 
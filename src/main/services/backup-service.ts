@@ -0,0 +1,211 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import { execFile } from 'child_process'
+import { promisify } from 'util'
+import type { AppSettings, BackupSummary } from '@shared/types/settings'
+import { normalizePath } from '@shared/util/path-util'
+import { folderThreadStore } from './folder-thread-store'
+import { threadStore } from './thread-store'
+import { workspaceService } from './workspace-service'
+import { settingsService } from './settings-service'
+import { getAppDataDir, getCacheDir } from '../util/paths'
+import { getPendingRestoreDir } from '../util/data-dir'
+import { logger } from '../util/logger'
+
+const execFileAsync = promisify(execFile)
+
+const BACKUP_FORMAT_VERSION = 1
+const BACKUP_MANIFEST_FILE = 'backup.json'
+
+/** electron-store files (in the data dir) included in a backup */
+const STORE_FILES = ['settings.json', 'installed-agents.json', 'workspaces.json', 'permission-rules.json', 'threads.json']
+
+interface BackupManifest {
+  version: number
+  createdAt: string
+  includesCache: boolean
+  /** .agent/threads folders in the archive, by the directory that held them */
+  threadDirs: Array<{ archiveDir: string; sourcePath: string; workspaceId: string; threadCount: number }>
+}
+
+/**
+ * BackupService exports the data dir and every thread folder referenced by the
+ * thread cache into a single .tar.gz, and restores from one. API keys and MCP
+ * server env values are stripped on export and kept from the current settings on import.
+ */
+export class BackupService {
+  async exportBackup(archivePath: string, options: { includeCache?: boolean } = {}): Promise<BackupSummary> {
+    const staging = fs.mkdtempSync(path.join(os.tmpdir(), 'agentmanager-backup-'))
+    try {
+      const dataDir = path.join(staging, 'data')
+      fs.mkdirSync(dataDir, { recursive: true })
+
+      let files = 0
+      for (const name of STORE_FILES) {
+        const source = path.join(getAppDataDir(), name)
+        if (!fs.existsSync(source)) continue
+        if (name === 'settings.json') {
+          const raw = JSON.parse(fs.readFileSync(source, 'utf-8')) as Partial<AppSettings>
+          fs.writeFileSync(path.join(dataDir, name), JSON.stringify(stripSecrets(raw), null, 2), 'utf-8')
+        } else {
+          fs.copyFileSync(source, path.join(dataDir, name))
+        }
+        files++
+      }
+
+      if (options.includeCache) {
+        fs.cpSync(getCacheDir(), path.join(dataDir, 'cache'), { recursive: true })
+      }
+
+      // Every folder holding threads: workspaces plus worktrees known to the cache
+      const sources = new Map<string, { sourcePath: string; workspaceId: string }>()
+      for (const ws of workspaceService.list()) {
+        sources.set(normalizePath(ws.path), { sourcePath: ws.path, workspaceId: ws.id })
+      }
+      for (const t of threadStore.loadAll()) {
+        if (t.useWorktree && t.worktreePath && !sources.has(normalizePath(t.worktreePath))) {
+          sources.set(normalizePath(t.worktreePath), { sourcePath: t.worktreePath, workspaceId: t.workspaceId })
+        }
+      }
+
+      const threadDirs: BackupManifest['threadDirs'] = []
+      for (const { sourcePath, workspaceId } of sources.values()) {
+        const threadsDir = folderThreadStore.getThreadsDir(sourcePath)
+        if (!fs.existsSync(threadsDir)) continue
+        const archiveDir = `threads/${threadDirs.length}`
+        fs.cpSync(threadsDir, path.join(staging, archiveDir), { recursive: true })
+        threadDirs.push({
+          archiveDir,
+          sourcePath,
+          workspaceId,
+          threadCount: folderThreadStore.listThreadIds(sourcePath).length
+        })
+      }
+
+      const manifest: BackupManifest = {
+        version: BACKUP_FORMAT_VERSION,
+        createdAt: new Date().toISOString(),
+        includesCache: !!options.includeCache,
+        threadDirs
+      }
+      fs.writeFileSync(path.join(staging, BACKUP_MANIFEST_FILE), JSON.stringify(manifest, null, 2), 'utf-8')
+
+      fs.mkdirSync(path.dirname(archivePath), { recursive: true })
+      await execFileAsync('tar', ['-czf', archivePath, '-C', staging, '.'])
+
+      const summary = toSummary(archivePath, manifest, files)
+      logger.info(
+        `Backup exported to ${archivePath}: ${summary.sizeBytes} bytes, ` +
+          `${summary.threadCount} threads from ${threadDirs.length} folders`
+      )
+      return summary
+    } finally {
+      fs.rmSync(staging, { recursive: true, force: true })
+    }
+  }
+
+  /**
+   * Restore a backup. Thread folders are restored only where their original
+   * directory still exists. Data files (settings, installed agents, ...) and the
+   * cache are staged and applied on the next start, since running services
+   * would otherwise write their in-memory state over them.
+   */
+  async importBackup(archivePath: string): Promise<BackupSummary> {
+    if (!fs.existsSync(archivePath)) throw new Error(`Backup not found: ${archivePath}`)
+
+    const staging = fs.mkdtempSync(path.join(os.tmpdir(), 'agentmanager-restore-'))
+    try {
+      await execFileAsync('tar', ['-xzf', archivePath, '-C', staging])
+
+      const manifestPath = path.join(staging, BACKUP_MANIFEST_FILE)
+      if (!fs.existsSync(manifestPath)) throw new Error('Not an AgentManager backup: missing manifest')
+      const manifest = JSON.parse(fs.readFileSync(manifestPath, 'utf-8')) as BackupManifest
+      if (manifest.version > BACKUP_FORMAT_VERSION) {
+        throw new Error(`Backup format v${manifest.version} is newer than supported (v${BACKUP_FORMAT_VERSION})`)
+      }
+
+      const dataDir = path.join(staging, 'data')
+      const pendingDir = getPendingRestoreDir()
+      fs.rmSync(pendingDir, { recursive: true, force: true })
+      fs.mkdirSync(pendingDir, { recursive: true })
+      let files = 0
+      for (const name of STORE_FILES) {
+        const source = path.join(dataDir, name)
+        if (!fs.existsSync(source)) continue
+        const target = path.join(pendingDir, name)
+        if (name === 'settings.json') {
+          const restored = JSON.parse(fs.readFileSync(source, 'utf-8')) as Partial<AppSettings>
+          fs.writeFileSync(target, JSON.stringify(restoreSecrets(restored, settingsService.get()), null, 2), 'utf-8')
+        } else {
+          fs.copyFileSync(source, target)
+        }
+        files++
+      }
+
+      if (manifest.includesCache && fs.existsSync(path.join(dataDir, 'cache'))) {
+        fs.cpSync(path.join(dataDir, 'cache'), path.join(pendingDir, path.basename(getCacheDir())), { recursive: true })
+      }
+
+      for (const entry of manifest.threadDirs) {
+        if (!fs.existsSync(entry.sourcePath)) {
+          logger.warn(`Skipping threads for missing directory: ${entry.sourcePath}`)
+          continue
+        }
+        fs.mkdirSync(folderThreadStore.getAgentDir(entry.sourcePath), { recursive: true })
+        fs.cpSync(path.join(staging, entry.archiveDir), folderThreadStore.getThreadsDir(entry.sourcePath), {
+          recursive: true
+        })
+        threadStore.syncWorkspaceToCache(entry.sourcePath, entry.workspaceId)
+      }
+
+      const summary = { ...toSummary(archivePath, manifest, files), restartRequired: files > 0 || manifest.includesCache }
+      logger.info(
+        `Backup imported from ${archivePath}: ${summary.threadCount} threads` +
+          (summary.restartRequired ? '; restart to apply the restored settings and data' : '')
+      )
+      return summary
+    } finally {
+      fs.rmSync(staging, { recursive: true, force: true })
+    }
+  }
+}
+
+function toSummary(archivePath: string, manifest: BackupManifest, dataFiles: number): BackupSummary {
+  return {
+    archivePath,
+    sizeBytes: fs.statSync(archivePath).size,
+    createdAt: manifest.createdAt,
+    dataFiles,
+    includesCache: manifest.includesCache,
+    threadFolders: manifest.threadDirs.length,
+    threadCount: manifest.threadDirs.reduce((sum, d) => sum + d.threadCount, 0)
+  }
+}
+
+function stripSecrets(settings: Partial<AppSettings>): Partial<AppSettings> {
+  const copy = JSON.parse(JSON.stringify(settings)) as Partial<AppSettings>
+  for (const agent of Object.values(copy.agents || {})) {
+    delete agent.apiKeys
+    delete agent.apiKey
+  }
+  for (const server of copy.mcp?.servers || []) {
+    delete server.env
+  }
+  return copy
+}
+
+function restoreSecrets(restored: Partial<AppSettings>, current: AppSettings): Partial<AppSettings> {
+  for (const [agentId, agent] of Object.entries(restored.agents || {})) {
+    const existing = current.agents[agentId]
+    if (existing?.apiKeys) agent.apiKeys = existing.apiKeys
+    if (existing?.apiKey) agent.apiKey = existing.apiKey
+  }
+  for (const server of restored.mcp?.servers || []) {
+    const existing = current.mcp.servers.find((s) => s.id === server.id)
+    if (existing?.env) server.env = existing.env
+  }
+  return restored
+}
+
+export const backupService = new BackupService()
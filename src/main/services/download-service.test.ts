@@ -0,0 +1,249 @@
+import { execFile } from 'child_process'
+import { randomBytes } from 'crypto'
+import fs from 'fs'
+import http from 'http'
+import type { AddressInfo } from 'net'
+import os from 'os'
+import path from 'path'
+import { promisify } from 'util'
+import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import { DownloadService } from './download-service'
+import { getAgentsDir } from '../util/paths'
+
+interface Checker {
+  verifyChecksum(filePath: string, expected: string | undefined, source: string): Promise<void>
+}
+
+const execFileAsync = promisify(execFile)
+
+let workDir: string
+
+beforeEach(() => {
+  workDir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'download-service-')))
+})
+
+afterEach(() => {
+  fs.rmSync(workDir, { recursive: true, force: true })
+})
+
+describe('checksum verification', () => {
+  const FIXTURE = 'agent binary'
+  const FIXTURE_SHA256 = 'ca1483c93fb2dba268c878c931aac5925c08ef48aa9c264f4809f20a2ea43ba9'
+  const checker = new DownloadService() as unknown as Checker
+
+  function writeFixture(): string {
+    const file = path.join(workDir, 'agent.tar.gz')
+    fs.writeFileSync(file, FIXTURE)
+    return file
+  }
+
+  it('accepts a matching digest regardless of case', async () => {
+    const file = writeFixture()
+
+    await checker.verifyChecksum(file, FIXTURE_SHA256, 'fixture')
+    await checker.verifyChecksum(file, ` ${FIXTURE_SHA256.toUpperCase()}\n`, 'fixture')
+
+    expect(fs.readFileSync(file, 'utf-8')).toBe(FIXTURE)
+  })
+
+  it('deletes the file and names both digests on a mismatch', async () => {
+    const file = writeFixture()
+    const expected = '0'.repeat(64)
+
+    await expect(checker.verifyChecksum(file, expected, 'fixture')).rejects.toThrow(
+      `Checksum mismatch for fixture: expected sha256 ${expected}, got ${FIXTURE_SHA256}`
+    )
+    expect(fs.existsSync(file)).toBe(false)
+  })
+
+  it('keeps the file when no checksum is published', async () => {
+    const file = writeFixture()
+
+    await checker.verifyChecksum(file, undefined, 'fixture')
+
+    expect(fs.existsSync(file)).toBe(true)
+  })
+})
+
+describe('download progress', () => {
+  let server: http.Server | undefined
+
+  afterEach(async () => {
+    await new Promise((resolve) => server?.close(resolve) ?? resolve(undefined))
+    server = undefined
+  })
+
+  /** Serve `body` with a Content-Length, in small chunks so the download takes a while */
+  async function serve(body: Buffer): Promise<string> {
+    server = http.createServer(async (_req, res) => {
+      res.writeHead(200, { 'Content-Length': body.length, 'Content-Type': 'application/gzip' })
+      for (let offset = 0; offset < body.length; offset += 32 * 1024) {
+        res.write(body.subarray(offset, offset + 32 * 1024))
+        await new Promise((resolve) => setTimeout(resolve, 10))
+      }
+      res.end()
+    })
+    await new Promise<void>((resolve) => server!.listen(0, '127.0.0.1', resolve))
+    return `http://127.0.0.1:${(server!.address() as AddressInfo).port}/agent.tar.gz`
+  }
+
+  it.skipIf(process.platform === 'win32')('reports monotonically increasing progress up to the full size', async () => {
+    const buildDir = path.join(workDir, 'build')
+    fs.mkdirSync(buildDir)
+    fs.writeFileSync(path.join(buildDir, 'agent'), '#!/bin/sh\necho agent\n')
+    // Random bytes don't compress, so the archive stays around 2 MB
+    fs.writeFileSync(path.join(buildDir, 'payload.bin'), randomBytes(2 * 1024 * 1024))
+    const archive = path.join(workDir, 'agent.tar.gz')
+    await execFileAsync('tar', ['-czf', archive, '-C', buildDir, 'agent', 'payload.bin'])
+    const body = fs.readFileSync(archive)
+    const url = await serve(body)
+
+    const progress: Array<{ downloaded: number; total?: number }> = []
+    const cmdPath = await new DownloadService().downloadAndExtract(
+      'progress-agent',
+      '1.0.0',
+      { archive: url, cmd: 'agent' },
+      (downloaded, total) => progress.push({ downloaded, total })
+    )
+
+    expect(fs.existsSync(cmdPath)).toBe(true)
+    expect(progress.length).toBeGreaterThan(1)
+    for (let i = 1; i < progress.length; i++) {
+      expect(progress[i].downloaded).toBeGreaterThanOrEqual(progress[i - 1].downloaded)
+    }
+    expect(progress.every((p) => p.total === body.length)).toBe(true)
+    expect(progress.at(-1)?.downloaded).toBe(body.length)
+  })
+})
+
+describe('archive entries escaping the install directory', () => {
+  interface Extractor {
+    extract(archivePath: string, destDir: string): Promise<void>
+  }
+
+  const extractor = new DownloadService() as unknown as Extractor
+  let buildDir: string
+  let destDir: string
+  let outsideDir: string
+
+  beforeEach(() => {
+    // Siblings, so ../ means the same directory from either
+    buildDir = path.join(workDir, 'install', 'build')
+    destDir = path.join(workDir, 'install', 'agent')
+    outsideDir = path.join(workDir, 'outside')
+    for (const dir of [buildDir, destDir, outsideDir]) fs.mkdirSync(dir, { recursive: true })
+  })
+
+  /** Archive `entries` as named, from buildDir; -P keeps leading ../ and / in tar names */
+  async function archive(name: string, entries: string[]): Promise<string> {
+    const archivePath = path.join(workDir, name)
+    if (name.endsWith('.zip')) {
+      await execFileAsync('zip', ['-q', '-y', archivePath, ...entries], { cwd: buildDir })
+    } else {
+      await execFileAsync('tar', ['-czPf', archivePath, ...entries], { cwd: buildDir })
+    }
+    return archivePath
+  }
+
+  /** Write a file at `target` for archiving, then remove it so extraction would have to recreate it */
+  async function archiveAndRemove(name: string, entry: string, target: string): Promise<string> {
+    fs.writeFileSync(target, 'pwned')
+    const archivePath = await archive(name, [entry])
+    fs.rmSync(target)
+    return archivePath
+  }
+
+  it.skipIf(process.platform === 'win32').each(['evil.tar.gz', 'evil.zip'])(
+    'refuses ../ entries in %s',
+    async (name) => {
+      const target = path.join(workDir, 'install', 'evil.txt')
+      const archivePath = await archiveAndRemove(name, '../evil.txt', target)
+
+      await expect(extractor.extract(archivePath, destDir)).rejects.toThrow('escapes the install directory')
+      expect(fs.existsSync(target)).toBe(false)
+    }
+  )
+
+  it.skipIf(process.platform === 'win32')('refuses absolute entries', async () => {
+    const target = path.join(outsideDir, 'absolute.txt')
+    const archivePath = await archiveAndRemove('absolute.tar.gz', target, target)
+
+    await expect(extractor.extract(archivePath, destDir)).rejects.toThrow('absolute path entry')
+    expect(fs.existsSync(target)).toBe(false)
+  })
+
+  it.skipIf(process.platform === 'win32').each(['link.tar.gz', 'link.zip'])(
+    'refuses a symlink pointing outside in %s',
+    async (name) => {
+      fs.symlinkSync(outsideDir, path.join(buildDir, 'escape'))
+      const archivePath = await archive(name, ['escape'])
+
+      await expect(extractor.extract(archivePath, destDir)).rejects.toThrow('points outside the install directory')
+      expect(fs.readdirSync(outsideDir)).toEqual([])
+      expect(fs.existsSync(path.join(destDir, 'escape'))).toBe(false)
+    }
+  )
+})
+
+describe('xz and bzip2 archives', () => {
+  interface Extractor {
+    extract(archivePath: string, destDir: string): Promise<void>
+  }
+
+  it.skipIf(process.platform === 'win32').each([
+    ['agent.tar.xz', 'J'],
+    ['agent.txz', 'J'],
+    ['agent.tar.bz2', 'j'],
+    ['agent.tbz2', 'j']
+  ])('extracts %s', async (name, compression) => {
+    const buildDir = path.join(workDir, 'build')
+    const destDir = path.join(workDir, 'install')
+    fs.mkdirSync(path.join(buildDir, 'bin'), { recursive: true })
+    fs.mkdirSync(destDir)
+    fs.writeFileSync(path.join(buildDir, 'bin', 'agent'), '#!/bin/sh\necho agent\n', { mode: 0o755 })
+    const archivePath = path.join(workDir, name)
+    await execFileAsync('tar', [`-c${compression}f`, archivePath, '-C', buildDir, 'bin'])
+
+    await (new DownloadService() as unknown as Extractor).extract(archivePath, destDir)
+
+    expect(fs.readFileSync(path.join(destDir, 'bin', 'agent'), 'utf-8')).toBe('#!/bin/sh\necho agent\n')
+  })
+})
+
+describe('pruning old versions', () => {
+  /** Lay down agents/<agentId>/<version>/bin/agent for each version, 100 bytes each */
+  function installVersions(agentId: string, versions: string[]): string {
+    const agentDir = path.join(getAgentsDir(), agentId)
+    for (const version of versions) {
+      fs.mkdirSync(path.join(agentDir, version, 'bin'), { recursive: true })
+      fs.writeFileSync(path.join(agentDir, version, 'bin', 'agent'), 'x'.repeat(100))
+    }
+    return agentDir
+  }
+
+  it('removes every version but the kept one and reports the bytes freed', () => {
+    const agentDir = installVersions('prune-agent', ['1.0.0', '1.1.0', '2.0.0'])
+
+    const freed = new DownloadService().pruneOldVersions('prune-agent', '2.0.0')
+
+    expect(freed).toBe(200)
+    expect(fs.readdirSync(agentDir)).toEqual(['2.0.0'])
+  })
+
+  it('keeps the version holding the active executable', () => {
+    const agentDir = installVersions('pinned-agent', ['1.0.0', '1.1.0', '2.0.0'])
+
+    const freed = new DownloadService().pruneOldVersions(
+      'pinned-agent',
+      '2.0.0',
+      path.join(agentDir, '1.1.0', 'bin', 'agent')
+    )
+
+    expect(freed).toBe(100)
+    expect(fs.readdirSync(agentDir).sort()).toEqual(['1.1.0', '2.0.0'])
+  })
+
+  it('does nothing for an agent without installed versions', () => {
+    expect(new DownloadService().pruneOldVersions('never-installed', '1.0.0')).toBe(0)
+  })
+})
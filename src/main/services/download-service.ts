@@ -1,24 +1,43 @@
 import fs from 'fs'
+import { createHash } from 'crypto'
 import path from 'path'
 import { pipeline } from 'stream/promises'
 import { createWriteStream } from 'fs'
-import { Readable } from 'stream'
+import { Readable, Transform } from 'stream'
 import { execFile } from 'child_process'
 import { promisify } from 'util'
 import type { BinaryTarget } from '@shared/types/agent'
-import { getDownloadsDir, getAgentInstallDir } from '../util/paths'
+import { getDownloadsDir, getAgentInstallDir, getAgentsDir } from '../util/paths'
+import { getCurrentPlatformTarget } from '../util/platform'
+import { readBinaryHeader } from '../util/binary-arch'
+import type { BinaryFormat, BinaryHeader } from '../util/binary-arch'
 import { logger } from '../util/logger'
 
 const execFileAsync = promisify(execFile)
 
+/** Minimum time between download progress callbacks */
+const PROGRESS_INTERVAL_MS = 250
+
+/** Suffix for archives that are still being streamed to disk */
+const PARTIAL_SUFFIX = '.partial'
+
+const DAY_MS = 24 * 60 * 60 * 1000
+
+/** Output limit when listing archive entries */
+const LIST_MAX_BUFFER = 32 * 1024 * 1024
+
 export class DownloadService {
+  /** Archive paths with a download currently in flight (never pruned) */
+  private activeDownloads = new Set<string>()
+
   /**
    * Download and extract a binary agent
    */
   async downloadAndExtract(
     agentId: string,
     version: string,
-    target: BinaryTarget
+    target: BinaryTarget,
+    onProgress?: (downloadedBytes: number, totalBytes?: number) => void
   ): Promise<string> {
     const downloadDir = getDownloadsDir()
     const installDir = getAgentInstallDir(agentId, version)
@@ -36,9 +55,33 @@ export class DownloadService {
       throw new Error(`Download failed: ${response.status} ${response.statusText}`)
     }
 
-    // Stream to disk
-    const readableNodeStream = Readable.fromWeb(response.body as import('stream/web').ReadableStream)
-    await pipeline(readableNodeStream, createWriteStream(archivePath))
+    // Stream to a .partial file first so interrupted downloads are recognisable
+    const partialPath = archivePath + PARTIAL_SUFFIX
+    this.activeDownloads.add(partialPath)
+    try {
+      const readableNodeStream = Readable.fromWeb(response.body as import('stream/web').ReadableStream)
+      const contentLength = Number(response.headers.get('content-length'))
+      const totalBytes = contentLength > 0 ? contentLength : undefined
+      let downloadedBytes = 0
+      let lastReportAt = 0
+      const progress = new Transform({
+        transform(chunk: Buffer, _encoding, callback) {
+          downloadedBytes += chunk.length
+          const now = Date.now()
+          if (onProgress && now - lastReportAt >= PROGRESS_INTERVAL_MS) {
+            lastReportAt = now
+            onProgress(downloadedBytes, totalBytes)
+          }
+          callback(null, chunk)
+        }
+      })
+      await pipeline(readableNodeStream, progress, createWriteStream(partialPath))
+      onProgress?.(downloadedBytes, totalBytes)
+      await this.verifyChecksum(partialPath, target.sha256, archiveUrl)
+      fs.renameSync(partialPath, archivePath)
+    } finally {
+      this.activeDownloads.delete(partialPath)
+    }
 
     logger.info(`Downloaded to ${archivePath}, extracting to ${installDir}`)
 
@@ -53,9 +96,12 @@ export class DownloadService {
       if (!found) {
         throw new Error(`Executable not found after extraction: ${target.cmd}`)
       }
+      this.verifyHostArchitecture(found)
       return found
     }
 
+    this.verifyHostArchitecture(cmdPath)
+
     // Make executable on unix
     if (process.platform !== 'win32') {
       fs.chmodSync(cmdPath, 0o755)
@@ -69,14 +115,146 @@ export class DownloadService {
     return cmdPath
   }
 
+  /**
+   * Best-effort check that the executable's format and architecture match the host,
+   * so a wrong download fails here rather than as a confusing spawn error.
+   * Scripts and unknown formats pass; x86_64 binaries are allowed on arm64
+   * macOS/Windows, which run them under emulation.
+   */
+  private verifyHostArchitecture(executablePath: string): void {
+    const platformTarget = getCurrentPlatformTarget()
+    if (!platformTarget) return
+
+    let header: BinaryHeader | null
+    try {
+      header = readBinaryHeader(executablePath)
+    } catch (err) {
+      logger.warn(`Could not read header of ${executablePath}:`, err)
+      return
+    }
+    if (!header) return
+
+    const [os, hostArch] = platformTarget.split('-') as [string, string]
+    const expectedFormat: BinaryFormat = os === 'darwin' ? 'macho' : os === 'windows' ? 'pe' : 'elf'
+    if (header.format !== expectedFormat) {
+      throw new Error(`Downloaded binary is ${header.format.toUpperCase()} but host is ${os}: ${path.basename(executablePath)}`)
+    }
+    if (header.archs.includes(hostArch)) return
+
+    if (hostArch === 'aarch64' && os !== 'linux' && header.archs.includes('x86_64')) {
+      logger.warn(`${path.basename(executablePath)} targets x86_64; it will run under emulation on ${platformTarget}`)
+      return
+    }
+    throw new Error(`Downloaded binary targets ${header.archs.join('/')} but host is ${hostArch}`)
+  }
+
+  /** Compare the file's SHA-256 against the expected digest; delete it and throw on mismatch. */
+  private async verifyChecksum(filePath: string, expected: string | undefined, source: string): Promise<void> {
+    if (!expected) {
+      logger.warn(`No checksum published for ${source}; skipping integrity check`)
+      return
+    }
+
+    const hash = createHash('sha256')
+    await pipeline(fs.createReadStream(filePath), hash)
+    const actual = hash.digest('hex')
+    if (actual.toLowerCase() !== expected.trim().toLowerCase()) {
+      fs.rmSync(filePath, { force: true })
+      throw new Error(`Checksum mismatch for ${source}: expected sha256 ${expected}, got ${actual}`)
+    }
+    logger.info(`Checksum verified for ${source}`)
+  }
+
+  /**
+   * Remove leftovers from the downloads directory: orphaned `.partial` files
+   * and any archive older than `maxAgeDays`. In-flight downloads are skipped.
+   */
+  pruneDownloads(maxAgeDays = 7): { freedBytes: number; removedFiles: number } {
+    const downloadDir = getDownloadsDir()
+    const cutoff = Date.now() - maxAgeDays * DAY_MS
+    let freedBytes = 0
+    let removedFiles = 0
+
+    for (const entry of fs.readdirSync(downloadDir, { withFileTypes: true })) {
+      const entryPath = path.join(downloadDir, entry.name)
+      if (this.activeDownloads.has(entryPath)) continue
+
+      try {
+        const stat = fs.statSync(entryPath)
+        const isOrphanedPartial = entry.name.endsWith(PARTIAL_SUFFIX)
+        if (!isOrphanedPartial && stat.mtimeMs >= cutoff) continue
+
+        const size = entry.isDirectory() ? this.dirSize(entryPath) : stat.size
+        fs.rmSync(entryPath, { recursive: true, force: true })
+        freedBytes += size
+        removedFiles++
+      } catch (err) {
+        logger.warn(`Failed to prune download ${entryPath}:`, err)
+      }
+    }
+
+    if (removedFiles > 0) {
+      logger.info(`Pruned ${removedFiles} download(s), freed ${freedBytes} bytes`)
+    }
+    return { freedBytes, removedFiles }
+  }
+
+  /**
+   * Remove every agents/<id>/<version> directory except `keepVersion` and the one
+   * holding `keepPath` (the active executable). Returns the bytes reclaimed.
+   */
+  pruneOldVersions(agentId: string, keepVersion: string, keepPath?: string): number {
+    const agentDir = path.join(getAgentsDir(), agentId)
+    if (!fs.existsSync(agentDir)) return 0
+
+    let freedBytes = 0
+    for (const entry of fs.readdirSync(agentDir, { withFileTypes: true })) {
+      if (!entry.isDirectory() || entry.name === keepVersion) continue
+      const versionDir = path.join(agentDir, entry.name)
+      if (keepPath && !path.relative(versionDir, keepPath).startsWith('..')) continue
+
+      try {
+        const size = this.dirSize(versionDir)
+        fs.rmSync(versionDir, { recursive: true, force: true })
+        freedBytes += size
+      } catch (err) {
+        // Typically a binary still running on Windows; retried on the next update
+        logger.warn(`Failed to remove old version ${versionDir}:`, err)
+      }
+    }
+
+    if (freedBytes > 0) {
+      logger.info(`Pruned old versions of ${agentId}, freed ${freedBytes} bytes`)
+    }
+    return freedBytes
+  }
+
+  private dirSize(dir: string): number {
+    let total = 0
+    for (const entry of fs.readdirSync(dir, { withFileTypes: true, recursive: true })) {
+      if (entry.isFile()) {
+        try {
+          total += fs.statSync(path.join(entry.parentPath || dir, entry.name)).size
+        } catch { /* ignore */ }
+      }
+    }
+    return total
+  }
+
   private async extract(archivePath: string, destDir: string): Promise<void> {
+    // Refuse archives with entries that would land outside destDir (zip-slip)
+    const entries = await this.listArchiveEntries(archivePath)
+    for (const entry of entries) {
+      assertEntryInside(destDir, entry, archivePath)
+    }
+
     const ext = archivePath.toLowerCase()
+    const compression = tarCompressionFlag(ext)
 
     // Use execFileAsync (no shell) to prevent injection via path metacharacters
-    if (ext.endsWith('.tar.gz') || ext.endsWith('.tgz')) {
-      await execFileAsync('tar', ['-xzf', archivePath, '-C', destDir])
-    } else if (ext.endsWith('.tar.xz')) {
-      await execFileAsync('tar', ['-xJf', archivePath, '-C', destDir])
+    if (compression) {
+      await this.assertTarLinksInside(archivePath, compression, entries, destDir)
+      await execFileAsync('tar', [`-x${compression}f`, archivePath, '-C', destDir])
     } else if (ext.endsWith('.zip')) {
       if (process.platform === 'win32') {
         await execFileAsync('powershell', [
@@ -92,6 +270,93 @@ export class DownloadService {
     } else {
       throw new Error(`Unsupported archive format: ${path.extname(archivePath)}`)
     }
+
+    this.assertNoEscapingSymlinks(destDir, archivePath)
+  }
+
+  private async listArchiveEntries(archivePath: string): Promise<string[]> {
+    const ext = archivePath.toLowerCase()
+    let command: string
+    let args: string[]
+    let env: NodeJS.ProcessEnv | undefined
+    const compression = tarCompressionFlag(ext)
+    if (compression) {
+      command = 'tar'
+      args = [`-t${compression}f`, archivePath]
+    } else if (ext.endsWith('.zip') && process.platform === 'win32') {
+      // Path passed via env so it is never interpolated into the script
+      command = 'powershell'
+      args = [
+        '-NoProfile', '-NonInteractive', '-command',
+        'Add-Type -AssemblyName System.IO.Compression.FileSystem; ' +
+          '$zip = [IO.Compression.ZipFile]::OpenRead($env:AGENT_ARCHIVE_PATH); ' +
+          'try { $zip.Entries | ForEach-Object { $_.FullName } } finally { $zip.Dispose() }'
+      ]
+      env = { ...process.env, AGENT_ARCHIVE_PATH: archivePath }
+    } else if (ext.endsWith('.zip')) {
+      command = 'unzip'
+      args = ['-Z1', archivePath]
+    } else {
+      throw new Error(`Unsupported archive format: ${path.extname(archivePath)}`)
+    }
+
+    const { stdout } = await execFileAsync(command, args, { env, maxBuffer: LIST_MAX_BUFFER })
+    return stdout.split(/\r?\n/).filter((line) => line.length > 0)
+  }
+
+  /**
+   * tar creates links as it goes, so a later entry could be written through a
+   * link into a directory outside destDir before extraction finishes. Refuse
+   * symlink and hardlink entries whose target leaves destDir up front.
+   * (unzip creates symlinks only after all files, so zips are checked afterwards.)
+   */
+  private async assertTarLinksInside(
+    archivePath: string,
+    compression: string,
+    entries: string[],
+    destDir: string
+  ): Promise<void> {
+    const { stdout } = await execFileAsync('tar', [`-tv${compression}f`, archivePath], { maxBuffer: LIST_MAX_BUFFER })
+    const lines = stdout.split(/\r?\n/).filter((line) => line.length > 0)
+    if (lines.length !== entries.length) {
+      throw new Error(`Refusing to extract ${path.basename(archivePath)}: could not match the archive listing to its entries`)
+    }
+
+    const root = path.resolve(destDir)
+    lines.forEach((line, i) => {
+      const type = line[0]
+      if (type !== 'l' && type !== 'h') return
+      // Both GNU tar and bsdtar end the line with "<name> -> <target>" or "<name> link to <target>"
+      const marker = ` ${entries[i]}${type === 'l' ? ' -> ' : ' link to '}`
+      const at = line.indexOf(marker)
+      const target = at < 0 ? '' : line.slice(at + marker.length)
+      const resolved = type === 'l' ? path.resolve(root, path.dirname(entries[i]), target) : path.resolve(root, target)
+      if (!target || path.isAbsolute(target) || path.win32.isAbsolute(target) || !isInside(root, resolved)) {
+        throw new Error(
+          `Refusing to extract ${path.basename(archivePath)}: link "${entries[i]}" points outside the install directory`
+        )
+      }
+    })
+  }
+
+  /** Checked again after extraction (zip symlinks, tar links): remove the install and throw if any symlink resolves outside destDir. */
+  private assertNoEscapingSymlinks(destDir: string, archivePath: string): void {
+    const root = fs.realpathSync(destDir)
+    for (const entry of fs.readdirSync(destDir, { withFileTypes: true, recursive: true })) {
+      if (!entry.isSymbolicLink()) continue
+      const linkPath = path.join(entry.parentPath || destDir, entry.name)
+      const target = path.resolve(path.dirname(linkPath), fs.readlinkSync(linkPath))
+      let resolved = target
+      try {
+        resolved = fs.realpathSync(target)
+      } catch { /* dangling link: judge by its literal target */ }
+      if (!isInside(root, resolved) && !isInside(path.resolve(destDir), target)) {
+        fs.rmSync(destDir, { recursive: true, force: true })
+        throw new Error(
+          `Refusing to install ${path.basename(archivePath)}: symlink ${path.relative(destDir, linkPath)} points outside the install directory`
+        )
+      }
+    }
   }
 
   private findExecutable(dir: string, cmd: string): string | null {
@@ -110,3 +375,28 @@ export class DownloadService {
 }
 
 export const downloadService = new DownloadService()
+
+/** tar decompression flag for a lowercased archive name, or null if it isn't a tarball */
+function tarCompressionFlag(ext: string): 'z' | 'J' | 'j' | null {
+  if (ext.endsWith('.tar.gz') || ext.endsWith('.tgz')) return 'z'
+  if (ext.endsWith('.tar.xz') || ext.endsWith('.txz')) return 'J'
+  if (ext.endsWith('.tar.bz2') || ext.endsWith('.tbz2') || ext.endsWith('.tbz')) return 'j'
+  return null
+}
+
+function isInside(root: string, candidate: string): boolean {
+  const rel = path.relative(root, candidate)
+  return rel === '' || (rel !== '..' && !rel.startsWith(`..${path.sep}`) && !path.isAbsolute(rel))
+}
+
+/** Throw if an archive entry name is absolute or resolves outside destDir */
+function assertEntryInside(destDir: string, entry: string, archivePath: string): void {
+  const name = entry.replace(/\\/g, '/')
+  if (path.isAbsolute(name) || path.win32.isAbsolute(name) || name.startsWith('/')) {
+    throw new Error(`Refusing to extract ${path.basename(archivePath)}: absolute path entry "${entry}"`)
+  }
+  const root = path.resolve(destDir)
+  if (!isInside(root, path.resolve(root, name))) {
+    throw new Error(`Refusing to extract ${path.basename(archivePath)}: entry "${entry}" escapes the install directory`)
+  }
+}
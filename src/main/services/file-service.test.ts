@@ -0,0 +1,165 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+import type { FileTreeNode } from '@shared/types/project'
+import { FileService } from './file-service'
+
+const fileService = new FileService()
+
+let workDir: string
+
+beforeEach(() => {
+  workDir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'file-service-')))
+})
+
+afterEach(() => {
+  fs.rmSync(workDir, { recursive: true, force: true })
+})
+
+function writeFiles(files: Record<string, string>): void {
+  for (const [file, content] of Object.entries(files)) {
+    fs.mkdirSync(path.dirname(path.join(workDir, file)), { recursive: true })
+    fs.writeFileSync(path.join(workDir, file), content)
+  }
+}
+
+/** What a lazy listing should hold for recursively read nodes: no children, just whether there are any */
+function shallow(nodes: FileTreeNode[]): FileTreeNode[] {
+  return nodes.map(({ children, ...node }) => (node.type === 'directory' ? { ...node, hasChildren: children!.length > 0 } : node))
+}
+
+describe('lazy tree expansion', () => {
+  beforeEach(() => {
+    writeFiles({
+      'README.md': 'readme',
+      'src/index.ts': 'index',
+      'src/nested/deep.ts': 'deep',
+      'only-ignored/node_modules/pkg/index.js': 'pkg'
+    })
+    fs.mkdirSync(path.join(workDir, 'empty'))
+  })
+
+  it('lists one level with hasChildren matching the recursive listing', () => {
+    const recursive = fileService.readTree(workDir, 3)
+    const lazy = fileService.readDir(workDir)
+
+    expect(recursive.map((n) => n.name)).toEqual(['empty', 'only-ignored', 'src', 'README.md'])
+    expect(lazy).toEqual(shallow(recursive))
+    expect(lazy.map((n) => n.hasChildren)).toEqual([false, false, true, undefined])
+    expect(lazy.every((n) => n.children === undefined)).toBe(true)
+  })
+
+  it('expands a directory on demand to the same nodes the recursive walk found', () => {
+    const recursiveSrc = fileService.readTree(workDir, 3).find((n) => n.name === 'src')!
+
+    const expanded = fileService.readDir(path.join(workDir, 'src'))
+
+    expect(expanded).toEqual(shallow(recursiveSrc.children!))
+    expect(expanded.map((n) => n.path)).toEqual([path.join(workDir, 'src', 'nested'), path.join(workDir, 'src', 'index.ts')])
+  })
+
+  it('stops expanding at the depth limit', () => {
+    const tree = fileService.readTree(workDir, 2, true)
+    const src = tree.find((n) => n.name === 'src')!
+
+    expect(src.children?.map((n) => n.name)).toEqual(['nested', 'index.ts'])
+    expect(src.children?.[0]).toMatchObject({ type: 'directory', hasChildren: true })
+    expect(src.children?.[0]).not.toHaveProperty('children')
+  })
+})
+
+describe('atomic writes', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it.skipIf(process.platform === 'win32')('keeps the permissions of the file it overwrites', () => {
+    const target = path.join(workDir, 'script.sh')
+    fs.writeFileSync(target, 'old')
+    fs.chmodSync(target, 0o751)
+
+    fileService.writeFile(target, 'new')
+
+    expect(fs.readFileSync(target, 'utf-8')).toBe('new')
+    expect(fs.statSync(target).mode & 0o7777).toBe(0o751)
+  })
+
+  it('leaves the original and no temp file behind when the write fails', () => {
+    const target = path.join(workDir, 'notes.txt')
+    fs.writeFileSync(target, 'original content')
+    const writeFileSync = fs.writeFileSync
+    // Write part of the content, then fail as a full disk would
+    vi.spyOn(fs, 'writeFileSync').mockImplementationOnce((file, data) => {
+      writeFileSync(file, String(data).slice(0, 3))
+      throw new Error('ENOSPC: no space left on device')
+    })
+
+    expect(() => fileService.writeFile(target, 'replacement content')).toThrow('ENOSPC')
+
+    expect(fs.readFileSync(target, 'utf-8')).toBe('original content')
+    expect(fs.readdirSync(workDir)).toEqual(['notes.txt'])
+  })
+
+  it('creates missing directories only when asked to', () => {
+    const target = path.join(workDir, 'a', 'b', 'new.txt')
+
+    expect(() => fileService.writeFile(target, 'x')).toThrow('Directory does not exist')
+    expect(fs.existsSync(path.join(workDir, 'a'))).toBe(false)
+
+    fileService.writeFile(target, 'x', true)
+    expect(fs.readFileSync(target, 'utf-8')).toBe('x')
+  })
+
+  it('refuses to write outside the root', () => {
+    fs.mkdirSync(path.join(workDir, 'root'))
+    const root = path.join(workDir, 'root')
+
+    expect(() => fileService.writeFile('../escape.txt', 'x', false, root)).toThrow('Refusing to write outside')
+    expect(() => fileService.writeFile(path.join(workDir, 'escape.txt'), 'x', false, root)).toThrow('Refusing to write outside')
+    expect(() => fileService.writeFile('.', 'x', false, root)).toThrow('Refusing to write outside')
+    expect(fs.existsSync(path.join(workDir, 'escape.txt'))).toBe(false)
+
+    // Names that merely start with '..' stay inside
+    fileService.writeFile('..notes', 'inside', false, root)
+    fileService.writeFile('sub/../ok.txt', 'inside', false, root)
+    expect(fs.readdirSync(root).sort()).toEqual(['..notes', 'ok.txt'])
+  })
+})
+
+describe('tree metadata', () => {
+  it('adds file sizes and modification times when requested', () => {
+    writeFiles({ 'src/index.ts': '12345', 'notes.txt': '' })
+    const modified = new Date('2026-01-02T03:04:05.000Z')
+    fs.utimesSync(path.join(workDir, 'src/index.ts'), modified, modified)
+
+    const tree = fileService.readTree(workDir, 3, false, true)
+
+    const [src, notes] = tree
+    expect(src).toMatchObject({ name: 'src', type: 'directory' })
+    expect(src).not.toHaveProperty('size')
+    expect(Date.parse(src.modified!)).not.toBeNaN()
+    expect(notes).toMatchObject({ name: 'notes.txt', size: 0 })
+    expect(src.children?.[0]).toMatchObject({ name: 'index.ts', size: 5, modified: '2026-01-02T03:04:05.000Z' })
+  })
+
+  it('leaves the fields out by default', () => {
+    writeFiles({ 'notes.txt': 'abc' })
+
+    const [notes] = fileService.readTree(workDir)
+
+    expect(notes).not.toHaveProperty('size')
+    expect(notes).not.toHaveProperty('modified')
+  })
+
+  it.skipIf(process.platform === 'win32')('keeps walking past entries that cannot be stat-ed', () => {
+    writeFiles({ 'notes.txt': 'abc' })
+    fs.symlinkSync(path.join(workDir, 'missing'), path.join(workDir, 'broken-link'))
+
+    const tree = fileService.readTree(workDir, 3, false, true)
+
+    expect(tree.map((n) => n.name)).toEqual(['broken-link', 'notes.txt'])
+    expect(tree[0]).not.toHaveProperty('modified')
+    expect(tree[1]).toMatchObject({ size: 3 })
+  })
+})
@@ -22,17 +22,27 @@ const IGNORED_ENTRIES = new Set([
 
 export class FileService {
   /**
-   * Build a file tree for a directory
+   * Build a file tree for a directory. With `lazy`, directories at the depth
+   * limit get no `children` but a `hasChildren` flag, so the UI can expand
+   * them on demand through readDir instead of walking everything upfront.
+   * `includeMetadata` adds size and modification time, at the cost of a stat per entry.
    */
-  readTree(dirPath: string, depth = 3): FileTreeNode[] {
+  readTree(dirPath: string, depth = 3, lazy = false, includeMetadata = false): FileTreeNode[] {
     try {
-      return this.buildTree(dirPath, depth, 0)
+      return this.buildTree(dirPath, depth, 0, { lazy, includeMetadata })
     } catch (error) {
       logger.error(`Failed to read tree: ${dirPath}`, error)
       return []
     }
   }
 
+  /**
+   * Immediate children of one directory, subdirectories left unexpanded
+   */
+  readDir(dirPath: string, includeMetadata = false): FileTreeNode[] {
+    return this.readTree(dirPath, 1, true, includeMetadata)
+  }
+
   /**
    * Read file contents
    */
@@ -40,6 +50,53 @@ export class FileService {
     return fs.readFileSync(filePath, 'utf-8')
   }
 
+  /**
+   * Write a file atomically: the content goes to a temp file next to the target
+   * which is then renamed over it, so readers never see a partial file. The
+   * original file's mode is kept. Missing parent directories are only created
+   * with `createDirs`; with `root`, relative paths resolve against it and the
+   * target may not escape it.
+   */
+  writeFile(filePath: string, content: string, createDirs = false, root?: string): void {
+    const target = root ? path.resolve(root, filePath) : path.resolve(filePath)
+    if (root) {
+      const relative = path.relative(path.resolve(root), target)
+      if (!relative || relative === '..' || relative.startsWith('..' + path.sep) || path.isAbsolute(relative)) {
+        throw new Error(`Refusing to write outside ${root}: ${filePath}`)
+      }
+    }
+
+    const dir = path.dirname(target)
+    if (!fs.existsSync(dir)) {
+      if (!createDirs) throw new Error(`Directory does not exist: ${dir}`)
+      fs.mkdirSync(dir, { recursive: true })
+    }
+
+    let mode: number | undefined
+    try {
+      mode = fs.statSync(target).mode & 0o7777
+    } catch {
+      // New file: default mode
+    }
+
+    const tempPath = path.join(dir, `.${path.basename(target)}.${process.pid}.${Date.now()}.tmp`)
+    try {
+      const fd = fs.openSync(tempPath, 'wx', mode ?? 0o666)
+      try {
+        fs.writeFileSync(fd, content, 'utf-8')
+        fs.fsyncSync(fd)
+      } finally {
+        fs.closeSync(fd)
+      }
+      // openSync's mode is filtered by the umask; restore the original exactly
+      if (mode !== undefined) fs.chmodSync(tempPath, mode)
+      fs.renameSync(tempPath, target)
+    } catch (error) {
+      fs.rmSync(tempPath, { force: true })
+      throw error
+    }
+  }
+
   /**
    * Get list of changed files (tracked by git) in a directory
    */
@@ -90,7 +147,12 @@ export class FileService {
     }
   }
 
-  private buildTree(dirPath: string, maxDepth: number, currentDepth: number): FileTreeNode[] {
+  private buildTree(
+    dirPath: string,
+    maxDepth: number,
+    currentDepth: number,
+    options: { lazy: boolean; includeMetadata: boolean }
+  ): FileTreeNode[] {
     if (currentDepth >= maxDepth) return []
 
     const entries = fs.readdirSync(dirPath, { withFileTypes: true })
@@ -114,11 +176,21 @@ export class FileService {
       }
 
       if (entry.isDirectory()) {
-        node.children = this.buildTree(fullPath, maxDepth, currentDepth + 1)
+        if (options.lazy && currentDepth + 1 >= maxDepth) {
+          node.hasChildren = this.hasVisibleEntries(fullPath)
+        } else {
+          node.children = this.buildTree(fullPath, maxDepth, currentDepth + 1, options)
+        }
       } else {
         node.extension = path.extname(entry.name).slice(1)
+      }
+
+      if (options.includeMetadata) {
+        // A failed stat (broken symlink, permissions) just leaves the fields unset
         try {
-          node.size = fs.statSync(fullPath).size
+          const stat = fs.statSync(fullPath)
+          node.modified = stat.mtime.toISOString()
+          if (!entry.isDirectory()) node.size = stat.size
         } catch { /* ignore */ }
       }
 
@@ -127,6 +199,22 @@ export class FileService {
 
     return nodes
   }
+
+  private hasVisibleEntries(dirPath: string): boolean {
+    try {
+      const dir = fs.opendirSync(dirPath)
+      try {
+        for (let entry = dir.readSync(); entry; entry = dir.readSync()) {
+          if (!IGNORED_ENTRIES.has(entry.name)) return true
+        }
+        return false
+      } finally {
+        dir.closeSync()
+      }
+    } catch {
+      return false
+    }
+  }
 }
 
 export const fileService = new FileService()
@@ -0,0 +1,92 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import type { Message, SessionInfo } from '@shared/types/session'
+import { FolderThreadStore } from './folder-thread-store'
+
+const THREAD_ID = 'thread-1'
+
+let workDir: string
+
+beforeEach(() => {
+  workDir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'folder-thread-store-')))
+})
+
+afterEach(() => {
+  fs.rmSync(workDir, { recursive: true, force: true })
+})
+
+function session(messages: Message[] = []): SessionInfo {
+  return {
+    sessionId: THREAD_ID,
+    connectionId: '',
+    agentId: 'mock-agent',
+    agentName: 'Mock Agent',
+    title: 'Thread',
+    createdAt: new Date(0).toISOString(),
+    workingDir: workDir,
+    status: 'idle',
+    messages,
+    useWorktree: false,
+    workspaceId: 'ws-1'
+  }
+}
+
+function message(index: number, text = `message ${index}`): Message {
+  return {
+    id: `msg-${index}`,
+    role: index % 2 === 0 ? 'user' : 'agent',
+    content: [{ type: 'text', text }],
+    timestamp: new Date(index * 1000).toISOString()
+  }
+}
+
+function messageLines(store: FolderThreadStore): string[] {
+  const file = path.join(store.getThreadDir(workDir, THREAD_ID), 'messages.jsonl')
+  return fs.readFileSync(file, 'utf-8').split('\n').filter((line) => line.length > 0)
+}
+
+describe('streaming appends', () => {
+  it('keeps one entry per message across 5000 streamed messages', () => {
+    const store = new FolderThreadStore()
+    store.saveThread(workDir, session())
+
+    const messages: Message[] = []
+    for (let i = 0; i < 5000; i++) {
+      // Each message streams in twice: a first chunk, then its final text
+      store.appendMessage(workDir, THREAD_ID, { ...message(i, 'partial'), isStreaming: true })
+      const final = message(i)
+      store.appendMessage(workDir, THREAD_ID, final)
+      messages.push(final)
+    }
+
+    const stored = store.readMessages(workDir, THREAD_ID)
+    expect(stored).toHaveLength(5000)
+    expect(new Set(stored.map((m) => m.id)).size).toBe(5000)
+    expect(stored.map((m) => m.id)).toEqual(messages.map((m) => m.id))
+    expect(stored[4999].content).toEqual([{ type: 'text', text: 'message 4999' }])
+
+    store.updateMessages(workDir, THREAD_ID, messages, true)
+    expect(messageLines(store)).toHaveLength(5000)
+  })
+})
+
+describe('incremental updates', () => {
+  it('appends only new lines across 1000 updates', () => {
+    const store = new FolderThreadStore()
+    store.saveThread(workDir, session())
+
+    const messages: Message[] = []
+    for (let i = 0; i < 1000; i++) {
+      messages.push(message(i))
+      store.updateMessages(workDir, THREAD_ID, [...messages])
+    }
+
+    const lines = messageLines(store)
+    expect(lines).toHaveLength(1000)
+    const ids = lines.map((line) => (JSON.parse(line) as { id: string }).id)
+    expect(new Set(ids).size).toBe(1000)
+    expect(ids).toEqual(messages.map((m) => m.id))
+  })
+})
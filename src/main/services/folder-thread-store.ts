@@ -1,7 +1,15 @@
 import fs from 'fs'
 import path from 'path'
 import crypto from 'crypto'
-import type { SessionInfo, Message, PersistedThread, ContentBlock } from '@shared/types/session'
+import type {
+  SessionInfo,
+  Message,
+  PersistedThread,
+  ContentBlock,
+  StopReason,
+  UsageCost,
+  ReportedCost
+} from '@shared/types/session'
 import type {
   ThreadManifest,
   StoredMessage,
@@ -21,6 +29,11 @@ import { APP_NAME, CLIENT_INFO } from '@shared/constants'
 import { logger } from '../util/logger'
 
 const INTERACTION_MODE_METADATA_KEY = 'interactionMode'
+const ACP_SESSION_ID_METADATA_KEY = 'acpSessionId'
+const SYSTEM_PROMPT_METADATA_KEY = 'systemPrompt'
+const USAGE_METADATA_KEY = 'usage'
+/** Integrator metadata is nested under its own key so it can't clobber ours */
+const USER_METADATA_KEY = 'custom'
 
 const DEFAULT_GITIGNORE = `# Agent Thread Storage Format - default .gitignore
 # Conversation threads are not committed by default.
@@ -35,7 +48,24 @@ threads/*/assets/
 # !threads/*/thread.json
 `
 
+/** Size and mtime of a messages.jsonl file as last seen by this process. */
+interface FileFingerprint {
+  mtimeMs: number
+  size: number
+}
+
+/** What this process last wrote to a messages.jsonl: latest line per message id, in file order. */
+interface WrittenMessages {
+  lines: Map<string, string>
+  lineCount: number
+}
+
 export class FolderThreadStore {
+  /** Last known fingerprint per messages.jsonl path, used to detect external edits. */
+  private messageFileFingerprints = new Map<string, FileFingerprint>()
+  /** Lines written per messages.jsonl path, so updates can append instead of rewriting. */
+  private writtenMessages = new Map<string, WrittenMessages>()
+
   // ---- Directory helpers ----
 
   getAgentDir(workspacePath: string): string {
@@ -93,59 +123,185 @@ export class FolderThreadStore {
       const stored = this.messageToStored(m, threadDir)
       return JSON.stringify(stored)
     })
-    fs.writeFileSync(messagesPath, lines.length > 0 ? lines.join('\n') + '\n' : '', 'utf-8')
+    this.rewriteMessages(messagesPath, session.messages, lines)
 
     logger.info(`Thread saved to folder: ${session.sessionId}`)
   }
 
-  updateMessages(workspacePath: string, threadId: string, messages: Message[]): void {
+  /**
+   * Persist messages to messages.jsonl, appending only new or changed messages when
+   * the file still matches what this process wrote; otherwise the file is rewritten.
+   * `compact` always rewrites, leaving one line per message (done once a prompt ends).
+   * Returns the messages actually written, and whether external edits were merged in.
+   */
+  updateMessages(
+    workspacePath: string,
+    threadId: string,
+    messages: Message[],
+    compact = false
+  ): { messages: Message[]; merged: boolean } {
     const threadDir = this.getThreadDir(workspacePath, threadId)
     if (!fs.existsSync(threadDir)) {
       logger.warn(`Thread dir not found for update: ${threadDir}`)
-      return
+      return { messages, merged: false }
     }
 
-    // Rewrite messages.jsonl
-    const messagesPath = path.join(threadDir, MESSAGES_FILE)
-    const lines = messages.map((m) => {
+    const toLine = (m: Message): string => {
       const rest = { ...m }
       delete rest.isStreaming
-      const stored = this.messageToStored(rest as Message, threadDir)
-      return JSON.stringify(stored)
-    })
-    fs.writeFileSync(messagesPath, lines.length > 0 ? lines.join('\n') + '\n' : '', 'utf-8')
+      return JSON.stringify(this.messageToStored(rest as Message, threadDir))
+    }
+
+    // Rewrite messages.jsonl, merging in any edits made outside this process
+    const messagesPath = path.join(threadDir, MESSAGES_FILE)
+    let merged = false
+    if (this.hasChangedExternally(messagesPath)) {
+      logger.warn(`messages.jsonl changed externally, merging before rewrite: ${messagesPath}`)
+      const stored = this.readMessages(workspacePath, threadId)
+      const diskLines = new Map(stored.map((sm) => [sm.id, JSON.stringify(sm)]))
+      const written = this.writtenMessages.get(messagesPath)?.lines
+      const inMemory = new Map(messages.map((m) => [m.id, m]))
+      // The disk copy wins when it was edited there since our last write and ours wasn't
+      const preferDisk = (id: string): boolean => {
+        const last = written?.get(id)
+        return last !== undefined && diskLines.get(id) !== last && toLine(inMemory.get(id)!) === last
+      }
+      const onDisk = stored.map((sm) => this.storedToMessage(sm, threadDir))
+      messages = mergeMessages(onDisk, messages, preferDisk)
+      merged = true
+      this.writtenMessages.delete(messagesPath)
+    }
+    const lines = messages.map(toLine)
+    if (merged || compact || !this.appendChangedMessages(messagesPath, messages, lines)) {
+      this.rewriteMessages(messagesPath, messages, lines)
+    }
 
     // Update thread.json stats
     this.updateManifestStats(threadDir, messages)
+    return { messages, merged }
   }
 
-  updateManifestTitle(workspacePath: string, threadId: string, title: string): void {
+  /**
+   * Rewrite messages.jsonl with exactly `messages`, without merging in what is
+   * on disk. Used for edits and deletions made in the app, where a merge would
+   * bring removed messages back as disk-only ones.
+   */
+  replaceMessages(workspacePath: string, threadId: string, messages: Message[]): void {
     const threadDir = this.getThreadDir(workspacePath, threadId)
-    const manifestPath = path.join(threadDir, THREAD_MANIFEST_FILE)
-    if (!fs.existsSync(manifestPath)) return
+    if (!fs.existsSync(threadDir)) {
+      logger.warn(`Thread dir not found for update: ${threadDir}`)
+      return
+    }
 
-    try {
-      const manifest: ThreadManifest = JSON.parse(fs.readFileSync(manifestPath, 'utf-8'))
-      manifest.title = title
-      this.writeJsonAtomic(manifestPath, manifest)
-    } catch (err) {
-      logger.warn(`Failed to update manifest title: ${manifestPath}`, err)
+    const messagesPath = path.join(threadDir, MESSAGES_FILE)
+    const lines = messages.map((m) => JSON.stringify(this.messageToStored(m, threadDir)))
+    this.rewriteMessages(messagesPath, messages, lines)
+    this.updateManifestStats(threadDir, messages)
+  }
+
+  /**
+   * Append a single message line to messages.jsonl without rewriting the file.
+   * Used while a prompt is streaming; a later line for the same id supersedes
+   * earlier ones until the compacting updateMessages() at the end of the prompt.
+   */
+  appendMessage(workspacePath: string, threadId: string, message: Message): void {
+    const threadDir = this.getThreadDir(workspacePath, threadId)
+    if (!fs.existsSync(threadDir)) {
+      logger.warn(`Thread dir not found for append: ${threadDir}`)
+      return
+    }
+
+    const messagesPath = path.join(threadDir, MESSAGES_FILE)
+    // Leave the fingerprint stale on external edits so the next rewrite merges them
+    const changedExternally = this.hasChangedExternally(messagesPath)
+    const rest = { ...message }
+    delete rest.isStreaming
+    const stored = this.messageToStored(rest as Message, threadDir)
+    const line = JSON.stringify(stored)
+    fs.appendFileSync(messagesPath, line + '\n', 'utf-8')
+    if (!changedExternally) this.recordFingerprint(messagesPath)
+    // Still track our own lines so the merge can tell their edits from ours
+    const written = this.writtenMessages.get(messagesPath)
+    if (written) {
+      written.lines.set(message.id, line)
+      written.lineCount++
     }
   }
 
+  updateManifestTitle(workspacePath: string, threadId: string, title: string): void {
+    this.patchManifest(workspacePath, threadId, 'title', (manifest) => {
+      manifest.title = title
+    })
+  }
+
   updateManifestWorktreeBranch(workspacePath: string, threadId: string, newBranch: string): void {
+    this.patchManifest(workspacePath, threadId, 'worktree branch', (manifest) => {
+      if (manifest.context.worktree) {
+        manifest.context.worktree.branch = newBranch
+      }
+    })
+  }
+
+  updateManifestParent(workspacePath: string, threadId: string, parentThreadId: string | undefined): void {
+    this.patchManifest(workspacePath, threadId, 'parent', (manifest) => {
+      if (parentThreadId) {
+        manifest.parentThreadId = parentThreadId
+      } else {
+        delete manifest.parentThreadId
+      }
+    })
+  }
+
+  updateManifestAcpSessionId(workspacePath: string, threadId: string, acpSessionId: string): void {
+    this.patchManifest(workspacePath, threadId, 'ACP session id', (manifest) => {
+      manifest.metadata = { ...manifest.metadata, [ACP_SESSION_ID_METADATA_KEY]: acpSessionId }
+    })
+  }
+
+  updateManifestSystemPrompt(workspacePath: string, threadId: string, systemPrompt: string | undefined): void {
+    this.patchManifest(workspacePath, threadId, 'system prompt', (manifest) => {
+      setManifestMetadata(manifest, SYSTEM_PROMPT_METADATA_KEY, systemPrompt)
+    })
+  }
+
+  updateManifestMetadata(workspacePath: string, threadId: string, metadata: Record<string, unknown> | undefined): void {
+    this.patchManifest(workspacePath, threadId, 'metadata', (manifest) => {
+      setManifestMetadata(manifest, USER_METADATA_KEY, metadata)
+    })
+  }
+
+  updateManifestCost(
+    workspacePath: string,
+    threadId: string,
+    totalCost: UsageCost,
+    reportedCost: ReportedCost | undefined
+  ): void {
+    this.patchManifest(workspacePath, threadId, 'cost', (manifest) => {
+      setManifestMetadata(manifest, USAGE_METADATA_KEY, usageMetadata(totalCost, reportedCost))
+    })
+  }
+
+  /**
+   * Read-modify-write the manifest alone. Unlike saveThread() this leaves
+   * messages.jsonl untouched, so callers holding an older message list can't
+   * clobber newer lines.
+   */
+  private patchManifest(
+    workspacePath: string,
+    threadId: string,
+    what: string,
+    apply: (manifest: ThreadManifest) => void
+  ): void {
     const threadDir = this.getThreadDir(workspacePath, threadId)
     const manifestPath = path.join(threadDir, THREAD_MANIFEST_FILE)
     if (!fs.existsSync(manifestPath)) return
 
     try {
       const manifest: ThreadManifest = JSON.parse(fs.readFileSync(manifestPath, 'utf-8'))
-      if (manifest.context.worktree) {
-        manifest.context.worktree.branch = newBranch
-      }
+      apply(manifest)
       this.writeJsonAtomic(manifestPath, manifest)
     } catch (err) {
-      logger.warn(`Failed to update manifest worktree branch: ${manifestPath}`, err)
+      logger.warn(`Failed to update manifest ${what}: ${manifestPath}`, err)
     }
   }
 
@@ -153,6 +309,9 @@ export class FolderThreadStore {
     const threadDir = this.getThreadDir(workspacePath, threadId)
     if (fs.existsSync(threadDir)) {
       fs.rmSync(threadDir, { recursive: true, force: true })
+      const messagesPath = path.join(threadDir, MESSAGES_FILE)
+      this.messageFileFingerprints.delete(messagesPath)
+      this.writtenMessages.delete(messagesPath)
       logger.info(`Thread removed from folder: ${threadId}`)
     }
   }
@@ -180,9 +339,27 @@ export class FolderThreadStore {
     )
     if (!fs.existsSync(messagesPath)) return []
     try {
+      // No fingerprint here: reading an external edit must not mark it as merged
       const content = fs.readFileSync(messagesPath, 'utf-8')
       const lines = content.split('\n').filter((line) => line.trim().length > 0)
-      return lines.map((line) => JSON.parse(line) as StoredMessage)
+      // Streaming appends may write the same message several times; keep the
+      // latest version at the position where the message first appeared.
+      const byId = new Map<string, StoredMessage>()
+      for (let i = 0; i < lines.length; i++) {
+        let stored: StoredMessage
+        try {
+          stored = JSON.parse(lines[i]) as StoredMessage
+        } catch (err) {
+          // A cut-off last line is a write still in progress (or interrupted); skip it
+          if (i === lines.length - 1) {
+            logger.warn(`Ignoring partial last line of ${messagesPath}`)
+            break
+          }
+          throw err
+        }
+        byId.set(stored.id, stored)
+      }
+      return Array.from(byId.values())
     } catch (err) {
       logger.warn(`Failed to read messages: ${messagesPath}`, err)
       return []
@@ -225,6 +402,7 @@ export class FolderThreadStore {
       timestamp: message.timestamp,
       content
     }
+    if (message.stopReason) stored.stopReason = message.stopReason
 
     if (message.toolCalls && message.toolCalls.length > 0) {
       stored.toolCalls = message.toolCalls.map((tc) => {
@@ -257,6 +435,7 @@ export class FolderThreadStore {
       timestamp: stored.timestamp,
       content
     }
+    if (stored.stopReason) message.stopReason = stored.stopReason as StopReason
 
     if (stored.toolCalls && stored.toolCalls.length > 0) {
       message.toolCalls = stored.toolCalls.map((stc) => ({
@@ -296,16 +475,26 @@ export class FolderThreadStore {
           ? {
               worktree: {
                 path: session.worktreePath,
-                branch: session.worktreeBranch
+                branch: session.worktreeBranch,
+                ...(session.sourcePath ? { sourcePath: session.sourcePath } : {})
               }
             }
           : {})
       },
       stats,
       parentThreadId: session.parentSessionId,
-      metadata: session.interactionMode
-        ? { [INTERACTION_MODE_METADATA_KEY]: session.interactionMode }
-        : undefined
+      metadata:
+        session.interactionMode || session.acpSessionId || session.systemPrompt || session.totalCost || session.metadata
+          ? {
+              ...(session.interactionMode ? { [INTERACTION_MODE_METADATA_KEY]: session.interactionMode } : {}),
+              ...(session.acpSessionId ? { [ACP_SESSION_ID_METADATA_KEY]: session.acpSessionId } : {}),
+              ...(session.systemPrompt ? { [SYSTEM_PROMPT_METADATA_KEY]: session.systemPrompt } : {}),
+              ...(session.totalCost
+                ? { [USAGE_METADATA_KEY]: usageMetadata(session.totalCost, session.reportedCost) }
+                : {}),
+              ...(session.metadata ? { [USER_METADATA_KEY]: session.metadata } : {})
+            }
+          : undefined
     }
   }
 
@@ -314,6 +503,9 @@ export class FolderThreadStore {
     messages: Message[],
     workspaceId: string
   ): PersistedThread {
+    const usage = isPlainObject(manifest.metadata?.[USAGE_METADATA_KEY])
+      ? (manifest.metadata?.[USAGE_METADATA_KEY] as { totalCost?: unknown; reportedCost?: unknown })
+      : undefined
     return {
       sessionId: manifest.threadId,
       agentId: manifest.agent.id,
@@ -322,6 +514,9 @@ export class FolderThreadStore {
       createdAt: manifest.createdAt,
       worktreePath: manifest.context.worktree?.path,
       worktreeBranch: manifest.context.worktree?.branch,
+      sourcePath: manifest.context.worktree
+        ? manifest.context.worktree.sourcePath
+        : manifest.context.workingDir,
       workingDir: manifest.context.workingDir,
       messages,
       interactionMode:
@@ -330,7 +525,20 @@ export class FolderThreadStore {
           : undefined,
       useWorktree: !!manifest.context.worktree,
       workspaceId,
-      parentSessionId: manifest.parentThreadId
+      parentSessionId: manifest.parentThreadId,
+      acpSessionId:
+        typeof manifest.metadata?.[ACP_SESSION_ID_METADATA_KEY] === 'string'
+          ? (manifest.metadata?.[ACP_SESSION_ID_METADATA_KEY] as string)
+          : undefined,
+      metadata: isPlainObject(manifest.metadata?.[USER_METADATA_KEY])
+        ? (manifest.metadata?.[USER_METADATA_KEY] as Record<string, unknown>)
+        : undefined,
+      totalCost: readCost(usage?.totalCost),
+      reportedCost: readReportedCost(usage?.reportedCost),
+      systemPrompt:
+        typeof manifest.metadata?.[SYSTEM_PROMPT_METADATA_KEY] === 'string'
+          ? (manifest.metadata?.[SYSTEM_PROMPT_METADATA_KEY] as string)
+          : undefined
     }
   }
 
@@ -555,6 +763,60 @@ export class FolderThreadStore {
     }
   }
 
+  private rewriteMessages(messagesPath: string, messages: Message[], lines: string[]): void {
+    fs.writeFileSync(messagesPath, lines.length > 0 ? lines.join('\n') + '\n' : '', 'utf-8')
+    this.recordFingerprint(messagesPath)
+    this.writtenMessages.set(messagesPath, {
+      lines: new Map(messages.map((m, i) => [m.id, lines[i]])),
+      lineCount: lines.length
+    })
+  }
+
+  /**
+   * Append lines for messages that are new or changed since the last write.
+   * Returns false when a full rewrite is needed instead: nothing is known about
+   * the file, messages were removed or reordered, or superseded lines would
+   * make the file more than twice as long as the thread.
+   */
+  private appendChangedMessages(messagesPath: string, messages: Message[], lines: string[]): boolean {
+    const written = this.writtenMessages.get(messagesPath)
+    if (!written || this.hasChangedExternally(messagesPath)) return false
+
+    // Previously written messages must still be the leading messages, in order
+    const knownIds = Array.from(written.lines.keys())
+    if (knownIds.length > messages.length) return false
+    for (let i = 0; i < knownIds.length; i++) {
+      if (messages[i].id !== knownIds[i]) return false
+    }
+
+    const changed = lines.filter((line, i) => written.lines.get(messages[i].id) !== line)
+    if (changed.length === 0) return true
+    if (written.lineCount + changed.length > messages.length * 2) return false
+
+    fs.appendFileSync(messagesPath, changed.join('\n') + '\n', 'utf-8')
+    this.recordFingerprint(messagesPath)
+    messages.forEach((m, i) => written.lines.set(m.id, lines[i]))
+    written.lineCount += changed.length
+    return true
+  }
+
+  private recordFingerprint(filePath: string): void {
+    try {
+      const stat = fs.statSync(filePath)
+      this.messageFileFingerprints.set(filePath, { mtimeMs: stat.mtimeMs, size: stat.size })
+    } catch {
+      this.messageFileFingerprints.delete(filePath)
+    }
+  }
+
+  /** True if the file differs from what we last read or wrote. Unknown files are not flagged. */
+  private hasChangedExternally(filePath: string): boolean {
+    const known = this.messageFileFingerprints.get(filePath)
+    if (!known || !fs.existsSync(filePath)) return false
+    const stat = fs.statSync(filePath)
+    return stat.mtimeMs !== known.mtimeMs || stat.size !== known.size
+  }
+
   private writeJsonAtomic(filePath: string, data: unknown): void {
     const tmpPath = filePath + '.tmp'
     fs.writeFileSync(tmpPath, JSON.stringify(data, null, 2) + '\n', 'utf-8')
@@ -585,4 +847,56 @@ export class FolderThreadStore {
   }
 }
 
+/**
+ * Merge messages found on disk with the in-memory list.
+ * On id conflicts the in-memory copy wins unless `preferDisk` says only the disk
+ * copy was edited; messages only present on disk are kept.
+ * The result is ordered by timestamp (stable for equal timestamps).
+ */
+function mergeMessages(onDisk: Message[], inMemory: Message[], preferDisk: (id: string) => boolean): Message[] {
+  const diskById = new Map(onDisk.map((m) => [m.id, m]))
+  const kept = inMemory.map((m) => (diskById.has(m.id) && preferDisk(m.id) ? diskById.get(m.id)! : m))
+  const inMemoryIds = new Set(inMemory.map((m) => m.id))
+  const diskOnly = onDisk.filter((m) => !inMemoryIds.has(m.id))
+  if (diskOnly.length === 0) return kept
+  return [...kept, ...diskOnly].sort((a, b) => a.timestamp.localeCompare(b.timestamp))
+}
+
+function usageMetadata(totalCost: UsageCost, reportedCost: ReportedCost | undefined): Record<string, unknown> {
+  return { totalCost, ...(reportedCost ? { reportedCost } : {}) }
+}
+
+function readCost(value: unknown): UsageCost | undefined {
+  if (!isPlainObject(value)) return undefined
+  return typeof value.amount === 'number' && typeof value.currency === 'string'
+    ? { amount: value.amount, currency: value.currency }
+    : undefined
+}
+
+function readReportedCost(value: unknown): ReportedCost | undefined {
+  const cost = readCost(value)
+  return cost && isPlainObject(value) && typeof value.acpSessionId === 'string'
+    ? { ...cost, acpSessionId: value.acpSessionId }
+    : undefined
+}
+
+/** Set or (for undefined) remove one of our keys in the manifest metadata */
+function setManifestMetadata(manifest: ThreadManifest, key: string, value: unknown): void {
+  const metadata = { ...manifest.metadata }
+  if (value === undefined) {
+    delete metadata[key]
+  } else {
+    metadata[key] = value
+  }
+  if (Object.keys(metadata).length > 0) {
+    manifest.metadata = metadata
+  } else {
+    delete manifest.metadata
+  }
+}
+
+function isPlainObject(value: unknown): value is Record<string, unknown> {
+  return typeof value === 'object' && value !== null && !Array.isArray(value)
+}
+
 export const folderThreadStore = new FolderThreadStore()
@@ -0,0 +1,816 @@
+import fs from 'fs'
+import path from 'path'
+import { afterEach, describe, expect, it } from 'vitest'
+import type { DiffResult } from '@shared/types/project'
+import { GitService } from './git-service'
+import { folderThreadStore } from './folder-thread-store'
+import { settingsService } from './settings-service'
+import { GitConflictError } from '../util/git-error'
+import { cloneRepo, commitFile, git, initBareRemote, initRepo, removeTempDirs, tempDir } from '../test/git-repo'
+
+const gitService = new GitService()
+
+afterEach(() => {
+  removeTempDirs()
+})
+
+describe('push', () => {
+  function repoWithRemote(): { repo: string; remote: string } {
+    const repo = initRepo()
+    const remote = initBareRemote()
+    git(repo, 'remote', 'add', 'origin', remote)
+    return { repo, remote }
+  }
+
+  it('refuses to push without a remote', async () => {
+    const repo = initRepo()
+
+    await expect(gitService.push(repo)).rejects.toThrow('no git remote is configured')
+  })
+
+  it('refuses to publish a branch without an upstream unless asked to', async () => {
+    const { repo, remote } = repoWithRemote()
+
+    await expect(gitService.push(repo)).rejects.toThrow("Branch 'main' has no upstream")
+    expect(git(remote, 'branch', '--list')).toBe('')
+  })
+
+  it('publishes the branch and sets its upstream', async () => {
+    const { repo, remote } = repoWithRemote()
+
+    const result = await gitService.push(repo, undefined, true)
+
+    expect(result).toEqual({ branch: 'main', remote: 'origin', remoteUrl: remote, upstreamSet: true, pullRequestUrl: undefined })
+    expect(git(remote, 'rev-parse', 'main')).toBe(git(repo, 'rev-parse', 'HEAD'))
+    expect(git(repo, 'rev-parse', '--abbrev-ref', 'main@{upstream}')).toBe('origin/main')
+  })
+
+  it('pushes new commits to the tracked branch, even under another name', async () => {
+    const { repo, remote } = repoWithRemote()
+    git(repo, 'push', '-q', '-u', 'origin', 'main:published')
+    const head = commitFile(repo, 'notes.txt', 'more\n', 'Add notes')
+
+    const result = await gitService.push(repo)
+
+    expect(result.upstreamSet).toBe(false)
+    expect(git(remote, 'rev-parse', 'published')).toBe(head)
+    expect(git(remote, 'branch', '--list', 'main')).toBe('')
+  })
+
+  it('refuses a remote that does not exist', async () => {
+    const { repo } = repoWithRemote()
+
+    await expect(gitService.push(repo, 'upstream', true)).rejects.toThrow("remote 'upstream' does not exist")
+  })
+})
+
+describe('fetch and pull', () => {
+  /** Two clones of one bare remote: `upstream` publishes, `local` syncs */
+  function clones(): { upstream: string; local: string } {
+    const upstream = initRepo()
+    const remote = initBareRemote()
+    git(upstream, 'remote', 'add', 'origin', remote)
+    git(upstream, 'push', '-q', '-u', 'origin', 'main')
+    return { upstream, local: cloneRepo(remote) }
+  }
+
+  function publish(upstream: string, file: string, content: string): string {
+    const head = commitFile(upstream, file, content, `Update ${file}`)
+    git(upstream, 'push', '-q')
+    return head
+  }
+
+  it('fetch updates behind without touching the work tree', async () => {
+    const { upstream, local } = clones()
+    publish(upstream, 'remote.txt', 'from upstream\n')
+
+    const status = await gitService.fetch(local)
+
+    expect(status).toMatchObject({ branch: 'main', ahead: 0, behind: 1, isClean: true })
+    expect(git(local, 'ls-files')).toBe('README.md')
+  })
+
+  it('pull merges the upstream commits', async () => {
+    const { upstream, local } = clones()
+    const head = publish(upstream, 'remote.txt', 'from upstream\n')
+
+    const status = await gitService.pull(local)
+
+    expect(status).toMatchObject({ ahead: 0, behind: 0 })
+    expect(git(local, 'rev-parse', 'HEAD')).toBe(head)
+  })
+
+  it('pull --rebase replays local commits on top of upstream', async () => {
+    const { upstream, local } = clones()
+    commitFile(local, 'local.txt', 'mine\n', 'Local change')
+    const upstreamHead = publish(upstream, 'remote.txt', 'from upstream\n')
+
+    const status = await gitService.pull(local, undefined, true)
+
+    expect(status).toMatchObject({ ahead: 1, behind: 0 })
+    expect(git(local, 'rev-parse', 'HEAD~1')).toBe(upstreamHead)
+    expect(git(local, 'rev-list', '--merges', 'HEAD')).toBe('')
+  })
+
+  it.each([
+    [false, 'merge'],
+    [true, 'rebase']
+  ] as const)('reports conflicted files (rebase: %s)', async (rebase, operation) => {
+    const { upstream, local } = clones()
+    commitFile(local, 'README.md', 'local readme\n', 'Local readme')
+    publish(upstream, 'README.md', 'upstream readme\n')
+
+    const pull = gitService.pull(local, undefined, rebase)
+
+    await expect(pull).rejects.toBeInstanceOf(GitConflictError)
+    await expect(pull).rejects.toMatchObject({ files: ['README.md'], operation, aborted: false })
+  })
+
+  it('refuses to pull a branch without an upstream', async () => {
+    const repo = initRepo()
+
+    await expect(gitService.pull(repo)).rejects.toThrow("branch 'main' has no upstream")
+  })
+})
+
+describe('mergeWorktree', () => {
+  /** A repo on main plus a worktree on `feature` with one commit */
+  function repoWithFeature(): { repo: string; worktree: string; featureHead: string } {
+    const repo = initRepo()
+    const worktree = path.join(tempDir('git-worktree-'), 'feature')
+    git(repo, 'worktree', 'add', '-q', '-b', 'feature', worktree)
+    const featureHead = commitFile(worktree, 'feature.txt', 'feature\n', 'Add feature')
+    return { repo, worktree, featureHead }
+  }
+
+  function parents(repo: string, rev = 'main'): string[] {
+    return git(repo, 'rev-list', '--parents', '-n', '1', rev).split(' ').slice(1)
+  }
+
+  it('fast-forwards when the base has not moved', async () => {
+    const { repo, featureHead } = repoWithFeature()
+
+    const result = await gitService.mergeWorktree(repo, 'feature', 'main', 'ff-only')
+
+    expect(result).toEqual({ branch: 'feature', into: 'main', strategy: 'ff-only', commit: featureHead })
+    expect(git(repo, 'rev-parse', 'main')).toBe(featureHead)
+  })
+
+  it('creates a merge commit and switches back to the original branch', async () => {
+    const { repo, featureHead } = repoWithFeature()
+    const mainHead = commitFile(repo, 'main.txt', 'main\n', 'Main change')
+    git(repo, 'switch', '-q', '-c', 'dev')
+
+    const result = await gitService.mergeWorktree(repo, 'feature', 'main', 'merge')
+
+    expect(parents(repo)).toEqual([mainHead, featureHead])
+    expect(result.commit).toBe(git(repo, 'rev-parse', 'main'))
+    expect(git(repo, 'branch', '--show-current')).toBe('dev')
+  })
+
+  it('squashes the branch into a single commit', async () => {
+    const { repo } = repoWithFeature()
+    const mainHead = git(repo, 'rev-parse', 'main')
+
+    await gitService.mergeWorktree(repo, 'feature', 'main', 'squash')
+
+    expect(parents(repo)).toEqual([mainHead])
+    expect(git(repo, 'show', 'main:feature.txt')).toBe('feature')
+  })
+
+  it('refuses a fast-forward once the branches have diverged', async () => {
+    const { repo } = repoWithFeature()
+    commitFile(repo, 'main.txt', 'main\n', 'Main change')
+
+    await expect(gitService.mergeWorktree(repo, 'feature', 'main', 'ff-only')).rejects.toThrow('git merge failed')
+  })
+
+  it.each(['merge', 'squash'] as const)('aborts a conflicting %s and lists the files', async (strategy) => {
+    const { repo, worktree } = repoWithFeature()
+    commitFile(worktree, 'README.md', 'feature readme\n', 'Feature readme')
+    const mainHead = commitFile(repo, 'README.md', 'main readme\n', 'Main readme')
+
+    const merge = gitService.mergeWorktree(repo, 'feature', 'main', strategy)
+
+    await expect(merge).rejects.toBeInstanceOf(GitConflictError)
+    await expect(merge).rejects.toMatchObject({ files: ['README.md'], operation: 'merge', aborted: true })
+    expect(git(repo, 'rev-parse', 'main')).toBe(mainHead)
+    expect(git(repo, 'status', '--porcelain')).toBe('')
+    expect(await gitService.getStatus(repo)).toMatchObject({ inMerge: false, conflicted: [] })
+  })
+})
+
+describe('stage, unstage and discard', () => {
+  it('moves a modified file between the index and the work tree', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'changed\n')
+
+    const modified = await gitService.getStatus(repo)
+    expect(modified.modified).toContain('README.md')
+    expect(modified.staged).not.toContain('README.md')
+
+    const staged = await gitService.stage(repo, ['README.md'])
+    expect(staged.staged).toContain('README.md')
+
+    const unstaged = await gitService.unstage(repo, ['README.md'])
+    expect(unstaged.staged).not.toContain('README.md')
+    expect(unstaged.modified).toContain('README.md')
+    expect(fs.readFileSync(path.join(repo, 'README.md'), 'utf-8')).toBe('changed\n')
+  })
+
+  it('stages and unstages a new file', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'new.txt'), 'new\n')
+
+    expect((await gitService.getStatus(repo)).untracked).toEqual(['new.txt'])
+
+    const staged = await gitService.stage(repo, ['new.txt'])
+    expect(staged.staged).toContain('new.txt')
+    expect(staged.untracked).toEqual([])
+
+    expect((await gitService.unstage(repo, ['new.txt'])).untracked).toEqual(['new.txt'])
+  })
+
+  it('discards tracked changes and deletes untracked files', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'changed\n')
+    fs.writeFileSync(path.join(repo, 'scratch.txt'), 'scratch\n')
+
+    const status = await gitService.discard(repo, ['README.md', 'scratch.txt'])
+
+    expect(status.isClean).toBe(true)
+    expect(fs.readFileSync(path.join(repo, 'README.md'), 'utf-8')).toBe('readme\n')
+    expect(fs.existsSync(path.join(repo, 'scratch.txt'))).toBe(false)
+  })
+
+  it('deletes an untracked directory only when recursive', async () => {
+    const repo = initRepo()
+    fs.mkdirSync(path.join(repo, 'build'))
+    fs.writeFileSync(path.join(repo, 'build', 'out.txt'), 'out\n')
+
+    await expect(gitService.discard(repo, ['build'])).rejects.toThrow('Refusing to delete untracked directories')
+    expect(fs.existsSync(path.join(repo, 'build', 'out.txt'))).toBe(true)
+
+    expect((await gitService.discard(repo, ['build'], true)).isClean).toBe(true)
+    expect(fs.existsSync(path.join(repo, 'build'))).toBe(false)
+  })
+})
+
+describe('diff statistics', () => {
+  function repoWithChanges(): string {
+    const repo = initRepo()
+    commitFile(repo, 'notes.txt', 'one\ntwo\nthree\n', 'Add notes')
+    fs.writeFileSync(path.join(repo, 'logo.bin'), Buffer.from([0, 1, 2, 3, 0, 255]))
+    git(repo, 'add', 'logo.bin')
+    git(repo, 'commit', '-q', '-m', 'Add logo')
+
+    fs.writeFileSync(path.join(repo, 'notes.txt'), 'one\n2\nthree\nfour\n')
+    fs.writeFileSync(path.join(repo, 'logo.bin'), Buffer.from([0, 9, 9, 9, 0, 255]))
+    return repo
+  }
+
+  it('counts added and removed lines per file and in total', async () => {
+    const repo = repoWithChanges()
+
+    const diff = await gitService.getDiff(repo, undefined, false)
+
+    expect(diff.files.find((f) => f.path === 'notes.txt')).toEqual({
+      path: 'notes.txt',
+      changeKind: 'modified',
+      oldPath: undefined,
+      additions: 2,
+      deletions: 1,
+      isBinary: false
+    })
+    expect(diff.totalAdditions).toBe(2)
+    expect(diff.totalDeletions).toBe(1)
+  })
+
+  it('flags binary files and never loads their content', async () => {
+    const repo = repoWithChanges()
+
+    const diff = await gitService.getDiff(repo, 'logo.bin', true)
+
+    expect(diff.files).toHaveLength(1)
+    expect(diff.files[0]).toMatchObject({ path: 'logo.bin', isBinary: true, additions: 0, deletions: 0 })
+    expect(diff.files[0].oldContent).toBeUndefined()
+    expect(diff.files[0].newContent).toBeUndefined()
+  })
+
+  it('loads old and new content only when asked to', async () => {
+    const repo = repoWithChanges()
+
+    const withContent = await gitService.getDiff(repo, 'notes.txt', true)
+    const statsOnly = await gitService.getDiff(repo, 'notes.txt', false)
+
+    expect(withContent.files[0]).toMatchObject({ oldContent: 'one\ntwo\nthree\n', newContent: 'one\n2\nthree\nfour\n' })
+    expect(statsOnly.files[0].oldContent).toBeUndefined()
+    expect(statsOnly.files[0].newContent).toBeUndefined()
+  })
+})
+
+describe('diff parsing', () => {
+  interface Parser {
+    parseDiff(diffText: string, workingDir: string, includeContent: boolean): Promise<DiffResult>
+  }
+
+  const parse = (diffText: string): Promise<DiffResult> =>
+    (gitService as unknown as Parser).parseDiff(diffText, process.cwd(), false)
+
+  it('reads the path and kind of each section', async () => {
+    const diff = await parse(
+      [
+        'diff --git a/src/app.ts b/src/app.ts',
+        'index 1111111..2222222 100644',
+        '--- a/src/app.ts',
+        '+++ b/src/app.ts',
+        '@@ -1 +1 @@',
+        '-old',
+        '+new',
+        'diff --git a/docs/new page.md b/docs/new page.md',
+        'new file mode 100644',
+        'index 0000000..3333333',
+        '--- /dev/null',
+        '+++ b/docs/new page.md\t',
+        '@@ -0,0 +1,2 @@',
+        '+# Title',
+        '+body',
+        'diff --git a/old notes.txt b/old notes.txt',
+        'deleted file mode 100644',
+        'index 4444444..0000000',
+        '--- a/old notes.txt\t',
+        '+++ /dev/null',
+        '@@ -1 +0,0 @@',
+        '-gone',
+        'diff --git a/a b/c.txt b/x b/y.txt',
+        'similarity index 90%',
+        'rename from a b/c.txt',
+        'rename to x b/y.txt',
+        'index 5555555..6666666 100644',
+        '--- a/a b/c.txt\t',
+        '+++ b/x b/y.txt\t',
+        '@@ -1 +1 @@',
+        '-before',
+        '+after',
+        ''
+      ].join('\n')
+    )
+
+    expect(diff.files.map(({ path, oldPath, changeKind, additions, deletions }) => ({ path, oldPath, changeKind, additions, deletions }))).toEqual([
+      { path: 'src/app.ts', oldPath: undefined, changeKind: 'modified', additions: 1, deletions: 1 },
+      { path: 'docs/new page.md', oldPath: undefined, changeKind: 'added', additions: 2, deletions: 0 },
+      { path: 'old notes.txt', oldPath: undefined, changeKind: 'deleted', additions: 0, deletions: 1 },
+      { path: 'x b/y.txt', oldPath: 'a b/c.txt', changeKind: 'renamed', additions: 1, deletions: 1 }
+    ])
+  })
+
+  it('unquotes paths git escapes', async () => {
+    const diff = await parse(
+      [
+        'diff --git "a/caf\\303\\251 \\"menu\\".txt" "b/caf\\303\\251 \\"menu\\".txt"',
+        'index 1111111..2222222 100644',
+        '--- "a/caf\\303\\251 \\"menu\\".txt"',
+        '+++ "b/caf\\303\\251 \\"menu\\".txt"',
+        '@@ -1 +1 @@',
+        '-a',
+        '+b',
+        ''
+      ].join('\n')
+    )
+
+    expect(diff.files[0].path).toBe('café "menu".txt')
+  })
+
+  it('loads content from HEAD for deletions and from disk for additions', async () => {
+    const repo = initRepo()
+    commitFile(repo, 'old notes.txt', 'gone\n', 'Add notes')
+    fs.rmSync(path.join(repo, 'old notes.txt'))
+    fs.writeFileSync(path.join(repo, 'new page.md'), 'fresh\n')
+    git(repo, 'add', '-A')
+
+    const diff = await gitService.getDiff(repo)
+
+    expect(diff.files.find((f) => f.path === 'old notes.txt')).toMatchObject({
+      changeKind: 'deleted',
+      oldContent: 'gone\n',
+      newContent: ''
+    })
+    expect(diff.files.find((f) => f.path === 'new page.md')).toMatchObject({
+      changeKind: 'added',
+      oldContent: '',
+      newContent: 'fresh\n'
+    })
+  })
+
+  it('follows a staged rename', async () => {
+    const repo = initRepo()
+    git(repo, 'mv', 'README.md', 'docs guide.md')
+
+    const diff = await gitService.getDiff(repo)
+
+    expect(diff.files).toEqual([
+      expect.objectContaining({ path: 'docs guide.md', oldPath: 'README.md', changeKind: 'renamed', oldContent: 'readme\n', newContent: 'readme\n' })
+    ])
+  })
+})
+
+describe('log', () => {
+  /** Initial commit plus commits "Commit 1".."Commit 4"; returns all hashes, oldest first */
+  function repoWithHistory(): { repo: string; hashes: string[] } {
+    const repo = initRepo()
+    const hashes = [git(repo, 'rev-parse', 'HEAD')]
+    for (let i = 1; i <= 4; i++) {
+      hashes.push(commitFile(repo, `file${i}.txt`, `${i}\n`, `Commit ${i}: fix "quotes" | pipes`))
+    }
+    return { repo, hashes }
+  }
+
+  it('lists commits newest first with author and subject', async () => {
+    const { repo, hashes } = repoWithHistory()
+
+    const log = await gitService.log(repo)
+
+    expect(log.map((c) => c.hash)).toEqual([...hashes].reverse())
+    expect(log[0]).toMatchObject({
+      author: 'Test',
+      authorEmail: 'test@example.com',
+      subject: 'Commit 4: fix "quotes" | pipes'
+    })
+    expect(Date.parse(log[0].date)).not.toBeNaN()
+    expect(log[0].relativeDate).toMatch(/ago$/)
+  })
+
+  it('pages with skip and limit', async () => {
+    const { repo, hashes } = repoWithHistory()
+    const newestFirst = [...hashes].reverse()
+
+    expect((await gitService.log(repo, 2, 0)).map((c) => c.hash)).toEqual(newestFirst.slice(0, 2))
+    expect((await gitService.log(repo, 2, 2)).map((c) => c.hash)).toEqual(newestFirst.slice(2, 4))
+    expect((await gitService.log(repo, 2, 4)).map((c) => c.hash)).toEqual(newestFirst.slice(4))
+    expect(await gitService.log(repo, 2, 10)).toEqual([])
+  })
+
+  it('flags commits that are not on the upstream yet', async () => {
+    const { repo, hashes } = repoWithHistory()
+    git(repo, 'remote', 'add', 'origin', initBareRemote())
+    git(repo, 'push', '-q', '-u', 'origin', `${hashes[2]}:refs/heads/main`)
+    git(repo, 'branch', '-q', '--set-upstream-to', 'origin/main')
+
+    const log = await gitService.log(repo)
+
+    expect(log.filter((c) => c.unpushed).map((c) => c.hash)).toEqual([hashes[4], hashes[3]])
+  })
+
+  it('returns an empty list for a repository without commits', async () => {
+    const repo = tempDir('git-empty-')
+    git(repo, 'init', '-q')
+
+    expect(await gitService.log(repo)).toEqual([])
+  })
+})
+
+describe('stash', () => {
+  it('stashes a modified file and restores it on pop', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'work in progress\n')
+
+    const stashed = await gitService.stashSave(repo, 'park it')
+
+    expect(stashed.isClean).toBe(true)
+    expect(fs.readFileSync(path.join(repo, 'README.md'), 'utf-8')).toBe('readme\n')
+    expect(await gitService.stashList(repo)).toMatchObject([{ index: 0, branch: 'main', message: 'park it' }])
+
+    const popped = await gitService.stashPop(repo)
+
+    expect(popped.modified).toEqual(['README.md'])
+    expect(fs.readFileSync(path.join(repo, 'README.md'), 'utf-8')).toBe('work in progress\n')
+    expect(await gitService.stashList(repo)).toEqual([])
+  })
+
+  it('includes untracked files only when asked to', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'changed\n')
+    fs.writeFileSync(path.join(repo, 'new.txt'), 'new\n')
+
+    expect((await gitService.stashSave(repo)).untracked).toEqual(['new.txt'])
+    await gitService.stashPop(repo)
+
+    const stashed = await gitService.stashSave(repo, undefined, true)
+    expect(stashed.isClean).toBe(true)
+    expect(fs.existsSync(path.join(repo, 'new.txt'))).toBe(false)
+
+    await gitService.stashPop(repo)
+    expect(fs.readFileSync(path.join(repo, 'new.txt'), 'utf-8')).toBe('new\n')
+  })
+
+  it('refuses to stash a clean tree', async () => {
+    const repo = initRepo()
+
+    await expect(gitService.stashSave(repo)).rejects.toThrow('Nothing to stash')
+  })
+
+  it('keeps the stash when popping conflicts', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'stashed\n')
+    await gitService.stashSave(repo, 'conflicting')
+    commitFile(repo, 'README.md', 'committed\n', 'Change README')
+
+    await expect(gitService.stashPop(repo)).rejects.toBeInstanceOf(GitConflictError)
+    expect(await gitService.stashList(repo)).toMatchObject([{ index: 0, message: 'conflicting' }])
+  })
+})
+
+describe('amend', () => {
+  it('rewrites only the message of the last commit', async () => {
+    const repo = initRepo()
+    const before = commitFile(repo, 'a.txt', 'a\n', 'Add a')
+
+    const result = await gitService.amend(repo, 'Add file a', [])
+
+    expect(result).toEqual({ hash: git(repo, 'rev-parse', 'HEAD'), message: 'Add file a', branch: 'main' })
+    expect(result.hash).not.toBe(before)
+    expect(git(repo, 'rev-list', '--count', 'HEAD')).toBe('2')
+    expect(git(repo, 'show', '--pretty=format:', '--name-only', 'HEAD')).toBe('a.txt')
+  })
+
+  it('adds new files and keeps the message with no-edit', async () => {
+    const repo = initRepo()
+    commitFile(repo, 'a.txt', 'a\n', 'Add a')
+    fs.writeFileSync(path.join(repo, 'b.txt'), 'b\n')
+
+    const result = await gitService.amend(repo, undefined, ['b.txt'])
+
+    expect(result.message).toBe('Add a')
+    expect(git(repo, 'rev-list', '--count', 'HEAD')).toBe('2')
+    expect(git(repo, 'show', '--pretty=format:', '--name-only', 'HEAD').split('\n').sort()).toEqual(['a.txt', 'b.txt'])
+    expect((await gitService.getStatus(repo)).isClean).toBe(true)
+  })
+
+  it('refuses to amend a pushed commit unless forced', async () => {
+    const repo = initRepo()
+    git(repo, 'remote', 'add', 'origin', initBareRemote())
+    git(repo, 'push', '-q', '-u', 'origin', 'main')
+    const pushed = git(repo, 'rev-parse', 'HEAD')
+
+    await expect(gitService.amend(repo, 'Rewritten', [])).rejects.toThrow('already been pushed')
+    expect(git(repo, 'rev-parse', 'HEAD')).toBe(pushed)
+
+    const forced = await gitService.amend(repo, 'Rewritten', [], true)
+    expect(forced.hash).not.toBe(pushed)
+    expect(forced.message).toBe('Rewritten')
+  })
+})
+
+describe('suggesting a commit message', () => {
+  function stageFiles(repo: string, files: Record<string, string>): void {
+    for (const [file, content] of Object.entries(files)) {
+      fs.mkdirSync(path.dirname(path.join(repo, file)), { recursive: true })
+      fs.writeFileSync(path.join(repo, file), content)
+      git(repo, 'add', '--', file)
+    }
+  }
+
+  function setCommitPrefix(commitPrefix: string): void {
+    settingsService.set({ git: { ...settingsService.get().git, commitPrefix } })
+  }
+
+  it.each([
+    { shape: 'only test files', files: { 'src/api/client.test.ts': 'test\n' }, expected: 'test(api): add client.test.ts' },
+    { shape: 'only markdown', files: { 'README.md': 'new readme\n' }, expected: 'docs: update README.md' },
+    { shape: 'only tooling', files: { 'package.json': '{}\n' }, expected: 'chore: add package.json' },
+    {
+      shape: 'a new source file',
+      files: { 'src/api/routes.ts': 'a\nb\nc\n', 'README.md': 'x\n' },
+      expected: 'feat(api): update routes.ts, README.md'
+    }
+  ])('picks the type for $shape', async ({ files, expected }) => {
+    setCommitPrefix('')
+    const repo = initRepo()
+    stageFiles(repo, files)
+
+    expect(await gitService.suggestCommitMessage(repo)).toBe(expected)
+  })
+
+  it('treats edits to existing code as a fix scoped to the busiest area', async () => {
+    setCommitPrefix('')
+    const repo = initRepo()
+    commitFile(repo, 'src/ui/button.ts', 'a\n', 'Add button')
+    commitFile(repo, 'src/api/client.ts', 'a\n', 'Add client')
+    stageFiles(repo, { 'src/ui/button.ts': 'b\nc\nd\n', 'src/api/client.ts': 'b\n' })
+
+    expect(await gitService.suggestCommitMessage(repo)).toBe('fix(ui): update button.ts, client.ts')
+  })
+
+  it('summarises long file lists', async () => {
+    setCommitPrefix('')
+    const repo = initRepo()
+    stageFiles(repo, { 'lib/a.ts': '1\n2\n3\n', 'lib/b.ts': '1\n2\n', 'lib/c.ts': '1\n', 'lib/d.ts': '1\n' })
+
+    expect(await gitService.suggestCommitMessage(repo)).toBe('feat(lib): add a.ts, b.ts and 2 more')
+  })
+
+  it('prepends the configured commit prefix', async () => {
+    setCommitPrefix('agent: ')
+    const repo = initRepo()
+    stageFiles(repo, { 'docs/guide.md': 'guide\n' })
+
+    expect(await gitService.suggestCommitMessage(repo)).toBe('agent: docs(docs): add guide.md')
+  })
+
+  it('refuses when nothing is staged', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'unstaged\n')
+
+    await expect(gitService.suggestCommitMessage(repo)).rejects.toThrow('Nothing is staged')
+  })
+})
+
+describe('co-author trailers', () => {
+  function commitWith(message: string, coAuthors: string[]): Promise<string> {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, 'README.md'), 'changed\n')
+    return gitService.commit(repo, message, ['README.md'], coAuthors).then(() => git(repo, 'log', '-1', '--pretty=format:%B'))
+  }
+
+  it('appends a Co-authored-by trailer after a blank line', async () => {
+    const body = await commitWith('Update README', ['Claude Agent <agent@example.com>'])
+
+    expect(body).toBe('Update README\n\nCo-authored-by: Claude Agent <agent@example.com>')
+  })
+
+  it('normalizes bare names and bare emails', async () => {
+    const body = await commitWith('Update README', ['Mock Agent', 'bot@example.com', '  '])
+
+    expect(body.split('\n').slice(2)).toEqual([
+      'Co-authored-by: Mock Agent <mock-agent@users.noreply.agentmanager.local>',
+      'Co-authored-by: bot <bot@example.com>'
+    ])
+  })
+
+  it('de-duplicates co-authors by email, including trailers already in the message', async () => {
+    const body = await commitWith('Update README\n\nCo-authored-by: Existing <existing@example.com>', [
+      'Mock Agent',
+      'mock agent',
+      'Someone Else <EXISTING@example.com>',
+      'Agent <agent@example.com>',
+      'agent@example.com'
+    ])
+
+    expect(body).toBe(
+      [
+        'Update README',
+        '',
+        'Co-authored-by: Existing <existing@example.com>',
+        'Co-authored-by: Mock Agent <mock-agent@users.noreply.agentmanager.local>',
+        'Co-authored-by: Agent <agent@example.com>'
+      ].join('\n')
+    )
+  })
+})
+
+describe('creating a branch', () => {
+  it('creates a branch without checking it out', async () => {
+    const repo = initRepo()
+
+    expect(await gitService.createBranch(repo, 'feature')).toEqual({ branch: 'feature', checkedOut: false })
+    expect(git(repo, 'branch', '--show-current')).toBe('main')
+    expect(git(repo, 'rev-parse', 'feature')).toBe(git(repo, 'rev-parse', 'main'))
+    expect(fs.readdirSync(repo).sort()).toEqual(['.git', 'README.md'])
+  })
+
+  it('checks out the new branch from a base', async () => {
+    const repo = initRepo()
+    const base = git(repo, 'rev-parse', 'HEAD')
+    commitFile(repo, 'later.txt', 'later\n', 'Later commit')
+
+    expect(await gitService.createBranch(repo, 'from-base', base, true)).toEqual({ branch: 'from-base', checkedOut: true })
+    expect(git(repo, 'branch', '--show-current')).toBe('from-base')
+    expect(git(repo, 'rev-parse', 'HEAD')).toBe(base)
+  })
+
+  it('refuses to overwrite an existing branch unless forced', async () => {
+    const repo = initRepo()
+    const first = git(repo, 'rev-parse', 'HEAD')
+    git(repo, 'branch', 'feature')
+    const head = commitFile(repo, 'more.txt', 'more\n', 'More')
+
+    await expect(gitService.createBranch(repo, 'feature')).rejects.toThrow("Branch 'feature' already exists")
+    expect(git(repo, 'rev-parse', 'feature')).toBe(first)
+
+    await gitService.createBranch(repo, 'feature', undefined, false, true)
+    expect(git(repo, 'rev-parse', 'feature')).toBe(head)
+  })
+})
+
+describe('conflicts in status', () => {
+  it('reports conflicted files of an unfinished merge once', async () => {
+    const repo = initRepo()
+    git(repo, 'switch', '-q', '-c', 'feature')
+    commitFile(repo, 'README.md', 'feature\n', 'Feature README')
+    commitFile(repo, 'both.txt', 'feature\n', 'Feature adds both.txt')
+    commitFile(repo, 'clean.txt', 'clean\n', 'Feature adds clean.txt')
+    git(repo, 'switch', '-q', 'main')
+    commitFile(repo, 'README.md', 'main\n', 'Main README')
+    commitFile(repo, 'both.txt', 'main\n', 'Main adds both.txt')
+    expect(() => git(repo, 'merge', 'feature')).toThrow()
+
+    const status = await gitService.getStatus(repo)
+
+    expect(status.inMerge).toBe(true)
+    expect([...status.conflicted].sort()).toEqual(['README.md', 'both.txt'])
+    expect(status.staged).toEqual(['clean.txt'])
+    expect(status.modified).toEqual([])
+    expect(status.isClean).toBe(false)
+  })
+
+  it('clears once the merge is resolved', async () => {
+    const repo = initRepo()
+    git(repo, 'switch', '-q', '-c', 'feature')
+    commitFile(repo, 'README.md', 'feature\n', 'Feature README')
+    git(repo, 'switch', '-q', 'main')
+    commitFile(repo, 'README.md', 'main\n', 'Main README')
+    expect(() => git(repo, 'merge', 'feature')).toThrow()
+    commitFile(repo, 'README.md', 'resolved\n', 'Merge feature')
+
+    const status = await gitService.getStatus(repo)
+
+    expect(status).toMatchObject({ inMerge: false, conflicted: [], isClean: true })
+  })
+
+  it('is not in a merge on a plain repository', async () => {
+    expect(await gitService.getStatus(initRepo())).toMatchObject({ inMerge: false, conflicted: [] })
+  })
+})
+
+describe('worktree naming', () => {
+  const defaults = settingsService.get().git
+
+  afterEach(() => {
+    settingsService.set({ git: defaults })
+  })
+
+  function useTemplates(worktreeDirTemplate?: string, worktreeBranchTemplate?: string): string {
+    const worktreeBaseDir = tempDir('worktrees-')
+    settingsService.set({ git: { ...defaults, worktreeBaseDir, worktreeDirTemplate, worktreeBranchTemplate } })
+    return worktreeBaseDir
+  }
+
+  it('names the directory and branch from the templates', async () => {
+    const base = useTemplates('{workspace}-{title-slug}', 'wt/{session}')
+    const repo = initRepo()
+
+    const worktree = await gitService.createWorktree(repo, 's1', undefined, undefined, { workspace: 'My Project', title: 'Fix login' })
+
+    expect(worktree.path).toBe(path.join(base, 'my-project-fix-login'))
+    expect(worktree.branch).toBe('wt/s1')
+    expect(git(worktree.path, 'branch', '--show-current')).toBe('wt/s1')
+  })
+
+  it('falls back to the default names without templates', async () => {
+    const base = useTemplates()
+    const repo = initRepo()
+
+    const worktree = await gitService.createWorktree(repo, 's1')
+
+    expect(worktree.path).toBe(path.join(base, 'thread-s1'))
+    expect(worktree.branch).toBe('am-s1')
+  })
+
+  it('refuses a template that renders an illegal branch name', async () => {
+    useTemplates(undefined, 'bad {title-slug} {session}')
+    const repo = initRepo()
+
+    await expect(gitService.createWorktree(repo, 's1', undefined, undefined, { title: 'x' })).rejects.toThrow(
+      "Invalid worktree branch 'bad x s1'"
+    )
+    expect(git(repo, 'branch', '--list')).toBe('* main')
+  })
+})
+
+describe('pruning orphaned worktrees', () => {
+  const defaults = settingsService.get().git
+
+  afterEach(() => {
+    settingsService.set({ git: defaults })
+  })
+
+  it('removes only worktrees no live session uses', async () => {
+    settingsService.set({ git: { ...defaults, worktreeBaseDir: tempDir('worktrees-') } })
+    const repo = initRepo()
+    const live = await gitService.createWorktree(repo, 'live')
+    const dead = await gitService.createWorktree(repo, 'dead')
+    // A worktree that hosts thread folders of its own is still in use
+    const hosting = await gitService.createWorktree(repo, 'hosting')
+    fs.mkdirSync(folderThreadStore.getThreadDir(hosting.path, 'thread-1'), { recursive: true })
+    // Worktrees outside the managed base directory belong to the user
+    const external = path.join(tempDir('external-'), 'wt')
+    git(repo, 'worktree', 'add', '-q', '-b', 'external', external)
+
+    const removed = await gitService.pruneOrphanWorktrees(repo, [live.path])
+
+    expect(removed).toEqual([dead.path])
+    expect(fs.existsSync(dead.path)).toBe(false)
+    const remaining = (await gitService.listWorktrees(repo)).filter((wt) => !wt.isMain).map((wt) => wt.path)
+    expect(remaining.sort()).toEqual([external, hosting.path, live.path].sort())
+  })
+})
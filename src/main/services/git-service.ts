@@ -1,22 +1,67 @@
 import simpleGit from 'simple-git'
 import path from 'path'
 import fs from 'fs'
-import type { GitStatus, WorktreeInfo, CommitResult } from '@shared/types/git'
+import type {
+  GitStatus,
+  WorktreeInfo,
+  CommitResult,
+  CommitEntry,
+  CreateBranchResult,
+  MergeResult,
+  MergeStrategy,
+  PushResult,
+  StashEntry
+} from '@shared/types/git'
 import type { DiffResult, FileDiff } from '@shared/types/project'
-import { DEFAULT_WORKTREE_PREFIX } from '@shared/constants'
+import { DEFAULT_WORKTREE_BRANCH_TEMPLATE, DEFAULT_WORKTREE_DIR_TEMPLATE } from '@shared/constants'
+import { normalizePath } from '@shared/util/path-util'
 import { settingsService } from './settings-service'
+import { folderThreadStore } from './folder-thread-store'
 import { getWorktreesDir } from '../util/paths'
+import { branchNameError, renderWorktreeTemplate, worktreeDirError } from '../util/worktree-template'
+import type { WorktreeTemplateContext } from '../util/worktree-template'
 import { logger } from '../util/logger'
+import { GitConflictError } from '../util/git-error'
+
+/** Paths with unresolved merge conflicts */
+async function conflictedFiles(workingDir: string): Promise<string[]> {
+  return (await simpleGit(workingDir).raw(['diff', '--name-only', '--diff-filter=U']))
+    .split('\n')
+    .map((f) => f.trim())
+    .filter(Boolean)
+}
+
+/** simple-git for commands that talk to remotes: credential prompts fail instead of hanging */
+function remoteGit(workingDir: string): ReturnType<typeof simpleGit> {
+  return simpleGit(workingDir).env({ ...process.env, GIT_TERMINAL_PROMPT: '0', GCM_INTERACTIVE: 'never' })
+}
+
+/** The branch's configured upstream; read from config since remote names may contain '/' */
+async function upstreamOf(workingDir: string, branch: string): Promise<{ remote: string; branch: string } | null> {
+  const git = simpleGit(workingDir)
+  try {
+    const remote = (await git.raw(['config', '--get', `branch.${branch}.remote`])).trim()
+    const merge = (await git.raw(['config', '--get', `branch.${branch}.merge`])).trim()
+    if (!remote || !merge) return null
+    return { remote, branch: merge.replace(/^refs\/heads\//, '') }
+  } catch {
+    // git config exits non-zero when the key is unset
+    return null
+  }
+}
 
 export class GitService {
   /**
-   * Create a worktree for a session
+   * Create a worktree for a session. Directory and branch names come from the
+   * git.worktreeDirTemplate / worktreeBranchTemplate settings, expanded with
+   * `naming` (workspace name, thread title); `customBranchName` overrides the branch.
    */
   async createWorktree(
     projectPath: string,
     sessionId: string,
     baseBranch?: string,
-    customBranchName?: string
+    customBranchName?: string,
+    naming: Omit<WorktreeTemplateContext, 'session'> = {}
   ): Promise<WorktreeInfo> {
     const git = simpleGit(projectPath)
 
@@ -26,9 +71,22 @@ export class GitService {
       throw new Error(`Not a git repository: ${projectPath}`)
     }
 
-    const branchName = customBranchName || `${DEFAULT_WORKTREE_PREFIX}${sessionId}`
+    const { worktreeDirTemplate, worktreeBranchTemplate } = settingsService.get().git
+    const context: WorktreeTemplateContext = { ...naming, session: sessionId }
+    const branchName =
+      customBranchName || renderWorktreeTemplate(worktreeBranchTemplate || DEFAULT_WORKTREE_BRANCH_TEMPLATE, context)
+    const branchError = branchNameError(branchName)
+    if (branchError) {
+      throw new Error(`Invalid worktree branch '${branchName}': ${branchError}`)
+    }
+    const dirName = renderWorktreeTemplate(worktreeDirTemplate || DEFAULT_WORKTREE_DIR_TEMPLATE, context)
+    const dirError = worktreeDirError(dirName)
+    if (dirError) {
+      throw new Error(`Invalid worktree directory '${dirName}': ${dirError}`)
+    }
+
     const worktreeBase = this.getWorktreeBase(projectPath)
-    const worktreePath = path.join(worktreeBase, `thread-${sessionId}`)
+    const worktreePath = path.join(worktreeBase, dirName)
 
     // Ensure base directory exists
     fs.mkdirSync(worktreeBase, { recursive: true })
@@ -102,6 +160,44 @@ export class GitService {
     return this.parseWorktreeList(result)
   }
 
+  /**
+   * Remove worktrees this app created for the project (those under the
+   * worktree base) that no session uses anymore, then prune git's records of
+   * missing ones. `keepPaths` are the worktree paths of every known session.
+   * A worktree that still holds threads in its own .agent/ folder is kept even
+   * when missing from `keepPaths`, since the thread cache may still be rebuilding.
+   * Returns the removed paths.
+   */
+  async pruneOrphanWorktrees(projectPath: string, keepPaths: string[]): Promise<string[]> {
+    const normalize = (p: string): string => normalizePath(path.resolve(p))
+    const keep = new Set(keepPaths.map(normalize))
+    const base = normalize(this.getWorktreeBase(projectPath)) + '/'
+
+    const orphans = (await this.listWorktrees(projectPath)).filter(
+      (wt) =>
+        !wt.isMain &&
+        normalize(wt.path).startsWith(base) &&
+        !keep.has(normalize(wt.path)) &&
+        folderThreadStore.listThreadIds(wt.path).length === 0
+    )
+
+    const removed: string[] = []
+    for (const wt of orphans) {
+      try {
+        await this.removeWorktree(projectPath, wt.path)
+        removed.push(wt.path)
+      } catch (error) {
+        logger.warn(`Failed to prune orphaned worktree ${wt.path}:`, error)
+      }
+    }
+    await simpleGit(projectPath).raw(['worktree', 'prune'])
+
+    if (removed.length > 0) {
+      logger.info(`Pruned ${removed.length} orphaned worktree(s) for ${projectPath}`)
+    }
+    return removed
+  }
+
   /**
    * List local branches for a repository (current branch first).
    */
@@ -124,22 +220,29 @@ export class GitService {
   async getStatus(workingDir: string): Promise<GitStatus> {
     const git = simpleGit(workingDir)
     const status = await git.status()
+    const conflicted = new Set(status.conflicted)
+
+    // --git-path resolves inside linked worktrees, where .git is a file
+    const mergeHead = (await git.raw(['rev-parse', '--git-path', 'MERGE_HEAD'])).trim()
 
     return {
       branch: status.current || '',
       isClean: status.isClean(),
-      staged: status.staged,
-      modified: status.modified,
+      staged: status.staged.filter((f) => !conflicted.has(f)),
+      modified: status.modified.filter((f) => !conflicted.has(f)),
       untracked: status.not_added,
+      conflicted: status.conflicted,
+      inMerge: fs.existsSync(path.resolve(workingDir, mergeHead)),
       ahead: status.ahead,
       behind: status.behind
     }
   }
 
   /**
-   * Get diff for a working directory
+   * Get diff for a working directory. With `includeContent` off only the
+   * per-file line counts are returned, without reading old/new file contents.
    */
-  async getDiff(workingDir: string, filePath?: string): Promise<DiffResult> {
+  async getDiff(workingDir: string, filePath?: string, includeContent = true): Promise<DiffResult> {
     const git = simpleGit(workingDir)
 
     // Get diff of all changes (staged + unstaged)
@@ -154,30 +257,462 @@ export class GitService {
       diffText = await git.diff(['--cached', ...(filePath ? ['--', filePath] : [])])
     }
 
-    return this.parseDiff(diffText, workingDir)
+    return this.parseDiff(diffText, workingDir, includeContent)
   }
 
   /**
-   * Stage files and commit
+   * Stage files and commit, crediting `coAuthors` with Co-authored-by trailers
    */
   async commit(
     workingDir: string,
     message: string,
-    files: string[]
+    files: string[],
+    coAuthors: string[] = []
   ): Promise<CommitResult> {
     const git = simpleGit(workingDir)
+    const fullMessage = withCoAuthorTrailers(message, coAuthors)
 
     await git.add(files)
-    const result = await git.commit(message)
+    const result = await git.commit(fullMessage)
     const status = await git.status()
 
     return {
       hash: result.commit,
-      message,
+      message: fullMessage,
+      branch: status.current || ''
+    }
+  }
+
+  /**
+   * Stage files and amend the last commit, with a new message or keeping the
+   * old one. Refuses to rewrite a commit that is already on the upstream (or
+   * any remote) unless `force` is set.
+   */
+  async amend(workingDir: string, message: string | undefined, files: string[], force = false): Promise<CommitResult> {
+    const git = simpleGit(workingDir)
+    const head = await this.getHead(workingDir)
+    if (!head) {
+      throw new Error('Cannot amend: the repository has no commits')
+    }
+    if (!force && !(await this.getUnpushedHashes(workingDir)).has(head)) {
+      throw new Error('Cannot amend: the last commit has already been pushed. Amend with force to rewrite published history.')
+    }
+
+    if (files.length > 0) {
+      await git.add(files)
+    }
+    await git.raw(['commit', '--amend', ...(message !== undefined ? ['-m', message] : ['--no-edit'])])
+
+    const hash = (await git.revparse(['HEAD'])).trim()
+    const finalMessage = (await git.raw(['log', '-1', '--pretty=format:%B'])).trim()
+    const status = await git.status()
+    logger.info(`Amended ${head.slice(0, 7)} → ${hash.slice(0, 7)} in ${workingDir}`)
+
+    return {
+      hash,
+      message: finalMessage,
       branch: status.current || ''
     }
   }
 
+  /**
+   * Suggest a conventional-commit subject for the staged changes, prefixed with
+   * the configured commit prefix. Purely heuristic and deterministic: the type
+   * comes from which kinds of files changed, the scope from the directory with
+   * the most changed lines.
+   */
+  async suggestCommitMessage(workingDir: string): Promise<string> {
+    const git = simpleGit(workingDir)
+    const [numstat, nameStatus] = await Promise.all([
+      git.raw(['diff', '--cached', '--no-renames', '--numstat']),
+      git.raw(['diff', '--cached', '--no-renames', '--name-status'])
+    ])
+
+    const statusByPath = new Map<string, string>()
+    for (const line of nameStatus.split('\n').filter(Boolean)) {
+      const [code, filePath] = line.split('\t')
+      statusByPath.set(filePath, code[0])
+    }
+
+    const changes = numstat
+      .split('\n')
+      .filter(Boolean)
+      .map((line) => {
+        const [added, removed, filePath] = line.split('\t')
+        // Binary files report "-" for both counts
+        const lines = (Number(added) || 0) + (Number(removed) || 0)
+        return { path: filePath, lines, status: statusByPath.get(filePath) ?? 'M' }
+      })
+    if (changes.length === 0) {
+      throw new Error('Nothing is staged')
+    }
+
+    return settingsService.get().git.commitPrefix + conventionalCommitLine(changes)
+  }
+
+  /**
+   * Commit history of the current branch, newest first, paged with skip/limit.
+   * A repository without commits yields an empty list.
+   */
+  async log(workingDir: string, limit = 50, skip = 0): Promise<CommitEntry[]> {
+    const git = simpleGit(workingDir)
+    try {
+      await git.raw(['rev-parse', '--verify', 'HEAD'])
+    } catch {
+      return []
+    }
+
+    // Unit/record separators keep subjects with any punctuation intact
+    const output = await git.raw([
+      'log',
+      `--max-count=${limit}`,
+      `--skip=${skip}`,
+      '--pretty=format:%H%x1f%an%x1f%ae%x1f%aI%x1f%ar%x1f%s%x1e',
+      'HEAD'
+    ])
+
+    const unpushed = await this.getUnpushedHashes(workingDir)
+    return output
+      .split('\x1e')
+      .map((record) => record.replace(/^\n/, ''))
+      .filter(Boolean)
+      .map((record) => {
+        const [hash, author, authorEmail, date, relativeDate, subject] = record.split('\x1f')
+        return { hash, author, authorEmail, date, relativeDate, subject: subject ?? '', unpushed: unpushed.has(hash) }
+      })
+  }
+
+  /** Commits on HEAD missing from its upstream, or from every remote when no upstream is set */
+  private async getUnpushedHashes(workingDir: string): Promise<Set<string>> {
+    const git = simpleGit(workingDir)
+    let range: string[]
+    try {
+      await git.raw(['rev-parse', '--abbrev-ref', '--symbolic-full-name', '@{u}'])
+      range = ['@{u}..HEAD']
+    } catch {
+      range = ['HEAD', '--not', '--remotes']
+    }
+    const output = await git.raw(['rev-list', ...range])
+    return new Set(output.split('\n').filter(Boolean))
+  }
+
+  /**
+   * Stash uncommitted changes (and untracked files when asked), leaving a clean tree
+   */
+  async stashSave(workingDir: string, message?: string, includeUntracked = false): Promise<GitStatus> {
+    const git = simpleGit(workingDir)
+    const args = ['stash', 'push']
+    if (includeUntracked) args.push('--include-untracked')
+    if (message) args.push('-m', message)
+
+    const output = await git.raw(args)
+    if (/No local changes to save/.test(output)) {
+      throw new Error('Nothing to stash: the working tree has no changes')
+    }
+
+    logger.info(`Stashed changes in ${workingDir}${message ? `: ${message}` : ''}`)
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * Apply and drop stash@{index}. On conflicts git keeps the stash; a
+   * GitConflictError lists the conflicted files.
+   */
+  async stashPop(workingDir: string, index = 0): Promise<GitStatus> {
+    const git = simpleGit(workingDir)
+    let failure: Error | undefined
+    try {
+      await git.raw(['stash', 'pop', `stash@{${index}}`])
+    } catch (error) {
+      failure = error as Error
+    }
+
+    // A conflicting pop may report only on stdout, so check the index either way
+    const conflicted = await conflictedFiles(workingDir)
+    if (conflicted.length > 0) {
+      throw new GitConflictError(conflicted, 'stash')
+    }
+    if (failure) {
+      throw new Error(`git stash pop failed: ${failure.message.trim()}`)
+    }
+
+    logger.info(`Popped stash@{${index}} in ${workingDir}`)
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * List stash entries, newest (index 0) first
+   */
+  async stashList(workingDir: string): Promise<StashEntry[]> {
+    const output = await simpleGit(workingDir).raw(['stash', 'list', '--pretty=format:%H%x1f%aI%x1f%gs%x1e'])
+    return output
+      .split('\x1e')
+      .map((record) => record.replace(/^\n/, ''))
+      .filter(Boolean)
+      .map((record, index) => {
+        const [hash, date, subject = ''] = record.split('\x1f')
+        // Reflog subject: "On <branch>: <message>" or "WIP on <branch>: <head subject>"
+        const match = subject.match(/^(?:WIP on|On) ([^:]+): (.*)$/)
+        return { index, hash, date, branch: match?.[1], message: match ? match[2] : subject }
+      })
+  }
+
+  /**
+   * Stage files for the next commit
+   */
+  async stage(workingDir: string, files: string[]): Promise<GitStatus> {
+    if (files.length > 0) {
+      await simpleGit(workingDir).raw(['add', '--', ...files])
+    }
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * Move staged changes back to the working tree, leaving the file contents untouched
+   */
+  async unstage(workingDir: string, files: string[]): Promise<GitStatus> {
+    if (files.length > 0) {
+      await simpleGit(workingDir).raw(['restore', '--staged', '--', ...files])
+    }
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * Throw away working-tree changes: tracked files are checked out from the
+   * index, untracked ones are deleted. Untracked directories are only removed
+   * when `recursive` is set.
+   */
+  async discard(workingDir: string, files: string[], recursive = false): Promise<GitStatus> {
+    const git = simpleGit(workingDir)
+    const status = await git.status()
+    const untrackedEntries = status.not_added
+    // Entries may be individual files (-uall) or collapsed directories ending in '/'
+    const isUntracked = (file: string): boolean => {
+      const dir = file.replace(/\/+$/, '') + '/'
+      return untrackedEntries.some(
+        (entry) => entry === file || entry.startsWith(dir) || (entry.endsWith('/') && file.startsWith(entry))
+      )
+    }
+
+    const tracked = files.filter((file) => !isUntracked(file))
+    const untracked = files.filter(isUntracked)
+
+    const directories = untracked.filter((file) => {
+      try {
+        return fs.statSync(path.join(workingDir, file)).isDirectory()
+      } catch {
+        return false
+      }
+    })
+    if (directories.length > 0 && !recursive) {
+      throw new Error(`Refusing to delete untracked directories without recursive: ${directories.join(', ')}`)
+    }
+
+    if (tracked.length > 0) {
+      await git.raw(['checkout', '--', ...tracked])
+    }
+    if (untracked.length > 0) {
+      await git.raw(['clean', '-f', ...(recursive ? ['-d'] : []), '--', ...untracked])
+    }
+
+    logger.info(`Discarded ${files.length} path(s) in ${workingDir}`)
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * Push the current branch, to its upstream branch when one is configured on the
+   * target remote. Without an upstream the push is refused unless
+   * `setUpstream` is set, in which case it runs `git push -u <remote> <branch>`.
+   * Credential prompts are disabled so a push needing auth fails instead of hanging.
+   */
+  async push(workingDir: string, remote?: string, setUpstream = false): Promise<PushResult> {
+    const git = remoteGit(workingDir)
+
+    const status = await git.status()
+    const branch = status.current
+    if (!branch || status.detached) {
+      throw new Error('Cannot push: HEAD is not on a branch')
+    }
+
+    const remotes = await git.getRemotes(true)
+    if (remotes.length === 0) {
+      throw new Error('Cannot push: no git remote is configured for this repository')
+    }
+
+    const upstream = await upstreamOf(workingDir, branch)
+    const targetRemote =
+      remote ||
+      upstream?.remote ||
+      (remotes.some((r) => r.name === 'origin') ? 'origin' : remotes[0].name)
+    const remoteInfo = remotes.find((r) => r.name === targetRemote)
+    if (!remoteInfo) {
+      throw new Error(`Cannot push: remote '${targetRemote}' does not exist`)
+    }
+    if (!upstream && !setUpstream) {
+      throw new Error(`Branch '${branch}' has no upstream; push with setUpstream to publish it to '${targetRemote}'`)
+    }
+
+    // Push to the tracked branch, which may be named differently from the local one
+    const remoteBranch = upstream && upstream.remote === targetRemote && !setUpstream ? upstream.branch : branch
+    try {
+      await git.raw([
+        'push',
+        ...(setUpstream ? ['--set-upstream'] : []),
+        targetRemote,
+        remoteBranch === branch ? branch : `${branch}:${remoteBranch}`
+      ])
+    } catch (error) {
+      const message = (error as Error).message
+      if (/terminal prompts disabled|could not read (Username|Password)|Authentication failed|Permission denied \(publickey\)/i.test(message)) {
+        throw new Error(
+          `Authentication failed pushing to '${targetRemote}'. Configure a credential helper or SSH key for ${remoteInfo.refs.push}.`
+        )
+      }
+      throw new Error(`git push failed: ${message.trim()}`)
+    }
+
+    const remoteUrl = remoteInfo.refs.push || remoteInfo.refs.fetch || undefined
+    logger.info(`Pushed ${branch} to ${targetRemote}/${remoteBranch}${setUpstream ? ' (upstream set)' : ''}`)
+    return {
+      branch,
+      remote: targetRemote,
+      remoteUrl,
+      upstreamSet: setUpstream,
+      pullRequestUrl: remoteUrl ? pullRequestUrlFor(remoteUrl, remoteBranch) : undefined
+    }
+  }
+
+  /**
+   * Fetch from a remote (all remotes when omitted) and return the refreshed
+   * status so ahead/behind reflect the remote.
+   */
+  async fetch(workingDir: string, remote?: string): Promise<GitStatus> {
+    const git = remoteGit(workingDir)
+    try {
+      await git.raw(remote ? ['fetch', '--prune', remote] : ['fetch', '--prune', '--all'])
+    } catch (error) {
+      throw new Error(`git fetch failed: ${(error as Error).message.trim()}`)
+    }
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * Pull the current branch's upstream (or `remote`/<branch>), merging or
+   * rebasing. On conflicts the merge/rebase is left in progress for the user to
+   * resolve and a GitConflictError lists the conflicted files.
+   */
+  async pull(workingDir: string, remote?: string, rebase = false): Promise<GitStatus> {
+    const git = remoteGit(workingDir)
+    const status = await git.status()
+    if (!status.current || status.detached) {
+      throw new Error('Cannot pull: HEAD is not on a branch')
+    }
+    if (!remote && !status.tracking) {
+      throw new Error(`Cannot pull: branch '${status.current}' has no upstream`)
+    }
+
+    const args = ['pull', rebase ? '--rebase' : '--no-rebase']
+    if (remote) args.push(remote, status.current)
+    try {
+      await git.raw(args)
+    } catch (error) {
+      const conflicted = await conflictedFiles(workingDir)
+      if (conflicted.length > 0) {
+        throw new GitConflictError(conflicted, rebase ? 'rebase' : 'merge')
+      }
+      throw new Error(`git pull failed: ${(error as Error).message.trim()}`)
+    }
+
+    logger.info(`Pulled ${status.current}${rebase ? ' (rebase)' : ''} in ${workingDir}`)
+    return this.getStatus(workingDir)
+  }
+
+  /**
+   * Merge a worktree's branch into `into` inside the main repository. If the repo
+   * isn't on `into` it must be clean; it is switched for the merge and back after.
+   * On conflicts the merge is aborted and a GitConflictError lists the files.
+   */
+  async mergeWorktree(
+    projectPath: string,
+    branch: string,
+    into: string,
+    strategy: MergeStrategy = 'merge'
+  ): Promise<MergeResult> {
+    const git = simpleGit(projectPath)
+    const status = await git.status()
+    const original = status.current
+    const switchBranch = original !== into
+    if (switchBranch && !status.isClean()) {
+      throw new Error(`Cannot merge into '${into}': ${projectPath} has uncommitted changes on '${original}'`)
+    }
+
+    if (switchBranch) await git.checkout(into)
+    try {
+      try {
+        if (strategy === 'ff-only') {
+          await git.raw(['merge', '--ff-only', branch])
+        } else if (strategy === 'squash') {
+          await git.raw(['merge', '--squash', branch])
+          await git.commit(`Squash merge branch '${branch}' into ${into}`)
+        } else {
+          await git.raw(['merge', '--no-ff', '--no-edit', branch])
+        }
+      } catch (error) {
+        const conflicted = await conflictedFiles(projectPath)
+        if (conflicted.length > 0) {
+          // --squash leaves no MERGE_HEAD, so `merge --abort` doesn't apply
+          await git.raw(strategy === 'squash' ? ['reset', '--merge'] : ['merge', '--abort'])
+          throw new GitConflictError(conflicted, 'merge', true)
+        }
+        throw new Error(`git merge failed: ${(error as Error).message.trim()}`)
+      }
+
+      const commit = (await git.revparse(['HEAD'])).trim()
+      logger.info(`Merged ${branch} into ${into} (${strategy}) at ${commit}`)
+      return { branch, into, strategy, commit }
+    } finally {
+      if (switchBranch && original) {
+        await git.checkout(original).catch((err) => logger.warn(`Failed to switch back to ${original}:`, err))
+      }
+    }
+  }
+
+  /**
+   * Create a branch in place (no worktree) from `base` or HEAD, optionally
+   * switching to it. An existing branch is only reset when `force` is set.
+   */
+  async createBranch(
+    workingDir: string,
+    name: string,
+    base?: string,
+    checkout = false,
+    force = false
+  ): Promise<CreateBranchResult> {
+    const git = simpleGit(workingDir)
+    const exists = (await git.branchLocal()).all.includes(name)
+    if (exists && !force) {
+      throw new Error(`Branch '${name}' already exists`)
+    }
+
+    const startPoint = base ? [base] : []
+    if (checkout) {
+      await git.raw(['switch', force ? '-C' : '-c', name, ...startPoint])
+    } else {
+      await git.raw(['branch', ...(force ? ['--force'] : []), name, ...startPoint])
+    }
+
+    logger.info(`Branch created: ${name}${base ? ` from ${base}` : ''}${checkout ? ' (checked out)' : ''} in ${workingDir}`)
+    return { branch: name, checkedOut: checkout }
+  }
+
+  /**
+   * Switch the working directory to an existing local branch
+   */
+  async switchBranch(workingDir: string, name: string): Promise<void> {
+    await simpleGit(workingDir).raw(['switch', name])
+    logger.info(`Switched to branch ${name} in ${workingDir}`)
+  }
+
   /**
    * Rename the current branch of a worktree
    */
@@ -308,11 +843,11 @@ export class GitService {
     return worktrees
   }
 
-  private async parseDiff(diffText: string, workingDir: string): Promise<DiffResult> {
+  private async parseDiff(diffText: string, workingDir: string, includeContent: boolean): Promise<DiffResult> {
     const files: FileDiff[] = []
 
     if (!diffText.trim()) {
-      return { files }
+      return { files, totalAdditions: 0, totalDeletions: 0 }
     }
 
     // Simple diff parsing - split by file boundaries
@@ -320,10 +855,16 @@ export class GitService {
 
     for (const section of fileSections) {
       const lines = section.split('\n')
-      const headerMatch = lines[0]?.match(/a\/(.*?) b\/(.*)/)
-      if (!headerMatch) continue
+      const header = parseDiffHeader(lines)
+      if (!header) continue
 
-      const filePath = headerMatch[2]
+      const { path: filePath, oldPath, changeKind } = header
+      const { additions, deletions, isBinary } = countHunkLines(lines)
+
+      if (!includeContent || isBinary) {
+        files.push({ path: filePath, changeKind, oldPath, additions, deletions, isBinary })
+        continue
+      }
 
       // Try to read current and original content for the diff viewer
       let oldContent = ''
@@ -331,16 +872,17 @@ export class GitService {
 
       try {
         const fullPath = path.join(workingDir, filePath)
-        if (fs.existsSync(fullPath)) {
+        if (changeKind !== 'deleted' && fs.existsSync(fullPath)) {
           newContent = fs.readFileSync(fullPath, 'utf-8')
         }
 
-        // Get the original content from git
-        const git = simpleGit(workingDir)
-        try {
-          oldContent = await git.show([`HEAD:${filePath}`])
-        } catch {
-          oldContent = '' // New file
+        // Get the original content from git; added files have none
+        if (changeKind !== 'added') {
+          try {
+            oldContent = await simpleGit(workingDir).show([`HEAD:${oldPath ?? filePath}`])
+          } catch {
+            oldContent = '' // No HEAD yet
+          }
         }
       } catch {
         // Ignore read errors
@@ -348,13 +890,230 @@ export class GitService {
 
       files.push({
         path: filePath,
+        changeKind,
+        oldPath,
+        additions,
+        deletions,
+        isBinary,
         oldContent,
         newContent
       })
     }
 
-    return { files }
+    return {
+      files,
+      totalAdditions: files.reduce((sum, file) => sum + file.additions, 0),
+      totalDeletions: files.reduce((sum, file) => sum + file.deletions, 0)
+    }
+  }
+}
+
+/**
+ * Work out path and kind of change for one `diff --git` section (the leading
+ * "diff --git " already stripped). Prefers the unambiguous rename and ---/+++
+ * lines over the first line, which can't be split reliably when paths contain " b/".
+ */
+function parseDiffHeader(lines: string[]): { path: string; oldPath?: string; changeKind: FileDiff['changeKind'] } | null {
+  let changeKind: FileDiff['changeKind'] = 'modified'
+  let renameFrom: string | undefined
+  let renameTo: string | undefined
+  let minusPath: string | undefined
+  let plusPath: string | undefined
+
+  for (const line of lines.slice(1)) {
+    if (line.startsWith('@@')) break
+    if (line.startsWith('new file mode')) changeKind = 'added'
+    else if (line.startsWith('deleted file mode')) changeKind = 'deleted'
+    else if (line.startsWith('rename from ')) renameFrom = unquoteGitPath(line.slice('rename from '.length))
+    else if (line.startsWith('rename to ')) renameTo = unquoteGitPath(line.slice('rename to '.length))
+    else if (line.startsWith('--- ')) minusPath = stripDiffPrefix(line.slice(4), 'a/')
+    else if (line.startsWith('+++ ')) plusPath = stripDiffPrefix(line.slice(4), 'b/')
+  }
+
+  if (renameFrom !== undefined && renameTo !== undefined) {
+    return { path: renameTo, oldPath: renameFrom, changeKind: 'renamed' }
+  }
+
+  const headerPaths = splitDiffGitLine(lines[0] ?? '')
+  const filePath = (changeKind === 'deleted' ? minusPath : plusPath) ?? headerPaths?.[changeKind === 'deleted' ? 0 : 1]
+  return filePath ? { path: filePath, changeKind } : null
+}
+
+/** Path from a ---/+++ line; undefined for /dev/null */
+function stripDiffPrefix(raw: string, prefix: string): string | undefined {
+  // Git appends a tab after names containing spaces
+  const name = unquoteGitPath(raw.replace(/\t$/, ''))
+  if (name === '/dev/null') return undefined
+  return name.startsWith(prefix) ? name.slice(prefix.length) : name
+}
+
+/** Split "a/X b/Y" from the first line of a section; only trustworthy when both sides are equal or quoted */
+function splitDiffGitLine(line: string): [string, string] | null {
+  const quoted = line.match(/^("(?:[^"\\]|\\.)*"|\S+) ("(?:[^"\\]|\\.)*"|\S+)$/)
+  if (quoted && (quoted[1].startsWith('"') || quoted[2].startsWith('"'))) {
+    const a = unquoteGitPath(quoted[1])
+    const b = unquoteGitPath(quoted[2])
+    if (a.startsWith('a/') && b.startsWith('b/')) return [a.slice(2), b.slice(2)]
+  }
+
+  // "a/P b/P": same path on both sides, so the line is exactly twice as long plus the prefixes
+  const length = (line.length - 5) / 2
+  if (Number.isInteger(length) && line.startsWith('a/') && line.slice(2 + length, 5 + length) === ' b/') {
+    const a = line.slice(2, 2 + length)
+    if (a === line.slice(5 + length)) return [a, a]
+  }
+
+  const match = line.match(/^a\/(.*?) b\/(.*)$/)
+  return match ? [match[1], match[2]] : null
+}
+
+/** Undo git's C-style quoting ("a/t\303\251st\tx"); unquoted names are returned as-is */
+function unquoteGitPath(name: string): string {
+  if (!name.startsWith('"') || !name.endsWith('"')) return name
+
+  const bytes: number[] = []
+  const body = name.slice(1, -1)
+  const escapes: Record<string, number> = { a: 7, b: 8, t: 9, n: 10, v: 11, f: 12, r: 13, '"': 34, '\\': 92 }
+  for (let i = 0; i < body.length; i++) {
+    const ch = body[i]
+    if (ch !== '\\') {
+      bytes.push(...Buffer.from(ch, 'utf-8'))
+      continue
+    }
+    const next = body[i + 1]
+    if (/[0-7]/.test(next)) {
+      bytes.push(parseInt(body.slice(i + 1, i + 4), 8))
+      i += 3
+    } else {
+      bytes.push(escapes[next] ?? next.charCodeAt(0))
+      i += 1
+    }
+  }
+  return Buffer.from(bytes).toString('utf-8')
+}
+
+/**
+ * Coerce a co-author into "Name <email>": a bare email gets its local part as
+ * name, a bare name gets a placeholder noreply address. Empty input yields null.
+ */
+function normalizeCoAuthor(raw: string): string | null {
+  const value = raw.trim().replace(/^Co-authored-by:\s*/i, '')
+  if (!value) return null
+
+  const full = value.match(/^(.*?)\s*<([^<>\s]+@[^<>\s]+)>$/)
+  if (full) return `${full[1] || full[2].split('@')[0]} <${full[2]}>`
+
+  const bareEmail = value.match(/^<?([^<>\s]+@[^<>\s]+)>?$/)
+  if (bareEmail) return `${bareEmail[1].split('@')[0]} <${bareEmail[1]}>`
+
+  const name = value.replace(/[<>]/g, '').trim()
+  const slug = name.toLowerCase().replace(/[^a-z0-9]+/g, '-').replace(/^-|-$/g, '') || 'agent'
+  return `${name} <${slug}@users.noreply.agentmanager.local>`
+}
+
+/** Append Co-authored-by trailers, skipping duplicates (by email) and ones already in the message */
+function withCoAuthorTrailers(message: string, coAuthors: string[]): string {
+  const emailOf = (author: string): string => author.slice(author.lastIndexOf('<') + 1, -1).toLowerCase()
+  const existing = new Set(
+    [...message.matchAll(/^Co-authored-by:\s*(.+)$/gim)]
+      .map((m) => normalizeCoAuthor(m[1]))
+      .filter((a): a is string => a !== null)
+      .map(emailOf)
+  )
+
+  const trailers: string[] = []
+  for (const raw of coAuthors) {
+    const author = normalizeCoAuthor(raw)
+    if (!author || existing.has(emailOf(author))) continue
+    existing.add(emailOf(author))
+    trailers.push(`Co-authored-by: ${author}`)
+  }
+  if (trailers.length === 0) return message
+
+  // Join an existing trailer block instead of starting a new paragraph
+  const trimmed = message.trimEnd()
+  const lastParagraph = trimmed.split(/\n\s*\n/).pop() ?? ''
+  const endsWithTrailers = trimmed.includes('\n') && lastParagraph.split('\n').every((l) => /^[\w-]+: .+/.test(l))
+  return `${trimmed}${endsWithTrailers ? '\n' : '\n\n'}${trailers.join('\n')}`
+}
+
+const TEST_FILE_PATTERN = /(^|\/)(tests?|__tests__|spec)\/|\.(test|spec)\.[cm]?[jt]sx?$|_test\.(go|rs|py)$|(^|\/)test_[^/]+\.py$/
+const DOCS_FILE_PATTERN = /\.(md|mdx|markdown|rst|txt)$/i
+const CHORE_FILE_PATTERN =
+  /(^|\/)(package(-lock)?\.json|pnpm-lock\.yaml|yarn\.lock|Cargo\.(toml|lock)|\.gitignore|\.editorconfig|\.npmrc)$|^\.github\/|(^|\/)[^/]*\.config\.[cm]?[jt]s$|(^|\/)tsconfig[^/]*\.json$/
+
+/** Build "type(scope): subject" from staged files (path, changed line count, A/M/D status) */
+function conventionalCommitLine(changes: Array<{ path: string; lines: number; status: string }>): string {
+  const every = (pattern: RegExp): boolean => changes.every((c) => pattern.test(c.path))
+  const type = every(TEST_FILE_PATTERN)
+    ? 'test'
+    : every(DOCS_FILE_PATTERN)
+      ? 'docs'
+      : every(CHORE_FILE_PATTERN)
+        ? 'chore'
+        : changes.some((c) => c.status === 'A')
+          ? 'feat'
+          : 'fix'
+
+  // Scope: the parent directory with the most changed lines (ties broken by name)
+  const linesByArea = new Map<string, number>()
+  for (const change of changes) {
+    const area = path.posix.basename(path.posix.dirname(change.path))
+    linesByArea.set(area, (linesByArea.get(area) ?? 0) + change.lines)
   }
+  const [area] = [...linesByArea.entries()].sort((a, b) => b[1] - a[1] || a[0].localeCompare(b[0]))[0]
+  const scope = area && area !== '.' && area !== 'src' ? `(${area})` : ''
+
+  const verb = changes.every((c) => c.status === 'A')
+    ? 'add'
+    : changes.every((c) => c.status === 'D')
+      ? 'remove'
+      : 'update'
+  const names = [...changes]
+    .sort((a, b) => b.lines - a.lines || a.path.localeCompare(b.path))
+    .map((c) => path.posix.basename(c.path))
+  const listed = names.slice(0, 2).join(', ')
+  const subject = names.length > 2 ? `${listed} and ${names.length - 2} more` : listed
+
+  return `${type}${scope}: ${verb} ${subject}`
+}
+
+/** Count +/- lines inside the hunks of one file's diff section */
+function countHunkLines(lines: string[]): { additions: number; deletions: number; isBinary: boolean } {
+  let additions = 0
+  let deletions = 0
+  let isBinary = false
+  let inHunk = false
+
+  for (const line of lines) {
+    if (line.startsWith('@@')) {
+      inHunk = true
+    } else if (!inHunk) {
+      // File headers (---/+++) come before the first hunk and aren't content
+      if (/^Binary files .* differ$/.test(line) || line === 'GIT binary patch') isBinary = true
+    } else if (line.startsWith('+')) {
+      additions++
+    } else if (line.startsWith('-')) {
+      deletions++
+    }
+  }
+
+  return { additions, deletions, isBinary }
+}
+
+/** Web URL for opening a PR/MR from `branch`, for well-known hosts; undefined otherwise */
+function pullRequestUrlFor(remoteUrl: string, branch: string): string | undefined {
+  // git@host:owner/repo.git, ssh://git@host/owner/repo.git, https://host/owner/repo(.git)
+  const match =
+    remoteUrl.match(/^[\w.-]+@([^:]+):(.+?)(?:\.git)?\/?$/) ||
+    remoteUrl.match(/^(?:ssh|https?|git):\/\/(?:[^@/]+@)?([^/:]+)(?::\d+)?\/(.+?)(?:\.git)?\/?$/)
+  if (!match) return undefined
+  const [, host, repoPath] = match
+  const encoded = encodeURIComponent(branch)
+  if (host.includes('github')) return `https://${host}/${repoPath}/pull/new/${encoded}`
+  if (host.includes('gitlab')) return `https://${host}/${repoPath}/-/merge_requests/new?merge_request[source_branch]=${encoded}`
+  if (host.includes('bitbucket')) return `https://${host}/${repoPath}/pull-requests/new?source=${encoded}`
+  return undefined
 }
 
 export const gitService = new GitService()
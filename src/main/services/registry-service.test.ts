@@ -0,0 +1,452 @@
+import { generateKeyPairSync, randomUUID, sign } from 'crypto'
+import fs from 'fs'
+import http from 'http'
+import type { AddressInfo } from 'net'
+import path from 'path'
+import Store from 'electron-store'
+import { afterEach, describe, expect, it, vi } from 'vitest'
+import type { Mock } from 'vitest'
+import type { AcpRegistry, AcpRegistryAgent } from '@shared/types/agent'
+import { ACP_REGISTRY_URL } from '@shared/constants'
+import { RegistryService } from './registry-service'
+import { settingsService } from './settings-service'
+import { getIconCacheDir } from '../util/paths'
+
+/** A local registry endpoint and the headers of every request it received */
+interface StubServer {
+  url: string
+  requests: http.IncomingHttpHeaders[]
+}
+
+/** Same backing data as settingsService */
+const settings = new Store({ name: 'settings' })
+const servers: http.Server[] = []
+
+afterEach(async () => {
+  vi.restoreAllMocks()
+  settings.clear()
+  await Promise.all(servers.splice(0).map((server) => new Promise((resolve) => server.close(resolve))))
+})
+
+function registryAgent(id: string, name = id): AcpRegistryAgent {
+  return { id, name, version: '1.0.0', description: '', authors: [], license: 'MIT', icon: '', distribution: { npx: { package: id } } }
+}
+
+function registry(version: string, agents: AcpRegistryAgent[]): AcpRegistry {
+  return { version, agents, extensions: [] }
+}
+
+/** Answer every request with `handler`; each server gets a unique URL so disk caches never collide */
+async function serve(handler: (req: http.IncomingMessage, res: http.ServerResponse) => void): Promise<StubServer> {
+  const requests: http.IncomingHttpHeaders[] = []
+  const server = http.createServer((req, res) => {
+    requests.push(req.headers)
+    handler(req, res)
+  })
+  servers.push(server)
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve))
+  return { url: `http://127.0.0.1:${(server.address() as AddressInfo).port}/${randomUUID()}/registry.json`, requests }
+}
+
+function serveRegistry(data: AcpRegistry, headers: http.OutgoingHttpHeaders = {}): Promise<StubServer> {
+  return serve((_req, res) => {
+    res.writeHead(200, { 'Content-Type': 'application/json', ...headers })
+    res.end(JSON.stringify(data))
+  })
+}
+
+function serveError(status: number): Promise<StubServer> {
+  return serve((_req, res) => {
+    res.writeHead(status)
+    res.end()
+  })
+}
+
+function useSources(...registrySources: string[]): void {
+  settingsService.set({ general: { ...settingsService.get().general, registrySources } })
+}
+
+describe('registry sources', () => {
+  it('defaults to the ACP registry', () => {
+    expect(new RegistryService().getSources()).toEqual([ACP_REGISTRY_URL])
+  })
+
+  it('merges sources in order, the later one winning on an id collision', async () => {
+    const first = await serveRegistry(registry('1', [registryAgent('alpha'), registryAgent('shared', 'Shared (first)')]))
+    const second = await serveRegistry(registry('2', [registryAgent('shared', 'Shared (second)'), registryAgent('beta')]))
+    useSources(first.url, second.url)
+
+    const merged = await new RegistryService().fetch()
+
+    expect(merged.version).toBe('1+2')
+    expect(merged.agents.map(({ id, name, source }) => ({ id, name, source }))).toEqual([
+      { id: 'alpha', name: 'alpha', source: first.url },
+      { id: 'shared', name: 'Shared (second)', source: second.url },
+      { id: 'beta', name: 'beta', source: second.url }
+    ])
+    expect(merged.sourceErrors).toBeUndefined()
+  })
+
+  it('reports a failing source while keeping the others', async () => {
+    const working = await serveRegistry(registry('1', [registryAgent('alpha')]))
+    const broken = await serveError(500)
+    useSources(working.url, broken.url)
+
+    const merged = await new RegistryService().fetch()
+
+    expect(merged.agents.map((a) => a.id)).toEqual(['alpha'])
+    expect(merged.sourceErrors).toEqual([{ source: broken.url, error: 'Registry fetch failed: 500 Internal Server Error' }])
+  })
+
+  it('fails when no source loads', async () => {
+    const broken = await serveError(404)
+    const alsoBroken = await serveError(500)
+    useSources(broken.url, alsoBroken.url)
+
+    await expect(new RegistryService().fetch()).rejects.toThrow('Registry fetch failed: 404 Not Found')
+  })
+
+  it('serves the merged cache of every source without fetching', async () => {
+    const first = await serveRegistry(registry('1', [registryAgent('alpha')]))
+    const second = await serveRegistry(registry('2', [registryAgent('beta')]))
+    useSources(first.url, second.url)
+    await new RegistryService().fetch()
+
+    // A fresh service reads the per-source disk caches
+    const cached = new RegistryService().getCached()
+
+    expect(cached?.agents.map((a) => [a.id, a.source])).toEqual([
+      ['alpha', first.url],
+      ['beta', second.url]
+    ])
+    expect(first.requests).toHaveLength(1)
+    expect(second.requests).toHaveLength(1)
+  })
+
+  it('validates and de-duplicates new sources', async () => {
+    const service = new RegistryService()
+    const source = await serveRegistry(registry('1', [registryAgent('alpha')]))
+
+    await expect(service.setSources(['not a url'])).rejects.toThrow('Invalid registry URL: not a url')
+    await expect(service.setSources(['ftp://example.com/registry.json'])).rejects.toThrow('must use http or https')
+    expect(service.getSources()).toEqual([ACP_REGISTRY_URL])
+
+    const merged = await service.setSources([` ${source.url} `, source.url, ''])
+    expect(service.getSources()).toEqual([source.url])
+    expect(merged.agents.map((a) => a.id)).toEqual(['alpha'])
+  })
+})
+
+describe('conditional fetches', () => {
+  const LAST_MODIFIED = 'Wed, 01 Jul 2026 10:00:00 GMT'
+
+  /** A registry whose body changes with `current.version`; answers 304 when the client's ETag matches */
+  async function serveVersioned(current: { version: string }): Promise<StubServer> {
+    return serve((req, res) => {
+      const etag = `"v${current.version}"`
+      if (req.headers['if-none-match'] === etag) {
+        res.writeHead(304, { ETag: etag })
+        res.end()
+        return
+      }
+      res.writeHead(200, { 'Content-Type': 'application/json', ETag: etag, 'Last-Modified': LAST_MODIFIED })
+      res.end(JSON.stringify(registry(current.version, [registryAgent(`agent-v${current.version}`)])))
+    })
+  }
+
+  function alwaysRevalidate(): void {
+    settingsService.set({ general: { ...settingsService.get().general, registryCacheTtlSecs: 0 } })
+  }
+
+  it('revalidates with the stored validators and reuses the cache on a 304', async () => {
+    const server = await serveVersioned({ version: '1' })
+    useSources(server.url)
+    alwaysRevalidate()
+    const service = new RegistryService()
+
+    const fresh = await service.fetch()
+    const revalidated = await service.fetch()
+
+    expect(fresh._meta?.fromCache).toBe(false)
+    expect(server.requests[0]['if-none-match']).toBeUndefined()
+    expect(server.requests[1]).toMatchObject({ 'if-none-match': '"v1"', 'if-modified-since': LAST_MODIFIED })
+    expect(revalidated._meta).toMatchObject({ fromCache: true })
+    expect(revalidated._meta?.stale).toBeUndefined()
+    expect(revalidated.agents.map((a) => a.id)).toEqual(['agent-v1'])
+  })
+
+  it('replaces the cache when the registry changed', async () => {
+    const current = { version: '1' }
+    const server = await serveVersioned(current)
+    useSources(server.url)
+    alwaysRevalidate()
+    await new RegistryService().fetch()
+
+    current.version = '2'
+    // A new service instance revalidates from the disk cache's validators
+    const updated = await new RegistryService().fetch()
+
+    expect(server.requests[1]['if-none-match']).toBe('"v1"')
+    expect(updated._meta?.fromCache).toBe(false)
+    expect(updated.agents.map((a) => a.id)).toEqual(['agent-v2'])
+    expect(new RegistryService().getCached()?.agents.map((a) => a.id)).toEqual(['agent-v2'])
+  })
+
+  it('sends no validators when the server gave none', async () => {
+    const server = await serveRegistry(registry('1', [registryAgent('alpha')]))
+    useSources(server.url)
+    alwaysRevalidate()
+    const service = new RegistryService()
+
+    await service.fetch()
+    const second = await service.fetch()
+
+    expect(server.requests).toHaveLength(2)
+    expect(server.requests[1]['if-none-match']).toBeUndefined()
+    expect(server.requests[1]['if-modified-since']).toBeUndefined()
+    expect(second._meta?.fromCache).toBe(false)
+  })
+})
+
+describe('forced refresh', () => {
+  it('hits the server even while the cache is fresh', async () => {
+    const data = registry('1', [registryAgent('alpha')])
+    const server = await serve((_req, res) => {
+      res.writeHead(200, { 'Content-Type': 'application/json' })
+      res.end(JSON.stringify(data))
+    })
+    useSources(server.url)
+    const service = new RegistryService()
+    await service.fetch()
+
+    const cached = await service.fetch()
+    expect(server.requests).toHaveLength(1)
+    expect(cached._meta?.fromCache).toBe(true)
+
+    data.agents.push(registryAgent('just-shipped'))
+    const forced = await service.fetch(true)
+
+    expect(server.requests).toHaveLength(2)
+    expect(forced._meta?.fromCache).toBe(false)
+    expect(forced.agents.map((a) => a.id)).toEqual(['alpha', 'just-shipped'])
+    expect(new RegistryService().getCached()?.agents.map((a) => a.id)).toEqual(['alpha', 'just-shipped'])
+  })
+
+  it('falls back to the cache, marked stale, when the server is unreachable', async () => {
+    let up = true
+    const server = await serve((_req, res) => {
+      if (!up) {
+        res.writeHead(503)
+        res.end()
+        return
+      }
+      res.writeHead(200, { 'Content-Type': 'application/json' })
+      res.end(JSON.stringify(registry('1', [registryAgent('alpha')])))
+    })
+    useSources(server.url)
+    const service = new RegistryService()
+    await service.fetch()
+
+    up = false
+    const forced = await service.fetch(true)
+
+    expect(server.requests).toHaveLength(2)
+    expect(forced._meta).toMatchObject({ fromCache: true, stale: true })
+    expect(forced.agents.map((a) => a.id)).toEqual(['alpha'])
+  })
+})
+
+describe('icon cache', () => {
+  const ICON = '<svg xmlns="http://www.w3.org/2000/svg"></svg>'
+
+  afterEach(() => {
+    new RegistryService().clearIconCache()
+    vi.unstubAllGlobals()
+  })
+
+  /** Icons come from the fixed CDN URL, so the network is stubbed at fetch() and counted */
+  function stubCdn(respond: () => Promise<Response>): Mock<() => Promise<Response>> {
+    const cdn = vi.fn(respond)
+    vi.stubGlobal('fetch', cdn)
+    return cdn
+  }
+
+  it('serves repeat requests from memory, then from disk, without refetching', async () => {
+    const cdn = stubCdn(async () => new Response(ICON))
+    const service = new RegistryService()
+
+    expect(await service.fetchRegistryIconSvg('alpha', 'alpha.svg')).toBe(ICON)
+    expect(await service.fetchRegistryIconSvg('alpha', 'alpha.svg')).toBe(ICON)
+    expect(await new RegistryService().fetchRegistryIconSvg('alpha', 'alpha.svg')).toBe(ICON)
+
+    expect(cdn).toHaveBeenCalledTimes(1)
+    expect(fs.readFileSync(path.join(getIconCacheDir(), 'alpha.svg'), 'utf-8')).toBe(ICON)
+  })
+
+  it('refetches an expired icon and serves the stale copy when the CDN is down', async () => {
+    const iconPath = path.join(getIconCacheDir(), 'alpha.svg')
+    fs.writeFileSync(iconPath, ICON)
+    const lastWeek = new Date(Date.now() - 7 * 24 * 60 * 60 * 1000)
+    fs.utimesSync(iconPath, lastWeek, lastWeek)
+    const cdn = stubCdn(async () => {
+      throw new TypeError('fetch failed')
+    })
+
+    expect(await new RegistryService().fetchRegistryIconSvg('alpha', 'alpha.svg')).toBe(ICON)
+    expect(cdn).toHaveBeenCalledTimes(1)
+  })
+
+  it('fetches again after the cache is cleared', async () => {
+    const cdn = stubCdn(async () => new Response(ICON))
+    const service = new RegistryService()
+    await service.fetchRegistryIconSvg('alpha', 'alpha.svg')
+
+    service.clearIconCache()
+    await service.fetchRegistryIconSvg('alpha', 'alpha.svg')
+
+    expect(cdn).toHaveBeenCalledTimes(2)
+  })
+
+  it('ignores icons outside the registry CDN', async () => {
+    const cdn = stubCdn(async () => new Response(ICON))
+
+    expect(await new RegistryService().fetchRegistryIconSvg('alpha', 'https://example.com/alpha.svg')).toBeNull()
+    expect(cdn).not.toHaveBeenCalled()
+  })
+})
+
+describe('search', () => {
+  const agents: AcpRegistryAgent[] = [
+    { ...registryAgent('super-coder', 'Super Coder'), license: 'Apache-2.0', distribution: { uvx: { package: 'super-coder' } } },
+    { ...registryAgent('reviewer', 'Reviewer'), description: 'Reviews what the coder wrote', distribution: { binary: {} } },
+    registryAgent('coder-pro', 'Coder Pro'),
+    registryAgent('coder', 'Coder')
+  ]
+
+  function cachedService(): RegistryService {
+    const service = new RegistryService()
+    vi.spyOn(service, 'getCached').mockReturnValue(registry('1', agents))
+    return service
+  }
+
+  it('ranks exact, prefix and substring id/name matches above description matches', () => {
+    const ids = (query: string): string[] => cachedService().search(query).map((a) => a.id)
+
+    expect(ids('coder')).toEqual(['coder', 'coder-pro', 'super-coder', 'reviewer'])
+    expect(ids('  CODER ')).toEqual(['coder', 'coder-pro', 'super-coder', 'reviewer'])
+    expect(ids('review')).toEqual(['reviewer'])
+    expect(ids('nothing like it')).toEqual([])
+  })
+
+  it('lists everything by name for an empty query', () => {
+    expect(cachedService().search('').map((a) => a.name)).toEqual(['Coder', 'Coder Pro', 'Reviewer', 'Super Coder'])
+  })
+
+  it('filters by distribution type and license', () => {
+    const service = cachedService()
+
+    expect(service.search('', { distribution: 'npx' }).map((a) => a.id)).toEqual(['coder', 'coder-pro'])
+    expect(service.search('coder', { distribution: 'binary' }).map((a) => a.id)).toEqual(['reviewer'])
+    expect(service.search('', { license: 'apache-2.0' }).map((a) => a.id)).toEqual(['super-coder'])
+    expect(service.search('coder', { distribution: 'npx', license: 'Apache-2.0' })).toEqual([])
+  })
+
+  it('returns nothing while the cache is cold', () => {
+    expect(new RegistryService().search('coder')).toEqual([])
+  })
+})
+
+describe('signed registries', () => {
+  const { publicKey, privateKey } = generateKeyPairSync('ed25519')
+  const PUBLIC_KEY_PEM = publicKey.export({ type: 'spki', format: 'pem' }).toString()
+  // The raw 32-byte key follows the 12-byte SPKI header
+  const PUBLIC_KEY_BASE64 = publicKey.export({ type: 'spki', format: 'der' }).subarray(12).toString('base64')
+
+  function body(agentId: string): Buffer {
+    return Buffer.from(JSON.stringify(registry('1', [registryAgent(agentId)])))
+  }
+
+  /** Serve `current.body`, signed in a response header or a sibling `.sig` file, or unsigned */
+  function serveSigned(current: { body: Buffer; signature?: Buffer; via?: 'header' | 'sidecar' }): Promise<StubServer> {
+    return serve((req, res) => {
+      if (req.url?.endsWith('.sig')) {
+        res.writeHead(current.via === 'sidecar' && current.signature ? 200 : 404)
+        res.end(current.via === 'sidecar' ? current.signature?.toString('hex') : undefined)
+        return
+      }
+      const headers = current.via === 'header' && current.signature ? { 'X-Registry-Signature': current.signature.toString('base64') } : {}
+      res.writeHead(200, { 'Content-Type': 'application/json', ...headers })
+      res.end(current.body)
+    })
+  }
+
+  function requireSignature(registryPublicKey?: string): void {
+    settingsService.set({
+      general: { ...settingsService.get().general, verifyRegistrySignature: true, registryPublicKey }
+    })
+  }
+
+  it('accepts a body signed in the response header', async () => {
+    const signed = body('alpha')
+    const server = await serveSigned({ body: signed, signature: sign(null, signed, privateKey), via: 'header' })
+    useSources(server.url)
+    requireSignature(PUBLIC_KEY_PEM)
+
+    expect((await new RegistryService().fetch()).agents.map((a) => a.id)).toEqual(['alpha'])
+  })
+
+  it('accepts a body signed in a sibling .sig file', async () => {
+    const signed = body('alpha')
+    const server = await serveSigned({ body: signed, signature: sign(null, signed, privateKey), via: 'sidecar' })
+    useSources(server.url)
+    requireSignature(PUBLIC_KEY_BASE64)
+
+    expect((await new RegistryService().fetch()).agents.map((a) => a.id)).toEqual(['alpha'])
+    expect(server.requests).toHaveLength(2)
+  })
+
+  it('refuses a tampered body and caches nothing', async () => {
+    const server = await serveSigned({ body: body('evil'), signature: sign(null, body('alpha'), privateKey), via: 'header' })
+    useSources(server.url)
+    requireSignature(PUBLIC_KEY_PEM)
+    const service = new RegistryService()
+
+    await expect(service.fetch()).rejects.toThrow(`Registry signature verification failed for ${server.url}`)
+    expect(service.getCached()).toBeNull()
+  })
+
+  it('falls back to the last verified cache when a refresh fails verification', async () => {
+    const signed = body('alpha')
+    const current = { body: signed, signature: sign(null, signed, privateKey), via: 'header' as const }
+    const server = await serveSigned(current)
+    useSources(server.url)
+    requireSignature(PUBLIC_KEY_PEM)
+    const service = new RegistryService()
+    await service.fetch()
+
+    current.body = body('evil')
+    const refreshed = await service.fetch(true)
+
+    expect(refreshed.agents.map((a) => a.id)).toEqual(['alpha'])
+    expect(refreshed._meta).toMatchObject({ fromCache: true, stale: true })
+  })
+
+  it('refuses unsigned registries, or any without a configured key, when verification is on', async () => {
+    const server = await serveSigned({ body: body('alpha') })
+    useSources(server.url)
+
+    requireSignature(PUBLIC_KEY_PEM)
+    await expect(new RegistryService().fetch()).rejects.toThrow('Registry signature not found')
+
+    requireSignature(undefined)
+    await expect(new RegistryService().fetch()).rejects.toThrow('no public key is configured')
+  })
+
+  it('accepts unsigned registries when verification is off', async () => {
+    const server = await serveSigned({ body: body('alpha') })
+    useSources(server.url)
+
+    expect((await new RegistryService().fetch()).agents.map((a) => a.id)).toEqual(['alpha'])
+    expect(server.requests).toHaveLength(1)
+  })
+})
@@ -1,86 +1,330 @@
 import fs from 'fs'
-import type { AcpRegistry } from '@shared/types/agent'
+import path from 'path'
+import type {
+  AcpRegistry,
+  AcpRegistryAgent,
+  AcpRegistryAgentDetails,
+  AcpRegistryExtension,
+  AgentInstallability,
+  PlatformTarget,
+  RegistryExtensionMcpServer,
+  RegistrySearchFilters,
+  RegistrySourceError
+} from '@shared/types/agent'
 import { ACP_CDN_URL, ACP_REGISTRY_URL, REGISTRY_CACHE_TTL_MS, getAgentIconUrl } from '@shared/constants'
-import { getRegistryCachePath } from '../util/paths'
+import { settingsService } from './settings-service'
+import { getIconCacheDir, getRegistryCachePath } from '../util/paths'
+import { getCurrentPlatformTarget } from '../util/platform'
+import { decodeSignature, verifyRegistrySignature } from '../util/registry-signature'
 import { logger } from '../util/logger'
 
 interface CachedRegistry {
   fetchedAt: number
   data: AcpRegistry
+  /** Validators from the last 200 response, sent back on refresh */
+  etag?: string
+  lastModified?: string
+  /** The payload passed signature verification when it was fetched */
+  verified?: boolean
 }
 
+interface SourceResult {
+  data: AcpRegistry
+  fetchedAt: number
+  fromCache: boolean
+  /** The fetch failed and an expired cache was returned */
+  stale?: boolean
+}
+
+/** Cached icons stay fresh this long before the CDN is asked again */
+const ICON_CACHE_TTL_MS = 24 * 60 * 60 * 1000
+/** Icons kept in memory; Map insertion order doubles as LRU order */
+const ICON_MEMORY_CACHE_SIZE = 64
+/** Response header carrying a base64 detached signature of the body */
+const REGISTRY_SIGNATURE_HEADER = 'x-registry-signature'
+
 export class RegistryService {
-  private cache: CachedRegistry | null = null
+  /** Per-source caches, keyed by registry URL */
+  private caches = new Map<string, CachedRegistry>()
+  private iconCache = new Map<string, { svg: string; fetchedAt: number }>()
+
+  /**
+   * Fetch every configured registry source (each with its own cache) and merge
+   * them. A failing source is reported in `sourceErrors` as long as another
+   * one loads; if all fail, the first error is thrown. `force` skips the
+   * freshness checks and always goes to the network.
+   */
+  async fetch(force = false): Promise<AcpRegistry> {
+    const sources = this.getSources()
+    const results = await Promise.allSettled(sources.map((source) => this.fetchSource(source, force)))
+
+    const loaded: Array<{ source: string; data: AcpRegistry }> = []
+    const sourceErrors: RegistrySourceError[] = []
+    let fromCache = true
+    let stale = false
+    let oldestFetch = Date.now()
+    results.forEach((result, i) => {
+      if (result.status === 'fulfilled') {
+        loaded.push({ source: sources[i], data: result.value.data })
+        fromCache &&= result.value.fromCache
+        stale ||= !!result.value.stale
+        oldestFetch = Math.min(oldestFetch, result.value.fetchedAt)
+      } else {
+        sourceErrors.push({ source: sources[i], error: (result.reason as Error).message })
+      }
+    })
+
+    if (loaded.length === 0) {
+      throw (results[0] as PromiseRejectedResult).reason
+    }
+    return {
+      ...mergeRegistries(loaded, sourceErrors),
+      _meta: { fromCache, cacheAgeMs: Math.max(0, Date.now() - oldestFetch), ...(stale ? { stale } : {}) }
+    }
+  }
+
+  /** Replace the configured registry sources and fetch from them */
+  async setSources(sources: string[]): Promise<AcpRegistry> {
+    const normalized = Array.from(new Set(sources.map((s) => s.trim()).filter(Boolean)))
+    for (const source of normalized) {
+      let url: URL
+      try {
+        url = new URL(source)
+      } catch {
+        throw new Error(`Invalid registry URL: ${source}`)
+      }
+      if (url.protocol !== 'https:' && url.protocol !== 'http:') {
+        throw new Error(`Registry URL must use http or https: ${source}`)
+      }
+    }
+
+    settingsService.set({ general: { ...settingsService.get().general, registrySources: normalized } })
+    logger.info(`Registry sources set: ${this.getSources().join(', ')}`)
+    return this.fetch()
+  }
+
+  /**
+   * Fetch one registry source, with caching. A stale cache is revalidated with
+   * If-None-Match / If-Modified-Since; a 304 only refreshes its timestamp.
+   * With signature verification on, only verified payloads are accepted,
+   * cached, or used as a fallback.
+   */
+  private async fetchSource(source: string, force: boolean): Promise<SourceResult> {
+    const ttlMs = force ? 0 : this.getCacheTtlMs()
+    const verify = !!settingsService.get().general.verifyRegistrySignature
+    const usable = (cached: CachedRegistry | null | undefined): CachedRegistry | undefined =>
+      cached && (!verify || cached.verified) ? cached : undefined
 
-  /** Fetch the registry from CDN, with caching */
-  async fetch(): Promise<AcpRegistry> {
     // Check in-memory cache first
-    if (this.cache && Date.now() - this.cache.fetchedAt < REGISTRY_CACHE_TTL_MS) {
-      logger.debug('Returning in-memory cached registry')
-      return this.cache.data
+    const memoryCache = usable(this.caches.get(source))
+    if (memoryCache && Date.now() - memoryCache.fetchedAt < ttlMs) {
+      logger.debug(`Returning in-memory cached registry for ${source}`)
+      return { ...memoryCache, fromCache: true }
     }
 
     // Check disk cache
-    const diskCache = this.loadFromDisk()
-    if (diskCache && Date.now() - diskCache.fetchedAt < REGISTRY_CACHE_TTL_MS) {
-      logger.debug('Returning disk cached registry')
-      this.cache = diskCache
-      return diskCache.data
+    const diskCache = usable(this.loadFromDisk(source))
+    if (diskCache && Date.now() - diskCache.fetchedAt < ttlMs) {
+      logger.debug(`Returning disk cached registry for ${source}`)
+      this.caches.set(source, diskCache)
+      return { ...diskCache, fromCache: true }
     }
 
-    // Fetch fresh from CDN
-    logger.info('Fetching ACP registry from CDN...')
+    // Fetch fresh, or confirm the stale copy is still current
+    const stale = diskCache ?? memoryCache
+    const headers: Record<string, string> = {}
+    if (stale?.etag) headers['If-None-Match'] = stale.etag
+    if (stale?.lastModified) headers['If-Modified-Since'] = stale.lastModified
+
+    logger.info(`Fetching ACP registry from ${source}...`)
     try {
-      const response = await fetch(ACP_REGISTRY_URL)
+      const response = await fetch(source, { headers })
+      if (response.status === 304 && stale) {
+        const revalidated: CachedRegistry = { ...stale, fetchedAt: Date.now() }
+        this.caches.set(source, revalidated)
+        this.saveToDisk(source, revalidated)
+        logger.info(`Registry at ${source} not modified; cache revalidated`)
+        return { ...revalidated, fromCache: true }
+      }
       if (!response.ok) {
         throw new Error(`Registry fetch failed: ${response.status} ${response.statusText}`)
       }
 
-      const data = (await response.json()) as AcpRegistry
-      const cached: CachedRegistry = { fetchedAt: Date.now(), data }
+      const body = Buffer.from(await response.arrayBuffer())
+      if (verify) {
+        await this.verifySource(source, response, body)
+      }
+
+      const data = JSON.parse(body.toString('utf-8')) as AcpRegistry
+      if (!data || !Array.isArray(data.agents)) {
+        throw new Error('Registry response has no agents list')
+      }
+      const cached: CachedRegistry = {
+        fetchedAt: Date.now(),
+        data,
+        etag: response.headers.get('etag') ?? undefined,
+        lastModified: response.headers.get('last-modified') ?? undefined,
+        ...(verify ? { verified: true } : {})
+      }
 
       // Update caches
-      this.cache = cached
-      this.saveToDisk(cached)
+      this.caches.set(source, cached)
+      this.saveToDisk(source, cached)
 
-      logger.info(`Registry loaded: ${data.agents.length} agents, version ${data.version}`)
-      return data
+      logger.info(`Registry loaded from ${source}: ${data.agents.length} agents, version ${data.version}`)
+      return { ...cached, fromCache: false }
     } catch (error) {
-      logger.error('Failed to fetch registry:', error)
+      logger.error(`Failed to fetch registry from ${source}:`, error)
 
       // Fallback to stale cache if available
       if (diskCache) {
         logger.warn('Using stale disk cache as fallback')
-        this.cache = diskCache
-        return diskCache.data
+        this.caches.set(source, diskCache)
+        return { ...diskCache, fromCache: true, stale: true }
       }
-      if (this.cache) {
+      if (memoryCache) {
         logger.warn('Using stale memory cache as fallback')
-        return this.cache.data
+        return { ...memoryCache, fromCache: true, stale: true }
       }
 
       throw error
     }
   }
 
-  /** Get cached registry without fetching */
+  /**
+   * Check a registry body against its detached ed25519 signature, taken from
+   * the response header or else the sibling `<url>.sig`. Throws on any failure.
+   */
+  private async verifySource(source: string, response: Response, body: Buffer): Promise<void> {
+    const publicKey = settingsService.get().general.registryPublicKey?.trim()
+    if (!publicKey) {
+      throw new Error('Registry signature verification is enabled but no public key is configured')
+    }
+
+    let signature: Buffer
+    const header = response.headers.get(REGISTRY_SIGNATURE_HEADER)
+    if (header) {
+      signature = decodeSignature(Buffer.from(header))
+    } else {
+      const sigResponse = await fetch(`${source}.sig`)
+      if (!sigResponse.ok) {
+        throw new Error(`Registry signature not found for ${source}: ${sigResponse.status} ${sigResponse.statusText}`)
+      }
+      signature = decodeSignature(Buffer.from(await sigResponse.arrayBuffer()))
+    }
+
+    if (!verifyRegistrySignature(body, signature, publicKey)) {
+      throw new Error(`Registry signature verification failed for ${source}`)
+    }
+    logger.info(`Registry signature verified for ${source}`)
+  }
+
+  /** Look up a single agent by id (cache first, fetching if needed) */
+  async getAgent(agentId: string): Promise<AcpRegistryAgentDetails | null> {
+    let agent = this.getCached()?.agents.find((a) => a.id === agentId)
+    if (!agent) {
+      const registry = await this.fetch()
+      agent = registry.agents.find((a) => a.id === agentId)
+    }
+    if (!agent) return null
+
+    const binaryPlatforms = agent.distribution.binary
+      ? (Object.keys(agent.distribution.binary) as PlatformTarget[]).filter(
+          (p) => !!agent!.distribution.binary![p]
+        )
+      : []
+
+    return {
+      ...agent,
+      binaryPlatforms,
+      availableOnCurrentPlatform: isInstallableOnCurrentPlatform(agent)
+    }
+  }
+
+  /**
+   * Search the cached registry by id/name/description substring (case-insensitive)
+   * and filter by distribution type and license. Exact id/name matches rank
+   * first, then prefix and substring matches on id/name, then description
+   * matches. A cold cache returns [] so the caller can fetch first.
+   */
+  search(query: string, filters: RegistrySearchFilters = {}): AcpRegistryAgent[] {
+    const registry = this.getCached()
+    if (!registry) return []
+
+    const needle = query.trim().toLowerCase()
+    const license = filters.license?.toLowerCase()
+    const ranked: Array<{ agent: AcpRegistryAgent; rank: number }> = []
+
+    for (const agent of registry.agents) {
+      if (filters.distribution && !agent.distribution[filters.distribution]) continue
+      if (license && agent.license?.toLowerCase() !== license) continue
+
+      const rank = needle ? searchRank(agent, needle) : 0
+      if (rank >= 0) ranked.push({ agent, rank })
+    }
+
+    return ranked.sort((a, b) => a.rank - b.rank || a.agent.name.localeCompare(b.agent.name)).map((r) => r.agent)
+  }
+
+  /** Check up front whether an agent can be installed here, to avoid a failed install */
+  async checkInstallable(agentId: string): Promise<AgentInstallability> {
+    const agent = await this.getAgent(agentId)
+    if (!agent) {
+      return { installable: false, reason: `Agent not found in registry: ${agentId}` }
+    }
+    return getInstallability(agent)
+  }
+
+  /** List ACP extensions from the registry, skipping entries without an id or name */
+  async listExtensions(): Promise<AcpRegistryExtension[]> {
+    return parseExtensions(await this.fetch())
+  }
+
+  /** Cached extension entries for the given ids (no fetch); unknown ids are skipped */
+  getCachedExtensions(extensionIds: string[]): AcpRegistryExtension[] {
+    const registry = this.getCached()
+    if (!registry || extensionIds.length === 0) return []
+    const wanted = new Set(extensionIds)
+    return parseExtensions(registry).filter((ext) => wanted.has(ext.id))
+  }
+
+  /** Get the merged cached registries without fetching */
   getCached(): AcpRegistry | null {
-    if (this.cache) return this.cache.data
-    const diskCache = this.loadFromDisk()
-    if (diskCache) {
-      this.cache = diskCache
-      return diskCache.data
+    const verify = !!settingsService.get().general.verifyRegistrySignature
+    const loaded: Array<{ source: string; data: AcpRegistry }> = []
+    for (const source of this.getSources()) {
+      let cached = this.caches.get(source)
+      if (!cached) {
+        cached = this.loadFromDisk(source) ?? undefined
+        if (cached) this.caches.set(source, cached)
+      }
+      if (cached && (!verify || cached.verified)) loaded.push({ source, data: cached.data })
     }
-    return null
+    return loaded.length > 0 ? mergeRegistries(loaded, []) : null
+  }
+
+  /** Configured registry URLs, falling back to the built-in ACP registry */
+  getSources(): string[] {
+    const sources = settingsService.get().general.registrySources
+    return sources && sources.length > 0 ? sources : [ACP_REGISTRY_URL]
   }
 
-  /** Fetch a trusted ACP registry SVG icon. */
+  /**
+   * Fetch a trusted ACP registry SVG icon. Icons are cached in memory (LRU) and
+   * under cache/icons; a stale copy is served when the CDN can't be reached.
+   */
   async fetchRegistryIconSvg(agentId: string, icon?: string): Promise<string | null> {
     const iconUrl = getAgentIconUrl(agentId, icon)
     if (!iconUrl || !iconUrl.startsWith(ACP_CDN_URL)) {
       return null
     }
 
+    const cached = this.iconCache.get(agentId) ?? this.loadIconFromDisk(agentId)
+    if (cached) {
+      this.rememberIcon(agentId, cached)
+      if (Date.now() - cached.fetchedAt < ICON_CACHE_TTL_MS) return cached.svg
+    }
+
     try {
       const response = await fetch(iconUrl)
       if (!response.ok) {
@@ -90,36 +334,103 @@ export class RegistryService {
           status: response.status,
           statusText: response.statusText
         })
-        return null
+        return cached?.svg ?? null
       }
 
       const svg = await response.text()
       if (!svg.includes('<svg')) {
         logger.warn('Registry icon response was not valid SVG', { agentId, iconUrl })
-        return null
+        return cached?.svg ?? null
       }
 
+      const entry = { svg, fetchedAt: Date.now() }
+      this.rememberIcon(agentId, entry)
+      try {
+        fs.writeFileSync(this.getIconCachePath(agentId), svg, 'utf-8')
+      } catch (error) {
+        logger.warn('Failed to write icon cache:', error)
+      }
       return svg
     } catch (error) {
       logger.warn('Failed to fetch registry icon SVG', { agentId, iconUrl, error })
+      return cached?.svg ?? null
+    }
+  }
+
+  /** Drop every cached icon, in memory and on disk */
+  clearIconCache(): void {
+    this.iconCache.clear()
+    fs.rmSync(getIconCacheDir(), { recursive: true, force: true })
+    logger.info('Icon cache cleared')
+  }
+
+  private rememberIcon(agentId: string, entry: { svg: string; fetchedAt: number }): void {
+    this.iconCache.delete(agentId)
+    this.iconCache.set(agentId, entry)
+    if (this.iconCache.size > ICON_MEMORY_CACHE_SIZE) {
+      const oldest = this.iconCache.keys().next().value
+      if (oldest !== undefined) this.iconCache.delete(oldest)
+    }
+  }
+
+  private loadIconFromDisk(agentId: string): { svg: string; fetchedAt: number } | null {
+    try {
+      const iconPath = this.getIconCachePath(agentId)
+      // The file's mtime is the fetch time
+      return { svg: fs.readFileSync(iconPath, 'utf-8'), fetchedAt: fs.statSync(iconPath).mtimeMs }
+    } catch {
       return null
     }
   }
 
-  private loadFromDisk(): CachedRegistry | null {
+  private getIconCachePath(agentId: string): string {
+    return path.join(getIconCacheDir(), `${agentId.replace(/[^a-zA-Z0-9._-]/g, '_')}.svg`)
+  }
+
+  /** Cache freshness window; 0 always refetches, a large value pins the cache. */
+  private getCacheTtlMs(): number {
+    const ttlSecs = settingsService.get().general.registryCacheTtlSecs
+    if (typeof ttlSecs === 'number' && Number.isFinite(ttlSecs) && ttlSecs >= 0) {
+      return ttlSecs * 1000
+    }
+    return REGISTRY_CACHE_TTL_MS
+  }
+
+  private loadFromDisk(source: string): CachedRegistry | null {
     try {
-      const cachePath = getRegistryCachePath()
+      const cachePath = getRegistryCachePath(source)
       if (!fs.existsSync(cachePath)) return null
       const raw = fs.readFileSync(cachePath, 'utf-8')
-      return JSON.parse(raw) as CachedRegistry
+      const parsed = JSON.parse(raw) as Partial<CachedRegistry> & Partial<AcpRegistry>
+
+      if (parsed.data && Array.isArray(parsed.data.agents)) {
+        // Restore the real fetch time; a missing/invalid stamp is treated as stale
+        const fetchedAt = typeof parsed.fetchedAt === 'number' && Number.isFinite(parsed.fetchedAt)
+          ? parsed.fetchedAt
+          : 0
+        return {
+          fetchedAt,
+          data: parsed.data,
+          etag: typeof parsed.etag === 'string' ? parsed.etag : undefined,
+          lastModified: typeof parsed.lastModified === 'string' ? parsed.lastModified : undefined,
+          verified: parsed.verified === true ? true : undefined
+        }
+      }
+
+      // Legacy cache files stored the bare registry; use the file mtime as fetch time
+      if (Array.isArray(parsed.agents)) {
+        return { fetchedAt: fs.statSync(cachePath).mtimeMs, data: parsed as AcpRegistry }
+      }
+
+      return null
     } catch {
       return null
     }
   }
 
-  private saveToDisk(cached: CachedRegistry): void {
+  private saveToDisk(source: string, cached: CachedRegistry): void {
     try {
-      const cachePath = getRegistryCachePath()
+      const cachePath = getRegistryCachePath(source)
       fs.writeFileSync(cachePath, JSON.stringify(cached, null, 2), 'utf-8')
     } catch (error) {
       logger.warn('Failed to write registry cache to disk:', error)
@@ -127,4 +438,107 @@ export class RegistryService {
   }
 }
 
+/** 0 exact id/name, 1 id/name prefix, 2 id/name substring, 3 description; -1 no match */
+function searchRank(agent: AcpRegistryAgent, needle: string): number {
+  const keys = [agent.id.toLowerCase(), agent.name.toLowerCase()]
+  if (keys.some((k) => k === needle)) return 0
+  if (keys.some((k) => k.startsWith(needle))) return 1
+  if (keys.some((k) => k.includes(needle))) return 2
+  if (agent.description?.toLowerCase().includes(needle)) return 3
+  return -1
+}
+
+/** Typed view of the registry's extensions; malformed optional fields are dropped */
+function parseExtensions(registry: AcpRegistry): AcpRegistryExtension[] {
+  const raw = Array.isArray(registry.extensions) ? registry.extensions : []
+  const extensions: AcpRegistryExtension[] = []
+  const isStringArray = (value: unknown): value is string[] =>
+    Array.isArray(value) && value.every((v) => typeof v === 'string')
+  const isStringRecord = (value: unknown): value is Record<string, string> =>
+    !!value && typeof value === 'object' && !Array.isArray(value) && Object.values(value).every((v) => typeof v === 'string')
+
+  for (const entry of raw) {
+    if (!entry || typeof entry !== 'object') continue
+    const ext = entry as Record<string, unknown>
+    const id = typeof ext.id === 'string' ? ext.id : typeof ext.name === 'string' ? ext.name : ''
+    if (!id) continue
+
+    const mcpServers = Array.isArray(ext.mcpServers)
+      ? ext.mcpServers.filter(
+          (s): s is RegistryExtensionMcpServer =>
+            !!s && typeof s === 'object' && typeof (s as RegistryExtensionMcpServer).name === 'string'
+        )
+      : undefined
+
+    extensions.push({
+      ...ext,
+      id,
+      name: typeof ext.name === 'string' ? ext.name : id,
+      description: typeof ext.description === 'string' ? ext.description : '',
+      agents: isStringArray(ext.agents) ? ext.agents : undefined,
+      env: isStringRecord(ext.env) ? ext.env : undefined,
+      args: isStringArray(ext.args) ? ext.args : undefined,
+      mcpServers
+    })
+  }
+
+  return extensions
+}
+
+/**
+ * Merge registries in source order: a later source's agent or extension
+ * replaces an earlier one with the same id. Each agent records its source.
+ */
+function mergeRegistries(
+  loaded: Array<{ source: string; data: AcpRegistry }>,
+  sourceErrors: RegistrySourceError[]
+): AcpRegistry {
+  const agents = new Map<string, AcpRegistryAgent>()
+  const extensions = new Map<string, unknown>()
+  const unnamedExtensions: unknown[] = []
+
+  for (const { source, data } of loaded) {
+    for (const agent of data.agents) {
+      agents.delete(agent.id) // re-insert so the order follows the winning source
+      agents.set(agent.id, { ...agent, source })
+    }
+    for (const ext of Array.isArray(data.extensions) ? data.extensions : []) {
+      const id = ext && typeof ext === 'object' ? (ext as Record<string, unknown>).id : undefined
+      if (typeof id === 'string') {
+        extensions.delete(id)
+        extensions.set(id, ext)
+      } else {
+        unnamedExtensions.push(ext)
+      }
+    }
+  }
+
+  return {
+    version: loaded.map(({ data }) => data.version).join('+'),
+    agents: Array.from(agents.values()),
+    extensions: [...extensions.values(), ...unnamedExtensions],
+    ...(sourceErrors.length > 0 ? { sourceErrors } : {})
+  }
+}
+
+/** npx/uvx agents run anywhere; binary agents need a build for this platform. */
+export function getInstallability(agent: AcpRegistryAgent): AgentInstallability {
+  if (agent.distribution.npx || agent.distribution.uvx) return { installable: true }
+  if (!agent.distribution.binary) {
+    return { installable: false, reason: 'No supported distribution method' }
+  }
+  const platform = getCurrentPlatformTarget()
+  if (!platform) {
+    return { installable: false, reason: `Unsupported platform: ${process.platform}-${process.arch}` }
+  }
+  if (!agent.distribution.binary[platform]) {
+    return { installable: false, reason: `No binary available for ${platform}` }
+  }
+  return { installable: true }
+}
+
+export function isInstallableOnCurrentPlatform(agent: AcpRegistryAgent): boolean {
+  return getInstallability(agent).installable
+}
+
 export const registryService = new RegistryService()
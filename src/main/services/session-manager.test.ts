@@ -0,0 +1,628 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import Store from 'electron-store'
+import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import type { BrowserWindow } from 'electron'
+import type { ContentBlock, Message, SessionInfo, SessionUpdate, SessionUpdateEvent, UsageUpdate } from '@shared/types/session'
+import type { AcpClient } from './acp-client'
+import { SessionManagerService } from './session-manager'
+import { folderThreadStore } from './folder-thread-store'
+import { threadStore } from './thread-store'
+import { settingsService } from './settings-service'
+import { git, initRepo, removeTempDirs, tempDir } from '../test/git-repo'
+import { connectMockAgent, disconnectMockAgents } from '../test/mock-agent'
+
+/** Private restore path shared by ensureConnected and prompt recovery */
+interface Restorer {
+  restoreAcpSession(client: AcpClient, session: SessionInfo): Promise<{ method: 'resume' | 'load' | 'new' }>
+}
+
+let workDir: string
+
+beforeEach(() => {
+  workDir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'session-manager-')))
+  threadStore.setWorkspaceResolver(() => ({ path: workDir }))
+})
+
+afterEach(async () => {
+  await disconnectMockAgents()
+  fs.rmSync(workDir, { recursive: true, force: true })
+})
+
+function text(value: string): ContentBlock[] {
+  return [{ type: 'text', text: value }]
+}
+
+function agentText(message: Message | undefined): string {
+  return (message?.content ?? []).map((block) => (block.type === 'text' ? block.text : '')).join('')
+}
+
+function createSession(manager: SessionManagerService, client: AcpClient): Promise<SessionInfo> {
+  return manager.createSession({
+    connectionId: client.connectionId,
+    workingDir: workDir,
+    useWorktree: false,
+    workspaceId: 'ws-1'
+  })
+}
+
+describe('restoring a persisted thread', () => {
+  it('loads it through session/load when the agent supports it', async () => {
+    const first = await connectMockAgent({ MOCK_AGENT_LOAD_SESSION: '1' })
+    const session = await createSession(new SessionManagerService(), first)
+    const second = await connectMockAgent({ MOCK_AGENT_LOAD_SESSION: '1' })
+    const replayed: SessionUpdate[] = []
+    second.on('session-update', (event: SessionUpdateEvent) => {
+      if (event.sessionId === session.sessionId) replayed.push(event.update)
+    })
+
+    const result = await (new SessionManagerService() as unknown as Restorer).restoreAcpSession(second, session)
+
+    expect(result.method).toBe('load')
+    expect(second.getRemoteSessionId(session.sessionId)).toBe(session.acpSessionId)
+    expect(replayed).toContainEqual({ type: 'text_chunk', messageId: 'current', text: `Loaded ${session.acpSessionId}` })
+  })
+
+  it('falls back to session/new and sends the history along with the next prompt', async () => {
+    const first = await connectMockAgent()
+    const original = new SessionManagerService()
+    const { sessionId } = await createSession(original, first)
+    await original.prompt(sessionId, text('first question'))
+
+    const second = await connectMockAgent()
+    const manager = new SessionManagerService()
+    manager.syncWithStore()
+    const restored = manager.getSession(sessionId)!
+    const result = await (manager as unknown as Restorer).restoreAcpSession(second, restored)
+    restored.connectionId = second.connectionId
+    await manager.prompt(sessionId, text('second question'))
+
+    expect(result.method).toBe('new')
+    const reply = agentText(restored.messages.at(-1))
+    expect(reply).toContain('Previous conversation in this thread')
+    expect(reply).toContain('first question')
+    expect(reply).toContain('second question')
+  })
+})
+
+describe('forking a session', () => {
+  it('forks through session/fork when the agent supports it', async () => {
+    const client = await connectMockAgent({ MOCK_AGENT_FORK: '1' })
+    const manager = new SessionManagerService()
+    const source = await createSession(manager, client)
+    await manager.prompt(source.sessionId, text('first question'))
+
+    const fork = await manager.forkSession(source.sessionId)
+    await manager.prompt(fork.sessionId, text('second question'))
+
+    expect(fork.parentSessionId).toBe(source.sessionId)
+    expect(fork.acpSessionId).toBeTruthy()
+    expect(fork.acpSessionId).not.toBe(source.acpSessionId)
+    expect(manager.getSession(fork.sessionId)).toBe(fork)
+    expect(threadStore.loadAll().find((t) => t.sessionId === fork.sessionId)?.parentSessionId).toBe(source.sessionId)
+    // The agent kept the history itself, so none is replayed
+    expect(agentText(fork.messages.at(-1))).not.toContain('Previous conversation in this thread')
+  })
+
+  it('starts a new session and replays the history when the agent cannot fork', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const source = await createSession(manager, client)
+    await manager.prompt(source.sessionId, text('first question'))
+
+    const fork = await manager.forkSession(source.sessionId)
+    await manager.prompt(fork.sessionId, text('second question'))
+
+    expect(fork.parentSessionId).toBe(source.sessionId)
+    expect(fork.acpSessionId).not.toBe(source.acpSessionId)
+    expect(manager.getSession(fork.sessionId)).toBe(fork)
+    expect(threadStore.loadAll().find((t) => t.sessionId === fork.sessionId)?.parentSessionId).toBe(source.sessionId)
+    const reply = agentText(fork.messages.at(-1))
+    expect(reply).toContain('Previous conversation in this thread')
+    expect(reply).toContain('first question')
+    expect(reply).toContain('second question')
+  })
+})
+
+describe('session metadata', () => {
+  it('round-trips through the thread manifest without touching messages', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const { sessionId } = await createSession(manager, client)
+    // A line written straight to the thread folder, as streaming appends do
+    const streamed: Message = { id: 'streamed', role: 'agent', content: text('partial'), timestamp: new Date().toISOString() }
+    folderThreadStore.appendMessage(workDir, sessionId, streamed)
+
+    expect(manager.setMetadata(sessionId, { issue: 42, pr: 'https://example.com/pr/1' })).toEqual({
+      issue: 42,
+      pr: 'https://example.com/pr/1'
+    })
+    expect(manager.setMetadata(sessionId, { pr: null, label: 'bug' })).toEqual({ issue: 42, label: 'bug' })
+
+    expect(manager.getMetadata(sessionId)).toEqual({ issue: 42, label: 'bug' })
+    expect(new SessionManagerService().getMetadata(sessionId)).toEqual({ issue: 42, label: 'bug' })
+    expect(folderThreadStore.readManifest(workDir, sessionId)?.metadata?.custom).toEqual({ issue: 42, label: 'bug' })
+    expect(folderThreadStore.readMessages(workDir, sessionId).map((m) => m.id)).toContain('streamed')
+  })
+
+  it('drops the metadata once every key is removed', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const { sessionId } = await createSession(manager, client)
+
+    manager.setMetadata(sessionId, { issue: 42 })
+    manager.setMetadata(sessionId, { issue: null })
+
+    expect(manager.getMetadata(sessionId)).toEqual({})
+    expect(folderThreadStore.readManifest(workDir, sessionId)?.metadata?.custom).toBeUndefined()
+  })
+})
+
+describe('auto-commit', () => {
+  const defaults = settingsService.get().git
+
+  afterEach(() => {
+    settingsService.set({ git: defaults })
+    removeTempDirs()
+  })
+
+  /** A worktree session on a fresh repo in workDir, with auto-commit set as given */
+  async function worktreeSession(autoCommit: boolean): Promise<{ manager: SessionManagerService; session: SessionInfo; events: unknown[] }> {
+    settingsService.set({ git: { ...defaults, autoCommit, commitPrefix: 'agent: ', worktreeBaseDir: tempDir('worktrees-') } })
+    initRepo(workDir)
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const events: unknown[] = []
+    manager.setMainWindow({
+      isDestroyed: () => false,
+      webContents: { send: (channel: string, payload: unknown) => channel === 'session:auto-commit' && events.push(payload) }
+    } as unknown as BrowserWindow)
+    const session = await manager.createSession({
+      connectionId: client.connectionId,
+      workingDir: workDir,
+      useWorktree: true,
+      workspaceId: 'ws-1'
+    })
+    return { manager, session, events }
+  }
+
+  it('commits the agent edits with a message from the prompt when enabled', async () => {
+    const { manager, session, events } = await worktreeSession(true)
+    expect(session.worktreePath).toBe(session.workingDir)
+
+    await manager.prompt(session.sessionId, text('write notes.txt hello'))
+
+    const head = git(session.workingDir, 'rev-parse', 'HEAD')
+    expect(git(session.workingDir, 'log', '-1', '--pretty=format:%s')).toBe('agent: write notes.txt hello')
+    expect(git(session.workingDir, 'show', '--pretty=format:', '--name-only', 'HEAD')).toBe('notes.txt')
+    expect(git(session.workingDir, 'status', '--porcelain')).toBe('')
+    expect(events).toEqual([expect.objectContaining({ sessionId: session.sessionId, hash: head, branch: session.worktreeBranch })])
+  })
+
+  it('leaves the changes uncommitted when disabled', async () => {
+    const { manager, session, events } = await worktreeSession(false)
+    const before = git(session.workingDir, 'rev-parse', 'HEAD')
+
+    await manager.prompt(session.sessionId, text('write notes.txt hello'))
+
+    expect(git(session.workingDir, 'rev-parse', 'HEAD')).toBe(before)
+    expect(git(session.workingDir, 'status', '--porcelain')).toBe('?? notes.txt')
+    expect(events).toEqual([])
+  })
+
+  it('skips the commit when the prompt changed nothing', async () => {
+    const { manager, session, events } = await worktreeSession(true)
+    const before = git(session.workingDir, 'rev-parse', 'HEAD')
+
+    await manager.prompt(session.sessionId, text('just answer'))
+
+    expect(git(session.workingDir, 'rev-parse', 'HEAD')).toBe(before)
+    expect(events).toEqual([])
+  })
+})
+
+describe('prompt queue', () => {
+  /** A session whose manager records the queue events sent to the renderer */
+  async function queueSession(): Promise<{ manager: SessionManagerService; session: SessionInfo; events: Array<{ channel: string; payload: unknown }> }> {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const events: Array<{ channel: string; payload: unknown }> = []
+    manager.setMainWindow({
+      isDestroyed: () => false,
+      webContents: {
+        send: (channel: string, payload: unknown) => channel.startsWith('session:prompt-') && events.push({ channel, payload })
+      }
+    } as unknown as BrowserWindow)
+    return { manager, session: await createSession(manager, client), events }
+  }
+
+  /** User prompts in thread order, each with the agent reply that followed it */
+  function turns(session: SessionInfo): Array<[string, string]> {
+    return session.messages.flatMap((message, index): Array<[string, string]> =>
+      message.role === 'user' ? [[agentText(message), agentText(session.messages[index + 1])]] : []
+    )
+  }
+
+  it('queues a prompt that arrives while another is running', async () => {
+    const { manager, session, events } = await queueSession()
+
+    const first = manager.prompt(session.sessionId, text('first'))
+    const second = manager.prompt(session.sessionId, text('second'))
+
+    // Queued synchronously, before the running prompt has even reached the agent
+    expect(session.queuedPrompts).toEqual([expect.objectContaining({ preview: 'second' })])
+    expect(events).toEqual([
+      {
+        channel: 'session:prompt-queued',
+        payload: { sessionId: session.sessionId, prompt: session.queuedPrompts![0], position: 1 }
+      }
+    ])
+
+    expect(await first).toEqual({ stopReason: 'end_turn' })
+    expect(await second).toEqual({ stopReason: 'end_turn' })
+    expect(session.queuedPrompts).toBeUndefined()
+    expect(events.map((e) => e.channel)).toEqual(['session:prompt-queued', 'session:prompt-dequeued'])
+    expect(turns(session)).toEqual([
+      ['first', expect.stringContaining('Echo: first')],
+      ['second', expect.stringContaining('Echo: second')]
+    ])
+  })
+
+  it('runs queued prompts strictly in arrival order', async () => {
+    const { manager, session, events } = await queueSession()
+
+    const results = ['first', 'second', 'third', 'fourth'].map((word) => manager.prompt(session.sessionId, text(word)))
+    const queued = session.queuedPrompts!.map((p) => p.promptId)
+
+    expect(session.queuedPrompts!.map((p) => p.preview)).toEqual(['second', 'third', 'fourth'])
+    expect(await Promise.all(results)).toEqual(Array(4).fill({ stopReason: 'end_turn' }))
+    expect(events.filter((e) => e.channel === 'session:prompt-queued').map((e) => (e.payload as { position: number }).position)).toEqual([1, 2, 3])
+    expect(events.filter((e) => e.channel === 'session:prompt-dequeued').map((e) => (e.payload as { promptId: string }).promptId)).toEqual(queued)
+    expect(turns(session)).toEqual(
+      ['first', 'second', 'third', 'fourth'].map((word) => [word, expect.stringContaining(`Echo: ${word}`)])
+    )
+  })
+
+  it('drops the queued prompts on cancel', async () => {
+    const { manager, session } = await queueSession()
+
+    const running = manager.prompt(session.sessionId, text('first'))
+    const queued = [manager.prompt(session.sessionId, text('second')), manager.prompt(session.sessionId, text('third'))]
+    await manager.cancel(session.sessionId)
+
+    expect(session.queuedPrompts).toBeUndefined()
+    expect(await Promise.all(queued)).toEqual([{ stopReason: 'cancelled' }, { stopReason: 'cancelled' }])
+    await running
+    expect(turns(session).map(([prompt]) => prompt)).toEqual(['first'])
+  })
+
+  it('rejects a prompt once the queue is full', async () => {
+    const { manager, session } = await queueSession()
+
+    const running = [manager.prompt(session.sessionId, text('running'))]
+    for (let i = 0; i < 10; i++) running.push(manager.prompt(session.sessionId, text(`queued ${i}`)))
+
+    await expect(manager.prompt(session.sessionId, text('one too many'))).rejects.toMatchObject({ code: 'PROMPT_IN_PROGRESS' })
+    expect(session.queuedPrompts).toHaveLength(10)
+    manager.clearQueue(session.sessionId)
+    await Promise.all(running)
+  })
+})
+
+describe('usage tracking', () => {
+  afterEach(() => {
+    new Store({ name: 'settings' }).clear()
+  })
+
+  /** A session whose manager records budget warnings, and a way to feed it usage reports */
+  async function usageSession(): Promise<{
+    manager: SessionManagerService
+    session: SessionInfo
+    warnings: unknown[]
+    report: (usage: UsageUpdate) => void
+  }> {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const warnings: unknown[] = []
+    manager.setMainWindow({
+      isDestroyed: () => false,
+      webContents: { send: (channel: string, payload: unknown) => channel === 'session:budget-warning' && warnings.push(payload) }
+    } as unknown as BrowserWindow)
+    const session = await createSession(manager, client)
+    const report = (usage: UsageUpdate): void => {
+      client.emit('session-update', { sessionId: session.sessionId, update: { type: 'usage_update', usage } })
+    }
+    return { manager, session, warnings, report }
+  }
+
+  it('warns exactly once when the context crosses the default threshold', async () => {
+    const { manager, session, warnings, report } = await usageSession()
+
+    report({ used: 700, size: 1000 })
+    expect(warnings).toEqual([])
+    report({ used: 800, size: 1000 })
+    report({ used: 900, size: 1000 })
+    report({ used: 950, size: 1000 })
+
+    expect(warnings).toEqual([{ sessionId: session.sessionId, used: 800, size: 1000, ratio: 0.8, threshold: 0.8 }])
+    expect(manager.getUsage(session.sessionId).usage).toMatchObject({ used: 950, size: 1000 })
+  })
+
+  it('warns again after dropping back under the threshold', async () => {
+    const { warnings, report } = await usageSession()
+
+    report({ used: 850, size: 1000 })
+    report({ used: 300, size: 1000 })
+    report({ used: 900, size: 1000 })
+
+    expect(warnings).toEqual([expect.objectContaining({ used: 850 }), expect.objectContaining({ used: 900 })])
+  })
+
+  it('uses the configured threshold', async () => {
+    settingsService.set({ general: { ...settingsService.get().general, contextWarningThreshold: 0.5 } })
+    const { warnings, report } = await usageSession()
+
+    report({ used: 499, size: 1000 })
+    report({ used: 500, size: 1000 })
+
+    expect(warnings).toEqual([expect.objectContaining({ used: 500, ratio: 0.5, threshold: 0.5 })])
+  })
+
+  it('accumulates cost across reports and keeps it after a restart', async () => {
+    const { manager, session, report } = await usageSession()
+
+    report({ used: 100, size: 1000, cost: { amount: 0.5, currency: 'USD' } })
+    report({ used: 200, size: 1000, cost: { amount: 1.25, currency: 'USD' } })
+    // A lower running total means a fresh agent session; all of it is new cost
+    report({ used: 50, size: 1000, cost: { amount: 0.25, currency: 'USD' } })
+    expect(manager.getUsage(session.sessionId).totalCost).toEqual({ amount: 1.5, currency: 'USD' })
+
+    await manager.closeSession(session.sessionId, false)
+
+    expect(new SessionManagerService().getUsage(session.sessionId)).toEqual({
+      usage: null,
+      totalCost: { amount: 1.5, currency: 'USD' }
+    })
+  })
+})
+
+describe('prompt attachments', () => {
+  it('records links in the thread instead of the file contents', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const { sessionId } = await createSession(manager, client)
+    fs.writeFileSync(path.join(workDir, 'notes.md'), 'x'.repeat(4096))
+
+    await manager.prompt(sessionId, text('see attached'), undefined, ['notes.md'])
+
+    const stored = folderThreadStore.readMessages(workDir, sessionId)
+    expect(stored[0].content).toEqual([
+      { type: 'text', text: 'see attached' },
+      expect.objectContaining({ type: 'resource_link', name: 'notes.md', mimeType: 'text/markdown', size: 4096 })
+    ])
+    expect(JSON.stringify(stored)).not.toContain('x'.repeat(4096))
+  })
+
+  it('fails the prompt before it reaches the agent when an attachment is invalid', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+
+    await expect(manager.prompt(session.sessionId, text('see attached'), undefined, ['missing.md'])).rejects.toMatchObject({
+      code: 'INVALID_ATTACHMENT'
+    })
+    expect(session.messages).toEqual([])
+  })
+})
+
+describe('editing messages', () => {
+  /** A session with two finished turns; its manager records messages-changed events */
+  async function twoTurnSession(): Promise<{ manager: SessionManagerService; session: SessionInfo; events: unknown[] }> {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const events: unknown[] = []
+    manager.setMainWindow({
+      isDestroyed: () => false,
+      webContents: { send: (channel: string, payload: unknown) => channel === 'session:messages-changed' && events.push(payload) }
+    } as unknown as BrowserWindow)
+    const session = await createSession(manager, client)
+    await manager.prompt(session.sessionId, text('first question'))
+    await manager.prompt(session.sessionId, text('second question'))
+    events.length = 0
+    return { manager, session, events }
+  }
+
+  function userMessage(session: SessionInfo, value: string): Message {
+    return session.messages.find((m) => m.role === 'user' && agentText(m) === value)!
+  }
+
+  it('edits a message in place and rewrites the thread', async () => {
+    const { manager, session, events } = await twoTurnSession()
+    const ids = session.messages.map((m) => m.id)
+    const target = userMessage(session, 'first question')
+
+    const updated = manager.editMessage(session.sessionId, target.id, text('first question, reworded'))
+
+    expect(updated.map((m) => m.id)).toEqual(ids)
+    expect(session.messages).toBe(updated)
+    const stored = folderThreadStore.readMessages(workDir, session.sessionId)
+    expect(stored.map((m) => m.id)).toEqual(ids)
+    expect(agentText(stored.find((m) => m.id === target.id))).toBe('first question, reworded')
+    expect(events).toEqual([{ sessionId: session.sessionId, messages: updated }])
+  })
+
+  it('truncates everything after a message deleted with rewind', async () => {
+    const { manager, session, events } = await twoTurnSession()
+    const target = userMessage(session, 'second question')
+    const kept = session.messages.slice(0, session.messages.indexOf(target)).map((m) => m.id)
+
+    const updated = manager.deleteMessage(session.sessionId, target.id, true)
+
+    expect(updated.map((m) => m.id)).toEqual(kept)
+    expect(folderThreadStore.readMessages(workDir, session.sessionId).map((m) => m.id)).toEqual(kept)
+    expect(events).toEqual([{ sessionId: session.sessionId, messages: updated }])
+  })
+
+  it('removes only the message itself without rewind', async () => {
+    const { manager, session } = await twoTurnSession()
+    const target = userMessage(session, 'first question')
+    const rest = session.messages.filter((m) => m !== target).map((m) => m.id)
+
+    expect(manager.deleteMessage(session.sessionId, target.id).map((m) => m.id)).toEqual(rest)
+  })
+
+  it('rejects unknown messages and sessions', async () => {
+    const { manager, session } = await twoTurnSession()
+
+    expect(() => manager.deleteMessage(session.sessionId, 'missing')).toThrow(`Message missing not found in session ${session.sessionId}`)
+    expect(() => manager.editMessage('missing', session.messages[0].id, text('x'))).toThrow('Session not found: missing')
+  })
+
+  it('refuses changes while a prompt is running', async () => {
+    const { manager, session } = await twoTurnSession()
+    const running = manager.prompt(session.sessionId, text('third question'))
+
+    expect(() => manager.deleteMessage(session.sessionId, session.messages[0].id)).toThrow(
+      `Session ${session.sessionId} is running a prompt`
+    )
+    await running
+  })
+})
+
+describe('retrying the last prompt', () => {
+  it('replaces a failed turn with a clean single exchange', async () => {
+    const client = await connectMockAgent({ MOCK_AGENT_FAIL_ONCE: '1' })
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+
+    await expect(manager.prompt(session.sessionId, text('hello'))).rejects.toThrow('Simulated failure')
+    expect(session.status).toBe('error')
+    expect(agentText(session.messages.at(-1))).toBe('Partial reply')
+
+    expect(await manager.retryLast(session.sessionId)).toEqual({ stopReason: 'end_turn' })
+
+    const live = manager.getSession(session.sessionId)!
+    expect(live.messages.map((m) => m.role)).toEqual(['user', 'agent'])
+    expect(live.messages[0].content).toEqual(text('hello'))
+    expect(agentText(live.messages[1])).toContain('Echo: hello')
+    expect(agentText(live.messages[1])).not.toContain('Partial reply')
+    expect(folderThreadStore.readMessages(workDir, session.sessionId).map((m) => m.id)).toEqual(live.messages.map((m) => m.id))
+  })
+
+  it('refuses to retry a turn that completed', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+    await manager.prompt(session.sessionId, text('hello'))
+
+    await expect(manager.retryLast(session.sessionId)).rejects.toMatchObject({ code: 'NOTHING_TO_RETRY' })
+    expect(session.messages.map((m) => m.role)).toEqual(['user', 'agent'])
+  })
+})
+
+describe('session instructions', () => {
+  function occurrences(value: string, part: string): number {
+    return value.split(part).length - 1
+  }
+
+  it('sends the instructions once at the start of every prompt', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await manager.createSession({
+      connectionId: client.connectionId,
+      workingDir: workDir,
+      useWorktree: false,
+      workspaceId: 'ws-1',
+      systemPrompt: '  Answer in one word.  '
+    })
+
+    await manager.prompt(session.sessionId, text('first question'))
+    await manager.prompt(session.sessionId, text('second question'))
+
+    expect(session.systemPrompt).toBe('Answer in one word.')
+    const replies = session.messages.filter((m) => m.role === 'agent').map(agentText)
+    expect(replies).toHaveLength(2)
+    for (const reply of replies) {
+      expect(reply.startsWith('Echo: Instructions for this session:\n\nAnswer in one word.\n')).toBe(true)
+      expect(occurrences(reply, 'Answer in one word.')).toBe(1)
+    }
+    // The thread only shows what the user typed
+    expect(session.messages.filter((m) => m.role === 'user').map(agentText)).toEqual(['first question', 'second question'])
+  })
+
+  it('updates and clears the instructions mid-session and persists them', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+
+    manager.setSystemPrompt(session.sessionId, 'Reply in French.')
+    await manager.prompt(session.sessionId, text('first question'))
+    expect(agentText(session.messages.at(-1))).toContain('Reply in French.')
+    expect(threadStore.loadAll().find((t) => t.sessionId === session.sessionId)?.systemPrompt).toBe('Reply in French.')
+
+    manager.setSystemPrompt(session.sessionId, '   ')
+    await manager.prompt(session.sessionId, text('second question'))
+    expect(agentText(session.messages.at(-1))).not.toContain('Instructions for this session')
+    expect(session.systemPrompt).toBeUndefined()
+  })
+})
+
+describe('slash commands', () => {
+  /** Deliver a raw ACP available_commands_update as if the agent had sent it */
+  function advertise(client: AcpClient, session: SessionInfo, availableCommands: unknown[]): void {
+    ;(client as unknown as { handleSessionUpdate(params: Record<string, unknown>): void }).handleSessionUpdate({
+      sessionId: session.acpSessionId,
+      update: { sessionUpdate: 'available_commands_update', availableCommands }
+    })
+  }
+
+  it('keeps the latest advertised commands for the session', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+    expect(manager.getCommands(session.sessionId)).toEqual([])
+
+    advertise(client, session, [{ name: 'init', description: 'Create AGENTS.md' }])
+    advertise(client, session, [
+      { name: 'review', description: 'Review changes', input: { hint: 'path to review' } },
+      { name: 'compact', description: 'Summarize the conversation' }
+    ])
+
+    expect(manager.getCommands(session.sessionId)).toEqual([
+      { name: 'review', description: 'Review changes', input: { hint: 'path to review' } },
+      { name: 'compact', description: 'Summarize the conversation', input: undefined }
+    ])
+    expect(manager.getCommands('missing')).toEqual([])
+  })
+
+  it('sends a command invocation as /name followed by its arguments', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+    advertise(client, session, [
+      { name: 'review', description: 'Review changes', input: { hint: 'path to review' } },
+      { name: 'compact', description: 'Summarize the conversation' }
+    ])
+
+    await manager.runCommand(session.sessionId, '/review', '  src/main  ')
+    await manager.runCommand(session.sessionId, 'compact', '   ')
+
+    const users = session.messages.filter((m) => m.role === 'user')
+    expect(users.map((m) => m.content)).toEqual([text('/review src/main'), text('/compact')])
+    expect(agentText(session.messages.at(-1))).toContain('Echo: /compact')
+  })
+
+  it('rejects commands the agent did not advertise', async () => {
+    const client = await connectMockAgent()
+    const manager = new SessionManagerService()
+    const session = await createSession(manager, client)
+    advertise(client, session, [{ name: 'review', description: 'Review changes' }])
+
+    await expect(manager.runCommand(session.sessionId, '/deploy')).rejects.toMatchObject({
+      code: 'COMMAND_NOT_FOUND',
+      context: { sessionId: session.sessionId, command: 'deploy' }
+    })
+    expect(session.messages).toEqual([])
+  })
+})
@@ -1,15 +1,46 @@
 import { v4 as uuid } from 'uuid'
+import { fileURLToPath } from 'url'
 import type { BrowserWindow } from 'electron'
 import type { AgentConnection } from '@shared/types/agent'
-import type { SessionInfo, CreateSessionRequest, PermissionResponse, PermissionRequestEvent, InteractionMode, SessionUpdateEvent, WorktreeHookProgressEvent, HookStep, ContentBlock } from '@shared/types/session'
-import { applyUpdateToMessages } from '@shared/util/session-util'
+import type { MergeStrategy } from '@shared/types/git'
+import type { SessionInfo, Message, ActivePrompt, CreateSessionRequest, PermissionResponse, PermissionRequestEvent, InteractionMode, SessionUpdateEvent, WorktreeHookProgressEvent, HookStep, ContentBlock, StopReason, QueuedPromptPreview, SessionUsage, UsageCost, UsageUpdate, SlashCommand, ResourceLinkContent } from '@shared/types/session'
+import { applyStopReason, applyUpdateToMessages } from '@shared/util/session-util'
 import { agentManager } from './agent-manager'
 import { gitService } from './git-service'
 import { worktreeHookService } from './worktree-hook-service'
 import { threadStore } from './thread-store'
 import { workspaceService } from './workspace-service'
 import { settingsService } from './settings-service'
+import { registryService } from './registry-service'
+import { terminalService } from './terminal-service'
 import { logger } from '../util/logger'
+import { AppError } from '../util/app-error'
+import { attachmentBlocksFor, buildPromptAttachments } from '../util/prompt-attachments'
+import type { PromptAttachments } from '../util/prompt-attachments'
+
+/** Maximum number of agent launches running at once when resuming a workspace. */
+const RESUME_CONCURRENCY = 2
+/** Default interval for flushing live sessions' messages to disk */
+const DEFAULT_AUTO_SAVE_INTERVAL_SECS = 5
+/** Continuations sent after max_tokens when autoContinueOnMaxTokens is on and no limit is set */
+const DEFAULT_AUTO_CONTINUE_MAX_TURNS = 3
+const AUTO_CONTINUE_PROMPT = 'Continue from where you left off.'
+/** Auto-commit subjects are cut to this length (prefix not included) */
+const AUTO_COMMIT_SUBJECT_MAX = 72
+/** Prompts that may wait behind a running one before new ones are rejected */
+const MAX_QUEUED_PROMPTS = 10
+/** Length of the text preview shown for a queued prompt */
+const QUEUED_PROMPT_PREVIEW_MAX = 80
+/** Context fill ratio that triggers session:budget-warning when no threshold is set */
+const DEFAULT_CONTEXT_WARNING_THRESHOLD = 0.8
+
+interface QueuedPrompt extends QueuedPromptPreview {
+  content: ContentBlock[]
+  mode?: InteractionMode
+  attachments?: PromptAttachments
+  resolve: (result: { stopReason: string }) => void
+  reject: (error: unknown) => void
+}
 
 /**
  * SessionManager orchestrates sessions across agent connections.
@@ -20,30 +51,52 @@ export class SessionManagerService {
   private monitoredConnections = new Set<string>()
   private mainWindow: BrowserWindow | null = null
   private pendingPermissions = new Map<string, PermissionRequestEvent>()
+  /** Prompts currently in flight, by session id */
+  private activePrompts = new Map<string, ActivePrompt>()
+  /** Sessions with a prompt between arrival and completion, including agent reconnects */
+  private promptsInFlight = new Set<string>()
+  /** Prompts waiting for their session's running prompt, in delivery order */
+  private promptQueues = new Map<string, QueuedPrompt[]>()
+  /** Sessions whose context usage is above the warning threshold (warned once per crossing) */
+  private budgetWarned = new Set<string>()
+  /** Sessions whose accumulated cost changed since it was last persisted */
+  private unsavedCosts = new Set<string>()
+  /** Sessions whose in-memory messages have changed since they were last written */
+  private dirtySessions = new Set<string>()
+  private autoSaveTimer: ReturnType<typeof setTimeout> | null = null
+  /** Sessions restored via session/new whose persisted history must be sent with the next prompt */
+  private historyReplayPending = new Set<string>()
 
   setMainWindow(window: BrowserWindow): void {
     this.mainWindow = window
   }
 
-  /** Read enabled MCP servers from settings, mapped to the format ACP session/new expects */
-  private getEnabledMcpServers(): Record<string, unknown>[] {
-    const servers = settingsService.get().mcp.servers
-    return servers
-      .filter((s) => s.enabled)
-      .map((s) => ({
-        name: s.name,
-        transport: s.transport,
-        ...(s.command ? { command: s.command } : {}),
-        ...(s.args?.length ? { args: s.args } : {}),
-        ...(s.url ? { url: s.url } : {}),
-        ...(s.env && Object.keys(s.env).length ? { env: s.env } : {})
-      }))
+  /**
+   * Read enabled MCP servers from settings, plus those of the agent's enabled
+   * registry extensions, mapped to the format ACP session/new expects
+   */
+  private getEnabledMcpServers(agentId?: string): Record<string, unknown>[] {
+    const configured = settingsService.get().mcp.servers.filter((s) => s.enabled)
+    const extensionIds = agentId ? settingsService.getAgentSettings(agentId)?.extensions ?? [] : []
+    const fromExtensions = registryService
+      .getCachedExtensions(extensionIds)
+      .flatMap((ext) => ext.mcpServers ?? [])
+      .map((s) => ({ ...s, transport: s.transport ?? (s.url ? 'http' : 'stdio') }))
+
+    return [...configured, ...fromExtensions].map((s) => ({
+      name: s.name,
+      transport: s.transport,
+      ...(s.command ? { command: s.command } : {}),
+      ...(s.args?.length ? { args: s.args } : {}),
+      ...(s.url ? { url: s.url } : {}),
+      ...(s.env && Object.keys(s.env).length ? { env: s.env } : {})
+    }))
   }
 
   private async ensureAuthenticatedConnection(agentId: string, workingDir: string): Promise<AgentConnection> {
     const authResult = await agentManager.checkAuthentication(agentId, workingDir)
     if (!authResult.isAuthenticated) {
-      throw new Error(authResult.error || 'Authentication required')
+      throw new AppError('NOT_AUTHENTICATED', authResult.error || 'Authentication required', { agentId })
     }
     await agentManager.authenticateConnectionForSession(authResult.connection.connectionId)
     return authResult.connection
@@ -59,22 +112,38 @@ export class SessionManagerService {
    * Restore an ACP session using cascading strategy:
    * 1. Try session/resume (experimental, fastest - no history replay)
    * 2. Try session/load (standard - agent streams history back)
-   * 3. Fallback to session/new (no history restoration)
+   * 3. Fallback to session/new; persisted messages are replayed as context on the next prompt
    */
   private async restoreAcpSession(
     client: ReturnType<typeof agentManager.getClient>,
-    sessionId: string,
-    workingDir: string
+    session: SessionInfo
   ): Promise<{ method: 'resume' | 'load' | 'new' }> {
     if (!client) throw new Error('Client is null')
 
-    const mcpServers = this.getEnabledMcpServers()
+    const result = await this.restoreAcpSessionWith(client, session)
+    const remoteId = client.getRemoteSessionId(session.sessionId)
+    if (remoteId && remoteId !== session.acpSessionId) {
+      session.acpSessionId = remoteId
+      threadStore.updateAcpSessionId(session.sessionId, remoteId)
+    }
+    if (result.method === 'new' && session.messages.length > 0) {
+      this.historyReplayPending.add(session.sessionId)
+    }
+    return result
+  }
+
+  private async restoreAcpSessionWith(
+    client: NonNullable<ReturnType<typeof agentManager.getClient>>,
+    session: SessionInfo
+  ): Promise<{ method: 'resume' | 'load' | 'new' }> {
+    const { sessionId, workingDir, acpSessionId } = session
+    const mcpServers = this.getEnabledMcpServers(client.agentId)
 
     // Strategy 1: Try resume (experimental, no history replay needed)
     if (client.supportsResume) {
       try {
         logger.info(`Attempting session/resume for ${sessionId}`)
-        await client.resumeSession(sessionId, workingDir, mcpServers)
+        await client.resumeSession(sessionId, workingDir, mcpServers, acpSessionId)
         logger.info(`Successfully resumed session ${sessionId}`)
         return { method: 'resume' }
       } catch (error) {
@@ -86,7 +155,7 @@ export class SessionManagerService {
     if (client.supportsLoad) {
       try {
         logger.info(`Attempting session/load for ${sessionId}`)
-        await client.loadSession(sessionId, workingDir, mcpServers)
+        await client.loadSession(sessionId, workingDir, mcpServers, acpSessionId)
         logger.info(`Successfully loaded session ${sessionId}`)
         return { method: 'load' }
       } catch (error) {
@@ -94,12 +163,45 @@ export class SessionManagerService {
       }
     }
 
-    // Strategy 3: Fallback to new session (no history)
-    logger.info(`Falling back to session/new for ${sessionId} (no history restoration)`)
+    // Strategy 3: Fallback to new session (history is replayed as prompt context)
+    logger.info(`Falling back to session/new for ${sessionId} (no agent-side history restoration)`)
     await client.newSession(workingDir, mcpServers, sessionId)
     return { method: 'new' }
   }
 
+  /** Re-create the agent-side sessions lost when a crashed agent was respawned */
+  private async reestablishSessions(connectionId: string, sessionIds: string[]): Promise<void> {
+    const client = agentManager.getClient(connectionId)
+    if (!client) return
+    for (const sessionId of sessionIds) {
+      const session = this.sessions.get(sessionId)
+      if (!session || session.connectionId !== connectionId) continue
+      try {
+        await this.restoreAcpSession(client, session)
+        client.setSessionContext(sessionId, session.workspaceId)
+        logger.info(`Session ${sessionId} re-established after agent restart`)
+      } catch (error) {
+        logger.warn(`Failed to re-establish session ${sessionId} after agent restart:`, error)
+      }
+    }
+  }
+
+  /** Detach sessions from a connection whose agent could not be restarted; ensureConnected relaunches it */
+  private disconnectSessions(connectionId: string): void {
+    this.monitoredConnections.delete(connectionId)
+    for (const session of this.sessions.values()) {
+      if (session.connectionId !== connectionId) continue
+      session.connectionId = ''
+      session.status = 'disconnected'
+      if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+        this.mainWindow.webContents.send('session:update', {
+          sessionId: session.sessionId,
+          update: { type: 'status_change', status: 'disconnected' }
+        })
+      }
+    }
+  }
+
   private ensureListener(connectionId: string): void {
     if (this.monitoredConnections.has(connectionId)) return
 
@@ -118,12 +220,20 @@ export class SessionManagerService {
           } else if (event.update.type === 'current_mode_update') {
             session.interactionMode = event.update.modeId as InteractionMode
             threadStore.updateInteractionMode(event.sessionId, event.update.modeId as InteractionMode)
+          } else if (event.update.type === 'usage_update') {
+            this.recordUsage(session, event.update.usage)
           }
         }
       })
       client.on('permission-request', (event: PermissionRequestEvent) => {
         this.trackPermission(event)
       })
+      client.on('restarted', (sessionIds: string[]) => {
+        void this.reestablishSessions(connectionId, sessionIds)
+      })
+      client.on('failed', () => {
+        this.disconnectSessions(connectionId)
+      })
       this.monitoredConnections.add(connectionId)
     }
   }
@@ -131,7 +241,7 @@ export class SessionManagerService {
   async createSession(request: CreateSessionRequest): Promise<SessionInfo> {
     const client = agentManager.getClient(request.connectionId)
     if (!client) {
-      throw new Error(`Agent connection not found: ${request.connectionId}`)
+      throw new AppError('NOT_CONNECTED', `Agent connection not found: ${request.connectionId}`, { connectionId: request.connectionId })
     }
     await agentManager.authenticateConnectionForSession(request.connectionId)
 
@@ -150,7 +260,8 @@ export class SessionManagerService {
           request.workingDir,
           sessionLocalId,
           request.baseBranch || undefined,
-          request.branchName || undefined
+          request.branchName || undefined,
+          { workspace: workspaceService.get(request.workspaceId)?.name, title: request.title }
         )
         worktreePath = worktree.path
         worktreeBranch = worktree.branch
@@ -161,6 +272,16 @@ export class SessionManagerService {
       }
     }
 
+    // Work on a branch in place; unlike a failed worktree, don't silently stay on the current branch
+    if (!request.useWorktree && request.checkoutBranch) {
+      const branches = await gitService.listBranches(request.workingDir)
+      if (branches.includes(request.checkoutBranch)) {
+        await gitService.switchBranch(request.workingDir, request.checkoutBranch)
+      } else {
+        await gitService.createBranch(request.workingDir, request.checkoutBranch, request.baseBranch || undefined, true)
+      }
+    }
+
     // Execute worktree hooks (symlinks, commands, initial prompt)
     if (request.useWorktree && worktreePath) {
       try {
@@ -179,11 +300,12 @@ export class SessionManagerService {
     }
 
     // Create ACP session with our stable sessionId for mapping
-    const mcpServers = this.getEnabledMcpServers()
+    const mcpServers = this.getEnabledMcpServers(client.agentId)
     await client.newSession(workingDir, mcpServers, sessionId, {
       preferredModeId: request.interactionMode
     })
-    if (request.interactionMode) {
+    // Skip the round trip when the agent already reports the requested mode as current
+    if (request.interactionMode && request.interactionMode !== client.getModeCatalog().currentModeId) {
       try {
         await client.setMode(sessionId, request.interactionMode)
       } catch (error) {
@@ -207,12 +329,15 @@ export class SessionManagerService {
       createdAt: new Date().toISOString(),
       worktreePath,
       worktreeBranch,
+      sourcePath: request.workingDir,
       workingDir,
       status: 'active',
       messages: [],
       interactionMode: request.interactionMode,
       useWorktree: request.useWorktree,
-      workspaceId: request.workspaceId
+      workspaceId: request.workspaceId,
+      acpSessionId: client.getRemoteSessionId(sessionId),
+      systemPrompt: request.systemPrompt?.trim() || undefined
     }
 
     this.sessions.set(sessionId, session)
@@ -267,7 +392,7 @@ export class SessionManagerService {
       }
     }
 
-    if (!source) throw new Error(`Source session not found: ${sourceSessionId}`)
+    if (!source) throw new AppError('SESSION_NOT_FOUND', `Source session not found: ${sourceSessionId}`, { sessionId: sourceSessionId })
 
     // Recovery: if connection lost, re-launch agent
     let client = agentManager.getClient(source.connectionId)
@@ -277,23 +402,29 @@ export class SessionManagerService {
       source.connectionId = connection.connectionId
       this.sessions.set(sourceSessionId, source)
       client = agentManager.getClient(source.connectionId)!
-      await this.restoreAcpSession(client, sourceSessionId, source.workingDir)
+      await this.restoreAcpSession(client, source)
       client.setSessionContext(sourceSessionId, source.workspaceId)
     }
 
     // Verify agent connection
-    if (!client) throw new Error(`Agent connection not found: ${source.connectionId}`)
-
-    // Check agent capability
-    if (!client.supportsFork) {
-      throw new Error(`Agent ${client.agentName} does not support session/fork`)
-    }
+    if (!client) throw new AppError('NOT_CONNECTED', `Agent connection not found: ${source.connectionId}`, { connectionId: source.connectionId })
 
     // Generate IDs
     const newSessionId = uuid()
 
-    // Call ACP fork with our stable sessionId for mapping
-    await client.forkSession(sourceSessionId, source.workingDir, [], newSessionId)
+    if (client.supportsFork) {
+      // Call ACP fork with our stable sessionId for mapping
+      await client.forkSession(sourceSessionId, source.workingDir, [], newSessionId)
+    } else {
+      // No agent-side fork: start a fresh session and replay the parent's history as context
+      logger.info(`Agent ${client.agentName} does not support session/fork; forking via session/new`)
+      await client.newSession(source.workingDir, this.getEnabledMcpServers(client.agentId), newSessionId, {
+        preferredModeId: source.interactionMode
+      })
+      if (source.messages.length > 0) {
+        this.historyReplayPending.add(newSessionId)
+      }
+    }
 
     // Copy messages from the source session so the fork starts with full context
     const forkedMessages = source.messages.map((m) => ({ ...m }))
@@ -308,13 +439,16 @@ export class SessionManagerService {
       createdAt: new Date().toISOString(),
       worktreePath: source.worktreePath,
       worktreeBranch: source.worktreeBranch,
+      sourcePath: source.sourcePath,
       workingDir: source.workingDir,
       status: 'active',
       messages: forkedMessages,
       interactionMode: source.interactionMode,
       useWorktree: source.useWorktree,
       workspaceId: source.workspaceId,
-      parentSessionId: sourceSessionId
+      parentSessionId: sourceSessionId,
+      acpSessionId: client.getRemoteSessionId(newSessionId),
+      systemPrompt: source.systemPrompt
     }
 
     this.sessions.set(newSessionId, session)
@@ -326,7 +460,161 @@ export class SessionManagerService {
     return session
   }
 
-  async prompt(sessionId: string, content: ContentBlock[], mode?: InteractionMode): Promise<{ stopReason: string }> {
+  /**
+   * Send a prompt to the session's agent. Only one prompt runs per session at
+   * a time; a prompt arriving while another is in flight is queued and
+   * resolves once it has run. Queued prompts are delivered in arrival order
+   * after the running one (and its auto-continuations) finish.
+   *
+   * `attachments` are file paths (relative ones against the session's working
+   * dir) read up front, so a bad file fails the call right away. The agent gets
+   * their contents when its prompt capabilities allow, otherwise links; the
+   * thread only links to them.
+   */
+  async prompt(
+    sessionId: string,
+    content: ContentBlock[],
+    mode?: InteractionMode,
+    attachments: string[] = []
+  ): Promise<{ stopReason: string }> {
+    let built: PromptAttachments | undefined
+    if (attachments.length > 0) {
+      // Relative paths are relative to the thread, not to the app's cwd
+      const workingDir =
+        this.sessions.get(sessionId)?.workingDir ?? threadStore.loadAll().find((t) => t.sessionId === sessionId)?.workingDir
+      built = await buildPromptAttachments(attachments, workingDir)
+    }
+    return this.startPrompt(sessionId, content, mode, built)
+  }
+
+  private async startPrompt(
+    sessionId: string,
+    content: ContentBlock[],
+    mode: InteractionMode | undefined,
+    attachments: PromptAttachments | undefined,
+    continuation?: { turn: number; originalContent: ContentBlock[] }
+  ): Promise<{ stopReason: string }> {
+    if (this.promptsInFlight.has(sessionId)) {
+      return this.enqueuePrompt(sessionId, content, mode, attachments)
+    }
+
+    this.promptsInFlight.add(sessionId)
+    let stopReason: StopReason
+    try {
+      stopReason = await this.runPrompt(sessionId, content, mode, attachments, continuation?.originalContent)
+    } catch (error) {
+      this.promptsInFlight.delete(sessionId)
+      this.dispatchQueuedPrompt(sessionId)
+      throw error
+    }
+    this.promptsInFlight.delete(sessionId)
+
+    if (stopReason === 'max_tokens') {
+      const { autoContinueOnMaxTokens, autoContinueMaxTurns } = settingsService.get().general
+      const maxTurns = autoContinueMaxTurns ?? DEFAULT_AUTO_CONTINUE_MAX_TURNS
+      const turn = continuation?.turn ?? 0
+      if (autoContinueOnMaxTokens && turn < maxTurns) {
+        logger.info(`Session ${sessionId} hit max_tokens, auto-continuing (${turn + 1}/${maxTurns})`)
+        return this.startPrompt(sessionId, [{ type: 'text', text: AUTO_CONTINUE_PROMPT }], mode, undefined, {
+          turn: turn + 1,
+          originalContent: continuation?.originalContent ?? content
+        })
+      }
+    } else if (stopReason === 'refusal') {
+      logger.info(`Agent refused the prompt in session ${sessionId}`)
+    }
+
+    this.dispatchQueuedPrompt(sessionId)
+    return { stopReason }
+  }
+
+  private enqueuePrompt(
+    sessionId: string,
+    content: ContentBlock[],
+    mode: InteractionMode | undefined,
+    attachments: PromptAttachments | undefined
+  ): Promise<{ stopReason: string }> {
+    const queue = this.promptQueues.get(sessionId) ?? []
+    if (queue.length >= MAX_QUEUED_PROMPTS) {
+      throw new AppError(
+        'PROMPT_IN_PROGRESS',
+        `The agent is still working and ${queue.length} prompts are already queued`,
+        { sessionId }
+      )
+    }
+
+    const text = content
+      .flatMap((block) => (block.type === 'text' ? [block.text] : []))
+      .join(' ')
+      .replace(/\s+/g, ' ')
+      .trim()
+    return new Promise((resolve, reject) => {
+      const queued: QueuedPrompt = {
+        promptId: uuid(),
+        preview: text.length > QUEUED_PROMPT_PREVIEW_MAX ? `${text.slice(0, QUEUED_PROMPT_PREVIEW_MAX - 3)}...` : text,
+        queuedAt: new Date().toISOString(),
+        content,
+        mode,
+        attachments,
+        resolve,
+        reject
+      }
+      queue.push(queued)
+      this.promptQueues.set(sessionId, queue)
+      this.syncQueuePreviews(sessionId)
+      logger.info(`Prompt queued in session ${sessionId} (position ${queue.length})`)
+      if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+        this.mainWindow.webContents.send('session:prompt-queued', {
+          sessionId,
+          prompt: { promptId: queued.promptId, preview: queued.preview, queuedAt: queued.queuedAt },
+          position: queue.length
+        })
+      }
+    })
+  }
+
+  /** Hand the next queued prompt to the agent; its caller gets the result */
+  private dispatchQueuedPrompt(sessionId: string): void {
+    const next = this.promptQueues.get(sessionId)?.shift()
+    if (!next) return
+    this.syncQueuePreviews(sessionId)
+    if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+      this.mainWindow.webContents.send('session:prompt-dequeued', { sessionId, promptId: next.promptId })
+    }
+    this.startPrompt(sessionId, next.content, next.mode, next.attachments).then(next.resolve, next.reject)
+  }
+
+  /** Drop a session's queued prompts; each resolves as cancelled. Returns how many were dropped. */
+  clearQueue(sessionId: string): number {
+    const queue = this.promptQueues.get(sessionId) ?? []
+    this.promptQueues.delete(sessionId)
+    this.syncQueuePreviews(sessionId)
+    for (const queued of queue) {
+      queued.resolve({ stopReason: 'cancelled' })
+    }
+    if (queue.length > 0) logger.info(`Cleared ${queue.length} queued prompt(s) in session ${sessionId}`)
+    return queue.length
+  }
+
+  private syncQueuePreviews(sessionId: string): void {
+    const session = this.sessions.get(sessionId)
+    if (!session) return
+    const queue = this.promptQueues.get(sessionId) ?? []
+    if (queue.length === 0) {
+      this.promptQueues.delete(sessionId)
+      delete session.queuedPrompts
+      return
+    }
+    session.queuedPrompts = queue.map(({ promptId, preview, queuedAt }) => ({ promptId, preview, queuedAt }))
+  }
+
+  private async runPrompt(
+    sessionId: string,
+    content: ContentBlock[],
+    mode: InteractionMode | undefined,
+    attachments: PromptAttachments | undefined,
+    continuationOf?: ContentBlock[]
+  ): Promise<StopReason> {
     let session = this.sessions.get(sessionId)
     
     // Recovery: if not in memory, try to load from store
@@ -343,7 +631,7 @@ export class SessionManagerService {
       }
     }
 
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
 
     let client = agentManager.getClient(session.connectionId)
     
@@ -355,7 +643,7 @@ export class SessionManagerService {
       client = agentManager.getClient(session.connectionId)!
       
       // Restore ACP session with cascading strategy
-      await this.restoreAcpSession(client, sessionId, session.workingDir)
+      await this.restoreAcpSession(client, session)
       client.setSessionContext(sessionId, session.workspaceId)
     }
 
@@ -364,20 +652,26 @@ export class SessionManagerService {
     // Update status
     session.status = 'prompting'
 
-    // Add user message
-    session.messages.push({
+    // Add user message; an auto-continuation only goes to the agent, the
+    // thread keeps showing the prompt it continues
+    const userMessage: Message = {
       id: uuid(),
       role: 'user',
-      content: content,
+      content: attachments ? [...content, ...attachments.links] : content,
       timestamp: new Date().toISOString()
-    })
+    }
+    if (!continuationOf) {
+      session.messages.push(userMessage)
+      threadStore.appendMessage(sessionId, userMessage)
+    }
 
     // Subscribe directly to session-update events for this prompt.
     // This ensures agent messages are captured in session.messages
-    // before persistence.
+    // before persistence; the auto-save timer flushes them while streaming.
     const promptListener = (event: SessionUpdateEvent): void => {
       if (event.sessionId === sessionId) {
         session.messages = applyUpdateToMessages(session.messages, event.update)
+        this.markDirty(sessionId)
       }
     }
     client.on('session-update', promptListener)
@@ -387,26 +681,237 @@ export class SessionManagerService {
       threadStore.updateInteractionMode(sessionId, mode)
     }
 
+    // The agent lost this thread's history on restore; send it along once as context
+    let agentContent = attachments
+      ? [...content, ...attachmentBlocksFor(attachments, client.capabilities?.promptCapabilities)]
+      : content
+    if (this.historyReplayPending.has(sessionId)) {
+      this.historyReplayPending.delete(sessionId)
+      const transcript = formatTranscript(continuationOf ? session.messages : session.messages.slice(0, -1))
+      if (transcript) {
+        agentContent = [
+          { type: 'text', text: `Previous conversation in this thread, for context:\n\n${transcript}` },
+          ...agentContent
+        ]
+      }
+    }
+    // Instructions ride along with each prompt but are never stored as a message
+    if (session.systemPrompt) {
+      agentContent = [{ type: 'text', text: `Instructions for this session:\n\n${session.systemPrompt}` }, ...agentContent]
+    }
+
+    this.activePrompts.set(sessionId, { sessionId, promptId: userMessage.id, startedAt: userMessage.timestamp })
+    let stopReason: StopReason
     try {
-      const result = await client.prompt(sessionId, content, mode)
+      const result = await client.prompt(sessionId, agentContent, mode)
+      stopReason = (result.stopReason as StopReason) || 'end_turn'
 
       session.status = 'active'
-      // Persist messages after prompt completes
-      threadStore.updateMessages(sessionId, session.messages)
-      return { stopReason: result.stopReason }
+      session.messages = applyStopReason(session.messages, stopReason)
+      // Persist messages after prompt completes, leaving one line per message
+      this.saveMessages(sessionId, true)
+      if (stopReason !== 'cancelled' && stopReason !== 'refusal') {
+        await this.autoCommit(session, continuationOf ?? content)
+      }
     } catch (error) {
       session.status = 'error'
       // Still persist messages on error so conversation history is saved
-      threadStore.updateMessages(sessionId, session.messages)
+      this.saveMessages(sessionId, true)
       throw error
     } finally {
+      this.activePrompts.delete(sessionId)
       client.removeListener('session-update', promptListener)
+      if (this.unsavedCosts.delete(sessionId) && session.totalCost) {
+        threadStore.updateCost(sessionId, session.totalCost, session.reportedCost)
+      }
+    }
+    return stopReason
+  }
+
+  /**
+   * Commit everything in a worktree session after a prompt when git.autoCommit
+   * is on. Failures are logged only; they never fail the prompt.
+   */
+  private async autoCommit(session: SessionInfo, content: ContentBlock[]): Promise<void> {
+    const { autoCommit, commitPrefix } = settingsService.get().git
+    if (!autoCommit || !session.useWorktree) return
+
+    try {
+      const status = await gitService.getStatus(session.workingDir)
+      if (status.isClean) return
+
+      const promptText = content
+        .flatMap((block) => (block.type === 'text' ? [block.text] : []))
+        .join(' ')
+        .split('\n')
+        .map((line) => line.trim())
+        .find(Boolean)
+      const summary = promptText
+        ? promptText.length > AUTO_COMMIT_SUBJECT_MAX ? `${promptText.slice(0, AUTO_COMMIT_SUBJECT_MAX - 3)}...` : promptText
+        : `Changes from ${session.agentName}`
+
+      const result = await gitService.commit(session.workingDir, `${commitPrefix}${summary}`, ['-A'], [session.agentName])
+      logger.info(`Auto-committed ${result.hash} in session ${session.sessionId}`)
+      if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+        this.mainWindow.webContents.send('session:auto-commit', {
+          sessionId: session.sessionId,
+          hash: result.hash,
+          message: result.message,
+          branch: result.branch
+        })
+      }
+    } catch (error) {
+      logger.warn(`Auto-commit failed for session ${session.sessionId}:`, error)
+    }
+  }
+
+  /**
+   * Track the agent's latest usage report. Agents report the running cost of
+   * their own session, so the thread total grows by the difference; a drop
+   * means a fresh agent session whose whole cost is new.
+   */
+  private recordUsage(session: SessionInfo, usage: UsageUpdate): void {
+    const previous = session.usage
+    session.usage = { ...usage, updatedAt: new Date().toISOString() }
+
+    if (usage.cost && Number.isFinite(usage.cost.amount)) {
+      // Agents report a running total. Before this process saw a report, the
+      // baseline is the persisted one, as long as the agent kept its session.
+      const reported = session.reportedCost?.acpSessionId === session.acpSessionId ? session.reportedCost : undefined
+      const baseline = previous?.cost ?? reported
+      const lastAmount = baseline?.currency === usage.cost.currency ? baseline.amount : 0
+      const delta = usage.cost.amount >= lastAmount ? usage.cost.amount - lastAmount : usage.cost.amount
+      if (session.acpSessionId) {
+        session.reportedCost = { ...usage.cost, acpSessionId: session.acpSessionId }
+      }
+      const total: UsageCost =
+        session.totalCost && session.totalCost.currency === usage.cost.currency
+          ? { amount: session.totalCost.amount + delta, currency: usage.cost.currency }
+          : { amount: delta, currency: usage.cost.currency }
+      if (delta > 0 || !session.totalCost) {
+        session.totalCost = total
+        this.unsavedCosts.add(session.sessionId)
+      }
+    }
+
+    const general = settingsService.get().general
+    const threshold = general.contextWarningThreshold ?? DEFAULT_CONTEXT_WARNING_THRESHOLD
+    const ratio = usage.size > 0 ? usage.used / usage.size : 0
+    if (ratio < threshold) {
+      // Back under (e.g. after compaction): warn again on the next crossing
+      this.budgetWarned.delete(session.sessionId)
+      return
+    }
+    if (this.budgetWarned.has(session.sessionId)) return
+    this.budgetWarned.add(session.sessionId)
+    logger.info(`Session ${session.sessionId} context ${Math.round(ratio * 100)}% full`)
+    if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+      this.mainWindow.webContents.send('session:budget-warning', {
+        sessionId: session.sessionId,
+        used: usage.used,
+        size: usage.size,
+        ratio,
+        threshold
+      })
+    }
+  }
+
+  /** Latest usage report and accumulated cost of a live or persisted session */
+  getUsage(sessionId: string): { usage: SessionUsage | null; totalCost: UsageCost | null } {
+    const live = this.sessions.get(sessionId)
+    if (live) return { usage: live.usage ?? null, totalCost: live.totalCost ?? null }
+    const persisted = threadStore.loadAll().find((t) => t.sessionId === sessionId)
+    if (!persisted) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
+    return { usage: null, totalCost: persisted.totalCost ?? null }
+  }
+
+  /** Slash commands the session's agent last advertised; empty until it does */
+  getCommands(sessionId: string): SlashCommand[] {
+    const session = this.sessions.get(sessionId)
+    const client = session ? agentManager.getClient(session.connectionId) : undefined
+    return client?.getAvailableCommands(sessionId) ?? []
+  }
+
+  /**
+   * Invoke an advertised slash command. ACP agents take commands as prompt
+   * text, so this sends `/name args` as a regular (queueable) prompt.
+   */
+  async runCommand(sessionId: string, name: string, args?: string): Promise<{ stopReason: string }> {
+    const commandName = name.replace(/^\//, '')
+    if (!this.getCommands(sessionId).some((c) => c.name === commandName)) {
+      throw new AppError('COMMAND_NOT_FOUND', `Command not available in session ${sessionId}: /${commandName}`, {
+        sessionId,
+        command: commandName
+      })
+    }
+    const input = args?.trim()
+    return this.prompt(sessionId, [{ type: 'text', text: input ? `/${commandName} ${input}` : `/${commandName}` }])
+  }
+
+  listActivePrompts(): ActivePrompt[] {
+    return Array.from(this.activePrompts.values())
+  }
+
+  /** Cancel every in-flight prompt. Returns the ids of the sessions that were cancelled. */
+  async cancelAll(): Promise<string[]> {
+    const sessionIds = Array.from(this.activePrompts.keys())
+    const results = await Promise.allSettled(sessionIds.map((sessionId) => this.cancel(sessionId)))
+    results.forEach((result, i) => {
+      if (result.status === 'rejected') {
+        logger.warn(`Failed to cancel prompt for session ${sessionIds[i]}:`, result.reason)
+      }
+    })
+    return sessionIds.filter((_, i) => results[i].status === 'fulfilled')
+  }
+
+  private markDirty(sessionId: string): void {
+    this.dirtySessions.add(sessionId)
+    if (this.autoSaveTimer) return
+    const intervalSecs = settingsService.get().general.autoSaveIntervalSecs ?? DEFAULT_AUTO_SAVE_INTERVAL_SECS
+    this.autoSaveTimer = setTimeout(() => this.flushDirtySessions(), Math.max(1, intervalSecs) * 1000)
+  }
+
+  /** Write every dirty session's messages; unchanged messages are skipped by the append path. */
+  private flushDirtySessions(): void {
+    this.autoSaveTimer = null
+    for (const sessionId of Array.from(this.dirtySessions)) {
+      this.saveMessages(sessionId)
+    }
+  }
+
+  private saveMessages(sessionId: string, compact = false): void {
+    this.dirtySessions.delete(sessionId)
+    const session = this.sessions.get(sessionId)
+    if (!session) return
+    try {
+      const merged = threadStore.updateMessages(sessionId, session.messages, compact)
+      if (merged) this.adoptMergedMessages(session, merged)
+    } catch (error) {
+      logger.warn(`Failed to save messages for session ${sessionId}:`, error)
+    }
+  }
+
+  /** Take over messages merged from an external edit, keeping in-memory streaming state */
+  private adoptMergedMessages(session: SessionInfo, merged: Message[]): void {
+    const live = new Map(session.messages.map((m) => [m.id, m]))
+    session.messages = merged.map((m) => {
+      const current = live.get(m.id)
+      return current?.isStreaming ? current : m
+    })
+    if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+      this.mainWindow.webContents.send('session:messages-changed', {
+        sessionId: session.sessionId,
+        messages: session.messages
+      })
     }
   }
 
   async cancel(sessionId: string): Promise<void> {
     const session = this.sessions.get(sessionId)
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
+
+    // Cancelling stops everything the user asked for, not just the running prompt
+    this.clearQueue(sessionId)
 
     // If the prompt is currently blocked on permissions, dismiss them as cancelled.
     const pendingForSession = Array.from(this.pendingPermissions.values())
@@ -453,9 +958,9 @@ export class SessionManagerService {
 
   async setMode(sessionId: string, modeId: string): Promise<void> {
     const session = this.sessions.get(sessionId)
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
     const client = agentManager.getClient(session.connectionId)
-    if (!client) throw new Error(`Agent connection not found: ${session.connectionId}`)
+    if (!client) throw new AppError('NOT_CONNECTED', `Agent connection not found: ${session.connectionId}`, { connectionId: session.connectionId })
     await client.setMode(sessionId, modeId)
   }
 
@@ -472,7 +977,7 @@ export class SessionManagerService {
         this.sessions.set(sessionId, session)
       }
     }
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
     session.interactionMode = mode
     threadStore.updateInteractionMode(sessionId, mode)
 
@@ -487,17 +992,17 @@ export class SessionManagerService {
 
   async setModel(sessionId: string, modelId: string): Promise<void> {
     const session = this.sessions.get(sessionId)
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
     const client = agentManager.getClient(session.connectionId)
-    if (!client) throw new Error(`Agent connection not found: ${session.connectionId}`)
+    if (!client) throw new AppError('NOT_CONNECTED', `Agent connection not found: ${session.connectionId}`, { connectionId: session.connectionId })
     await client.setModel(sessionId, modelId)
   }
 
   async setConfigOption(sessionId: string, configId: string, value: string): Promise<unknown> {
     const session = this.sessions.get(sessionId)
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
     const client = agentManager.getClient(session.connectionId)
-    if (!client) throw new Error(`Agent connection not found: ${session.connectionId}`)
+    if (!client) throw new AppError('NOT_CONNECTED', `Agent connection not found: ${session.connectionId}`, { connectionId: session.connectionId })
     return await client.setConfigOption(sessionId, configId, value)
   }
 
@@ -537,18 +1042,7 @@ export class SessionManagerService {
     }
 
     // Build a summary of the conversation for the title prompt
-    const conversationText = session.messages
-      .map((m) => {
-        const text = m.content
-          .filter((b): b is { type: 'text'; text: string } => b.type === 'text')
-          .map((b) => b.text)
-          .join('\n')
-          .trim()
-        if (!text) return null
-        return `${m.role === 'user' ? 'User' : 'Agent'}: ${text}`
-      })
-      .filter((line): line is string => !!line)
-      .join('\n\n')
+    const conversationText = formatTranscript(session.messages)
 
     if (!conversationText.trim()) {
       logger.warn(`generateTitle: no conversation text for session: ${sessionId}, message count: ${session.messages.length}`)
@@ -575,7 +1069,7 @@ export class SessionManagerService {
 
       // Create a temporary session for the title generation
       const tempSessionId = `title-${uuid().slice(0, 8)}`
-      await client.newSession(session.workingDir, this.getEnabledMcpServers(), tempSessionId)
+      await client.newSession(session.workingDir, this.getEnabledMcpServers(client.agentId), tempSessionId)
       if (summarizationModel) {
         try {
           await client.setModel(tempSessionId, summarizationModel)
@@ -605,8 +1099,7 @@ export class SessionManagerService {
       if (!title) return null
 
       // Apply the generated title
-      threadStore.rename(sessionId, title)
-      session.title = title
+      this.renameSession(sessionId, title)
 
       // Notify the renderer
       if (this.mainWindow && !this.mainWindow.isDestroyed()) {
@@ -646,7 +1139,7 @@ export class SessionManagerService {
       }
     }
 
-    if (!session) throw new Error(`Session not found: ${sessionId}`)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
 
     // Check if already connected
     const existingClient = agentManager.getClient(session.connectionId)
@@ -671,7 +1164,7 @@ export class SessionManagerService {
       this.sessions.set(sessionId, session)
 
       const client = agentManager.getClient(session.connectionId)!
-      await this.restoreAcpSession(client, sessionId, session.workingDir)
+      await this.restoreAcpSession(client, session)
       if (session.interactionMode) {
         try {
           await client.setMode(sessionId, session.interactionMode)
@@ -706,6 +1199,60 @@ export class SessionManagerService {
     }
   }
 
+  /**
+   * Reconnect the most recent persisted threads of a workspace.
+   * Launches agents as needed (at most RESUME_CONCURRENCY at a time) and
+   * emits `workspace:resume-progress` after each thread settles.
+   * Returns the ids of the sessions that were successfully resumed.
+   */
+  async resumeWorkspaceSessions(workspaceId: string, max: number): Promise<string[]> {
+    const lastActivity = (t: { createdAt: string; messages: { timestamp: string }[] }): string =>
+      t.messages[t.messages.length - 1]?.timestamp || t.createdAt
+
+    const candidates = threadStore
+      .loadAll()
+      .filter((t) => t.workspaceId === workspaceId)
+      .sort((a, b) => lastActivity(b).localeCompare(lastActivity(a)))
+      .slice(0, Math.max(0, max))
+      .map((t) => t.sessionId)
+
+    const total = candidates.length
+    const resumed: string[] = []
+    let completed = 0
+    let next = 0
+
+    const worker = async (): Promise<void> => {
+      while (next < candidates.length) {
+        const sessionId = candidates[next++]
+        let error: string | undefined
+        try {
+          await this.ensureConnected(sessionId)
+          resumed.push(sessionId)
+        } catch (err) {
+          error = err instanceof Error ? err.message : String(err)
+          logger.warn(`resumeWorkspaceSessions: failed to resume ${sessionId}:`, err)
+        }
+        completed++
+        if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+          this.mainWindow.webContents.send('workspace:resume-progress', {
+            workspaceId,
+            sessionId,
+            completed,
+            total,
+            error
+          })
+        }
+      }
+    }
+
+    await Promise.all(
+      Array.from({ length: Math.min(RESUME_CONCURRENCY, total) }, () => worker())
+    )
+
+    logger.info(`Resumed ${resumed.length}/${total} sessions for workspace ${workspaceId}`)
+    return resumed
+  }
+
   getSession(sessionId: string): SessionInfo | undefined {
     return this.sessions.get(sessionId)
   }
@@ -714,7 +1261,243 @@ export class SessionManagerService {
     return Array.from(this.sessions.values())
   }
 
-  async removeSession(sessionId: string, cleanupWorktree = false): Promise<void> {
+  /**
+   * Reconcile the in-memory sessions with the thread cache: every persisted
+   * thread without a live session gets a disconnected entry (no connection,
+   * status 'disconnected') that ensureConnected/prompt upgrade on resume, and
+   * disconnected entries whose thread is gone are dropped. Live sessions are
+   * left untouched.
+   */
+  syncWithStore(): SessionInfo[] {
+    const persisted = threadStore.loadAll()
+    const persistedIds = new Set(persisted.map((t) => t.sessionId))
+    let added = 0
+    let dropped = 0
+
+    for (const thread of persisted) {
+      if (this.sessions.has(thread.sessionId)) continue
+      this.sessions.set(thread.sessionId, { ...thread, connectionId: '', status: 'disconnected' })
+      added++
+    }
+
+    for (const [sessionId, session] of this.sessions) {
+      if (!persistedIds.has(sessionId) && !session.connectionId && session.status === 'disconnected') {
+        this.sessions.delete(sessionId)
+        dropped++
+      }
+    }
+
+    if (added > 0 || dropped > 0) {
+      logger.info(`Session sync: ${added} disconnected session(s) added, ${dropped} stale removed`)
+    }
+    return this.listSessions()
+  }
+
+  /** Rename a thread; keeps the in-memory copy in step with the store */
+  renameSession(sessionId: string, title: string): void {
+    const session = this.sessions.get(sessionId)
+    if (session) session.title = title
+    threadStore.rename(sessionId, title)
+  }
+
+  /** Integrator metadata of a live or persisted session */
+  getMetadata(sessionId: string): Record<string, unknown> {
+    const session = this.sessions.get(sessionId) ?? threadStore.loadAll().find((t) => t.sessionId === sessionId)
+    if (!session) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
+    return session.metadata ?? {}
+  }
+
+  /**
+   * Merge `patch` into a session's metadata; keys set to null are removed.
+   * Persisted in the thread manifest. Returns the resulting metadata.
+   */
+  setMetadata(sessionId: string, patch: Record<string, unknown>): Record<string, unknown> {
+    const metadata = { ...this.getMetadata(sessionId) }
+    for (const [key, value] of Object.entries(patch)) {
+      if (value === null) {
+        delete metadata[key]
+      } else {
+        metadata[key] = value
+      }
+    }
+
+    const session = this.sessions.get(sessionId)
+    if (session) {
+      session.metadata = Object.keys(metadata).length > 0 ? metadata : undefined
+    }
+    threadStore.updateMetadata(sessionId, metadata)
+    return metadata
+  }
+
+  /**
+   * Re-send the last user prompt after it failed or was cancelled. The prompt
+   * and whatever partial reply followed it are replaced by the new attempt;
+   * attached files are read again so the agent gets their contents.
+   */
+  async retryLast(sessionId: string): Promise<{ stopReason: string }> {
+    if (this.promptsInFlight.has(sessionId)) {
+      throw new AppError('PROMPT_IN_PROGRESS', `Session ${sessionId} is running a prompt`, { sessionId })
+    }
+    const session = this.sessions.get(sessionId)
+    const messages = session?.messages ?? threadStore.loadAll().find((t) => t.sessionId === sessionId)?.messages
+    if (!messages) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
+
+    // Only a turn known to have failed is retried; a completed exchange is never rewound
+    const index = messages.map((m) => m.role).lastIndexOf('user')
+    const failed =
+      session?.status === 'error' ||
+      session?.status === 'cancelled' ||
+      messages[messages.length - 1]?.stopReason === 'cancelled'
+    if (index < 0 || !failed) {
+      throw new AppError('NOTHING_TO_RETRY', `Last prompt in session ${sessionId} did not fail`, { sessionId })
+    }
+    const { id, content } = messages[index]
+    const isAttachment = (block: ContentBlock): block is ResourceLinkContent =>
+      block.type === 'resource_link' && block.uri.startsWith('file:')
+    const paths = content.filter(isAttachment).map((block) => fileURLToPath(block.uri))
+    // Read them before rewinding, so a file that is gone fails the retry without losing the prompt
+    const attachments = paths.length > 0 ? await buildPromptAttachments(paths) : undefined
+
+    if (!session || !agentManager.getClient(session.connectionId)) {
+      await this.ensureConnected(sessionId)
+    }
+    this.deleteMessage(sessionId, id, true)
+    const live = this.sessions.get(sessionId)!
+    live.status = 'active'
+    logger.info(`Retrying last prompt in session ${sessionId}`)
+    return this.startPrompt(
+      sessionId,
+      content.filter((block) => !isAttachment(block)),
+      live.interactionMode,
+      attachments
+    )
+  }
+
+  /** Set or clear (empty/null) the instructions sent ahead of each prompt */
+  setSystemPrompt(sessionId: string, systemPrompt: string | null): void {
+    const value = systemPrompt?.trim() || undefined
+    const session = this.sessions.get(sessionId)
+    if (session) {
+      session.systemPrompt = value
+    } else if (!threadStore.loadAll().some((t) => t.sessionId === sessionId)) {
+      throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
+    }
+    threadStore.updateSystemPrompt(sessionId, value)
+  }
+
+  /** Replace the content of one message in a live or persisted thread */
+  editMessage(sessionId: string, messageId: string, content: ContentBlock[]): Message[] {
+    return this.changeMessages(sessionId, messageId, (messages, index) =>
+      messages.map((m, i) => (i === index ? { ...m, content } : m))
+    )
+  }
+
+  /**
+   * Remove one message. With `rewind`, every later message is removed too so
+   * the conversation can be re-run from that point; the agent's own session
+   * still remembers the removed turns.
+   */
+  deleteMessage(sessionId: string, messageId: string, rewind = false): Message[] {
+    return this.changeMessages(sessionId, messageId, (messages, index) =>
+      rewind ? messages.slice(0, index) : messages.filter((_, i) => i !== index)
+    )
+  }
+
+  private changeMessages(
+    sessionId: string,
+    messageId: string,
+    change: (messages: Message[], index: number) => Message[]
+  ): Message[] {
+    if (this.promptsInFlight.has(sessionId)) {
+      throw new AppError('PROMPT_IN_PROGRESS', `Session ${sessionId} is running a prompt`, { sessionId })
+    }
+    const session = this.sessions.get(sessionId)
+    const messages = session?.messages ?? threadStore.loadAll().find((t) => t.sessionId === sessionId)?.messages
+    if (!messages) throw new AppError('SESSION_NOT_FOUND', `Session not found: ${sessionId}`, { sessionId })
+
+    const index = messages.findIndex((m) => m.id === messageId)
+    if (index < 0) {
+      throw new AppError('MESSAGE_NOT_FOUND', `Message ${messageId} not found in session ${sessionId}`, {
+        sessionId,
+        messageId
+      })
+    }
+
+    const updated = change(messages, index)
+    if (session) session.messages = updated
+    this.dirtySessions.delete(sessionId)
+    threadStore.replaceMessages(sessionId, updated)
+    if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+      this.mainWindow.webContents.send('session:messages-changed', { sessionId, messages: updated })
+    }
+    return updated
+  }
+
+  /**
+   * Close a live session without deleting its persisted thread.
+   * The agent connection is kept alive for reuse unless `terminateConnection`
+   * is set and no other live session still uses it.
+   */
+  async closeSession(sessionId: string, terminateConnection: boolean): Promise<void> {
+    const session = this.sessions.get(sessionId)
+    if (!session) return
+
+    if (session.status === 'prompting') {
+      await this.cancel(sessionId)
+    }
+
+    // The thread stays, so write out what the session hasn't saved yet
+    if (this.dirtySessions.has(sessionId)) this.saveMessages(sessionId)
+    if (this.unsavedCosts.has(sessionId) && session.totalCost) {
+      threadStore.updateCost(sessionId, session.totalCost, session.reportedCost)
+    }
+    this.releaseSession(sessionId, session.connectionId)
+    this.sessions.delete(sessionId)
+
+    const connectionId = session.connectionId
+    if (!connectionId || !terminateConnection) return
+
+    const stillInUse = Array.from(this.sessions.values()).some((s) => s.connectionId === connectionId)
+    if (stillInUse) {
+      logger.info(`Session ${sessionId} closed; connection ${connectionId} kept for other sessions`)
+      return
+    }
+
+    agentManager.terminate(connectionId)
+    this.monitoredConnections.delete(connectionId)
+    logger.info(`Session ${sessionId} closed and connection ${connectionId} terminated`)
+  }
+
+  /**
+   * Drop everything kept alongside a live session: its terminals, queued
+   * prompts, cached commands and bookkeeping. Shared by close and remove.
+   */
+  private releaseSession(sessionId: string, connectionId?: string): void {
+    terminalService.killBySession(sessionId)
+    if (connectionId) agentManager.getClient(connectionId)?.releaseSession(sessionId)
+    this.clearQueue(sessionId)
+    this.budgetWarned.delete(sessionId)
+    this.unsavedCosts.delete(sessionId)
+    this.dirtySessions.delete(sessionId)
+    this.historyReplayPending.delete(sessionId)
+  }
+
+  /**
+   * Main repository a thread belongs to, for git operations that must not run inside
+   * its worktree. Threads created before sourcePath was recorded fall back to their
+   * workspace path (non-worktree threads: their working dir).
+   */
+  getSourcePath(thread: Pick<SessionInfo, 'sourcePath' | 'workspaceId' | 'workingDir' | 'useWorktree'>): string | undefined {
+    if (thread.sourcePath) return thread.sourcePath
+    if (!thread.useWorktree) return thread.workingDir
+    return workspaceService.get(thread.workspaceId)?.path
+  }
+
+  async removeSession(
+    sessionId: string,
+    cleanupWorktree = false,
+    mergeInto?: { branch: string; strategy?: MergeStrategy }
+  ): Promise<void> {
     const session = this.sessions.get(sessionId)
     // Also check persisted threads if not in memory
     const persisted = !session ? threadStore.loadAll().find((t) => t.sessionId === sessionId) : null
@@ -722,6 +1505,18 @@ export class SessionManagerService {
 
     if (!thread) return
 
+    // Fold the worktree branch back first; a failed merge (e.g. conflicts) aborts the removal
+    if (mergeInto && thread.useWorktree && thread.worktreeBranch) {
+      const projectPath = this.getSourcePath(thread)
+      if (!projectPath) {
+        throw new Error(`No project path known for session ${sessionId}; cannot merge ${thread.worktreeBranch}`)
+      }
+      await gitService.mergeWorktree(projectPath, thread.worktreeBranch, mergeInto.branch, mergeInto.strategy)
+    }
+
+    // Close the session's terminals; their shells hold the worktree as cwd
+    this.releaseSession(sessionId, session?.connectionId)
+
     // Terminate the agent connection first so it releases file handles on the worktree
     const connectionId = session?.connectionId
     if (connectionId) {
@@ -740,24 +1535,27 @@ export class SessionManagerService {
     // Clean up worktree if requested
     if (cleanupWorktree && thread.worktreePath && thread.useWorktree) {
       try {
-        // For worktree removal we need the original project path (workspace path, not the worktree itself)
-        const workspaces = workspaceService.list()
-        const workspace = workspaces.find((w) => w.id === thread.workspaceId)
-        if (workspace) {
-          await gitService.removeWorktree(workspace.path, thread.worktreePath)
+        // For worktree removal we need the original project path, not the worktree itself.
+        // Prefer the path recorded at creation; older threads fall back to their workspace path.
+        const projectPath = this.getSourcePath(thread)
+        if (projectPath) {
+          await gitService.removeWorktree(projectPath, thread.worktreePath)
           logger.info(`Worktree removed: ${thread.worktreePath}`)
+        } else {
+          logger.warn(`No project path known for worktree ${thread.worktreePath}; skipping removal`)
         }
       } catch (error) {
         logger.warn('Failed to clean up worktree:', error)
       }
     }
 
-    // Orphan children: promote child sessions to root level
-    for (const [, s] of this.sessions) {
-      if (s.parentSessionId === sessionId) {
-        s.parentSessionId = undefined
-        threadStore.save(s)
-      }
+    // Orphan children: promote child sessions to root level. Only the parent
+    // link is rewritten; in-memory copies may be older than the stored thread.
+    for (const child of threadStore.loadAll()) {
+      if (child.parentSessionId !== sessionId) continue
+      const live = this.sessions.get(child.sessionId)
+      if (live) live.parentSessionId = undefined
+      threadStore.updateParent(child.sessionId, undefined)
     }
 
     this.sessions.delete(sessionId)
@@ -765,5 +1563,21 @@ export class SessionManagerService {
   }
 }
 
+/** Render the text parts of a conversation as "User: ..." / "Agent: ..." paragraphs */
+function formatTranscript(messages: Message[]): string {
+  return messages
+    .map((m) => {
+      const text = m.content
+        .filter((b): b is { type: 'text'; text: string } => b.type === 'text')
+        .map((b) => b.text)
+        .join('\n')
+        .trim()
+      if (!text) return null
+      return `${m.role === 'user' ? 'User' : 'Agent'}: ${text}`
+    })
+    .filter((line): line is string => !!line)
+    .join('\n\n')
+}
+
 export const sessionManager = new SessionManagerService()
 
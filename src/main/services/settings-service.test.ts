@@ -0,0 +1,83 @@
+import Store from 'electron-store'
+import { afterEach, describe, expect, it } from 'vitest'
+import type { McpServerConfig } from '@shared/types/settings'
+import { settingsService } from './settings-service'
+
+const settings = new Store({ name: 'settings' })
+
+afterEach(() => {
+  settings.clear()
+})
+
+const stdioServer = { name: 'Files', transport: 'stdio' as const, command: 'npx', args: ['-y', 'mcp-files'] }
+const httpServer = { name: 'Docs', transport: 'http' as const, url: 'https://mcp.example.com/docs' }
+
+describe('MCP servers', () => {
+  it('adds servers with a generated id, enabled by default', () => {
+    const files = settingsService.addMcpServer(stdioServer)
+    const docs = settingsService.addMcpServer({ ...httpServer, id: 'docs', enabled: false })
+
+    expect(files).toEqual({ ...stdioServer, id: expect.any(String), enabled: true })
+    expect(files.id).not.toBe('')
+    expect(docs).toEqual({ ...httpServer, id: 'docs', enabled: false })
+    expect(settingsService.getMcpServers()).toEqual([files, docs])
+  })
+
+  it('updates one server by id and leaves the others alone', () => {
+    const files = settingsService.addMcpServer({ ...stdioServer, id: 'files' })
+    const docs = settingsService.addMcpServer({ ...httpServer, id: 'docs' })
+
+    const updated = settingsService.updateMcpServer('files', { args: ['-y', 'mcp-files', '--root', '/tmp'], id: 'renamed' })
+
+    expect(updated).toEqual({ ...files, args: ['-y', 'mcp-files', '--root', '/tmp'] })
+    expect(settingsService.getMcpServers()).toEqual([updated, docs])
+  })
+
+  it('toggles a server on and off', () => {
+    settingsService.addMcpServer({ ...httpServer, id: 'docs' })
+
+    expect(settingsService.toggleMcpServer('docs', false).enabled).toBe(false)
+    expect(settingsService.getMcpServers()[0].enabled).toBe(false)
+    expect(settingsService.toggleMcpServer('docs', true).enabled).toBe(true)
+  })
+
+  it('removes a server and returns it', () => {
+    const files = settingsService.addMcpServer({ ...stdioServer, id: 'files' })
+    const docs = settingsService.addMcpServer({ ...httpServer, id: 'docs' })
+
+    expect(settingsService.removeMcpServer('files')).toEqual(files)
+    expect(settingsService.getMcpServers()).toEqual([docs])
+  })
+
+  it('reports unknown ids', () => {
+    expect(() => settingsService.removeMcpServer('missing')).toThrow('MCP server not found: missing')
+    expect(() => settingsService.updateMcpServer('missing', { name: 'x' })).toThrow('MCP server not found: missing')
+  })
+
+  it('refuses a duplicate id', () => {
+    settingsService.addMcpServer({ ...stdioServer, id: 'files' })
+
+    expect(() => settingsService.addMcpServer({ ...httpServer, id: 'files' })).toThrow('MCP server already exists: files')
+    expect(settingsService.getMcpServers()).toHaveLength(1)
+  })
+
+  it.each<[string, Omit<McpServerConfig, 'id' | 'enabled'>, string]>([
+    ['a stdio server without a command', { name: 'Files', transport: 'stdio' }, 'stdio servers need a command'],
+    ['a stdio server with a blank command', { ...stdioServer, command: '  ' }, 'stdio servers need a command'],
+    ['an http server without a url', { name: 'Docs', transport: 'http' }, 'http servers need a valid url'],
+    ['an http server with a malformed url', { ...httpServer, url: 'not a url' }, 'http servers need a valid url'],
+    ['an sse server with a non-http url', { name: 'Events', transport: 'sse', url: 'ftp://example.com/events' }, 'url must be http or https'],
+    ['a server without a name', { ...stdioServer, name: '' }, 'name is required']
+  ])('rejects %s', (_label, server, message) => {
+    expect(() => settingsService.addMcpServer(server)).toThrow(message)
+    expect(settingsService.getMcpServers()).toEqual([])
+  })
+
+  it('validates updates against the resulting config', () => {
+    const docs = settingsService.addMcpServer({ ...httpServer, id: 'docs' })
+
+    expect(() => settingsService.updateMcpServer('docs', { transport: 'stdio' })).toThrow('stdio servers need a command')
+    expect(() => settingsService.updateMcpServer('docs', { url: 'mailto:someone@example.com' })).toThrow('url must be http or https')
+    expect(settingsService.getMcpServers()).toEqual([docs])
+  })
+})
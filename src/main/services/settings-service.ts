@@ -1,6 +1,33 @@
 import Store from 'electron-store'
+import { v4 as uuid } from 'uuid'
 import type { AppSettings, AgentSettings, McpServerConfig } from '@shared/types/settings'
 import { DEFAULT_SETTINGS } from '@shared/types/settings'
+import { AppError } from '../util/app-error'
+
+const MCP_TRANSPORTS: McpServerConfig['transport'][] = ['stdio', 'http', 'sse']
+
+/** Reject configs missing what their transport needs to start */
+function validateMcpServer(server: McpServerConfig): void {
+  const invalid = (message: string): AppError =>
+    new AppError('INVALID_MCP_SERVER', `Invalid MCP server "${server.name || server.id}": ${message}`, { serverId: server.id })
+
+  if (typeof server.name !== 'string' || !server.name.trim()) throw invalid('name is required')
+  if (!MCP_TRANSPORTS.includes(server.transport)) throw invalid(`unknown transport "${server.transport}"`)
+  if (server.transport === 'stdio') {
+    if (typeof server.command !== 'string' || !server.command.trim()) throw invalid('stdio servers need a command')
+    if (server.args !== undefined && (!Array.isArray(server.args) || server.args.some((a) => typeof a !== 'string'))) {
+      throw invalid('args must be a list of strings')
+    }
+  } else {
+    let protocol: string
+    try {
+      protocol = new URL(server.url ?? '').protocol
+    } catch {
+      throw invalid(`${server.transport} servers need a valid url`)
+    }
+    if (protocol !== 'http:' && protocol !== 'https:') throw invalid('url must be http or https')
+  }
+}
 
 const store = new Store<AppSettings>({
   name: 'settings',
@@ -39,20 +66,44 @@ export class SettingsService {
     return this.get().mcp.servers
   }
 
-  addMcpServer(server: McpServerConfig): void {
+  /** Add a server; an id is generated when missing. Returns the stored config. */
+  addMcpServer(server: Omit<McpServerConfig, 'id' | 'enabled'> & Partial<Pick<McpServerConfig, 'id' | 'enabled'>>): McpServerConfig {
     const current = this.get().mcp
-    store.set('mcp', { ...current, servers: [...current.servers, server] })
+    const added: McpServerConfig = { ...server, id: server.id || uuid(), enabled: server.enabled ?? true }
+    if (current.servers.some((s) => s.id === added.id)) {
+      throw new AppError('INVALID_MCP_SERVER', `MCP server already exists: ${added.id}`, { serverId: added.id })
+    }
+    validateMcpServer(added)
+    store.set('mcp', { ...current, servers: [...current.servers, added] })
+    return added
   }
 
-  removeMcpServer(serverId: string): void {
+  /** Remove a server. Returns the removed config. */
+  removeMcpServer(serverId: string): McpServerConfig {
     const current = this.get().mcp
+    const removed = this.findMcpServer(serverId)
     store.set('mcp', { ...current, servers: current.servers.filter((s) => s.id !== serverId) })
+    return removed
   }
 
-  updateMcpServer(serverId: string, updates: Partial<McpServerConfig>): void {
+  /** Merge `updates` into one server (its id can't change). Returns the updated config. */
+  updateMcpServer(serverId: string, updates: Partial<McpServerConfig>): McpServerConfig {
     const current = this.get().mcp
-    const servers = current.servers.map((s) => (s.id === serverId ? { ...s, ...updates } : s))
+    const updated: McpServerConfig = { ...this.findMcpServer(serverId), ...updates, id: serverId }
+    validateMcpServer(updated)
+    const servers = current.servers.map((s) => (s.id === serverId ? updated : s))
     store.set('mcp', { ...current, servers })
+    return updated
+  }
+
+  toggleMcpServer(serverId: string, enabled: boolean): McpServerConfig {
+    return this.updateMcpServer(serverId, { enabled })
+  }
+
+  private findMcpServer(serverId: string): McpServerConfig {
+    const server = this.getMcpServers().find((s) => s.id === serverId)
+    if (!server) throw new AppError('MCP_SERVER_NOT_FOUND', `MCP server not found: ${serverId}`, { serverId })
+    return server
   }
 }
 
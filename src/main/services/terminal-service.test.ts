@@ -0,0 +1,134 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import type { BrowserWindow } from 'electron'
+import Store from 'electron-store'
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+import { TerminalService } from './terminal-service'
+import { settingsService } from './settings-service'
+import { usePipePty } from '../test/pipe-pty'
+import type { PipePty } from '../test/pipe-pty'
+
+/** Same backing data as settingsService */
+const settings = new Store({ name: 'settings' })
+
+let workDir: string
+let service: TerminalService
+let spawned: PipePty[]
+let sent: Array<{ channel: string; payload: { terminalId: string; [key: string]: unknown } }>
+
+beforeEach(() => {
+  workDir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'terminal-service-')))
+  settingsService.set({ general: { ...settingsService.get().general, terminalShell: '/bin/sh' } })
+  service = new TerminalService()
+  spawned = usePipePty(service)
+  sent = []
+  service.setMainWindow({
+    isDestroyed: () => false,
+    webContents: { send: (channel: string, payload: { terminalId: string }) => sent.push({ channel, payload }) }
+  } as unknown as BrowserWindow)
+})
+
+afterEach(async () => {
+  service.killAll()
+  await Promise.all(spawned.map((pty) => pty.exited))
+  vi.restoreAllMocks()
+  vi.unstubAllEnvs()
+  settings.clear()
+  fs.rmSync(workDir, { recursive: true, force: true })
+})
+
+/** Everything a terminal printed, as forwarded on terminal:data */
+function output(terminalId: string): string {
+  return sent
+    .filter((e) => e.channel === 'terminal:data' && e.payload.terminalId === terminalId)
+    .map((e) => e.payload.data)
+    .join('')
+}
+
+function exits(): unknown[] {
+  return sent.filter((e) => e.channel === 'terminal:exit').map((e) => e.payload)
+}
+
+describe.skipIf(process.platform === 'win32')('terminal exit', () => {
+  it('reports the exit code and forgets the terminal', async () => {
+    const terminalId = service.create(workDir, 'session-1')
+    expect(service.listForSession('session-1')).toEqual([terminalId])
+
+    service.write(terminalId, 'exit 3\n')
+    await spawned[0].exited
+
+    expect(exits()).toEqual([{ terminalId, exitCode: 3 }])
+    expect(service.list()).toEqual([])
+    expect(service.getScrollback(terminalId)).toBe('')
+  })
+
+  it('sends no exit event for a terminal it killed itself', async () => {
+    const terminalId = service.create(workDir, 'session-1')
+
+    service.kill(terminalId)
+    await spawned[0].exited
+
+    expect(exits()).toEqual([])
+    expect(service.list()).toEqual([])
+  })
+})
+
+describe.skipIf(process.platform === 'win32')('terminal environment', () => {
+  it('adds the given env on top of the inherited process env, the given env winning', async () => {
+    vi.stubEnv('INHERITED_VAR', 'from-process')
+    vi.stubEnv('SHARED_VAR', 'from-process')
+    const terminalId = service.create(workDir, 'session-1', { CUSTOM_VAR: 'custom value', SHARED_VAR: 'explicit' })
+
+    service.write(terminalId, 'echo "[$CUSTOM_VAR|$SHARED_VAR|$INHERITED_VAR]"; pwd; exit\n')
+    await spawned[0].exited
+
+    expect(output(terminalId)).toBe(`[custom value|explicit|from-process]\n${workDir}\n`)
+  })
+})
+
+describe.skipIf(process.platform === 'win32')('terminals of a session', () => {
+  it('lists and kills every terminal of one session together', async () => {
+    const first = service.create(workDir, 'session-a')
+    const second = service.create(workDir, 'session-a')
+    const other = service.create(workDir, 'session-b')
+
+    expect(service.listForSession('session-a')).toEqual([first, second])
+    expect(service.list()).toEqual([
+      { terminalId: first, sessionId: 'session-a', cwd: workDir },
+      { terminalId: second, sessionId: 'session-a', cwd: workDir },
+      { terminalId: other, sessionId: 'session-b', cwd: workDir }
+    ])
+
+    service.killBySession('session-a')
+    await Promise.all([spawned[0].exited, spawned[1].exited])
+
+    expect(service.list()).toEqual([{ terminalId: other, sessionId: 'session-b', cwd: workDir }])
+    // The other session's shell is still running
+    service.write(other, 'echo alive; exit\n')
+    await spawned[2].exited
+    expect(output(other)).toBe('alive\n')
+  })
+})
+
+describe.skipIf(process.platform === 'win32')('terminal size', () => {
+  it('opens the pty at the requested size', () => {
+    service.create(workDir, 'session-1', {}, { cols: 132, rows: 43 })
+
+    expect(spawned[0].options).toMatchObject({ cols: 132, rows: 43, name: 'xterm-256color', cwd: workDir })
+  })
+
+  it('falls back to 80x24', () => {
+    service.create(workDir, 'session-1')
+
+    expect(spawned[0].options).toMatchObject({ cols: 80, rows: 24 })
+  })
+
+  it('resizes a live terminal', () => {
+    const terminalId = service.create(workDir, 'session-1')
+
+    service.resize(terminalId, 100, 30)
+
+    expect(spawned[0]).toMatchObject({ cols: 100, rows: 30 })
+  })
+})
@@ -2,9 +2,12 @@ import { BrowserWindow } from 'electron'
 import { v4 as uuid } from 'uuid'
 import { logger } from '../util/logger'
 import { settingsService } from './settings-service'
+import { ScrollbackBuffer } from '../util/scrollback-buffer'
+import { TERMINAL_SCROLLBACK_LINES } from '@shared/constants'
+import type { TerminalInfo } from '@shared/types/session'
 
 // node-pty types - imported dynamically since it's a native module
-type IPty = {
+export type IPty = {
   pid: number
   cols: number
   rows: number
@@ -15,11 +18,21 @@ type IPty = {
   kill: (signal?: string) => void
 }
 
+export interface PtySpawnOptions {
+  name: string
+  cols: number
+  rows: number
+  cwd: string
+  env: Record<string, string>
+}
+
 interface TerminalInstance {
   id: string
   pty: IPty
   sessionId: string
+  cwd: string
   disposables: Array<{ dispose: () => void }>
+  scrollback: ScrollbackBuffer
 }
 
 export class TerminalService {
@@ -42,32 +55,38 @@ export class TerminalService {
   }
 
   /**
-   * Create a new terminal instance
+   * Create a new terminal instance. `env` is layered over the inherited
+   * process env, so its entries take precedence. Opens at 80x24 unless a
+   * size is given.
    */
-  create(cwd: string, sessionId: string): string {
+  create(
+    cwd: string,
+    sessionId: string,
+    env: Record<string, string> = {},
+    size: { cols: number; rows: number } = { cols: 80, rows: 24 }
+  ): string {
     const terminalId = uuid()
 
     try {
-      // Dynamic import of node-pty
-      const pty = require('node-pty')
-
       const settings = settingsService.get()
       const configuredShell = settings.general.terminalShell
       const shell = configuredShell || this.getDefaultShell()
 
-      const ptyProcess: IPty = pty.spawn(shell, [], {
+      const ptyProcess = this.spawnPty(shell, {
         name: 'xterm-256color',
-        cols: 80,
-        rows: 24,
+        cols: size.cols,
+        rows: size.rows,
         cwd,
-        env: process.env as Record<string, string>
+        env: { ...(process.env as Record<string, string>), ...env }
       })
 
       const disposables: Array<{ dispose: () => void }> = []
+      const scrollback = new ScrollbackBuffer(this.getScrollbackLines())
 
-      // Forward terminal output to renderer
+      // Record and forward terminal output to renderer
       disposables.push(
         ptyProcess.onData((data: string) => {
+          scrollback.append(data)
           if (this.mainWindow && !this.mainWindow.isDestroyed()) {
             this.mainWindow.webContents.send('terminal:data', {
               terminalId,
@@ -77,11 +96,23 @@ export class TerminalService {
         })
       )
 
-      // Handle exit
+      // Report the exit and drop the instance
       disposables.push(
-        ptyProcess.onExit(({ exitCode }) => {
+        ptyProcess.onExit(({ exitCode, signal }) => {
           logger.info(`Terminal ${terminalId} exited with code ${exitCode}`)
+          const terminal = this.terminals.get(terminalId)
+          if (!terminal) return // killed by us; already cleaned up
+          for (const d of terminal.disposables) {
+            d.dispose()
+          }
           this.terminals.delete(terminalId)
+          if (this.mainWindow && !this.mainWindow.isDestroyed()) {
+            this.mainWindow.webContents.send('terminal:exit', {
+              terminalId,
+              exitCode,
+              ...(signal ? { signal } : {})
+            })
+          }
         })
       )
 
@@ -89,7 +120,9 @@ export class TerminalService {
         id: terminalId,
         pty: ptyProcess,
         sessionId,
-        disposables
+        cwd,
+        disposables,
+        scrollback
       })
 
       logger.info(`Terminal created: ${terminalId} (pid: ${ptyProcess.pid})`)
@@ -100,6 +133,15 @@ export class TerminalService {
     }
   }
 
+  /**
+   * Start the shell in a pseudo-terminal
+   */
+  private spawnPty(shell: string, options: PtySpawnOptions): IPty {
+    // Dynamic import of node-pty
+    const pty = require('node-pty')
+    return pty.spawn(shell, [], options)
+  }
+
   /**
    * Write data to terminal
    */
@@ -110,6 +152,13 @@ export class TerminalService {
     }
   }
 
+  /**
+   * Buffered output of a terminal, oldest first, so the UI can repaint after a reload
+   */
+  getScrollback(terminalId: string): string {
+    return this.terminals.get(terminalId)?.scrollback.toString() ?? ''
+  }
+
   /**
    * Resize terminal
    */
@@ -135,15 +184,37 @@ export class TerminalService {
     }
   }
 
+  /**
+   * List live terminals, optionally only those of one session
+   */
+  list(sessionId?: string): TerminalInfo[] {
+    return Array.from(this.terminals.values())
+      .filter((t) => !sessionId || t.sessionId === sessionId)
+      .map((t) => ({ terminalId: t.id, sessionId: t.sessionId, cwd: t.cwd }))
+  }
+
+  /**
+   * Ids of the live terminals belonging to a session
+   */
+  listForSession(sessionId: string): string[] {
+    return this.list(sessionId).map((t) => t.terminalId)
+  }
+
   /**
    * Kill all terminals for a session
    */
   killBySession(sessionId: string): void {
-    for (const [id, terminal] of this.terminals) {
-      if (terminal.sessionId === sessionId) {
-        this.kill(id)
-      }
+    for (const id of this.listForSession(sessionId)) {
+      this.kill(id)
+    }
+  }
+
+  private getScrollbackLines(): number {
+    const lines = settingsService.get().general.terminalScrollbackLines
+    if (typeof lines === 'number' && Number.isFinite(lines) && lines >= 1) {
+      return Math.floor(lines)
     }
+    return TERMINAL_SCROLLBACK_LINES
   }
 
   /**
@@ -0,0 +1,86 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import Store from 'electron-store'
+import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import type { PersistedThread, SessionInfo } from '@shared/types/session'
+import { folderThreadStore } from './folder-thread-store'
+import { threadStore } from './thread-store'
+
+/** Same backing data as the thread store's own cache */
+const cache = new Store<{ threads: PersistedThread[] }>({ name: 'threads' })
+
+let workspaces: Array<{ path: string; id: string }>
+
+beforeEach(() => {
+  cache.clear()
+  cache.set('threads', [])
+  workspaces = ['ws-a', 'ws-b'].map((id) => ({
+    id,
+    path: fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), `thread-store-${id}-`)))
+  }))
+  threadStore.setWorkspaceResolver((id) => workspaces.find((w) => w.id === id))
+})
+
+afterEach(() => {
+  for (const ws of workspaces) fs.rmSync(ws.path, { recursive: true, force: true })
+})
+
+function writeThread(workspace: { path: string; id: string }, sessionId: string): void {
+  const session: SessionInfo = {
+    sessionId,
+    connectionId: '',
+    agentId: 'mock-agent',
+    agentName: 'Mock Agent',
+    title: sessionId,
+    createdAt: new Date(0).toISOString(),
+    workingDir: workspace.path,
+    status: 'idle',
+    messages: [{ id: `${sessionId}-1`, role: 'user', content: [{ type: 'text', text: 'hello' }], timestamp: new Date(0).toISOString() }],
+    useWorktree: false,
+    workspaceId: workspace.id
+  }
+  folderThreadStore.saveThread(workspace.path, session)
+}
+
+function cachedIds(): string[] {
+  return threadStore.loadAll().map((t) => t.sessionId).sort()
+}
+
+describe('rebuilding the cache', () => {
+  it('rescans a single workspace without touching the others', () => {
+    writeThread(workspaces[0], 'thread-a')
+    writeThread(workspaces[1], 'thread-b')
+
+    threadStore.syncWorkspaceToCache(workspaces[0].path, workspaces[0].id)
+
+    expect(cachedIds()).toEqual(['thread-a'])
+  })
+
+  it('keeps cached threads of unchanged workspaces on an incremental rebuild', async () => {
+    writeThread(workspaces[0], 'thread-a')
+    writeThread(workspaces[1], 'thread-b')
+    await threadStore.rebuildCacheFromFolders(workspaces)
+    expect(cachedIds()).toEqual(['thread-a', 'thread-b'])
+
+    // Mark the cached copies so a rescan shows up as a title reset
+    cache.set('threads', threadStore.loadAll().map((t) => ({ ...t, title: 'cached' })))
+    // Edit workspace B's messages in place; only the file's own mtime changes
+    const messagesPath = path.join(folderThreadStore.getThreadDir(workspaces[1].path, 'thread-b'), 'messages.jsonl')
+    fs.appendFileSync(
+      messagesPath,
+      JSON.stringify({ id: 'thread-b-2', role: 'agent', timestamp: new Date(1000).toISOString(), content: [] }) + '\n'
+    )
+    const later = new Date(Date.now() + 60_000)
+    fs.utimesSync(messagesPath, later, later)
+
+    await threadStore.rebuildCacheFromFolders(workspaces, undefined, { incremental: true })
+
+    const threads = threadStore.loadAll()
+    const a = threads.find((t) => t.sessionId === 'thread-a')
+    const b = threads.find((t) => t.sessionId === 'thread-b')
+    expect(a?.title).toBe('cached')
+    expect(b?.title).toBe('thread-b')
+    expect(b?.messages.map((m) => m.id)).toEqual(['thread-b-1', 'thread-b-2'])
+  })
+})
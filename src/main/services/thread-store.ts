@@ -1,14 +1,17 @@
 import fs from 'fs'
 import path from 'path'
 import Store from 'electron-store'
-import type { PersistedThread, SessionInfo, Message } from '@shared/types/session'
-import { AGENT_DIR_NAME, THREADS_DIR_NAME } from '@shared/types/thread-format'
+import type { PersistedThread, SessionInfo, Message, ThreadCacheProgressEvent, UsageCost, ReportedCost } from '@shared/types/session'
+import { AGENT_DIR_NAME, MESSAGES_FILE, THREADS_DIR_NAME, THREAD_MANIFEST_FILE } from '@shared/types/thread-format'
+import { isSamePath, normalizePath } from '@shared/util/path-util'
 import { folderThreadStore } from './folder-thread-store'
 import { logger } from '../util/logger'
 
 interface ThreadStoreSchema {
   threads: PersistedThread[]
   migrationV1Complete?: boolean
+  /** .agent/ mtime per workspace path as of its last scan, for incremental rebuilds */
+  workspaceScanMtimes?: Record<string, number>
 }
 
 const store = new Store<ThreadStoreSchema>({
@@ -63,8 +66,44 @@ export class ThreadStore {
     this.cleanupLegacyWorktreeThread(session)
   }
 
-  /** Update only messages — writes to BOTH folder and cache. */
-  updateMessages(sessionId: string, messages: Message[]): void {
+  /**
+   * Update only messages — writes to BOTH folder and cache. `compact` rewrites
+   * messages.jsonl in full. Returns the merged list when external edits were
+   * merged in, so the caller can adopt it; null otherwise.
+   */
+  updateMessages(sessionId: string, messages: Message[], compact = false): Message[] | null {
+    const all = this.loadAll()
+    const idx = all.findIndex((t) => t.sessionId === sessionId)
+    if (idx < 0) return null
+
+    const strippedMessages = messages.map((m) => {
+      const rest = { ...m }
+      delete rest.isStreaming
+      return rest
+    })
+
+    // Write to .agent/ folder (primary). The folder store may merge in
+    // messages that were edited externally, so cache what it wrote.
+    let written: Message[] = strippedMessages
+    let merged = false
+    this.writeToFolder(all[idx], (storagePath) => {
+      const result = folderThreadStore.updateMessages(storagePath, sessionId, strippedMessages, compact)
+      written = result.messages
+      merged = result.merged
+    })
+
+    // Write to electron-store cache (secondary)
+    all[idx].messages = written
+    store.set('threads', all)
+    return merged ? written : null
+  }
+
+  /**
+   * Replace the thread's messages outright — writes to BOTH folder and cache.
+   * Unlike updateMessages() nothing on disk is merged back in, so removed
+   * messages stay removed.
+   */
+  replaceMessages(sessionId: string, messages: Message[]): void {
     const all = this.loadAll()
     const idx = all.findIndex((t) => t.sessionId === sessionId)
     if (idx < 0) return
@@ -77,7 +116,7 @@ export class ThreadStore {
 
     // Write to .agent/ folder (primary)
     this.writeToFolder(all[idx], (storagePath) => {
-      folderThreadStore.updateMessages(storagePath, sessionId, strippedMessages)
+      folderThreadStore.replaceMessages(storagePath, sessionId, strippedMessages)
     })
 
     // Write to electron-store cache (secondary)
@@ -85,6 +124,18 @@ export class ThreadStore {
     store.set('threads', all)
   }
 
+  /**
+   * Append one message to the thread's messages.jsonl (folder only).
+   * The cache catches up on the next updateMessages() call.
+   */
+  appendMessage(sessionId: string, message: Message): void {
+    const thread = this.loadAll().find((t) => t.sessionId === sessionId)
+    if (!thread) return
+    this.writeToFolder(thread, (storagePath) => {
+      folderThreadStore.appendMessage(storagePath, sessionId, message)
+    })
+  }
+
   /** Rename a thread — updates title in BOTH folder and cache. */
   rename(sessionId: string, title: string): void {
     const all = this.loadAll()
@@ -103,6 +154,58 @@ export class ThreadStore {
     logger.info(`Thread renamed: ${sessionId} → ${title}`)
   }
 
+  /** Replace a thread's integrator metadata — updates BOTH folder and cache. */
+  updateMetadata(sessionId: string, metadata: Record<string, unknown>): void {
+    const all = this.loadAll()
+    const idx = all.findIndex((t) => t.sessionId === sessionId)
+    if (idx < 0) return
+
+    all[idx].metadata = Object.keys(metadata).length > 0 ? metadata : undefined
+
+    // Update .agent/ folder manifest (primary)
+    this.writeToFolder(all[idx], (storagePath) => {
+      folderThreadStore.updateManifestMetadata(storagePath, sessionId, all[idx].metadata)
+    })
+
+    // Update electron-store cache (secondary)
+    store.set('threads', all)
+  }
+
+  /** Replace a thread's accumulated and last reported cost — updates BOTH folder and cache. */
+  updateCost(sessionId: string, totalCost: UsageCost, reportedCost: ReportedCost | undefined): void {
+    const all = this.loadAll()
+    const idx = all.findIndex((t) => t.sessionId === sessionId)
+    if (idx < 0) return
+
+    all[idx].totalCost = totalCost
+    all[idx].reportedCost = reportedCost
+
+    // Update .agent/ folder manifest (primary)
+    this.writeToFolder(all[idx], (storagePath) => {
+      folderThreadStore.updateManifestCost(storagePath, sessionId, totalCost, reportedCost)
+    })
+
+    // Update electron-store cache (secondary)
+    store.set('threads', all)
+  }
+
+  /** Replace a thread's standing instructions — updates BOTH folder and cache. */
+  updateSystemPrompt(sessionId: string, systemPrompt: string | undefined): void {
+    const all = this.loadAll()
+    const idx = all.findIndex((t) => t.sessionId === sessionId)
+    if (idx < 0) return
+
+    all[idx].systemPrompt = systemPrompt
+
+    // Update .agent/ folder manifest (primary)
+    this.writeToFolder(all[idx], (storagePath) => {
+      folderThreadStore.updateManifestSystemPrompt(storagePath, sessionId, systemPrompt)
+    })
+
+    // Update electron-store cache (secondary)
+    store.set('threads', all)
+  }
+
   /** Update a thread's worktree branch name — updates BOTH folder and cache. */
   updateWorktreeBranch(sessionId: string, newBranch: string): void {
     const all = this.loadAll()
@@ -121,6 +224,40 @@ export class ThreadStore {
     logger.info(`Thread branch renamed: ${sessionId} → ${newBranch}`)
   }
 
+  /** Re-point (or clear) a thread's parent — updates BOTH folder and cache. */
+  updateParent(sessionId: string, parentSessionId: string | undefined): void {
+    const all = this.loadAll()
+    const idx = all.findIndex((t) => t.sessionId === sessionId)
+    if (idx < 0) return
+
+    all[idx].parentSessionId = parentSessionId
+
+    // Update .agent/ folder manifest (primary)
+    this.writeToFolder(all[idx], (storagePath) => {
+      folderThreadStore.updateManifestParent(storagePath, sessionId, parentSessionId)
+    })
+
+    // Update electron-store cache (secondary)
+    store.set('threads', all)
+  }
+
+  /** Record the agent-side session id of a thread — updates BOTH folder and cache. */
+  updateAcpSessionId(sessionId: string, acpSessionId: string): void {
+    const all = this.loadAll()
+    const idx = all.findIndex((t) => t.sessionId === sessionId)
+    if (idx < 0) return
+
+    all[idx].acpSessionId = acpSessionId
+
+    // Update .agent/ folder manifest (primary)
+    this.writeToFolder(all[idx], (storagePath) => {
+      folderThreadStore.updateManifestAcpSessionId(storagePath, sessionId, acpSessionId)
+    })
+
+    // Update electron-store cache (secondary)
+    store.set('threads', all)
+  }
+
   /** Update a thread interaction mode — updates BOTH folder and cache. */
   updateInteractionMode(
     sessionId: string,
@@ -132,14 +269,9 @@ export class ThreadStore {
 
     all[idx].interactionMode = interactionMode
 
-    // Rewrite thread manifest to persist metadata (primary)
+    // Update .agent/ folder manifest (primary)
     this.writeToFolder(all[idx], (storagePath) => {
-      const sessionLike: SessionInfo = {
-        ...all[idx],
-        connectionId: '',
-        status: 'idle'
-      }
-      folderThreadStore.saveThread(storagePath, sessionLike)
+      folderThreadStore.updateManifestMetadata(storagePath, sessionId, all[idx].metadata)
     })
 
     // Update electron-store cache (secondary)
@@ -168,21 +300,50 @@ export class ThreadStore {
     logger.info(`Thread removed from store: ${sessionId}`)
   }
 
-  /** Rebuild the electron-store cache from all .agent/ folders across workspaces and worktrees. */
-  rebuildCacheFromFolders(
-    workspaces: Array<{ path: string; id: string }>
-  ): void {
+  /**
+   * Rebuild the electron-store cache from all .agent/ folders across workspaces and worktrees.
+   * Yields to the event loop between workspaces and reports progress after each one.
+   */
+  async rebuildCacheFromFolders(
+    workspaces: Array<{ path: string; id: string }>,
+    onProgress?: (event: ThreadCacheProgressEvent) => void,
+    options?: { incremental?: boolean }
+  ): Promise<number> {
+    const threads: PersistedThread[] = []
+    const total = workspaces.length
+    const existingCache = store.get('threads', [])
+    const scanMtimes = store.get('workspaceScanMtimes', {}) as Record<string, number>
+    let skipped = 0
+
     // Phase 1: Scan all workspace paths (catches non-worktree threads)
-    const threads = folderThreadStore.scanAllWorkspaces(workspaces)
-    const scannedPaths = new Set(workspaces.map((w) => w.path))
+    for (let i = 0; i < workspaces.length; i++) {
+      const ws = workspaces[i]
+      const mtime = this.getAgentDirMtime(ws.path)
+      if (options?.incremental && scanMtimes[normalizePath(ws.path)] === mtime) {
+        // Unchanged since the last scan: keep the cached threads stored in this workspace
+        threads.push(
+          ...existingCache.filter((t) => t.workspaceId === ws.id && !(t.useWorktree && t.worktreePath))
+        )
+        skipped++
+      } else {
+        try {
+          threads.push(...folderThreadStore.scanWorkspace(ws.path, ws.id))
+          scanMtimes[normalizePath(ws.path)] = mtime
+        } catch (err) {
+          logger.warn(`Failed to scan workspace path ${ws.path}:`, err)
+        }
+      }
+      onProgress?.({ phase: 'scanning', workspaceId: ws.id, completed: i + 1, total, threadCount: threads.length })
+      await new Promise((resolve) => setImmediate(resolve))
+    }
+    const scannedPaths = new Set(workspaces.map((w) => normalizePath(w.path)))
 
     // Phase 2: Collect known worktree paths from the existing cache
-    const existingCache = store.get('threads', [])
     const worktreeEntries: Array<{ path: string; workspaceId: string }> = []
 
     for (const t of existingCache) {
-      if (t.useWorktree && t.worktreePath && !scannedPaths.has(t.worktreePath)) {
-        scannedPaths.add(t.worktreePath) // deduplicate
+      if (t.useWorktree && t.worktreePath && !scannedPaths.has(normalizePath(t.worktreePath))) {
+        scannedPaths.add(normalizePath(t.worktreePath)) // deduplicate
         worktreeEntries.push({ path: t.worktreePath, workspaceId: t.workspaceId })
       }
     }
@@ -199,9 +360,13 @@ export class ThreadStore {
     }
 
     store.set('threads', threads)
+    store.set('workspaceScanMtimes', scanMtimes)
     logger.info(
-      `Cache rebuilt: ${threads.length} threads from ${workspaces.length} workspaces + ${worktreeEntries.length} worktrees`
+      `Cache rebuilt: ${threads.length} threads from ${workspaces.length} workspaces ` +
+        `(${skipped} unchanged) + ${worktreeEntries.length} worktrees`
     )
+    onProgress?.({ phase: 'done', completed: total, total, threadCount: threads.length })
+    return threads.length
   }
 
   /** Sync a single workspace's .agent/ threads into the cache, including its worktree threads. */
@@ -211,16 +376,16 @@ export class ThreadStore {
 
     // Also scan worktree paths that belong to this workspace
     const existingCache = this.loadAll()
-    const scannedPaths = new Set([workspacePath])
+    const scannedPaths = new Set([normalizePath(workspacePath)])
 
     for (const t of existingCache) {
       if (
         t.workspaceId === workspaceId &&
         t.useWorktree &&
         t.worktreePath &&
-        !scannedPaths.has(t.worktreePath)
+        !scannedPaths.has(normalizePath(t.worktreePath))
       ) {
-        scannedPaths.add(t.worktreePath)
+        scannedPaths.add(normalizePath(t.worktreePath))
         try {
           if (!fs.existsSync(t.worktreePath)) continue
           const wtThreads = folderThreadStore.scanWorkspace(t.worktreePath, workspaceId)
@@ -243,6 +408,10 @@ export class ThreadStore {
     }
 
     store.set('threads', all)
+    store.set('workspaceScanMtimes', {
+      ...(store.get('workspaceScanMtimes', {}) as Record<string, number>),
+      [normalizePath(workspacePath)]: this.getAgentDirMtime(workspacePath)
+    })
     if (folderThreads.length > 0) {
       logger.info(
         `Synced ${folderThreads.length} threads from workspace + worktrees: ${workspacePath}`
@@ -286,6 +455,28 @@ export class ThreadStore {
     return workspace?.path ?? null
   }
 
+  /**
+   * Latest mtime of a workspace's .agent/ and .agent/threads/ dirs and of each
+   * thread dir and its files (0 if missing). Editing a file in place only bumps
+   * that file's mtime, so the thread files have to be checked one by one.
+   */
+  private getAgentDirMtime(workspacePath: string): number {
+    const paths = [folderThreadStore.getAgentDir(workspacePath), folderThreadStore.getThreadsDir(workspacePath)]
+    for (const threadId of folderThreadStore.listThreadIds(workspacePath)) {
+      const threadDir = folderThreadStore.getThreadDir(workspacePath, threadId)
+      paths.push(threadDir, path.join(threadDir, THREAD_MANIFEST_FILE), path.join(threadDir, MESSAGES_FILE))
+    }
+    let mtime = 0
+    for (const p of paths) {
+      try {
+        mtime = Math.max(mtime, fs.statSync(p).mtimeMs)
+      } catch {
+        // Missing entries count as 0
+      }
+    }
+    return mtime
+  }
+
   /** Write to the correct .agent/ folder for a thread. */
   private writeToFolder(
     thread: ThreadPathInfo,
@@ -315,7 +506,7 @@ export class ThreadStore {
       if (!workspacePath) return
 
       // Only clean up if the old location differs from the new one
-      if (workspacePath === thread.worktreePath) return
+      if (isSamePath(workspacePath, thread.worktreePath)) return
 
       const oldThreadDir = path.join(
         workspacePath,
@@ -344,6 +535,7 @@ function toPersistedThread(session: SessionInfo): PersistedThread {
     createdAt: session.createdAt,
     worktreePath: session.worktreePath,
     worktreeBranch: session.worktreeBranch,
+    sourcePath: session.sourcePath,
     workingDir: session.workingDir,
     messages: session.messages.map((m) => {
       const rest = { ...m }
@@ -353,7 +545,12 @@ function toPersistedThread(session: SessionInfo): PersistedThread {
     interactionMode: session.interactionMode,
     useWorktree: session.useWorktree,
     workspaceId: session.workspaceId,
-    parentSessionId: session.parentSessionId
+    parentSessionId: session.parentSessionId,
+    acpSessionId: session.acpSessionId,
+    metadata: session.metadata,
+    totalCost: session.totalCost,
+    reportedCost: session.reportedCost,
+    systemPrompt: session.systemPrompt
   }
 }
 
@@ -1,6 +1,7 @@
 import Store from 'electron-store'
 import { v4 as uuid } from 'uuid'
 import type { WorkspaceInfo } from '@shared/types/workspace'
+import { isSamePath } from '@shared/util/path-util'
 import { gitService } from './git-service'
 import { threadStore } from './thread-store'
 
@@ -24,7 +25,7 @@ export class WorkspaceService {
 
   async create(path: string, name?: string): Promise<WorkspaceInfo> {
     const all = this.list()
-    const existing = all.find((w) => w.path === path)
+    const existing = all.find((w) => isSamePath(w.path, path))
     if (existing) {
       return existing
     }
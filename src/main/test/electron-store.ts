@@ -0,0 +1,48 @@
+// In-memory electron-store for Vitest (aliased in vitest.config.ts). Stores with
+// the same name share data like the real JSON files do, and values are copied in
+// and out so callers can't mutate what is stored.
+
+const files = new Map<string, Record<string, unknown>>()
+
+function clone<V>(value: V): V {
+  return value === undefined ? value : JSON.parse(JSON.stringify(value))
+}
+
+export default class Store<T extends Record<string, any> = Record<string, unknown>> {
+  private data: Record<string, unknown>
+
+  constructor(options: { name?: string; defaults?: Partial<T> } = {}) {
+    const name = options.name ?? 'config'
+    const data = files.get(name) ?? {}
+    for (const [key, value] of Object.entries(options.defaults ?? {})) {
+      if (!(key in data)) data[key] = clone(value)
+    }
+    files.set(name, data)
+    this.data = data
+  }
+
+  get store(): T {
+    return clone(this.data) as T
+  }
+
+  get(key: string, defaultValue?: unknown): any {
+    return key in this.data ? clone(this.data[key]) : defaultValue
+  }
+
+  set(key: string | Record<string, unknown>, value?: unknown): void {
+    const entries = typeof key === 'string' ? { [key]: value } : key
+    for (const [k, v] of Object.entries(entries)) this.data[k] = clone(v)
+  }
+
+  has(key: string): boolean {
+    return key in this.data
+  }
+
+  delete(key: string): void {
+    delete this.data[key]
+  }
+
+  clear(): void {
+    for (const key of Object.keys(this.data)) delete this.data[key]
+  }
+}
@@ -0,0 +1,38 @@
+// Stand-in for the electron module under Vitest (aliased in vitest.config.ts).
+// Services only need the app paths from it; userData is a fresh temp directory
+// per test file so nothing touches a real profile.
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+
+const userData = fs.mkdtempSync(path.join(os.tmpdir(), 'agent-manager-test-'))
+
+export const app = {
+  isPackaged: false,
+  getPath(): string {
+    return userData
+  },
+  getVersion(): string {
+    return '0.0.0'
+  }
+}
+
+export class BrowserWindow {
+  static getAllWindows(): BrowserWindow[] {
+    return []
+  }
+
+  static fromWebContents(): BrowserWindow | null {
+    return null
+  }
+}
+
+export const ipcMain = {
+  handle(): void {},
+  on(): void {}
+}
+
+export const dialog = {}
+export const shell = {}
+export const Menu = {}
+export const screen = {}
@@ -0,0 +1,61 @@
+// Throwaway git repositories for service tests, set up through the git CLI.
+import { execFileSync } from 'child_process'
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+
+const tempDirs: string[] = []
+
+/** Run git in `cwd` and return its trimmed stdout */
+export function git(cwd: string, ...args: string[]): string {
+  return execFileSync('git', args, { cwd, encoding: 'utf-8', stdio: ['ignore', 'pipe', 'pipe'] }).trim()
+}
+
+/** A fresh temp directory (symlinks resolved), removed by removeTempDirs() */
+export function tempDir(prefix: string): string {
+  const dir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), prefix)))
+  tempDirs.push(dir)
+  return dir
+}
+
+/** A repository on `main` with one commit adding README.md */
+export function initRepo(dir = tempDir('git-repo-')): string {
+  git(dir, 'init', '-q', '-b', 'main')
+  configureUser(dir)
+  commitFile(dir, 'README.md', 'readme\n', 'Initial commit')
+  return dir
+}
+
+/** A clone of `remote`, ready to commit */
+export function cloneRepo(remote: string): string {
+  const dir = tempDir('git-clone-')
+  git(dir, 'clone', '-q', remote, '.')
+  configureUser(dir)
+  return dir
+}
+
+/** A bare repository to use as a remote */
+export function initBareRemote(): string {
+  const dir = tempDir('git-remote-')
+  git(dir, 'init', '-q', '--bare', '-b', 'main')
+  return dir
+}
+
+/** Write `file` and commit it; returns the new commit's hash */
+export function commitFile(repo: string, file: string, content: string, message: string): string {
+  fs.mkdirSync(path.dirname(path.join(repo, file)), { recursive: true })
+  fs.writeFileSync(path.join(repo, file), content)
+  git(repo, 'add', '--', file)
+  git(repo, 'commit', '-q', '-m', message)
+  return git(repo, 'rev-parse', 'HEAD')
+}
+
+function configureUser(repo: string): void {
+  git(repo, 'config', 'user.name', 'Test')
+  git(repo, 'config', 'user.email', 'test@example.com')
+  git(repo, 'config', 'commit.gpgsign', 'false')
+}
+
+export function removeTempDirs(): void {
+  for (const dir of tempDirs.splice(0)) fs.rmSync(dir, { recursive: true, force: true })
+}
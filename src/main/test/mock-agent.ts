@@ -0,0 +1,37 @@
+// Connects services under test to scripts/mock-acp-agent.mjs, a stub ACP agent
+// run as a real child process.
+import { fileURLToPath } from 'url'
+import type { PermissionRequestEvent } from '@shared/types/session'
+import { AcpClient } from '../services/acp-client'
+import { agentManager } from '../services/agent-manager'
+
+export const MOCK_AGENT = fileURLToPath(new URL('../../../scripts/mock-acp-agent.mjs', import.meta.url))
+
+/**
+ * Launch the mock agent and register it with agentManager as a live connection.
+ * Permission requests are answered with `permissionOptionId` right away.
+ */
+export async function connectMockAgent(
+  env: Record<string, string> = {},
+  permissionOptionId = 'allow'
+): Promise<AcpClient> {
+  const client = new AcpClient('mock-agent', process.execPath, [MOCK_AGENT], { MOCK_AGENT_DELAY_MS: '1', ...env }, process.cwd())
+  client.on('permission-request', (event: PermissionRequestEvent) => {
+    client.resolvePermission({ requestId: event.requestId, optionId: permissionOptionId })
+  })
+  await client.start()
+  await client.initialize(5000)
+  ;(agentManager as unknown as { connections: Map<string, AcpClient> }).connections.set(client.connectionId, client)
+  return client
+}
+
+/** Terminate every connection registered by connectMockAgent */
+export async function disconnectMockAgents(): Promise<void> {
+  await Promise.all(
+    agentManager.listConnections().map(async ({ connectionId }) => {
+      const client = agentManager.getClient(connectionId)
+      agentManager.terminate(connectionId)
+      await client?.terminate(500)
+    })
+  )
+}
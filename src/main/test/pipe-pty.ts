@@ -0,0 +1,72 @@
+// Stands in for node-pty in terminal tests: the shell runs on plain pipes, which
+// is enough for scripted input, output, exit codes and kills.
+import { spawn } from 'child_process'
+import os from 'os'
+import { vi } from 'vitest'
+import type { IPty, PtySpawnOptions, TerminalService } from '../services/terminal-service'
+
+export type PipePty = IPty & {
+  /** Options the terminal was spawned with */
+  options: PtySpawnOptions
+  /** Resolves once the shell process has exited */
+  exited: Promise<void>
+}
+
+function spawnPipePty(shell: string, options: PtySpawnOptions): PipePty {
+  const child = spawn(shell, [], { cwd: options.cwd, env: options.env, stdio: 'pipe' })
+  const dataListeners = new Set<(data: string) => void>()
+  const exitListeners = new Set<(e: { exitCode: number; signal?: number }) => void>()
+  const subscribe = <T>(listeners: Set<T>, listener: T): { dispose: () => void } => {
+    listeners.add(listener)
+    return { dispose: () => listeners.delete(listener) }
+  }
+
+  const emitData = (chunk: Buffer): void => {
+    for (const listener of dataListeners) listener(chunk.toString('utf-8'))
+  }
+  child.stdout.on('data', emitData)
+  child.stderr.on('data', emitData)
+  // Writes racing the shell's exit fail with EPIPE, as they would on a closed pty
+  child.stdin.on('error', () => {})
+  const exited = new Promise<void>((resolve) => {
+    child.on('close', (code, signal) => {
+      for (const listener of exitListeners) {
+        listener({ exitCode: code ?? 0, ...(signal ? { signal: os.constants.signals[signal] } : {}) })
+      }
+      resolve()
+    })
+  })
+
+  return {
+    pid: child.pid ?? -1,
+    cols: options.cols,
+    rows: options.rows,
+    options,
+    exited,
+    onData: (listener) => subscribe(dataListeners, listener),
+    onExit: (listener) => subscribe(exitListeners, listener),
+    write: (data) => {
+      child.stdin.write(data)
+    },
+    resize(cols, rows) {
+      this.cols = cols
+      this.rows = rows
+    },
+    kill: (signal) => {
+      child.kill(signal as NodeJS.Signals | undefined)
+    }
+  }
+}
+
+/** Make `service` spawn pipe-backed shells; the returned list fills with each one it starts */
+export function usePipePty(service: TerminalService): PipePty[] {
+  const spawned: PipePty[] = []
+  vi.spyOn(service as unknown as { spawnPty: (shell: string, options: PtySpawnOptions) => IPty }, 'spawnPty').mockImplementation(
+    (shell, options) => {
+      const pty = spawnPipePty(shell, options)
+      spawned.push(pty)
+      return pty
+    }
+  )
+  return spawned
+}
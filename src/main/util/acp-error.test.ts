@@ -0,0 +1,35 @@
+import { describe, expect, it } from 'vitest'
+import { ACP_AUTH_REQUIRED, ACP_METHOD_NOT_FOUND, AcpError, parseAcpError } from './acp-error'
+
+describe('parseAcpError', () => {
+  it('keeps the code, message and data of a JSON-RPC error object', () => {
+    const error = parseAcpError({ code: ACP_METHOD_NOT_FOUND, message: 'Method not found', data: { method: 'logout' } })
+
+    expect(error).toBeInstanceOf(AcpError)
+    expect(error.code).toBe(-32601)
+    expect(error.rpcMessage).toBe('Method not found')
+    expect(error.data).toEqual({ method: 'logout' })
+    expect(error.message).toBe('ACP error -32601: Method not found | data: {"method":"logout"}')
+  })
+
+  it('leaves the data suffix off when there is none', () => {
+    const error = parseAcpError({ code: ACP_AUTH_REQUIRED, message: 'Authentication required' })
+
+    expect(error.code).toBe(-32000)
+    expect(error.data).toBeUndefined()
+    expect(error.message).toBe('ACP error -32000: Authentication required')
+  })
+
+  it.each([
+    ['a missing error', undefined],
+    ['a string', 'boom'],
+    ['a string code', { code: '-32601', message: 'Method not found' }],
+    ['a fractional code', { code: 1.5, message: 'Method not found' }]
+  ])('turns %s into an internal error', (_label, raw) => {
+    expect(parseAcpError(raw).code).toBe(-32603)
+  })
+
+  it('falls back to a generic message', () => {
+    expect(parseAcpError({ code: -32602 }).rpcMessage).toBe('Unknown error')
+  })
+})
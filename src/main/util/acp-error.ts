@@ -0,0 +1,27 @@
+/** JSON-RPC: the agent does not implement the requested method */
+export const ACP_METHOD_NOT_FOUND = -32601
+/** ACP: the agent needs authentication before it can handle the request */
+export const ACP_AUTH_REQUIRED = -32000
+
+/**
+ * A JSON-RPC error returned by an agent, keeping its numeric code and data so
+ * callers can match on the code instead of the message text.
+ */
+export class AcpError extends Error {
+  constructor(
+    readonly code: number,
+    readonly rpcMessage: string,
+    readonly data?: unknown
+  ) {
+    super(`ACP error ${code}: ${rpcMessage}${data ? ' | data: ' + JSON.stringify(data) : ''}`)
+    this.name = 'AcpError'
+  }
+}
+
+/** Build an AcpError from a response's `error` member; malformed objects become internal errors (-32603) */
+export function parseAcpError(error: unknown): AcpError {
+  const obj = error && typeof error === 'object' ? (error as Record<string, unknown>) : {}
+  const code = typeof obj.code === 'number' && Number.isInteger(obj.code) ? obj.code : -32603
+  const message = typeof obj.message === 'string' ? obj.message : 'Unknown error'
+  return new AcpError(code, message, obj.data)
+}
@@ -0,0 +1,83 @@
+import { describe, expect, it } from 'vitest'
+import { decodeIpcError } from '@shared/util/ipc-error'
+import { AppError, serializeError, toIpcError } from './app-error'
+import { AcpError } from './acp-error'
+import { GitConflictError } from './git-error'
+import { AgentSpawnError, MissingDependencyError } from './spawn-error'
+
+describe('serializeError', () => {
+  it('keeps the code, context and details of an AppError', () => {
+    const error = new AppError('SESSION_NOT_FOUND', 'Session not found: s1', { sessionId: 's1' }, { known: 2 })
+
+    expect(serializeError(error)).toEqual({
+      code: 'SESSION_NOT_FOUND',
+      message: 'Session not found: s1',
+      context: { sessionId: 's1' },
+      details: { known: 2 }
+    })
+    expect(serializeError(new AppError('NOT_CONNECTED', 'Not connected'))).toEqual({ code: 'NOT_CONNECTED', message: 'Not connected' })
+  })
+
+  it('maps a spawn failure to AGENT_SPAWN_FAILED', () => {
+    const error = new AgentSpawnError('agent-1', '/bin/agent', 'ENOENT', 'Reinstall it.')
+
+    expect(serializeError(error)).toEqual({
+      code: 'AGENT_SPAWN_FAILED',
+      message: "Failed to spawn agent 'agent-1' (/bin/agent): command not found. Reinstall it.",
+      context: { agentId: 'agent-1' },
+      details: { command: '/bin/agent', errno: 'ENOENT', hint: 'Reinstall it.' }
+    })
+  })
+
+  it('maps a missing tool to MISSING_DEPENDENCY', () => {
+    expect(serializeError(new MissingDependencyError('agent-1', 'node', 'Install Node.js.'))).toEqual({
+      code: 'MISSING_DEPENDENCY',
+      message: "Cannot launch agent 'agent-1': node was not found on PATH. Install Node.js.",
+      context: { agentId: 'agent-1' },
+      details: { tool: 'node', hint: 'Install Node.js.' }
+    })
+  })
+
+  it('maps ACP errors by their JSON-RPC code', () => {
+    expect(serializeError(new AcpError(-32000, 'Authentication required'))).toEqual({
+      code: 'NOT_AUTHENTICATED',
+      message: 'ACP error -32000: Authentication required',
+      details: { acpCode: -32000 }
+    })
+    expect(serializeError(new AcpError(-32603, 'Internal error', { reason: 'boom' }))).toEqual({
+      code: 'ACP_ERROR',
+      message: 'ACP error -32603: Internal error | data: {"reason":"boom"}',
+      details: { acpCode: -32603, data: { reason: 'boom' } }
+    })
+  })
+
+  it('maps git conflicts to GIT_CONFLICT with the files', () => {
+    expect(serializeError(new GitConflictError(['a.txt', 'b.txt'], 'merge', true))).toEqual({
+      code: 'GIT_CONFLICT',
+      message: 'Merge aborted: conflicts in 2 file(s): a.txt, b.txt',
+      details: { files: ['a.txt', 'b.txt'], operation: 'merge', aborted: true }
+    })
+  })
+
+  it('treats anything else as INTERNAL', () => {
+    expect(serializeError(new Error('boom'))).toEqual({ code: 'INTERNAL', message: 'boom' })
+    expect(serializeError('plain string')).toEqual({ code: 'INTERNAL', message: 'plain string' })
+  })
+})
+
+describe('IPC transport', () => {
+  it('survives the trip through an invoke() rejection', () => {
+    const error = new AppError('PROMPT_IN_PROGRESS', 'A prompt is already running', { sessionId: 's1' })
+    // Electron prefixes the message of errors thrown by invoke handlers
+    const received = new Error(`Error invoking remote method 'session:prompt': ${toIpcError(error).message}`)
+
+    expect(decodeIpcError(received)).toEqual(serializeError(error))
+  })
+
+  it('decodes errors that were not encoded as INTERNAL with a clean message', () => {
+    expect(decodeIpcError(new Error("Error invoking remote method 'x': Error: went wrong"))).toEqual({
+      code: 'INTERNAL',
+      message: 'went wrong'
+    })
+  })
+})
@@ -0,0 +1,69 @@
+import type { AppErrorCode, SerializedAppError } from '@shared/types/errors'
+import { encodeAppErrorMessage } from '@shared/util/ipc-error'
+import { AgentSpawnError, MissingDependencyError } from './spawn-error'
+import { ACP_AUTH_REQUIRED, AcpError } from './acp-error'
+import { GitConflictError } from './git-error'
+
+/**
+ * An error with a stable code for the renderer. `context` names what failed
+ * (e.g. the session id); the message stays the human-readable text for logs.
+ */
+export class AppError extends Error {
+  constructor(
+    readonly code: AppErrorCode,
+    message: string,
+    readonly context?: Record<string, string>,
+    readonly details?: Record<string, unknown>
+  ) {
+    super(message)
+    this.name = 'AppError'
+  }
+}
+
+/** Structured form of any thrown value; unknown errors are INTERNAL */
+export function serializeError(error: unknown): SerializedAppError {
+  if (error instanceof AppError) {
+    return {
+      code: error.code,
+      message: error.message,
+      ...(error.context ? { context: error.context } : {}),
+      ...(error.details ? { details: error.details } : {})
+    }
+  }
+  if (error instanceof AgentSpawnError) {
+    return {
+      code: 'AGENT_SPAWN_FAILED',
+      message: error.message,
+      context: { agentId: error.agentId },
+      details: { command: error.command, errno: error.code, hint: error.hint }
+    }
+  }
+  if (error instanceof MissingDependencyError) {
+    return {
+      code: 'MISSING_DEPENDENCY',
+      message: error.message,
+      context: { agentId: error.agentId },
+      details: { tool: error.tool, hint: error.hint }
+    }
+  }
+  if (error instanceof AcpError) {
+    return {
+      code: error.code === ACP_AUTH_REQUIRED ? 'NOT_AUTHENTICATED' : 'ACP_ERROR',
+      message: error.message,
+      details: { acpCode: error.code, ...(error.data !== undefined ? { data: error.data } : {}) }
+    }
+  }
+  if (error instanceof GitConflictError) {
+    return {
+      code: 'GIT_CONFLICT',
+      message: error.message,
+      details: { files: error.files, operation: error.operation, aborted: error.aborted }
+    }
+  }
+  return { code: 'INTERNAL', message: error instanceof Error ? error.message : String(error) }
+}
+
+/** The Error an IPC handler rethrows so the renderer can decode the structured form */
+export function toIpcError(error: unknown): Error {
+  return new Error(encodeAppErrorMessage(serializeError(error)))
+}
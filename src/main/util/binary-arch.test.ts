@@ -0,0 +1,99 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import { describe, expect, it } from 'vitest'
+import { parseBinaryHeader, readBinaryHeader } from './binary-arch'
+
+function elf(machine: number, bigEndian = false): Buffer {
+  const buf = Buffer.alloc(64)
+  buf.writeUInt32BE(0x7f454c46, 0)
+  buf[4] = 2 // 64-bit
+  buf[5] = bigEndian ? 2 : 1
+  if (bigEndian) buf.writeUInt16BE(machine, 18)
+  else buf.writeUInt16LE(machine, 18)
+  return buf
+}
+
+function machO(cpuType: number, bigEndian = false): Buffer {
+  const buf = Buffer.alloc(32)
+  if (bigEndian) {
+    buf.writeUInt32BE(0xfeedfacf, 0)
+    buf.writeUInt32BE(cpuType, 4)
+  } else {
+    buf.writeUInt32LE(0xfeedfacf, 0)
+    buf.writeUInt32LE(cpuType, 4)
+  }
+  return buf
+}
+
+function fatMachO(cpuTypes: number[]): Buffer {
+  const buf = Buffer.alloc(8 + cpuTypes.length * 20)
+  buf.writeUInt32BE(0xcafebabe, 0)
+  buf.writeUInt32BE(cpuTypes.length, 4)
+  cpuTypes.forEach((cpuType, i) => buf.writeUInt32BE(cpuType, 8 + i * 20))
+  return buf
+}
+
+function pe(machine: number): Buffer {
+  const peOffset = 0x80
+  const buf = Buffer.alloc(peOffset + 24)
+  buf.writeUInt16LE(0x5a4d, 0)
+  buf.writeUInt32LE(peOffset, 0x3c)
+  buf.writeUInt32LE(0x00004550, peOffset)
+  buf.writeUInt16LE(machine, peOffset + 4)
+  return buf
+}
+
+describe('parseBinaryHeader', () => {
+  it('reads the ELF machine in either byte order', () => {
+    expect(parseBinaryHeader(elf(0x3e))).toEqual({ format: 'elf', archs: ['x86_64'] })
+    expect(parseBinaryHeader(elf(0xb7, true))).toEqual({ format: 'elf', archs: ['aarch64'] })
+    expect(parseBinaryHeader(elf(0xf3))).toEqual({ format: 'elf', archs: ['elf-0xf3'] })
+  })
+
+  it('reads the cputype of a thin Mach-O in either byte order', () => {
+    expect(parseBinaryHeader(machO(0x0100000c))).toEqual({ format: 'macho', archs: ['aarch64'] })
+    expect(parseBinaryHeader(machO(0x01000007, true))).toEqual({ format: 'macho', archs: ['x86_64'] })
+  })
+
+  it('lists every architecture of a universal Mach-O', () => {
+    expect(parseBinaryHeader(fatMachO([0x01000007, 0x0100000c]))).toEqual({
+      format: 'macho',
+      archs: ['x86_64', 'aarch64']
+    })
+  })
+
+  it('reads the PE machine', () => {
+    expect(parseBinaryHeader(pe(0x8664))).toEqual({ format: 'pe', archs: ['x86_64'] })
+    expect(parseBinaryHeader(pe(0xaa64))).toEqual({ format: 'pe', archs: ['aarch64'] })
+  })
+
+  it('does not mistake a Java class file for a universal Mach-O', () => {
+    // Same magic, but minor/major version (0x0000_0041 = Java 21) where the arch count would be
+    const classFile = Buffer.alloc(64)
+    classFile.writeUInt32BE(0xcafebabe, 0)
+    classFile.writeUInt16BE(0, 4)
+    classFile.writeUInt16BE(65, 6)
+
+    expect(parseBinaryHeader(classFile)).toBeNull()
+  })
+
+  it('returns null for scripts and short files', () => {
+    expect(parseBinaryHeader(Buffer.from('#!/bin/sh\necho hello from a script\n'))).toBeNull()
+    expect(parseBinaryHeader(Buffer.from([0x7f, 0x45, 0x4c, 0x46]))).toBeNull()
+  })
+})
+
+describe('readBinaryHeader', () => {
+  it('reads the header from the start of a file', () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'binary-arch-'))
+    try {
+      const file = path.join(dir, 'agent')
+      fs.writeFileSync(file, Buffer.concat([elf(0xb7), Buffer.alloc(8192)]))
+
+      expect(readBinaryHeader(file)).toEqual({ format: 'elf', archs: ['aarch64'] })
+    } finally {
+      fs.rmSync(dir, { recursive: true, force: true })
+    }
+  })
+})
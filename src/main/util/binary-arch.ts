@@ -0,0 +1,77 @@
+import fs from 'fs'
+
+export type BinaryFormat = 'elf' | 'macho' | 'pe'
+
+export interface BinaryHeader {
+  format: BinaryFormat
+  /** Architectures in registry naming (x86_64, aarch64, ...); several for universal Mach-O */
+  archs: string[]
+}
+
+const HEADER_BYTES = 4096
+
+const ELF_MACHINES: Record<number, string> = { 0x03: 'x86', 0x28: 'arm', 0x3e: 'x86_64', 0xb7: 'aarch64' }
+const MACHO_CPU_TYPES: Record<number, string> = { 0x07: 'x86', 0x01000007: 'x86_64', 0x0c: 'arm', 0x0100000c: 'aarch64' }
+const PE_MACHINES: Record<number, string> = { 0x014c: 'x86', 0x8664: 'x86_64', 0x01c4: 'arm', 0xaa64: 'aarch64' }
+
+/** Read the executable header of a file; null if it is not ELF, Mach-O or PE (e.g. a script) */
+export function readBinaryHeader(filePath: string): BinaryHeader | null {
+  const fd = fs.openSync(filePath, 'r')
+  let buf: Buffer
+  try {
+    buf = Buffer.alloc(HEADER_BYTES)
+    buf = buf.subarray(0, fs.readSync(fd, buf, 0, HEADER_BYTES, 0))
+  } finally {
+    fs.closeSync(fd)
+  }
+  return parseBinaryHeader(buf)
+}
+
+export function parseBinaryHeader(buf: Buffer): BinaryHeader | null {
+  if (buf.length < 20) return null
+
+  // ELF: e_machine at offset 18, endianness from EI_DATA
+  if (buf.readUInt32BE(0) === 0x7f454c46) {
+    const machine = buf[5] === 2 ? buf.readUInt16BE(18) : buf.readUInt16LE(18)
+    return { format: 'elf', archs: [ELF_MACHINES[machine] || `elf-0x${machine.toString(16)}`] }
+  }
+
+  // Mach-O thin (either byte order), cputype follows the magic
+  const magicLE = buf.readUInt32LE(0)
+  if (magicLE === 0xfeedface || magicLE === 0xfeedfacf) {
+    return { format: 'macho', archs: [machoArch(buf.readUInt32LE(4))] }
+  }
+  const magicBE = buf.readUInt32BE(0)
+  if (magicBE === 0xfeedface || magicBE === 0xfeedfacf) {
+    return { format: 'macho', archs: [machoArch(buf.readUInt32BE(4))] }
+  }
+
+  // Mach-O universal: big-endian fat header, 20 bytes per fat_arch (cputype first).
+  // Java class files share the magic but have a large version number here.
+  if (magicBE === 0xcafebabe) {
+    const count = buf.readUInt32BE(4)
+    if (count > 0 && count < 20 && buf.length >= 8 + count * 20) {
+      const archs: string[] = []
+      for (let i = 0; i < count; i++) {
+        archs.push(machoArch(buf.readUInt32BE(8 + i * 20)))
+      }
+      return { format: 'macho', archs }
+    }
+    return null
+  }
+
+  // PE: "MZ", e_lfanew at 0x3c points at "PE\0\0" followed by the machine field
+  if (buf.readUInt16LE(0) === 0x5a4d && buf.length >= 0x40) {
+    const peOffset = buf.readUInt32LE(0x3c)
+    if (peOffset + 6 <= buf.length && buf.readUInt32LE(peOffset) === 0x00004550) {
+      const machine = buf.readUInt16LE(peOffset + 4)
+      return { format: 'pe', archs: [PE_MACHINES[machine] || `pe-0x${machine.toString(16)}`] }
+    }
+  }
+
+  return null
+}
+
+function machoArch(cpuType: number): string {
+  return MACHO_CPU_TYPES[cpuType] || `macho-0x${cpuType.toString(16)}`
+}
@@ -0,0 +1,87 @@
+import { app, dialog } from 'electron'
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import { logger } from './logger'
+
+const PROBE_FILE_NAME = '.write-test'
+const PENDING_RESTORE_DIR_NAME = 'pending-restore'
+
+export interface DataDirStatus {
+  path: string
+  /** True when userData wasn't writable and a temp directory is used instead */
+  isFallback: boolean
+  /** Why the default directory was rejected */
+  error?: string
+}
+
+function assertWritable(dir: string): void {
+  fs.mkdirSync(dir, { recursive: true })
+  const probe = path.join(dir, PROBE_FILE_NAME)
+  fs.writeFileSync(probe, String(process.pid))
+  fs.rmSync(probe, { force: true })
+}
+
+/**
+ * Make sure userData is writable before any electron-store is created; otherwise
+ * point userData at a temp directory so the app can still start. Throws if
+ * neither location is usable. Runs on import: index.ts imports this module first.
+ */
+function resolveDataDir(): DataDirStatus {
+  const preferred = app.getPath('userData')
+  try {
+    assertWritable(preferred)
+    return { path: preferred, isFallback: false }
+  } catch (err) {
+    const error = (err as Error).message
+    const fallback = path.join(os.tmpdir(), `${app.getName()}-data`)
+    logger.warn(`Data directory ${preferred} is not writable (${error}); falling back to ${fallback}`)
+    assertWritable(fallback)
+    app.setPath('userData', fallback)
+    return { path: fallback, isFallback: true, error }
+  }
+}
+
+/** Where a backup import stages data files until the next start */
+export function getPendingRestoreDir(): string {
+  return path.join(app.getPath('userData'), PENDING_RESTORE_DIR_NAME)
+}
+
+/**
+ * Move data files staged by a backup import into place. Runs before any
+ * service reads its store, so none of them can overwrite the restored data
+ * with what it still holds in memory.
+ */
+function applyPendingRestore(dataDir: string): void {
+  const pendingDir = getPendingRestoreDir()
+  if (!fs.existsSync(pendingDir)) return
+  try {
+    for (const name of fs.readdirSync(pendingDir)) {
+      fs.cpSync(path.join(pendingDir, name), path.join(dataDir, name), { recursive: true, force: true })
+    }
+    logger.info(`Applied data restored from backup: ${dataDir}`)
+  } catch (err) {
+    logger.error('Failed to apply restored backup data:', err)
+  } finally {
+    fs.rmSync(pendingDir, { recursive: true, force: true })
+  }
+}
+
+let status: DataDirStatus
+try {
+  status = resolveDataDir()
+  applyPendingRestore(status.path)
+} catch (err) {
+  // Nothing can be persisted; the stores would throw on first write anyway
+  logger.error('No writable data directory:', err)
+  dialog.showErrorBox(
+    'AgentManager cannot store its data',
+    `Neither ${app.getPath('userData')} nor a temporary directory is writable.\n\n${(err as Error).message}`
+  )
+  process.exit(1)
+}
+
+/** Where data is actually stored, resolved once at startup */
+export function getDataDirStatus(): DataDirStatus {
+  return status
+}
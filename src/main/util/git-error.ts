@@ -0,0 +1,20 @@
+/** A merge/rebase hit conflicts; the message lists the files */
+export class GitConflictError extends Error {
+  constructor(
+    readonly files: string[],
+    readonly operation: 'merge' | 'rebase' | 'stash',
+    /** True when the operation was rolled back rather than left in progress */
+    readonly aborted = false
+  ) {
+    const label = operation === 'rebase' ? 'Rebase' : operation === 'stash' ? 'Stash pop' : 'Merge'
+    const list = `${files.length} file(s): ${files.join(', ')}`
+    super(
+      aborted
+        ? `${label} aborted: conflicts in ${list}`
+        : operation === 'stash'
+          ? `${label} stopped with conflicts in ${list}. The stash was kept; drop it once they are resolved.`
+          : `${label} stopped with conflicts in ${list}. Resolve them and continue, or run git ${operation} --abort.`
+    )
+    this.name = 'GitConflictError'
+  }
+}
@@ -0,0 +1,78 @@
+import fs from 'fs'
+import path from 'path'
+import { getAppDataDir } from './paths'
+import { logger } from './logger'
+
+const LOCK_FILE_NAME = 'app.lock'
+/** Exclusive-create attempts; each retry follows a stale or vanished lock file */
+const LOCK_ATTEMPTS = 3
+
+let heldLockPath: string | null = null
+
+function isProcessAlive(pid: number): boolean {
+  try {
+    // Signal 0 checks for existence without delivering a signal
+    process.kill(pid, 0)
+    return true
+  } catch (err) {
+    // EPERM means the process exists but belongs to another user
+    return (err as NodeJS.ErrnoException).code === 'EPERM'
+  }
+}
+
+/**
+ * Acquire an advisory lock on the data directory.
+ * The lock file records the owning PID; a lock left behind by a dead process is taken over.
+ * Returns the PID of the other owner when the lock is held by a live process, or null on success.
+ */
+export function acquireDataDirLock(): number | null {
+  const lockPath = path.join(getAppDataDir(), LOCK_FILE_NAME)
+
+  for (let attempt = 0; attempt < LOCK_ATTEMPTS; attempt++) {
+    try {
+      const fd = fs.openSync(lockPath, 'wx')
+      fs.writeSync(fd, String(process.pid))
+      fs.closeSync(fd)
+      heldLockPath = lockPath
+      logger.info(`Data directory lock acquired: ${lockPath}`)
+      return null
+    } catch (err) {
+      if ((err as NodeJS.ErrnoException).code !== 'EEXIST') {
+        // Can't create the lock at all (e.g. read-only dir) — don't block startup on it
+        logger.warn('Failed to create data directory lock:', err)
+        return null
+      }
+    }
+
+    let contents: string
+    try {
+      contents = fs.readFileSync(lockPath, 'utf-8')
+    } catch (err) {
+      // The owner released the lock between our create and read; try the create again
+      if ((err as NodeJS.ErrnoException).code === 'ENOENT') continue
+      logger.warn('Failed to read data directory lock:', err)
+      return null
+    }
+
+    const ownerPid = Number.parseInt(contents.trim(), 10)
+    if (Number.isFinite(ownerPid) && ownerPid !== process.pid && isProcessAlive(ownerPid)) {
+      return ownerPid
+    }
+
+    logger.warn(`Removing stale data directory lock (pid ${ownerPid || 'unknown'})`)
+    fs.rmSync(lockPath, { force: true })
+  }
+
+  return null
+}
+
+/** Release the data directory lock if this process holds it. */
+export function releaseDataDirLock(): void {
+  if (!heldLockPath) return
+  try {
+    fs.rmSync(heldLockPath, { force: true })
+  } catch (err) {
+    logger.warn('Failed to release data directory lock:', err)
+  }
+  heldLockPath = null
+}
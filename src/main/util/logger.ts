@@ -1,22 +1,102 @@
+import fs from 'fs'
+import path from 'path'
+import { format } from 'util'
+import type { LogLevel } from '@shared/types/settings'
+
 const PREFIX = '[AgentManager]'
 
+const LEVEL_RANK: Record<LogLevel, number> = { debug: 0, info: 1, warn: 2, error: 3 }
+
+/** Rotate app.log once it grows past this size */
+const MAX_LOG_FILE_BYTES = 5 * 1024 * 1024
+/** Rotated files kept: app.log.1 (newest) .. app.log.N */
+const MAX_ROTATED_FILES = 3
+
+let currentLevel: LogLevel = process.env.DEBUG ? 'debug' : 'info'
+
+let logFilePath: string | null = null
+let logFileSize = 0
+
 function timestamp(): string {
   return new Date().toISOString()
 }
 
+function enabled(level: LogLevel): boolean {
+  return LEVEL_RANK[level] >= LEVEL_RANK[currentLevel]
+}
+
+/** Change the minimum level that is written; takes effect immediately */
+export function setLogLevel(level: LogLevel): void {
+  if (!(level in LEVEL_RANK)) throw new Error(`Unknown log level: ${level}`)
+  currentLevel = level
+}
+
+export function getLogLevel(): LogLevel {
+  return currentLevel
+}
+
+/** Also append log lines to `<logsDir>/app.log`, rotating it by size */
+export function initLogFile(logsDir: string): void {
+  logFilePath = path.join(logsDir, 'app.log')
+  try {
+    logFileSize = fs.statSync(logFilePath).size
+  } catch {
+    logFileSize = 0
+  }
+}
+
+export function getLogFilePath(): string | null {
+  return logFilePath
+}
+
+/** Last `maxLines` lines of the current log file */
+export function readLogTail(maxLines: number): string[] {
+  if (!logFilePath || !fs.existsSync(logFilePath)) return []
+  const lines = fs.readFileSync(logFilePath, 'utf-8').split('\n')
+  if (lines[lines.length - 1] === '') lines.pop()
+  return lines.slice(-maxLines)
+}
+
+function rotateLogFile(file: string): void {
+  for (let i = MAX_ROTATED_FILES - 1; i >= 1; i--) {
+    const from = `${file}.${i}`
+    if (fs.existsSync(from)) fs.renameSync(from, `${file}.${i + 1}`)
+  }
+  fs.renameSync(file, `${file}.1`)
+  logFileSize = 0
+}
+
+function writeToFile(line: string): void {
+  if (!logFilePath) return
+  try {
+    if (logFileSize >= MAX_LOG_FILE_BYTES) rotateLogFile(logFilePath)
+    const data = line + '\n'
+    fs.appendFileSync(logFilePath, data)
+    logFileSize += Buffer.byteLength(data)
+  } catch {
+    // Never let a broken log file take the app down; console output still works
+    logFilePath = null
+  }
+}
+
+function write(level: LogLevel, print: (line: string, ...args: unknown[]) => void, msg: string, args: unknown[]): void {
+  if (!enabled(level)) return
+  const line = `${timestamp()} ${PREFIX} [${level.toUpperCase()}] ${msg}`
+  print(line, ...args)
+  writeToFile(args.length > 0 ? format(line, ...args) : line)
+}
+
 export const logger = {
   info: (msg: string, ...args: unknown[]) => {
-    console.log(`${timestamp()} ${PREFIX} [INFO] ${msg}`, ...args)
+    write('info', console.log, msg, args)
   },
   warn: (msg: string, ...args: unknown[]) => {
-    console.warn(`${timestamp()} ${PREFIX} [WARN] ${msg}`, ...args)
+    write('warn', console.warn, msg, args)
   },
   error: (msg: string, ...args: unknown[]) => {
-    console.error(`${timestamp()} ${PREFIX} [ERROR] ${msg}`, ...args)
+    write('error', console.error, msg, args)
   },
   debug: (msg: string, ...args: unknown[]) => {
-    if (process.env.DEBUG) {
-      console.debug(`${timestamp()} ${PREFIX} [DEBUG] ${msg}`, ...args)
-    }
+    write('debug', console.debug, msg, args)
   }
 }
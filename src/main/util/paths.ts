@@ -1,6 +1,8 @@
 import { app } from 'electron'
 import path from 'path'
 import fs from 'fs'
+import { createHash } from 'crypto'
+import { ACP_REGISTRY_URL } from '@shared/constants'
 
 /** Base directory for all AgentManager data */
 export function getAppDataDir(): string {
@@ -30,9 +32,20 @@ export function getCacheDir(): string {
   return dir
 }
 
-/** Path to the cached registry JSON */
-export function getRegistryCachePath(): string {
-  return path.join(getCacheDir(), 'registry.json')
+/** Directory for cached agent icon SVGs */
+export function getIconCacheDir(): string {
+  const dir = path.join(getCacheDir(), 'icons')
+  fs.mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+/** Path to the cached registry JSON for a source; the built-in registry keeps the original file name */
+export function getRegistryCachePath(source?: string): string {
+  if (!source || source === ACP_REGISTRY_URL) {
+    return path.join(getCacheDir(), 'registry.json')
+  }
+  const key = createHash('sha256').update(source).digest('hex').slice(0, 16)
+  return path.join(getCacheDir(), `registry-${key}.json`)
 }
 
 /** Path for session persistence */
@@ -55,3 +68,10 @@ export function getDownloadsDir(): string {
   fs.mkdirSync(dir, { recursive: true })
   return dir
 }
+
+/** Directory for the application log */
+export function getLogsDir(): string {
+  const dir = path.join(getAppDataDir(), 'logs')
+  fs.mkdirSync(dir, { recursive: true })
+  return dir
+}
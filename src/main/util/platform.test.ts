@@ -0,0 +1,50 @@
+import { afterEach, describe, expect, it, vi } from 'vitest'
+import { buildWslSpawn, toWslPath } from './platform'
+
+describe('toWslPath', () => {
+  it.each([
+    ['D:\\Projects\\Foo', '/mnt/d/Projects/Foo'],
+    ['c:/Users/me', '/mnt/c/Users/me'],
+    ['C:\\', '/mnt/c/'],
+    ['/home/me/project', '/home/me/project'],
+    ['relative\\dir', 'relative\\dir']
+  ])('translates %j to %j', (input, expected) => {
+    expect(toWslPath(input)).toBe(expected)
+  })
+})
+
+describe('buildWslSpawn', () => {
+  afterEach(() => {
+    vi.unstubAllEnvs()
+  })
+
+  it('wraps the command in wsl with the distribution and translated cwd', () => {
+    vi.stubEnv('WSLENV', '')
+
+    const spawn = buildWslSpawn('npx', ['-y', 'agent'], 'C:\\work\\repo', {}, 'Ubuntu')
+
+    expect(spawn.command).toBe('wsl')
+    expect(spawn.args).toEqual(['--distribution', 'Ubuntu', '--cd', '/mnt/c/work/repo', '--', 'npx', '-y', 'agent'])
+    expect(spawn.env).toEqual({})
+  })
+
+  it('uses the default distribution when none is set', () => {
+    vi.stubEnv('WSLENV', '')
+
+    expect(buildWslSpawn('agent', [], 'D:\\p', {}).args).toEqual(['--cd', '/mnt/d/p', '--', 'agent'])
+  })
+
+  it('forwards env through WSLENV, translating path values', () => {
+    vi.stubEnv('WSLENV', 'USERPROFILE/p')
+
+    const spawn = buildWslSpawn('agent', [], 'C:\\p', {
+      API_KEY: 'secret',
+      CONFIG_DIR: 'C:\\Users\\me\\.config',
+      'BAD-NAME': 'x'
+    })
+
+    expect(spawn.env.WSLENV).toBe('USERPROFILE/p:API_KEY:CONFIG_DIR/p')
+    expect(spawn.env.API_KEY).toBe('secret')
+    expect(spawn.env.CONFIG_DIR).toBe('C:\\Users\\me\\.config')
+  })
+})
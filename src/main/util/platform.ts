@@ -83,3 +83,42 @@ export function toWslPath(windowsPath: string): string {
 
   return windowsPath
 }
+
+/** Windows absolute paths (C:\foo, D:/bar) that WSL should translate via WSLENV's /p flag */
+function isWindowsPathValue(value: string): boolean {
+  return /^[A-Za-z]:[\\/]/.test(value)
+}
+
+/**
+ * Rewrite a spawn to run inside WSL: `wsl [-d distro] --cd <cwd> -- <command> <args>`.
+ * wsl.exe has no flag for environment variables, so they are passed through the
+ * Windows environment and forwarded by listing them in WSLENV; path-valued ones
+ * get the /p flag so WSL translates them to /mnt/... paths.
+ */
+export function buildWslSpawn(
+  command: string,
+  args: string[],
+  cwd: string,
+  env: Record<string, string>,
+  distribution?: string
+): { command: string; args: string[]; env: Record<string, string> } {
+  const wslArgs: string[] = []
+  if (distribution) {
+    wslArgs.push('--distribution', distribution)
+  }
+  wslArgs.push('--cd', toWslPath(cwd), '--', command, ...args)
+
+  const forwarded: string[] = []
+  for (const [key, value] of Object.entries(env)) {
+    // WSLENV entries are colon/slash separated, so only plain names can be forwarded
+    if (!/^[A-Za-z_][A-Za-z0-9_]*$/.test(key)) {
+      logger.warn(`Skipping invalid environment variable name: ${key}`)
+      continue
+    }
+    forwarded.push(isWindowsPathValue(value) ? `${key}/p` : key)
+  }
+  const existing = process.env.WSLENV ? process.env.WSLENV.split(':').filter(Boolean) : []
+  const wslEnv = [...existing, ...forwarded].join(':')
+
+  return { command: 'wsl', args: wslArgs, env: wslEnv ? { ...env, WSLENV: wslEnv } : { ...env } }
+}
@@ -0,0 +1,45 @@
+import { execFile } from 'child_process'
+import { promisify } from 'util'
+
+const execFileAsync = promisify(execFile)
+
+/** Cumulative CPU time and resident memory of a process */
+export interface ProcessStats {
+  cpuSeconds: number
+  rssBytes: number
+}
+
+/**
+ * Read CPU time and resident memory for a PID using the platform's own tools
+ * (`ps` on macOS/Linux, PowerShell on Windows). Returns null if the process is gone.
+ */
+export async function readProcessStats(pid: number): Promise<ProcessStats | null> {
+  try {
+    if (process.platform === 'win32') {
+      const { stdout } = await execFileAsync('powershell', [
+        '-NoProfile', '-NonInteractive', '-command',
+        `Get-Process -Id ${pid} | Select-Object CPU,WorkingSet64 | ConvertTo-Json -Compress`
+      ])
+      const parsed = JSON.parse(stdout) as { CPU: number | null; WorkingSet64: number }
+      return { cpuSeconds: parsed.CPU || 0, rssBytes: parsed.WorkingSet64 || 0 }
+    }
+
+    const { stdout } = await execFileAsync('ps', ['-o', 'time=,rss=', '-p', String(pid)])
+    const [time, rssKb] = stdout.trim().split(/\s+/)
+    if (!time || !rssKb) return null
+    return { cpuSeconds: parseCpuTime(time), rssBytes: Number(rssKb) * 1024 }
+  } catch {
+    // ps exits non-zero / Get-Process throws when the PID no longer exists
+    return null
+  }
+}
+
+/** Parse `ps` cumulative time: [[dd-]hh:]mm:ss[.ff] */
+function parseCpuTime(value: string): number {
+  const [daysPart, clock] = value.includes('-') ? value.split('-') : ['0', value]
+  const seconds = clock
+    .split(':')
+    .reverse()
+    .reduce((total, part, i) => total + parseFloat(part) * Math.pow(60, i), 0)
+  return Number(daysPart) * 86400 + seconds
+}
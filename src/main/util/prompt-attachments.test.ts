@@ -0,0 +1,84 @@
+import fs from 'fs'
+import path from 'path'
+import { pathToFileURL } from 'url'
+import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import { attachmentBlocksFor, buildPromptAttachments } from './prompt-attachments'
+import { removeTempDirs, tempDir } from '../test/git-repo'
+
+/** The 8-byte PNG signature followed by the start of an IHDR chunk */
+const PNG_BYTES = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52])
+
+let dir: string
+
+beforeEach(() => {
+  dir = tempDir('prompt-attachments-')
+  fs.writeFileSync(path.join(dir, 'notes.md'), '# Notes\nhello\n')
+  fs.writeFileSync(path.join(dir, 'logo.png'), PNG_BYTES)
+})
+
+afterEach(() => {
+  removeTempDirs()
+})
+
+function uri(file: string): string {
+  return pathToFileURL(path.join(dir, file)).href
+}
+
+describe('buildPromptAttachments', () => {
+  it('embeds a text file as a resource block', async () => {
+    const { blocks, links } = await buildPromptAttachments(['notes.md'], dir)
+
+    expect(blocks).toEqual([
+      { type: 'resource', resource: { uri: uri('notes.md'), mimeType: 'text/markdown', text: '# Notes\nhello\n' } }
+    ])
+    expect(links).toEqual([{ type: 'resource_link', uri: uri('notes.md'), name: 'notes.md', mimeType: 'text/markdown', size: 14 }])
+  })
+
+  it('sends a PNG as a base64 image block', async () => {
+    const { blocks, links } = await buildPromptAttachments([path.join(dir, 'logo.png')])
+
+    expect(blocks).toEqual([{ type: 'image', data: PNG_BYTES.toString('base64'), mimeType: 'image/png', uri: uri('logo.png') }])
+    expect(links).toEqual([{ type: 'resource_link', uri: uri('logo.png'), name: 'logo.png', mimeType: 'image/png', size: 16 }])
+  })
+
+  it('keeps the attachments in the order given', async () => {
+    const { blocks } = await buildPromptAttachments(['logo.png', 'notes.md'], dir)
+
+    expect(blocks.map((b) => b.type)).toEqual(['image', 'resource'])
+  })
+
+  it('rejects a text file over the size cap', async () => {
+    fs.writeFileSync(path.join(dir, 'big.txt'), 'x'.repeat(1024 * 1024 + 1))
+
+    await expect(buildPromptAttachments(['big.txt'], dir)).rejects.toMatchObject({
+      code: 'INVALID_ATTACHMENT',
+      details: { size: 1024 * 1024 + 1, maxBytes: 1024 * 1024 }
+    })
+  })
+
+  it('rejects binary files that are not images', async () => {
+    fs.writeFileSync(path.join(dir, 'app.exe'), Buffer.from([0x4d, 0x5a, 0x00, 0x01]))
+
+    await expect(buildPromptAttachments(['app.exe'], dir)).rejects.toMatchObject({
+      code: 'INVALID_ATTACHMENT',
+      message: 'Unsupported attachment type: app.exe is a binary file'
+    })
+  })
+
+  it('rejects missing files and directories', async () => {
+    fs.mkdirSync(path.join(dir, 'src'))
+
+    await expect(buildPromptAttachments(['missing.md'], dir)).rejects.toMatchObject({ code: 'INVALID_ATTACHMENT' })
+    await expect(buildPromptAttachments(['src'], dir)).rejects.toMatchObject({ code: 'INVALID_ATTACHMENT' })
+  })
+})
+
+describe('attachmentBlocksFor', () => {
+  it('falls back to links for content the agent cannot take', async () => {
+    const attachments = await buildPromptAttachments(['notes.md', 'logo.png'], dir)
+
+    expect(attachmentBlocksFor(attachments, { image: true, audio: false, embeddedContext: true })).toEqual(attachments.blocks)
+    expect(attachmentBlocksFor(attachments, { image: false, audio: false, embeddedContext: false })).toEqual(attachments.links)
+    expect(attachmentBlocksFor(attachments, undefined)).toEqual(attachments.links)
+  })
+})
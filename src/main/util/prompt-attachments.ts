@@ -0,0 +1,115 @@
+import fs from 'fs'
+import path from 'path'
+import { pathToFileURL } from 'url'
+import type { ContentBlock, ResourceLinkContent } from '@shared/types/session'
+import type { AgentCapabilities } from '@shared/types/agent'
+import { AppError } from './app-error'
+
+/** Text files are embedded whole, so keep them to a size an agent can take in one prompt */
+const MAX_TEXT_ATTACHMENT_BYTES = 1024 * 1024
+const MAX_IMAGE_ATTACHMENT_BYTES = 5 * 1024 * 1024
+/** Leading bytes checked for NUL to tell text from binary */
+const BINARY_SNIFF_BYTES = 8000
+
+const IMAGE_MIME_TYPES: Record<string, string> = {
+  '.png': 'image/png',
+  '.jpg': 'image/jpeg',
+  '.jpeg': 'image/jpeg',
+  '.gif': 'image/gif',
+  '.webp': 'image/webp'
+}
+
+const TEXT_MIME_TYPES: Record<string, string> = {
+  '.md': 'text/markdown',
+  '.json': 'application/json',
+  '.html': 'text/html',
+  '.css': 'text/css',
+  '.csv': 'text/csv',
+  '.xml': 'application/xml',
+  '.yaml': 'application/yaml',
+  '.yml': 'application/yaml',
+  '.js': 'text/javascript',
+  '.mjs': 'text/javascript',
+  '.ts': 'text/x-typescript',
+  '.tsx': 'text/x-typescript',
+  '.py': 'text/x-python',
+  '.rs': 'text/x-rust',
+  '.go': 'text/x-go',
+  '.java': 'text/x-java',
+  '.sh': 'text/x-shellscript'
+}
+
+export interface PromptAttachments {
+  /** Embedded blocks sent to the agent */
+  blocks: ContentBlock[]
+  /** Links recorded in the persisted user message instead of the file contents */
+  links: ResourceLinkContent[]
+}
+
+/**
+ * Read attached files into ACP content blocks: images become base64 `image`
+ * blocks, text files embedded `resource` blocks. Oversized files and other
+ * binary files are rejected. Relative paths are resolved against `baseDir`.
+ */
+export async function buildPromptAttachments(filePaths: string[], baseDir?: string): Promise<PromptAttachments> {
+  const blocks: ContentBlock[] = []
+  const links: ResourceLinkContent[] = []
+
+  for (const filePath of filePaths) {
+    const resolved = baseDir ? path.resolve(baseDir, filePath) : path.resolve(filePath)
+    const name = path.basename(resolved)
+    const stat = await fs.promises.stat(resolved).catch(() => null)
+    if (!stat?.isFile()) {
+      throw new AppError('INVALID_ATTACHMENT', `Attachment not found or not a file: ${filePath}`, { path: filePath })
+    }
+
+    const ext = path.extname(resolved).toLowerCase()
+    const imageMime = IMAGE_MIME_TYPES[ext]
+    const maxBytes = imageMime ? MAX_IMAGE_ATTACHMENT_BYTES : MAX_TEXT_ATTACHMENT_BYTES
+    if (stat.size > maxBytes) {
+      throw new AppError(
+        'INVALID_ATTACHMENT',
+        `Attachment ${name} is ${formatBytes(stat.size)}; the limit is ${formatBytes(maxBytes)}`,
+        { path: filePath },
+        { size: stat.size, maxBytes }
+      )
+    }
+
+    const data = await fs.promises.readFile(resolved)
+    const uri = pathToFileURL(resolved).href
+    let mimeType: string
+    if (imageMime) {
+      mimeType = imageMime
+      blocks.push({ type: 'image', data: data.toString('base64'), mimeType, uri })
+    } else {
+      if (data.subarray(0, BINARY_SNIFF_BYTES).includes(0)) {
+        throw new AppError('INVALID_ATTACHMENT', `Unsupported attachment type: ${name} is a binary file`, { path: filePath })
+      }
+      mimeType = TEXT_MIME_TYPES[ext] ?? 'text/plain'
+      blocks.push({ type: 'resource', resource: { uri, mimeType, text: data.toString('utf-8') } })
+    }
+    links.push({ type: 'resource_link', uri, name, mimeType, size: stat.size })
+  }
+
+  return { blocks, links }
+}
+
+/**
+ * The attachment blocks an agent with the given prompt capabilities accepts.
+ * Every agent takes resource_link, so an image or embedded file it can't
+ * receive is sent as a link to the file instead.
+ */
+export function attachmentBlocksFor(
+  attachments: PromptAttachments,
+  capabilities: AgentCapabilities['promptCapabilities'] | undefined
+): ContentBlock[] {
+  return attachments.blocks.map((block, i) => {
+    if (block.type === 'image' && !capabilities?.image) return attachments.links[i]
+    if (block.type === 'resource' && !capabilities?.embeddedContext) return attachments.links[i]
+    return block
+  })
+}
+
+function formatBytes(bytes: number): string {
+  return bytes >= 1024 * 1024 ? `${(bytes / (1024 * 1024)).toFixed(1)} MB` : `${Math.ceil(bytes / 1024)} KB`
+}
@@ -0,0 +1,35 @@
+import { createPublicKey, verify } from 'crypto'
+import type { KeyObject } from 'crypto'
+
+/** DER prefix turning a raw 32-byte ed25519 key into an SPKI structure */
+const ED25519_SPKI_PREFIX = Buffer.from('302a300506032b6570032100', 'hex')
+const ED25519_SIGNATURE_LENGTH = 64
+
+/** Parse a trusted key given as PEM or as a base64/hex raw 32-byte ed25519 key */
+export function parseEd25519PublicKey(key: string): KeyObject {
+  const trimmed = key.trim()
+  if (trimmed.startsWith('-----BEGIN')) {
+    return createPublicKey(trimmed)
+  }
+  const raw = /^[0-9a-fA-F]{64}$/.test(trimmed) ? Buffer.from(trimmed, 'hex') : Buffer.from(trimmed, 'base64')
+  if (raw.length !== 32) {
+    throw new Error(`Registry public key must be 32 bytes, got ${raw.length}`)
+  }
+  return createPublicKey({ key: Buffer.concat([ED25519_SPKI_PREFIX, raw]), format: 'der', type: 'spki' })
+}
+
+/** A detached signature as served: 64 raw bytes, or base64/hex text */
+export function decodeSignature(data: Buffer): Buffer {
+  if (data.length === ED25519_SIGNATURE_LENGTH) return data
+  const text = data.toString('utf-8').trim()
+  const decoded = /^[0-9a-fA-F]{128}$/.test(text) ? Buffer.from(text, 'hex') : Buffer.from(text, 'base64')
+  if (decoded.length !== ED25519_SIGNATURE_LENGTH) {
+    throw new Error(`Registry signature must be ${ED25519_SIGNATURE_LENGTH} bytes, got ${decoded.length}`)
+  }
+  return decoded
+}
+
+/** Verify an ed25519 detached signature over the exact response bytes */
+export function verifyRegistrySignature(body: Buffer, signature: Buffer, publicKey: string): boolean {
+  return verify(null, body, parseEd25519PublicKey(publicKey), signature)
+}
@@ -0,0 +1,50 @@
+import { describe, expect, it } from 'vitest'
+import { ScrollbackBuffer } from './scrollback-buffer'
+
+describe('ScrollbackBuffer', () => {
+  it('returns exactly what was written, however it was chunked', () => {
+    const buffer = new ScrollbackBuffer(10)
+
+    buffer.append('$ ls\r\nREADME')
+    buffer.append('.md  src\r\n')
+    buffer.append('')
+    buffer.append('\x1b[32m$\x1b[0m ')
+
+    expect(buffer.toString()).toBe('$ ls\r\nREADME.md  src\r\n\x1b[32m$\x1b[0m ')
+  })
+
+  it('keeps a partial last line until its newline arrives', () => {
+    const buffer = new ScrollbackBuffer(2)
+    buffer.append('one\ntw')
+
+    expect(buffer.toString()).toBe('one\ntw')
+
+    buffer.append('o\nthree')
+    // The partial line does not count toward the capacity
+    expect(buffer.toString()).toBe('one\ntwo\nthree')
+
+    buffer.append('\n')
+    expect(buffer.toString()).toBe('two\nthree\n')
+  })
+
+  it('drops the oldest lines once over capacity, wrapping around repeatedly', () => {
+    const buffer = new ScrollbackBuffer(3)
+
+    for (let i = 1; i <= 8; i++) buffer.append(`line ${i}\n`)
+
+    expect(buffer.toString()).toBe('line 6\nline 7\nline 8\n')
+  })
+
+  it('wraps correctly when one chunk holds more lines than the capacity', () => {
+    const buffer = new ScrollbackBuffer(3)
+    buffer.append('a\n')
+
+    buffer.append('b\nc\nd\ne\nf')
+
+    expect(buffer.toString()).toBe('c\nd\ne\nf')
+  })
+
+  it('is empty before any output', () => {
+    expect(new ScrollbackBuffer(5).toString()).toBe('')
+  })
+})
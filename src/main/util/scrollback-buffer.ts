@@ -0,0 +1,33 @@
+/**
+ * Fixed-capacity ring of output lines. Each chunk is appended as it arrives;
+ * the unterminated tail is kept apart until its newline shows up.
+ */
+export class ScrollbackBuffer {
+  private lines: string[] = []
+  private start = 0
+  private partial = ''
+
+  constructor(private readonly capacity: number) {}
+
+  append(data: string): void {
+    const parts = (this.partial + data).split('\n')
+    this.partial = parts.pop() ?? ''
+    for (const line of parts) {
+      this.push(line + '\n')
+    }
+  }
+
+  toString(): string {
+    return this.lines.slice(this.start).join('') + this.lines.slice(0, this.start).join('') + this.partial
+  }
+
+  private push(line: string): void {
+    if (this.lines.length < this.capacity) {
+      this.lines.push(line)
+      return
+    }
+    // Full: overwrite the oldest line
+    this.lines[this.start] = line
+    this.start = (this.start + 1) % this.capacity
+  }
+}
@@ -0,0 +1,88 @@
+import path from 'path'
+
+/**
+ * Raised when the OS refuses to start an agent process. Keeps the errno code
+ * and a remediation hint so the UI can tell the user what to fix.
+ */
+export class AgentSpawnError extends Error {
+  constructor(
+    readonly agentId: string,
+    readonly command: string,
+    readonly code: string | undefined,
+    readonly hint: string
+  ) {
+    super(`Failed to spawn agent '${agentId}' (${command}): ${describeCode(code)}. ${hint}`)
+    this.name = 'AgentSpawnError'
+  }
+}
+
+/** Raised before spawning when a tool the agent's distribution needs (node, uv, ...) is not on PATH */
+export class MissingDependencyError extends Error {
+  constructor(
+    readonly agentId: string,
+    readonly tool: string,
+    readonly hint: string
+  ) {
+    super(`Cannot launch agent '${agentId}': ${tool} was not found on PATH. ${hint}`)
+    this.name = 'MissingDependencyError'
+  }
+}
+
+/** Tools an npx/uvx agent needs on the host, with the hint shown when one is missing */
+export const DISTRIBUTION_DEPENDENCIES: Record<string, Array<{ tool: string; hint: string }>> = {
+  npx: [
+    { tool: 'node', hint: 'Install Node.js from https://nodejs.org and restart AgentManager.' },
+    { tool: 'npx', hint: 'npx ships with Node.js; reinstall Node.js or add its bin directory to PATH.' }
+  ],
+  uvx: [
+    { tool: 'uv', hint: 'Install uv from https://docs.astral.sh/uv and restart AgentManager.' },
+    { tool: 'uvx', hint: 'uvx ships with uv; reinstall uv or add its bin directory to PATH.' }
+  ]
+}
+
+export function toSpawnError(err: NodeJS.ErrnoException, agentId: string, command: string, useWsl: boolean): AgentSpawnError {
+  return new AgentSpawnError(agentId, command, err.code, spawnHint(err.code, command, useWsl))
+}
+
+function describeCode(code: string | undefined): string {
+  switch (code) {
+    case 'ENOENT':
+      return 'command not found'
+    case 'EACCES':
+      return 'permission denied'
+    case 'EPERM':
+      return 'operation not permitted'
+    case 'ENOEXEC':
+      return 'not a valid executable'
+    default:
+      return code || 'unknown error'
+  }
+}
+
+function spawnHint(code: string | undefined, command: string, useWsl: boolean): string {
+  const name = path.basename(command).replace(/\.(cmd|exe)$/i, '')
+  if (useWsl) {
+    return code === 'ENOENT'
+      ? 'Install WSL (wsl --install) or turn off "Run in WSL" for this agent.'
+      : 'Check that the configured WSL distribution exists (wsl --list) and can start.'
+  }
+
+  switch (code) {
+    case 'ENOENT':
+      if (name === 'npx' || name === 'node') return 'Install Node.js (which provides npx) and make sure it is on your PATH.'
+      if (name === 'uvx' || name === 'uv') return 'Install uv (which provides uvx) and make sure it is on your PATH.'
+      return `Check that ${command} exists, or reinstall the agent.`
+    case 'EACCES':
+      return process.platform === 'win32'
+        ? `Check that your account may run ${command}.`
+        : `Make the binary executable: chmod +x "${command}".`
+    case 'EPERM':
+      return process.platform === 'darwin'
+        ? `macOS may have quarantined the binary: xattr -d com.apple.quarantine "${command}".`
+        : `The OS blocked ${command}; check antivirus or security policy settings.`
+    case 'ENOEXEC':
+      return 'The binary may be for a different platform or architecture; reinstall the agent.'
+    default:
+      return 'See the log for details.'
+  }
+}
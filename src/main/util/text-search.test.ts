@@ -0,0 +1,131 @@
+import fs from 'fs'
+import path from 'path'
+import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import type { FileSearchMatch, FileSearchOptions } from '@shared/types/project'
+import { globToRegExp, searchFiles } from './text-search'
+import { git, initRepo, removeTempDirs, tempDir } from '../test/git-repo'
+
+let root: string
+
+beforeEach(() => {
+  root = tempDir('text-search-')
+  const files: Record<string, string> = {
+    'README.md': '# Project\nCall getUser() to start.\n',
+    'src/user.ts': 'export function getUser() {\n  return USER\n}\nconst user = getUser()\n',
+    'src/util/format.ts': 'export const format = (user: string) => `[${user}]`\n',
+    'docs/guide.md': 'Use getuser with care.\n',
+    'node_modules/pkg/index.js': 'getUser()\n'
+  }
+  for (const [file, content] of Object.entries(files)) {
+    fs.mkdirSync(path.dirname(path.join(root, file)), { recursive: true })
+    fs.writeFileSync(path.join(root, file), content)
+  }
+  fs.writeFileSync(path.join(root, 'src/logo.bin'), Buffer.from([0x67, 0x65, 0x74, 0x55, 0x73, 0x65, 0x72, 0x00, 0x01]))
+})
+
+afterEach(() => {
+  removeTempDirs()
+})
+
+/** Run a search to completion; matches sorted by path and line */
+async function search(
+  query: string,
+  options: FileSearchOptions = {},
+  dir = root
+): Promise<{ matches: FileSearchMatch[]; batches: number; matchCount: number; truncated: boolean }> {
+  const matches: FileSearchMatch[] = []
+  let batches = 0
+  const result = await searchFiles(dir, query, options, (batch) => {
+    batches++
+    matches.push(...batch)
+  })
+  matches.sort((a, b) => a.path.localeCompare(b.path) || a.line - b.line)
+  return { matches, batches, ...result }
+}
+
+describe('searchFiles', () => {
+  it('finds literal text case-insensitively by default, skipping ignored and binary files', async () => {
+    const { matches, matchCount, truncated } = await search('getUser()')
+
+    expect(matches).toEqual([
+      { path: 'README.md', line: 2, column: 6, text: 'Call getUser() to start.' },
+      { path: 'src/user.ts', line: 1, column: 17, text: 'export function getUser() {' },
+      { path: 'src/user.ts', line: 4, column: 14, text: 'const user = getUser()' }
+    ])
+    expect(matchCount).toBe(3)
+    expect(truncated).toBe(false)
+  })
+
+  it('honors caseSensitive', async () => {
+    expect((await search('getuser')).matches.map((m) => m.path)).toContain('docs/guide.md')
+    expect((await search('getuser', { caseSensitive: true })).matches.map((m) => m.path)).toEqual(['docs/guide.md'])
+  })
+
+  it('treats the query as a regular expression with regex', async () => {
+    const { matches } = await search('^\\s+return [A-Z]+$', { regex: true, caseSensitive: true })
+
+    expect(matches).toEqual([{ path: 'src/user.ts', line: 2, column: 1, text: '  return USER' }])
+    // Without the flag the same text is literal and matches nothing
+    expect((await search('^\\s+return [A-Z]+$')).matches).toEqual([])
+  })
+
+  it('rejects an invalid regular expression', async () => {
+    await expect(search('(unclosed', { regex: true })).rejects.toThrow('Invalid regular expression')
+  })
+
+  it('only searches files matching the glob', async () => {
+    expect((await search('user', { glob: '*.md' })).matches.map((m) => m.path)).toEqual(['docs/guide.md', 'README.md'])
+    expect((await search('user', { glob: 'src/**/*.ts' })).matches.map((m) => m.path)).toEqual([
+      'src/user.ts',
+      'src/user.ts',
+      'src/user.ts',
+      'src/util/format.ts'
+    ])
+    expect((await search('user', { glob: 'src/*.ts' })).matches.map((m) => m.path)).not.toContain('src/util/format.ts')
+  })
+
+  it('stops at maxResults and reports truncation', async () => {
+    const { matches, matchCount, truncated } = await search('user', { maxResults: 2 })
+
+    expect(matches).toHaveLength(2)
+    expect(matchCount).toBe(2)
+    expect(truncated).toBe(true)
+  })
+
+  it('streams large result sets in batches', async () => {
+    fs.writeFileSync(path.join(root, 'many.txt'), 'needle\n'.repeat(120))
+
+    const { matches, batches } = await search('needle')
+
+    expect(matches).toHaveLength(120)
+    expect(batches).toBe(3)
+  })
+
+  it('honors .gitignore inside a repository', async () => {
+    const repo = initRepo()
+    fs.writeFileSync(path.join(repo, '.gitignore'), 'generated/\n')
+    fs.mkdirSync(path.join(repo, 'generated'))
+    fs.writeFileSync(path.join(repo, 'generated/out.txt'), 'needle\n')
+    fs.writeFileSync(path.join(repo, 'untracked.txt'), 'needle\n')
+    git(repo, 'add', '.gitignore')
+
+    expect((await search('needle', {}, repo)).matches.map((m) => m.path)).toEqual(['untracked.txt'])
+  })
+})
+
+describe('globToRegExp', () => {
+  it.each([
+    ['*.ts', 'src/a.ts', true],
+    ['*.ts', 'a.tsx', false],
+    ['src/*.ts', 'src/a.ts', true],
+    ['src/*.ts', 'src/deep/a.ts', false],
+    ['src/**/*.ts', 'src/a.ts', true],
+    ['src/**/*.ts', 'src/deep/er/a.ts', true],
+    ['*.{md,txt}', 'docs/a.txt', true],
+    ['file?.js', 'file1.js', true],
+    ['file[!0-9].js', 'file1.js', false],
+    ['file[!0-9].js', 'fileA.js', true]
+  ])('%s against %s is %s', (glob, file, expected) => {
+    expect(globToRegExp(glob).test(file)).toBe(expected)
+  })
+})
@@ -0,0 +1,151 @@
+import fs from 'fs'
+import path from 'path'
+import simpleGit from 'simple-git'
+import type { FileSearchMatch, FileSearchOptions } from '@shared/types/project'
+
+const DEFAULT_MAX_RESULTS = 500
+/** Larger files are skipped; they are almost never hand-written source */
+const MAX_FILE_SIZE = 2 * 1024 * 1024
+/** Bytes inspected for a NUL when deciding whether a file is binary */
+const BINARY_SNIFF_BYTES = 8000
+const MAX_LINE_LENGTH = 500
+/** Matches are handed to onBatch in groups of this size */
+const BATCH_SIZE = 50
+
+/** Directories skipped when walking a folder that isn't a git repository */
+const WALK_IGNORED = new Set(['node_modules', '.git', '.next', '__pycache__', '.venv', 'dist', 'build', '.cache'])
+
+/**
+ * Convert a glob to a RegExp over '/'-separated relative paths. Supports *, **,
+ * ?, [...] and {a,b}. A pattern without '/' matches the file name at any depth.
+ */
+export function globToRegExp(glob: string): RegExp {
+  let source = ''
+  let inBraces = false
+  for (let i = 0; i < glob.length; i++) {
+    const ch = glob[i]
+    if (ch === '*') {
+      if (glob[i + 1] === '*') {
+        const slashAfter = glob[i + 2] === '/'
+        source += slashAfter ? '(?:.*/)?' : '.*'
+        i += slashAfter ? 2 : 1
+      } else {
+        source += '[^/]*'
+      }
+    } else if (ch === '?') {
+      source += '[^/]'
+    } else if (ch === '[') {
+      const end = glob.indexOf(']', i + 1)
+      if (end === -1) {
+        source += '\\['
+      } else {
+        source += `[${glob.slice(i + 1, end).replace(/^!/, '^').replace(/\\/g, '\\\\')}]`
+        i = end
+      }
+    } else if (ch === '{') {
+      inBraces = true
+      source += '(?:'
+    } else if (ch === '}' && inBraces) {
+      inBraces = false
+      source += ')'
+    } else if (ch === ',' && inBraces) {
+      source += '|'
+    } else {
+      source += ch.replace(/[.+^$()|\\]/g, '\\$&')
+    }
+  }
+  return new RegExp(glob.includes('/') ? `^${source}$` : `(?:^|/)${source}$`)
+}
+
+/** Files under root, relative with '/' separators, honoring .gitignore inside a repository */
+async function listCandidateFiles(root: string): Promise<string[]> {
+  const git = simpleGit(root)
+  if (await git.checkIsRepo().catch(() => false)) {
+    const output = await git.raw(['ls-files', '-z', '--cached', '--others', '--exclude-standard'])
+    return output.split('\0').filter(Boolean)
+  }
+
+  const files: string[] = []
+  const walk = (dir: string, prefix: string): void => {
+    for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+      if (WALK_IGNORED.has(entry.name)) continue
+      const relative = prefix ? `${prefix}/${entry.name}` : entry.name
+      if (entry.isDirectory()) walk(path.join(dir, entry.name), relative)
+      else if (entry.isFile()) files.push(relative)
+    }
+  }
+  walk(root, '')
+  return files
+}
+
+function buildMatcher(query: string, options: FileSearchOptions): RegExp {
+  const flags = options.caseSensitive ? 'g' : 'gi'
+  if (options.regex) {
+    try {
+      return new RegExp(query, flags)
+    } catch (error) {
+      throw new Error(`Invalid regular expression: ${(error as Error).message}`)
+    }
+  }
+  return new RegExp(query.replace(/[.*+?^${}()|[\]\\]/g, '\\$&'), flags)
+}
+
+/**
+ * Search text files under `root` line by line. Matches are delivered in
+ * batches through `onBatch` while the search runs; `isCancelled` is polled
+ * between files. Binary and very large files are skipped.
+ */
+export async function searchFiles(
+  root: string,
+  query: string,
+  options: FileSearchOptions,
+  onBatch: (matches: FileSearchMatch[]) => void,
+  isCancelled: () => boolean = () => false
+): Promise<{ matchCount: number; truncated: boolean }> {
+  if (!query) return { matchCount: 0, truncated: false }
+
+  const matcher = buildMatcher(query, options)
+  const include = options.glob ? globToRegExp(options.glob) : null
+  const maxResults = options.maxResults ?? DEFAULT_MAX_RESULTS
+
+  let matchCount = 0
+  let batch: FileSearchMatch[] = []
+  const flush = (): void => {
+    if (batch.length > 0) onBatch(batch)
+    batch = []
+  }
+
+  for (const relative of await listCandidateFiles(root)) {
+    if (isCancelled()) break
+    if (include && !include.test(relative)) continue
+
+    let buffer: Buffer
+    try {
+      const fullPath = path.join(root, relative)
+      const stat = await fs.promises.stat(fullPath)
+      if (!stat.isFile() || stat.size > MAX_FILE_SIZE) continue
+      buffer = await fs.promises.readFile(fullPath)
+    } catch {
+      continue // Deleted or unreadable since listing
+    }
+    if (buffer.subarray(0, BINARY_SNIFF_BYTES).includes(0)) continue
+
+    const lines = buffer.toString('utf-8').split(/\r?\n/)
+    for (let i = 0; i < lines.length; i++) {
+      matcher.lastIndex = 0
+      const match = matcher.exec(lines[i])
+      if (!match) continue
+
+      batch.push({ path: relative, line: i + 1, column: match.index + 1, text: lines[i].slice(0, MAX_LINE_LENGTH) })
+      matchCount++
+      if (matchCount >= maxResults) {
+        flush()
+        return { matchCount, truncated: true }
+      }
+      if (batch.length >= BATCH_SIZE) flush()
+    }
+  }
+
+  flush()
+  return { matchCount, truncated: false }
+}
@@ -0,0 +1,73 @@
+import fs from 'fs'
+import path from 'path'
+import { describe, expect, it } from 'vitest'
+import { DEFAULT_WINDOW_STATE, fitToDisplays, loadWindowState, saveWindowState } from './window-state'
+import type { Rect } from './window-state'
+import { getAppDataDir } from './paths'
+
+const primary: Rect = { x: 0, y: 0, width: 1920, height: 1040 }
+// A second display to the left of the primary one
+const secondary: Rect = { x: -1280, y: 0, width: 1280, height: 1000 }
+
+describe('fitToDisplays', () => {
+  it('keeps a window that is on screen', () => {
+    const state = { width: 1400, height: 900, x: 100, y: 50, maximized: true }
+
+    expect(fitToDisplays(state, [primary, secondary])).toEqual(state)
+    expect(fitToDisplays({ ...state, x: -1200 }, [primary, secondary])).toEqual({ ...state, x: -1200 })
+  })
+
+  it('drops the position of a window on a display that is gone', () => {
+    const state = { width: 1000, height: 700, x: -1200, y: 100, maximized: false }
+
+    expect(fitToDisplays(state, [primary])).toEqual({ width: 1000, height: 700, x: undefined, y: undefined, maximized: false })
+  })
+
+  it('drops the position when too little of the title bar is reachable', () => {
+    const base = { width: 1000, height: 700, maximized: false }
+
+    // Only 50px overlap on the right edge
+    expect(fitToDisplays({ ...base, x: 1870, y: 100 }, [primary]).x).toBeUndefined()
+    // Exactly 100px is enough to grab it
+    expect(fitToDisplays({ ...base, x: 1820, y: 100 }, [primary]).x).toBe(1820)
+    // Title bar above the top of the display
+    expect(fitToDisplays({ ...base, x: 100, y: -20 }, [primary]).y).toBeUndefined()
+    // Title bar below the bottom of the display
+    expect(fitToDisplays({ ...base, x: 100, y: 1040 }, [primary]).y).toBeUndefined()
+  })
+
+  it('caps the size to the largest display and honors the minimum size', () => {
+    const state = { width: 3000, height: 2000, x: 0, y: 0, maximized: false }
+
+    expect(fitToDisplays(state, [primary, secondary])).toMatchObject({ width: 1920, height: 1040, x: 0, y: 0 })
+    expect(fitToDisplays({ ...state, width: 200, height: 100 }, [primary], 800, 600)).toMatchObject({ width: 800, height: 600 })
+  })
+
+  it('leaves the state alone when no displays are known', () => {
+    const state = { width: 5000, height: 5000, x: -9000, y: -9000, maximized: false }
+
+    expect(fitToDisplays(state, [])).toBe(state)
+  })
+})
+
+describe('window state file', () => {
+  it('round-trips saved geometry', () => {
+    const state = { width: 1280, height: 800, x: 10, y: 20, maximized: true }
+
+    saveWindowState(state)
+
+    expect(loadWindowState()).toEqual(state)
+  })
+
+  it('falls back to the defaults for a missing or malformed file', () => {
+    const file = path.join(getAppDataDir(), 'window-state.json')
+    fs.rmSync(file, { force: true })
+    expect(loadWindowState()).toEqual(DEFAULT_WINDOW_STATE)
+
+    fs.writeFileSync(file, '{ not json')
+    expect(loadWindowState()).toEqual(DEFAULT_WINDOW_STATE)
+
+    fs.writeFileSync(file, JSON.stringify({ width: '1280', height: 800 }))
+    expect(loadWindowState()).toEqual(DEFAULT_WINDOW_STATE)
+  })
+})
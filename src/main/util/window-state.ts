@@ -0,0 +1,82 @@
+import fs from 'fs'
+import path from 'path'
+import { getAppDataDir } from './paths'
+import { logger } from './logger'
+
+const WINDOW_STATE_FILE_NAME = 'window-state.json'
+
+export interface WindowState {
+  width: number
+  height: number
+  x?: number
+  y?: number
+  maximized: boolean
+}
+
+export interface Rect {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+
+export const DEFAULT_WINDOW_STATE: WindowState = { width: 1400, height: 900, maximized: false }
+
+/** Part of the window's title bar that must stay on a display to be grabbable */
+const MIN_VISIBLE_PX = 100
+
+function getWindowStatePath(): string {
+  return path.join(getAppDataDir(), WINDOW_STATE_FILE_NAME)
+}
+
+/** Saved geometry, or the defaults when missing or unreadable */
+export function loadWindowState(): WindowState {
+  try {
+    const parsed = JSON.parse(fs.readFileSync(getWindowStatePath(), 'utf-8')) as Partial<WindowState>
+    const isNum = (v: unknown): v is number => typeof v === 'number' && Number.isFinite(v)
+    if (!isNum(parsed.width) || !isNum(parsed.height)) return { ...DEFAULT_WINDOW_STATE }
+    return {
+      width: parsed.width,
+      height: parsed.height,
+      x: isNum(parsed.x) ? parsed.x : undefined,
+      y: isNum(parsed.y) ? parsed.y : undefined,
+      maximized: parsed.maximized === true
+    }
+  } catch {
+    return { ...DEFAULT_WINDOW_STATE }
+  }
+}
+
+export function saveWindowState(state: WindowState): void {
+  try {
+    fs.writeFileSync(getWindowStatePath(), JSON.stringify(state, null, 2), 'utf-8')
+  } catch (error) {
+    logger.warn('Failed to write window state:', error)
+  }
+}
+
+/**
+ * Make saved geometry safe for the current displays: the size is capped to
+ * the largest work area, and a position whose title bar would not be
+ * reachable on any display is dropped so the window gets centered instead.
+ */
+export function fitToDisplays(state: WindowState, workAreas: Rect[], minWidth = 0, minHeight = 0): WindowState {
+  if (workAreas.length === 0) return state
+
+  const maxWidth = Math.max(...workAreas.map((a) => a.width))
+  const maxHeight = Math.max(...workAreas.map((a) => a.height))
+  const width = Math.max(minWidth, Math.min(state.width, maxWidth))
+  const height = Math.max(minHeight, Math.min(state.height, maxHeight))
+
+  if (state.x === undefined || state.y === undefined) {
+    return { ...state, width, height, x: undefined, y: undefined }
+  }
+  const { x, y } = state
+
+  // The top strip of the window must overlap some display by a usable amount
+  const visible = workAreas.some((area) => {
+    const overlapX = Math.min(x + width, area.x + area.width) - Math.max(x, area.x)
+    return overlapX >= Math.min(MIN_VISIBLE_PX, width) && y >= area.y && y < area.y + area.height
+  })
+  return visible ? { ...state, width, height } : { ...state, width, height, x: undefined, y: undefined }
+}
@@ -0,0 +1,71 @@
+import { describe, expect, it } from 'vitest'
+import { branchNameError, renderWorktreeTemplate, slugify, worktreeDirError } from './worktree-template'
+
+const date = new Date('2026-03-04T12:00:00Z')
+
+describe('slugify', () => {
+  it('lowercases, strips accents and joins words with dashes', () => {
+    expect(slugify('Fix: the Café bug!')).toBe('fix-the-cafe-bug')
+    expect(slugify('  --Already-Slugged--  ')).toBe('already-slugged')
+  })
+
+  it('caps the length without leaving a trailing dash', () => {
+    expect(slugify('a'.repeat(50))).toHaveLength(40)
+    expect(slugify('abc def ghi', 8)).toBe('abc-def')
+  })
+})
+
+describe('renderWorktreeTemplate', () => {
+  it('expands every placeholder', () => {
+    expect(
+      renderWorktreeTemplate('{workspace}/{date}-{title-slug}-{session}', {
+        session: 'abc123',
+        workspace: 'My Project',
+        title: 'Fix: the Café bug!',
+        date
+      })
+    ).toBe('my-project/2026-03-04-fix-the-cafe-bug-abc123')
+  })
+
+  it('collapses separators left by placeholders without a value', () => {
+    expect(renderWorktreeTemplate('am-{workspace}-{session}', { session: 'abc123' })).toBe('am-abc123')
+    expect(renderWorktreeTemplate('{title-slug}_{session}', { session: 'abc123' })).toBe('abc123')
+  })
+
+  it('leaves unknown placeholders alone', () => {
+    expect(renderWorktreeTemplate('{user}-{session}', { session: 'abc123', date })).toBe('{user}-abc123')
+  })
+})
+
+describe('branchNameError', () => {
+  it.each(['am-abc123', 'feature/login', 'wt/2026-03-04-fix'])('accepts %s', (name) => {
+    expect(branchNameError(name)).toBeNull()
+  })
+
+  it.each([
+    ['', 'empty'],
+    ['has space', 'whitespace'],
+    ['-leading', "starts with '-'"],
+    ['a..b', "'..'"],
+    ['a@{1}', "'@{'"],
+    ['@', "'@{'"],
+    ['x~1', 'forbidden character'],
+    ['what?', 'forbidden character'],
+    ['a//b', 'empty component'],
+    ['a/.hidden', 'empty component'],
+    ['refs.lock', 'empty component'],
+    ['trailing.', "ends with '.'"]
+  ])('rejects %j', (name, reason) => {
+    expect(branchNameError(name)).toContain(reason)
+  })
+})
+
+describe('worktreeDirError', () => {
+  it.each(['thread-abc123', 'my-project/2026-03-04'])('accepts %s', (name) => {
+    expect(worktreeDirError(name)).toBeNull()
+  })
+
+  it.each(['', '/abs/path', 'C:\\abs', '../escape', 'a/./b', 'a:b', 'a|b'])('rejects %j', (name) => {
+    expect(worktreeDirError(name)).not.toBeNull()
+  })
+})
@@ -0,0 +1,60 @@
+export interface WorktreeTemplateContext {
+  session: string
+  workspace?: string
+  title?: string
+  date?: Date
+}
+
+/** Lowercase, dash-separated, ASCII-only form of free text, for use in paths and refs */
+export function slugify(text: string, maxLength = 40): string {
+  return text
+    .normalize('NFKD')
+    .replace(/[\u0300-\u036f]/g, '')
+    .toLowerCase()
+    .replace(/[^a-z0-9]+/g, '-')
+    .slice(0, maxLength)
+    .replace(/^-+|-+$/g, '')
+}
+
+/**
+ * Expand {session}, {workspace}, {date} (YYYY-MM-DD) and {title-slug}.
+ * Placeholders without a value render as empty; doubled separators left behind are collapsed.
+ */
+export function renderWorktreeTemplate(template: string, context: WorktreeTemplateContext): string {
+  const values: Record<string, string> = {
+    session: context.session,
+    workspace: context.workspace ? slugify(context.workspace) : '',
+    date: (context.date ?? new Date()).toISOString().slice(0, 10),
+    'title-slug': context.title ? slugify(context.title) : ''
+  }
+  return template
+    .replace(/\{(session|workspace|date|title-slug)\}/g, (_, key: string) => values[key])
+    .replace(/([-_/])[-_]+/g, '$1')
+    .replace(/^[-_]+|[-_]+$/g, '')
+}
+
+/** Why `name` isn't a legal branch name (see git check-ref-format), or null if it is */
+export function branchNameError(name: string): string | null {
+  if (!name) return 'branch name is empty'
+  if (/\s/.test(name)) return 'branch name contains whitespace'
+  if (name.startsWith('-')) return "branch name starts with '-'"
+  if (name.includes('..')) return "branch name contains '..'"
+  if (name.includes('@{') || name === '@') return "branch name contains '@{' or is '@'"
+  // eslint-disable-next-line no-control-regex
+  if (/[\x00-\x1f\x7f~^:?*[\\]/.test(name)) return 'branch name contains a forbidden character (~ ^ : ? * [ \\)'
+  if (name.split('/').some((part) => !part || part.startsWith('.') || part.endsWith('.lock'))) {
+    return "branch name has an empty component, a component starting with '.' or ending in '.lock'"
+  }
+  if (name.endsWith('.')) return "branch name ends with '.'"
+  return null
+}
+
+/** Why `name` can't be used as a worktree directory under the worktree base, or null */
+export function worktreeDirError(name: string): string | null {
+  if (!name) return 'worktree directory name is empty'
+  if (/^([a-zA-Z]:)?[\\/]/.test(name)) return 'worktree directory must be relative'
+  if (name.split(/[\\/]/).some((part) => part === '..' || part === '.')) return "worktree directory contains '.' or '..'"
+  // eslint-disable-next-line no-control-regex
+  if (/[<>:"|?*\x00-\x1f]/.test(name)) return 'worktree directory contains a character not allowed in file names'
+  return null
+}
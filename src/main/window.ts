@@ -1,17 +1,36 @@
-import { BrowserWindow, shell, app, Menu } from 'electron'
+import { BrowserWindow, shell, app, Menu, screen } from 'electron'
 import { join } from 'path'
+import { MAX_ZOOM_FACTOR, MIN_ZOOM_FACTOR } from '@shared/constants'
+import { settingsService } from './services/settings-service'
+import { DEFAULT_WINDOW_STATE, fitToDisplays, loadWindowState, saveWindowState } from './util/window-state'
 
 let mainWindow: BrowserWindow | null = null
 
+const MIN_WIDTH = 800
+const MIN_HEIGHT = 600
+/** Geometry is written this long after the last move/resize */
+const WINDOW_STATE_SAVE_DELAY_MS = 500
+
+/** Keep a zoom factor within bounds; anything unusable becomes 100% */
+export function clampZoom(factor: number | undefined): number {
+  if (typeof factor !== 'number' || !Number.isFinite(factor)) return 1
+  return Math.max(MIN_ZOOM_FACTOR, Math.min(MAX_ZOOM_FACTOR, factor))
+}
+
 export function createMainWindow(): BrowserWindow {
   // Remove the native application menu on all platforms
   Menu.setApplicationMenu(null)
 
+  const workAreas = screen.getAllDisplays().map((d) => d.workArea)
+  const state = fitToDisplays(loadWindowState(), workAreas, MIN_WIDTH, MIN_HEIGHT)
+
   mainWindow = new BrowserWindow({
-    width: 1400,
-    height: 900,
-    minWidth: 800,
-    minHeight: 600,
+    width: state.width,
+    height: state.height,
+    x: state.x,
+    y: state.y,
+    minWidth: MIN_WIDTH,
+    minHeight: MIN_HEIGHT,
     show: false,
     title: 'AgentManager',
     titleBarStyle: process.platform === 'darwin' ? 'hiddenInset' : 'default',
@@ -21,14 +40,18 @@ export function createMainWindow(): BrowserWindow {
       preload: join(__dirname, '../preload/index.js'),
       sandbox: false,
       contextIsolation: true,
-      nodeIntegration: false
+      nodeIntegration: false,
+      zoomFactor: clampZoom(settingsService.get().general.zoomLevel)
     }
   })
 
   mainWindow.on('ready-to-show', () => {
+    if (state.maximized) mainWindow?.maximize()
     mainWindow?.show()
   })
 
+  trackWindowState(mainWindow)
+
   // Open external links in browser
   mainWindow.webContents.setWindowOpenHandler(({ url }) => {
     shell.openExternal(url)
@@ -46,6 +69,39 @@ export function createMainWindow(): BrowserWindow {
   return mainWindow
 }
 
+/** Save geometry on move/resize (debounced) and immediately on close */
+function trackWindowState(window: BrowserWindow): void {
+  let timer: ReturnType<typeof setTimeout> | null = null
+
+  const save = (): void => {
+    if (timer) clearTimeout(timer)
+    timer = null
+    if (window.isDestroyed() || window.isMinimized() || window.isFullScreen()) return
+    // Keep the restored size while maximized so un-maximizing after a restart works
+    const bounds = window.isMaximized() ? window.getNormalBounds() : window.getBounds()
+    saveWindowState({ ...bounds, maximized: window.isMaximized() })
+  }
+  const scheduleSave = (): void => {
+    if (timer) clearTimeout(timer)
+    timer = setTimeout(save, WINDOW_STATE_SAVE_DELAY_MS)
+  }
+
+  window.on('resize', scheduleSave)
+  window.on('move', scheduleSave)
+  window.on('maximize', scheduleSave)
+  window.on('unmaximize', scheduleSave)
+  window.on('close', save)
+}
+
+/** Return the window to the default size, centered, and save that as its geometry */
+export function resetWindowGeometry(window: BrowserWindow): void {
+  if (window.isFullScreen()) window.setFullScreen(false)
+  if (window.isMaximized()) window.unmaximize()
+  window.setSize(DEFAULT_WINDOW_STATE.width, DEFAULT_WINDOW_STATE.height)
+  window.center()
+  saveWindowState({ ...window.getBounds(), maximized: false })
+}
+
 export function getMainWindow(): BrowserWindow | null {
   return mainWindow
 }
@@ -1,12 +1,28 @@
 import { contextBridge, ipcRenderer } from 'electron'
 import type { IpcChannels, IpcEvents, ElectronAPI } from '@shared/types/ipc'
+import type { IpcResult } from '@shared/types/errors'
+import { decodeIpcError } from '@shared/util/ipc-error'
 
 const api: ElectronAPI = {
   invoke<T extends keyof IpcChannels>(
     channel: T,
     data: IpcChannels[T]['request']
   ): Promise<IpcChannels[T]['response']> {
-    return ipcRenderer.invoke(channel, data)
+    // Rethrow with just the human-readable message; use invokeResult for the code
+    return ipcRenderer.invoke(channel, data).catch((error: unknown) => {
+      throw new Error(decodeIpcError(error).message)
+    })
+  },
+
+  async invokeResult<T extends keyof IpcChannels>(
+    channel: T,
+    data: IpcChannels[T]['request']
+  ): Promise<IpcResult<IpcChannels[T]['response']>> {
+    try {
+      return { ok: true, data: await ipcRenderer.invoke(channel, data) }
+    } catch (error) {
+      return { ok: false, error: decodeIpcError(error) }
+    }
   },
 
   on<T extends keyof IpcEvents>(
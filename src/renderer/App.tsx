@@ -9,8 +9,14 @@ import { useAcpFeaturesStore } from './stores/acp-features-store'
 import { useRouteStore } from './stores/route-store'
 import { useIpcEvent } from './hooks/useIpc'
 import { useTheme } from './hooks/useTheme'
-import type { SessionUpdateEvent, PermissionRequestEvent, PermissionResolvedEvent, WorktreeHookProgressEvent } from '@shared/types/session'
-import type { AgentConnection } from '@shared/types/agent'
+import type {
+  SessionUpdateEvent,
+  PermissionRequestEvent,
+  PermissionResolvedEvent,
+  WorktreeHookProgressEvent,
+  ThreadCacheProgressEvent
+} from '@shared/types/session'
+import type { IpcEvents } from '@shared/types/ipc'
 
 export default function App() {
   const {
@@ -25,7 +31,7 @@ export default function App() {
     activeDraftId,
     draftThread
   } = useSessionStore()
-  const { updateConnectionStatus, loadInstalled, fetchRegistry } = useAgentStore()
+  const { updateConnectionStatus, setLaunchIssue, loadInstalled, fetchRegistry } = useAgentStore()
   const { loadWorkspaces } = useWorkspaceStore()
   const { applyUpdate: applyAcpUpdate } = useAcpFeaturesStore()
   const currentRoute = useRouteStore((s) => s.current)
@@ -65,10 +71,25 @@ export default function App() {
   )
 
   const onAgentStatusChange = useCallback(
-    (event: { connectionId: string; status: AgentConnection['status']; error?: string }) => {
-      updateConnectionStatus(event.connectionId, event.status, event.error)
+    (event: IpcEvents['agent:status-change']) => {
+      if (event.connectionId) {
+        setLaunchIssue(event.agentId, null)
+        updateConnectionStatus(event.connectionId, event.status, event.error, event.hint)
+      } else {
+        setLaunchIssue(event.agentId, { status: event.status, error: event.error, hint: event.hint })
+      }
+    },
+    [updateConnectionStatus, setLaunchIssue]
+  )
+
+  // The thread cache is rebuilt in the background after startup; reload once it settles
+  const onThreadCacheProgress = useCallback(
+    (event: ThreadCacheProgressEvent) => {
+      if (event.phase === 'done') {
+        loadPersistedSessions()
+      }
     },
-    [updateConnectionStatus]
+    [loadPersistedSessions]
   )
 
   useIpcEvent('session:update', onSessionUpdate)
@@ -76,6 +97,7 @@ export default function App() {
   useIpcEvent('session:permission-resolved', onPermissionResolved)
   useIpcEvent('session:hook-progress', onHookProgress)
   useIpcEvent('agent:status-change', onAgentStatusChange)
+  useIpcEvent('thread-cache:progress', onThreadCacheProgress)
 
   // Load installed agents, workspaces, and persisted sessions on startup
   useEffect(() => {
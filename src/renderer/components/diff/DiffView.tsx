@@ -184,10 +184,14 @@ export function DiffView() {
             <div className="flex items-center justify-center h-full bg-surface-1">
               <Spinner size="sm" />
             </div>
+          ) : fileDiff?.isBinary ? (
+            <div className="flex items-center justify-center h-full bg-surface-1 text-text-muted text-sm">
+              Binary file changed
+            </div>
           ) : fileDiff ? (
             <MonacoDiffEditor
-              originalContent={fileDiff.oldContent}
-              modifiedContent={fileDiff.newContent}
+              originalContent={fileDiff.oldContent ?? ''}
+              modifiedContent={fileDiff.newContent ?? ''}
               filePath={diffViewSelectedFile || ''}
               sideBySide={sideBySide}
             />
@@ -1,5 +1,6 @@
 import React, { useState } from 'react'
 import { useWorkspaceStore } from '../../stores/workspace-store'
+import { isSamePath } from '@shared/util/path-util'
 import { Button } from '../common/Button'
 
 export function WorkspaceStep() {
@@ -36,7 +37,7 @@ export function WorkspaceStep() {
   }
 
   const alreadyExists = selectedPath
-    ? workspaces.some((w) => w.path === selectedPath)
+    ? workspaces.some((w) => isSamePath(w.path, selectedPath))
     : false
 
   return (
@@ -1,5 +1,5 @@
-import React, { useState } from 'react'
-import type { AcpRegistryAgent } from '@shared/types/agent'
+import React, { useEffect, useState } from 'react'
+import type { AcpRegistryAgent, AgentInstallability } from '@shared/types/agent'
 import { useAgentStore } from '../../stores/agent-store'
 import { Button } from '../common/Button'
 import { Badge } from '../common/Badge'
@@ -13,6 +13,25 @@ export function AgentCard({ agent }: AgentCardProps) {
   const { isInstalled, installAgent, uninstallAgent } = useAgentStore()
   const installed = isInstalled(agent.id)
   const [loading, setLoading] = useState(false)
+  const [installability, setInstallability] = useState<AgentInstallability>({ installable: true })
+
+  useEffect(() => {
+    if (installed) return
+    let cancelled = false
+    window.api
+      .invoke('registry:agent-installable', { agentId: agent.id })
+      .then((result) => {
+        if (!cancelled) setInstallability(result)
+      })
+      .catch(() => {
+        // Let the install attempt surface any error
+      })
+    return () => {
+      cancelled = true
+    }
+  }, [agent.id, installed])
+
+  const installBlocked = !installed && !installability.installable
 
   const handleToggleInstall = async () => {
     setLoading(true)
@@ -71,6 +90,8 @@ export function AgentCard({ agent }: AgentCardProps) {
           variant={installed ? 'danger' : 'primary'}
           size="sm"
           loading={loading}
+          disabled={installBlocked}
+          title={installBlocked ? installability.reason : undefined}
           onClick={handleToggleInstall}
           className="shrink-0"
         >
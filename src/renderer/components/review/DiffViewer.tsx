@@ -59,10 +59,14 @@ export function DiffViewer() {
 
       {/* Simple inline diff view */}
       <div className="flex-1 overflow-auto font-mono text-xs">
-        <SimpleDiffView
-          oldContent={fileDiff.oldContent}
-          newContent={fileDiff.newContent}
-        />
+        {fileDiff.isBinary ? (
+          <div className="p-4 text-text-muted text-center">Binary file changed</div>
+        ) : (
+          <SimpleDiffView
+            oldContent={fileDiff.oldContent ?? ''}
+            newContent={fileDiff.newContent ?? ''}
+          />
+        )}
       </div>
     </div>
   )
@@ -3,6 +3,7 @@ import React, { useEffect, useState } from 'react'
 import { getApiKeyEnvVarsForAgent } from '@shared/config/agent-env'
 import type { AppSettings, McpServerConfig } from '@shared/types/settings'
 import { DEFAULT_SETTINGS } from '@shared/types/settings'
+import { DEFAULT_WORKTREE_BRANCH_TEMPLATE, DEFAULT_WORKTREE_DIR_TEMPLATE } from '@shared/constants'
 
 import { useRouteStore } from '../../stores/route-store'
 import { useAgentStore } from '../../stores/agent-store'
@@ -322,6 +323,39 @@ export function SettingsView() {
                     />
                   </SettingsField>
 
+                  <SettingsField
+                    label="Worktree Directory Name"
+                    description="Placeholders: {session}, {workspace}, {date}, {title-slug}"
+                  >
+                    <input
+                      type="text"
+                      value={settings.git.worktreeDirTemplate || ''}
+                      onChange={(e) =>
+                        setSettings({
+                          ...settings,
+                          git: { ...settings.git, worktreeDirTemplate: e.target.value || undefined }
+                        })
+                      }
+                      placeholder={DEFAULT_WORKTREE_DIR_TEMPLATE}
+                      className="bg-surface-2 border border-border rounded px-2 py-1 text-sm text-text-primary flex-1"
+                    />
+                  </SettingsField>
+
+                  <SettingsField label="Worktree Branch Name" description="Same placeholders as the directory name">
+                    <input
+                      type="text"
+                      value={settings.git.worktreeBranchTemplate || ''}
+                      onChange={(e) =>
+                        setSettings({
+                          ...settings,
+                          git: { ...settings.git, worktreeBranchTemplate: e.target.value || undefined }
+                        })
+                      }
+                      placeholder={DEFAULT_WORKTREE_BRANCH_TEMPLATE}
+                      className="bg-surface-2 border border-border rounded px-2 py-1 text-sm text-text-primary flex-1"
+                    />
+                  </SettingsField>
+
                   <SettingsField label="Commit Prefix">
                     <input
                       type="text"
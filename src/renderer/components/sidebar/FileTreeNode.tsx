@@ -27,8 +27,21 @@ const FILE_ICONS: Record<string, string> = {
 
 export function FileTreeNode({ node, depth }: FileTreeNodeProps) {
   const [expanded, setExpanded] = useState(depth < 1)
+  const [loadedChildren, setLoadedChildren] = useState<FileTreeNodeType[] | null>(null)
+  const children = node.children ?? loadedChildren
 
   const isDir = node.type === 'directory'
+
+  const toggle = () => {
+    if (!isDir) return
+    setExpanded(!expanded)
+    if (!expanded && !children && node.hasChildren) {
+      window.api
+        .invoke('file:read-dir', { dirPath: node.path })
+        .then(setLoadedChildren)
+        .catch(() => setLoadedChildren([]))
+    }
+  }
   const icon = isDir
     ? expanded
       ? '📂'
@@ -38,7 +51,7 @@ export function FileTreeNode({ node, depth }: FileTreeNodeProps) {
   return (
     <div>
       <button
-        onClick={() => isDir && setExpanded(!expanded)}
+        onClick={toggle}
         className="w-full flex items-center gap-1 px-2 py-0.5 text-xs hover:bg-surface-2 transition-colors text-text-secondary hover:text-text-primary"
         style={{ paddingLeft: `${depth * 16 + 8}px` }}
       >
@@ -57,9 +70,9 @@ export function FileTreeNode({ node, depth }: FileTreeNodeProps) {
         <span className="truncate">{node.name}</span>
       </button>
 
-      {isDir && expanded && node.children && (
+      {isDir && expanded && children && (
         <div>
-          {node.children.map((child) => (
+          {children.map((child) => (
             <FileTreeNode key={child.path} node={child} depth={depth + 1} />
           ))}
         </div>
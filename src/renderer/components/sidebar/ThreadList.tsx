@@ -16,7 +16,8 @@ const statusDotColors: Record<string, string> = {
   error: 'bg-error',
   creating: 'bg-warning animate-pulse',
   initializing: 'bg-warning animate-pulse',
-  cancelled: 'bg-text-muted/60'
+  cancelled: 'bg-text-muted/60',
+  disconnected: 'bg-text-muted/60'
 }
 
 function SessionIcon({
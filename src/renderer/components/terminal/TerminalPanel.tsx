@@ -13,12 +13,13 @@ export function TerminalPanel() {
   const [terminalId, setTerminalId] = useState<string | null>(null)
   const [output, setOutput] = useState('')
   const [input, setInput] = useState('')
+  const [exitCode, setExitCode] = useState<number | null>(null)
   const outputRef = useRef<HTMLPreElement>(null)
   const inputRef = useRef<HTMLInputElement>(null)
 
   // Create terminal when session is active
   useEffect(() => {
-    if (activeSession && !terminalId) {
+    if (activeSession && !terminalId && exitCode === null) {
       window.api
         .invoke('terminal:create', {
           cwd: activeSession.workingDir,
@@ -29,7 +30,7 @@ export function TerminalPanel() {
           setOutput(`Terminal error: ${err.message}\n`)
         })
     }
-  }, [activeSession, terminalId])
+  }, [activeSession, terminalId, exitCode])
 
   // Listen for terminal output
   const handleTerminalData = useCallback(
@@ -43,6 +44,20 @@ export function TerminalPanel() {
 
   useIpcEvent('terminal:data', handleTerminalData)
 
+  // Keep the output but stop using the dead terminal until restarted
+  const handleTerminalExit = useCallback(
+    (data: { terminalId: string; exitCode: number }) => {
+      if (data.terminalId === terminalId) {
+        setOutput((prev) => prev + `\n[Process exited with code ${data.exitCode}]\n`)
+        setTerminalId(null)
+        setExitCode(data.exitCode)
+      }
+    },
+    [terminalId]
+  )
+
+  useIpcEvent('terminal:exit', handleTerminalExit)
+
   // Auto-scroll output
   useEffect(() => {
     if (outputRef.current) {
@@ -72,6 +87,18 @@ export function TerminalPanel() {
         <span className="text-xs text-text-secondary font-mono">Terminal</span>
         <span className="text-[10px] text-text-muted ml-2">{activeSession.workingDir}</span>
         <div className="flex-1" />
+        {exitCode !== null && (
+          <Button
+            variant="ghost"
+            size="sm"
+            onClick={() => {
+              setOutput('')
+              setExitCode(null)
+            }}
+          >
+            Restart
+          </Button>
+        )}
         {terminalId && (
           <Button
             variant="ghost"
@@ -1,10 +1,17 @@
 import React, { useMemo, useState } from 'react'
 import ReactMarkdown from 'react-markdown'
 import rehypeSanitize from 'rehype-sanitize'
-import type { Message, ContentBlock, PermissionRequestEvent, ToolCallInfo } from '@shared/types/session'
+import type { Message, ContentBlock, PermissionRequestEvent, StopReason, ToolCallInfo } from '@shared/types/session'
 import { ToolCallCard } from './ToolCallCard'
 import { useSessionStore } from '../../stores/session-store'
 
+const STOP_REASON_NOTES: Partial<Record<StopReason, string>> = {
+  refusal: 'The agent declined to respond to this request.',
+  cancelled: 'Cancelled. Tool calls that were still running are marked as failed.',
+  max_tokens: 'Stopped: the response reached the token limit.',
+  max_turn_requests: 'Stopped: the agent reached its turn limit.'
+}
+
 interface MessageBubbleProps {
   message: Message
   sessionId: string
@@ -279,6 +286,15 @@ export function MessageBubble({ message, sessionId, workingDir }: MessageBubbleP
           <span className="inline-block w-2 h-4 bg-accent animate-pulse rounded-sm ml-1" />
         )}
 
+        {/* Turns that didn't end normally */}
+        {!isUser && message.stopReason && STOP_REASON_NOTES[message.stopReason] && (
+          <div
+            className={`text-xs mt-1 px-1 ${message.stopReason === 'refusal' ? 'text-error' : 'text-warning'}`}
+          >
+            {STOP_REASON_NOTES[message.stopReason]}
+          </div>
+        )}
+
         {/* Timestamp */}
         <div className="text-[10px] text-text-muted mt-1 px-1">
           {new Date(message.timestamp).toLocaleTimeString()}
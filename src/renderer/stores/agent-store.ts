@@ -6,7 +6,8 @@ import type {
   AgentConnection,
   AgentAuthCheckResult,
   AgentModelCatalog,
-  AgentModeCatalog
+  AgentModeCatalog,
+  AgentStatus
 } from '@shared/types/agent'
 
 function sanitizeAgentCheckErrorMessage(message: string): string {
@@ -47,6 +48,8 @@ interface AgentState {
 
   // Active connections
   connections: AgentConnection[]
+  /** Latest failure of an agent that never got a connection, e.g. a missing dependency */
+  launchIssuesByAgent: Record<string, { status: AgentStatus; error?: string; hint?: string }>
   modelsByAgent: Record<string, AgentModelCatalog>
   modelsLoadingByAgent: Record<string, boolean>
   modelErrorsByAgent: Record<string, string>
@@ -64,7 +67,8 @@ interface AgentState {
   terminateAgent: (connectionId: string) => Promise<void>
   logoutAgent: (connectionId: string) => Promise<void>
   authenticateAgent: (connectionId: string, method: string, credentials?: Record<string, string>) => Promise<void>
-  updateConnectionStatus: (connectionId: string, status: AgentConnection['status'], error?: string) => void
+  updateConnectionStatus: (connectionId: string, status: AgentConnection['status'], error?: string, hint?: string) => void
+  setLaunchIssue: (agentId: string, issue: { status: AgentStatus; error?: string; hint?: string } | null) => void
   loadAgentModels: (agentId: string, projectPath: string) => Promise<AgentModelCatalog>
   refreshAgentModels: (agentId: string, projectPath: string) => Promise<AgentModelCatalog>
   loadAgentModes: (agentId: string, projectPath: string) => Promise<AgentModeCatalog>
@@ -82,6 +86,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
   authChecks: {},
   authCheckErrors: {},
   connections: [],
+  launchIssuesByAgent: {},
   modelsByAgent: {},
   modelsLoadingByAgent: {},
   modelErrorsByAgent: {},
@@ -204,14 +209,26 @@ export const useAgentStore = create<AgentState>((set, get) => ({
     }))
   },
 
-  updateConnectionStatus: (connectionId, status, error) => {
+  updateConnectionStatus: (connectionId, status, error, hint) => {
     set((state) => ({
       connections: state.connections.map((c) =>
-        c.connectionId === connectionId ? { ...c, status, error } : c
+        c.connectionId === connectionId ? { ...c, status, error, hint } : c
       )
     }))
   },
 
+  setLaunchIssue: (agentId, issue) => {
+    set((state) => {
+      const launchIssuesByAgent = { ...state.launchIssuesByAgent }
+      if (issue) {
+        launchIssuesByAgent[agentId] = issue
+      } else {
+        delete launchIssuesByAgent[agentId]
+      }
+      return { launchIssuesByAgent }
+    })
+  },
+
   loadAgentModels: async (agentId, projectPath) => {
     const cached = get().modelsByAgent[agentId]
     if (cached && cached.availableModels.length > 0) return cached
@@ -43,7 +43,8 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
 
     set({ fileTreeLoading: true })
     try {
-      const tree = await window.api.invoke('file:read-tree', { dirPath: project.path, depth: 3 })
+      // Top-level directories start expanded; deeper ones load when opened
+      const tree = await window.api.invoke('file:read-tree', { dirPath: project.path, depth: 2, lazy: true })
       set({ fileTree: tree, fileTreeLoading: false })
     } catch {
       set({ fileTreeLoading: false })
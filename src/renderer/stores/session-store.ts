@@ -15,6 +15,7 @@ import type {
   InteractionMode,
   ImageContent
 } from '@shared/types/session'
+import { applyStopReason } from '@shared/util/session-util'
 import { useWorkspaceStore } from './workspace-store'
 import { useProjectStore } from './project-store'
 import { useAgentStore } from './agent-store'
@@ -141,7 +142,7 @@ async function processPromptQueue(set: SetFn, get: GetFn, sessionId: string): Pr
       const effectiveMode = next.mode ?? currentSession?.interactionMode
 
       try {
-        await window.api.invoke('session:prompt', {
+        const result = await window.api.invoke('session:prompt', {
           sessionId,
           content: next.content,
           mode: effectiveMode
@@ -153,18 +154,7 @@ async function processPromptQueue(set: SetFn, get: GetFn, sessionId: string): Pr
         set((state) => ({
           sessions: state.sessions.map((s) => {
             if (s.sessionId !== sessionId) return s
-            const messages = s.messages.map((m) => {
-              const hasOpenToolCalls = m.toolCalls?.some((tc) => isOpenToolCallStatus(tc.status)) ?? false
-              if (!m.isStreaming && !hasOpenToolCalls) return m
-              return {
-                ...m,
-                isStreaming: false,
-                toolCalls: m.toolCalls?.map((tc) => ({
-                  ...tc,
-                  status: isOpenToolCallStatus(tc.status) ? ('completed' as const) : tc.status
-                }))
-              }
-            })
+            const messages = applyStopReason(s.messages, result.stopReason)
             return {
               ...s,
               status: hasPendingItems ? ('prompting' as const) : ('active' as const),
@@ -424,7 +414,11 @@ export const useSessionStore = create<SessionState>((set, get) => ({
         }
 
         // Proactively reconnect idle/disconnected sessions so the agent is ready
-        if (session.status === 'idle' && !session.connectionId && !reconnectingIds.has(sessionId)) {
+        if (
+          (session.status === 'idle' || session.status === 'disconnected') &&
+          !session.connectionId &&
+          !reconnectingIds.has(sessionId)
+        ) {
           reconnectingIds.add(sessionId)
           window.api.invoke('session:ensure-connected', { sessionId })
             .catch((err) => {
@@ -13,13 +13,14 @@ interface WorkspaceState {
   toggleExpanded: (id: string) => void
   openInVSCode: (path: string) => Promise<void>
   touchWorkspace: (id: string) => Promise<void>
+  resumeSessions: (id: string) => Promise<string[]>
   updateWorkspace: (
     id: string,
     updates: Partial<Pick<WorkspaceInfo, 'name' | 'lastAccessedAt' | 'defaultAgentId' | 'defaultModelId' | 'defaultInteractionMode' | 'defaultUseWorktree'>>
   ) => Promise<WorkspaceInfo>
 }
 
-export const useWorkspaceStore = create<WorkspaceState>((set) => ({
+export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
   workspaces: [],
   expandedWorkspaceIds: {},
   loading: false,
@@ -49,6 +50,7 @@ export const useWorkspaceStore = create<WorkspaceState>((set) => ({
         [workspace.id]: true
       }
     }))
+    void get().resumeSessions(workspace.id)
     return workspace
   },
 
@@ -86,6 +88,19 @@ export const useWorkspaceStore = create<WorkspaceState>((set) => ({
     }
   },
 
+  resumeSessions: async (id) => {
+    try {
+      const settings = await window.api.invoke('settings:get', undefined)
+      // Opt-in only: resuming launches one agent process per thread
+      if (!settings.general.resumeSessionsOnOpen) return []
+      const max = settings.general.resumeSessionsMax ?? 3
+      return await window.api.invoke('workspace:resume-sessions', { workspaceId: id, max })
+    } catch (error) {
+      console.warn('[workspace-store] Failed to resume workspace sessions:', error)
+      return []
+    }
+  },
+
   updateWorkspace: async (id, updates) => {
     const updated = await window.api.invoke('workspace:update', { id, updates })
     set((state) => ({
@@ -8,6 +8,7 @@ export const ACP_REGISTRY_URL =
 export const ACP_CDN_URL = 'https://cdn.agentclientprotocol.com'
 
 export const REGISTRY_CACHE_TTL_MS = 60 * 60 * 1000 // 1 hour
+export const TERMINAL_SCROLLBACK_LINES = 10000
 
 export function getAgentIconUrl(agentId: string, icon?: string): string | undefined {
   if (!icon) return undefined
@@ -19,6 +20,14 @@ export const APP_NAME = 'AgentManager'
 
 export const DEFAULT_WORKTREE_PREFIX = 'am-'
 
+/** Worktree naming when git.worktreeDirTemplate / worktreeBranchTemplate are unset */
+export const DEFAULT_WORKTREE_DIR_TEMPLATE = 'thread-{session}'
+export const DEFAULT_WORKTREE_BRANCH_TEMPLATE = `${DEFAULT_WORKTREE_PREFIX}{session}`
+
+/** Window zoom factor bounds (1 = 100%) */
+export const MIN_ZOOM_FACTOR = 0.5
+export const MAX_ZOOM_FACTOR = 3
+
 export const ACP_PROTOCOL_VERSION = 1
 
 export const CLIENT_INFO = {
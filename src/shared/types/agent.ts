@@ -7,6 +7,70 @@ export interface AcpRegistry {
   version: string
   agents: AcpRegistryAgent[]
   extensions: unknown[]
+  /** Sources that failed while others succeeded (merged registries only) */
+  sourceErrors?: RegistrySourceError[]
+  /** How the registry was obtained; set by the app, not part of the registry file */
+  _meta?: RegistryFetchMeta
+}
+
+export interface RegistryFetchMeta {
+  /** No registry body was downloaded: served from cache or revalidated with a 304 */
+  fromCache: boolean
+  /** Age of the oldest source's data, measured from its last fetch or revalidation */
+  cacheAgeMs: number
+  /** A source couldn't be reached and an expired cached copy was used instead */
+  stale?: boolean
+}
+
+export interface RegistrySourceError {
+  source: string
+  error: string
+}
+
+/** Registry agent plus platform availability resolved for the running host */
+export interface AcpRegistryAgentDetails extends AcpRegistryAgent {
+  /** Platforms with a binary build (empty for npx/uvx-only agents) */
+  binaryPlatforms: PlatformTarget[]
+  /** Whether the agent can be installed on the current platform */
+  availableOnCurrentPlatform: boolean
+}
+
+export interface RegistrySearchFilters {
+  distribution?: keyof AgentDistribution
+  /** Exact SPDX id, compared case-insensitively */
+  license?: string
+}
+
+/** Whether an agent can be installed on the running platform */
+export interface AgentInstallability {
+  installable: boolean
+  /** Human-readable reason when not installable */
+  reason?: string
+}
+
+/** ACP extension listed in the registry (minimal shape; unknown fields are kept as-is) */
+export interface AcpRegistryExtension {
+  id: string
+  name: string
+  description: string
+  /** Agent ids the extension attaches to; absent means any agent */
+  agents?: string[]
+  /** Added to the agent's environment at launch */
+  env?: Record<string, string>
+  /** Appended to the agent's command line at launch */
+  args?: string[]
+  /** MCP servers added to every session of the agent */
+  mcpServers?: RegistryExtensionMcpServer[]
+  [key: string]: unknown
+}
+
+export interface RegistryExtensionMcpServer {
+  name: string
+  transport?: 'stdio' | 'http' | 'sse'
+  command?: string
+  args?: string[]
+  url?: string
+  env?: Record<string, string>
 }
 
 export interface AcpRegistryAgent {
@@ -19,6 +83,8 @@ export interface AcpRegistryAgent {
   license: string
   icon: string
   distribution: AgentDistribution
+  /** Registry URL the entry came from, when several registry sources are configured */
+  source?: string
 }
 
 export interface AgentDistribution {
@@ -52,6 +118,8 @@ export interface BinaryTarget {
   archive: string
   cmd: string
   args?: string[]
+  /** Expected SHA-256 of the archive (hex). Downloads are rejected on mismatch. */
+  sha256?: string
 }
 
 /** Tracked installed agent */
@@ -68,9 +136,24 @@ export interface InstalledAgent {
   icon: string
   authors: string[]
   license: string
+  /** Capabilities reported by the agent on its last successful initialize */
+  capabilities?: AgentCapabilities
+  /** Auth methods reported by the agent on its last successful initialize */
+  authMethods?: AuthMethod[]
+  /** When capabilities/authMethods were last refreshed */
+  capabilitiesUpdatedAt?: string
 }
 
-export type AgentStatus = 'idle' | 'launching' | 'connected' | 'authenticating' | 'error' | 'terminated'
+export type AgentStatus =
+  | 'idle'
+  | 'launching'
+  | 'connected'
+  | 'authenticating'
+  | 'error'
+  | 'terminated'
+  | 'crashed'
+  | 'failed'
+  | 'missing-dependency'
 
 export interface AgentConnection {
   connectionId: string
@@ -82,6 +165,46 @@ export interface AgentConnection {
   capabilities?: AgentCapabilities
   authMethods?: AuthMethod[]
   error?: string
+  /** Suggested fix accompanying `error`, e.g. for a failed spawn */
+  hint?: string
+  /** Number of automatic respawns after unexpected exits */
+  restartCount?: number
+}
+
+/** OS process details for a live agent connection */
+export interface AgentProcessInfo {
+  connectionId: string
+  pid?: number
+  startedAt: string
+  /** Milliseconds since the process was spawned */
+  uptimeMs: number
+  running: boolean
+}
+
+/** Installed vs. registry version of one agent */
+export interface AgentUpdateInfo {
+  agentId: string
+  installedVersion: string
+  latestVersion: string
+  updateAvailable: boolean
+}
+
+/** Progress of a binary agent archive download */
+export interface AgentDownloadProgress {
+  agentId: string
+  downloadedBytes: number
+  /** From Content-Length; undefined when the server doesn't send it */
+  totalBytes?: number
+}
+
+/** Latest CPU/memory sample for a running agent process */
+export interface AgentResourceUsage {
+  connectionId: string
+  pid: number
+  /** CPU usage over the last sample interval, as a percentage of one core */
+  cpuPercent: number
+  memoryBytes: number
+  sampledAt: string
 }
 
 /**
@@ -0,0 +1,35 @@
+// ============================================================
+// Error Types (structured errors returned over IPC)
+// ============================================================
+
+/** Stable error kinds the renderer can branch on */
+export type AppErrorCode =
+  | 'SESSION_NOT_FOUND'
+  | 'MESSAGE_NOT_FOUND'
+  | 'COMMAND_NOT_FOUND'
+  | 'MCP_SERVER_NOT_FOUND'
+  | 'INVALID_MCP_SERVER'
+  | 'AGENT_NOT_FOUND'
+  | 'NOT_CONNECTED'
+  | 'NOT_AUTHENTICATED'
+  | 'PROMPT_IN_PROGRESS'
+  | 'NOTHING_TO_RETRY'
+  | 'INVALID_ATTACHMENT'
+  | 'AGENT_SPAWN_FAILED'
+  | 'MISSING_DEPENDENCY'
+  | 'GIT_CONFLICT'
+  | 'TIMEOUT'
+  | 'ACP_ERROR'
+  | 'INTERNAL'
+
+export interface SerializedAppError {
+  code: AppErrorCode
+  message: string
+  /** What the error is about, e.g. the failing session or connection id */
+  context?: Record<string, string>
+  /** Kind-specific data, e.g. the conflicted files of a GIT_CONFLICT */
+  details?: Record<string, unknown>
+}
+
+/** Outcome of ElectronAPI.invokeResult: the response, or the structured error */
+export type IpcResult<T> = { ok: true; data: T } | { ok: false; error: SerializedAppError }
@@ -8,6 +8,10 @@ export interface GitStatus {
   staged: string[]
   modified: string[]
   untracked: string[]
+  /** Unmerged paths (UU, AA, DD, AU, UA, DU, UD); not repeated in staged/modified */
+  conflicted: string[]
+  /** A merge is in progress (MERGE_HEAD exists) */
+  inMerge: boolean
   ahead: number
   behind: number
 }
@@ -26,3 +30,52 @@ export interface CommitResult {
   message: string
   branch: string
 }
+
+export interface CreateBranchResult {
+  branch: string
+  checkedOut: boolean
+}
+
+export interface CommitEntry {
+  hash: string
+  author: string
+  authorEmail: string
+  /** ISO 8601 author date */
+  date: string
+  /** e.g. "3 hours ago" */
+  relativeDate: string
+  subject: string
+  /** Not on the upstream branch yet (or on no remote at all when there is no upstream) */
+  unpushed: boolean
+}
+
+export interface StashEntry {
+  /** N in stash@{N} */
+  index: number
+  hash: string
+  /** ISO 8601 date the stash was created */
+  date: string
+  /** Branch the changes were stashed from */
+  branch?: string
+  message: string
+}
+
+export type MergeStrategy = 'ff-only' | 'merge' | 'squash'
+
+export interface MergeResult {
+  branch: string
+  into: string
+  strategy: MergeStrategy
+  /** HEAD of `into` after the merge */
+  commit: string
+}
+
+export interface PushResult {
+  branch: string
+  remote: string
+  remoteUrl?: string
+  /** Whether this push set the branch's upstream */
+  upstreamSet: boolean
+  /** Link to open a pull/merge request for the branch, for GitHub/GitLab/Bitbucket remotes */
+  pullRequestUrl?: string
+}
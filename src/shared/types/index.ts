@@ -6,3 +6,4 @@ export * from './settings'
 export * from './workspace'
 export * from './ipc'
 export * from './thread-format'
+export * from './errors'
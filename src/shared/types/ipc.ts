@@ -5,17 +5,27 @@
 
 import type {
   AcpRegistry,
+  AcpRegistryAgent,
+  AcpRegistryAgentDetails,
+  AcpRegistryExtension,
+  RegistrySearchFilters,
   InstalledAgent,
   AgentConnection,
   AgentAuthCheckResult,
+  AgentDownloadProgress,
+  AgentInstallability,
   AgentModelCatalog,
-  AgentModeCatalog
+  AgentProcessInfo,
+  AgentResourceUsage,
+  AgentModeCatalog,
+  AgentUpdateInfo
 } from './agent'
 import type {
   SessionInfo,
   PersistedThread,
   CreateSessionRequest,
   PromptResult,
+  ActivePrompt,
   SessionUpdateEvent,
   PermissionRequestEvent,
   PermissionResponse,
@@ -24,27 +34,74 @@ import type {
   InteractionMode,
   WorktreeHookProgressEvent,
   ConfigOption,
-  ContentBlock
+  ContentBlock,
+  FileAccessEvent,
+  AutoCommitEvent,
+  ThreadCacheProgressEvent,
+  TerminalInfo,
+  PromptQueuedEvent,
+  PromptDequeuedEvent,
+  SessionUsage,
+  UsageCost,
+  BudgetWarningEvent,
+  MessagesChangedEvent,
+  Message,
+  SlashCommand
 } from './session'
 import type { AgentProjectConfig } from './thread-format'
-import type { ProjectInfo, FileTreeNode, FileChange, DiffResult } from './project'
-import type { GitStatus, WorktreeInfo, CommitResult } from './git'
-import type { AppSettings } from './settings'
-import type { WorkspaceInfo } from './workspace'
+import type { IpcResult } from './errors'
+import type {
+  ProjectInfo,
+  FileTreeNode,
+  FileChange,
+  DiffResult,
+  FileSearchOptions,
+  FileSearchResultEvent,
+  FileSearchDoneEvent
+} from './project'
+import type {
+  GitStatus,
+  WorktreeInfo,
+  CommitResult,
+  CommitEntry,
+  CreateBranchResult,
+  MergeResult,
+  MergeStrategy,
+  PushResult,
+  StashEntry
+} from './git'
+import type { AppSettings, BackupSummary, LogLevel, McpServerConfig } from './settings'
+import type { WorkspaceInfo, WorkspaceResumeProgressEvent } from './workspace'
 
 // ============================================================
 // Request/Response channels (ipcMain.handle / ipcRenderer.invoke)
 // ============================================================
 export interface IpcChannels {
   // --- Registry ---
-  'registry:fetch': { request: void; response: AcpRegistry }
+  'registry:fetch': { request: { force?: boolean } | void; response: AcpRegistry }
   'registry:get-cached': { request: void; response: AcpRegistry | null }
+  'registry:search': { request: { query: string; filters?: RegistrySearchFilters }; response: AcpRegistryAgent[] }
+  'registry:clear-icon-cache': { request: void; response: void }
+  'registry:refresh': { request: void; response: AcpRegistry }
+  'registry:set-sources': { request: { sources: string[] }; response: AcpRegistry }
+  'registry:get-sources': { request: void; response: string[] }
   'registry:get-icon-svg': { request: { agentId: string; icon?: string }; response: string | null }
+  'registry:list-extensions': { request: void; response: AcpRegistryExtension[] }
+  'registry:get-agent': { request: { agentId: string }; response: AcpRegistryAgentDetails | null }
+  'registry:agent-installable': { request: { agentId: string }; response: AgentInstallability }
 
   // --- Agent Management ---
   'agent:install': { request: { agentId: string }; response: InstalledAgent }
   'agent:uninstall': { request: { agentId: string }; response: void }
+  'agent:install-extension': { request: { agentId: string; extensionId: string }; response: string[] }
+  'agent:uninstall-extension': { request: { agentId: string; extensionId: string }; response: string[] }
+  'agent:switch-distribution': {
+    request: { agentId: string; distributionType: InstalledAgent['distributionType'] }
+    response: InstalledAgent
+  }
   'agent:list-installed': { request: void; response: InstalledAgent[] }
+  'agent:check-updates': { request: void; response: AgentUpdateInfo[] }
+  'agent:update': { request: { agentId: string }; response: InstalledAgent }
   'agent:launch': { request: { agentId: string; projectPath: string; extraEnv?: Record<string, string> }; response: AgentConnection }
   'agent:check-auth': { request: { agentId: string; projectPath?: string }; response: AgentAuthCheckResult }
   'agent:terminate': { request: { connectionId: string }; response: void }
@@ -54,18 +111,53 @@ export interface IpcChannels {
   }
   'agent:logout': { request: { connectionId: string }; response: void }
   'agent:list-connections': { request: void; response: AgentConnection[] }
+  'agent:get-process-info': { request: { connectionId: string }; response: AgentProcessInfo | null }
+  'agent:get-resource-usage': { request: { connectionId: string }; response: AgentResourceUsage | null }
   'agent:get-models': { request: { agentId: string; projectPath: string; forceRefresh?: boolean }; response: AgentModelCatalog }
   'agent:get-modes': { request: { agentId: string; projectPath: string }; response: AgentModeCatalog }
 
   // --- Sessions ---
   'session:create': { request: CreateSessionRequest; response: SessionInfo }
-  'session:prompt': { request: { sessionId: string; content: ContentBlock[]; mode?: InteractionMode }; response: PromptResult }
+  'session:prompt': {
+    request: { sessionId: string; content: ContentBlock[]; mode?: InteractionMode; attachments?: string[] }
+    response: PromptResult
+  }
+  'session:retry': { request: { sessionId: string }; response: PromptResult }
+  'session:get-commands': { request: { sessionId: string }; response: SlashCommand[] }
+  'session:run-command': { request: { sessionId: string; name: string; args?: string }; response: PromptResult }
   'session:cancel': { request: { sessionId: string }; response: void }
+  'session:cancel-all': { request: void; response: string[] }
+  'session:list-active-prompts': { request: void; response: ActivePrompt[] }
+  'session:clear-queue': { request: { sessionId: string }; response: number }
+  'session:set-system-prompt': { request: { sessionId: string; systemPrompt: string | null }; response: void }
+  'session:edit-message': {
+    request: { sessionId: string; messageId: string; content: ContentBlock[] }
+    response: Message[]
+  }
+  'session:delete-message': {
+    request: { sessionId: string; messageId: string; rewind?: boolean }
+    response: Message[]
+  }
+  'session:get-usage': {
+    request: { sessionId: string }
+    response: { usage: SessionUsage | null; totalCost: UsageCost | null }
+  }
   'session:list': { request: void; response: SessionInfo[] }
+  'session:get': { request: { sessionId: string }; response: SessionInfo | null }
+  'session:sync': { request: void; response: SessionInfo[] }
+  'session:get-metadata': { request: { sessionId: string }; response: Record<string, unknown> }
+  'session:set-metadata': {
+    request: { sessionId: string; metadata: Record<string, unknown> }
+    response: Record<string, unknown>
+  }
   'session:list-persisted': { request: void; response: PersistedThread[] }
-  'session:remove': { request: { sessionId: string; cleanupWorktree: boolean }; response: void }
+  'session:remove': {
+    request: { sessionId: string; cleanupWorktree: boolean; mergeInto?: { branch: string; strategy?: MergeStrategy } }
+    response: void
+  }
+  'session:close': { request: { sessionId: string; terminateConnection: boolean }; response: void }
   'session:permission-response': { request: PermissionResponse; response: void }
-  'session:rebuild-cache': { request: void; response: { threadCount: number } }
+  'session:rebuild-cache': { request: { workspaceId?: string } | void; response: { threadCount: number } }
   'session:set-mode': { request: { sessionId: string; modeId: string }; response: void }
   'session:set-interaction-mode': { request: { sessionId: string; mode: InteractionMode }; response: void }
   'session:rename': { request: { sessionId: string; title: string }; response: void }
@@ -76,8 +168,20 @@ export interface IpcChannels {
   'session:ensure-connected': { request: { sessionId: string }; response: { connectionId: string } }
 
   // --- Files ---
-  'file:read-tree': { request: { dirPath: string; depth?: number }; response: FileTreeNode[] }
+  'file:read-tree': {
+    request: { dirPath: string; depth?: number; lazy?: boolean; includeMetadata?: boolean }
+    response: FileTreeNode[]
+  }
+  'file:read-dir': { request: { dirPath: string; includeMetadata?: boolean }; response: FileTreeNode[] }
+  'file:search': {
+    request: { searchId: string; root: string; query: string; options?: FileSearchOptions }
+    response: FileSearchDoneEvent
+  }
   'file:read': { request: { filePath: string }; response: string }
+  'file:write': {
+    request: { filePath: string; content: string; createDirs?: boolean; root?: string }
+    response: void
+  }
   'file:get-changes': { request: { workingDir: string }; response: FileChange[] }
 
   // --- Project ---
@@ -92,12 +196,48 @@ export interface IpcChannels {
   }
   'git:remove-worktree': { request: { projectPath: string; worktreePath: string }; response: void }
   'git:list-worktrees': { request: { projectPath: string }; response: WorktreeInfo[] }
+  'git:prune-worktrees': { request: { projectPath: string }; response: string[] }
   'git:list-branches': { request: { projectPath: string }; response: string[] }
   'git:commit': {
-    request: { worktreePath: string; message: string; files: string[] }
+    request: { worktreePath: string; message: string; files: string[]; coAuthors?: string[] }
+    response: CommitResult
+  }
+  'git:amend': {
+    request: { worktreePath: string; message?: string; files?: string[]; force?: boolean }
     response: CommitResult
   }
-  'git:diff': { request: { worktreePath: string; filePath?: string }; response: DiffResult }
+  'git:suggest-commit': { request: { worktreePath: string }; response: string }
+  'git:log': { request: { workingDir: string; limit?: number; skip?: number }; response: CommitEntry[] }
+  'git:stash-save': {
+    request: { workingDir: string; message?: string; includeUntracked?: boolean }
+    response: GitStatus
+  }
+  'git:stash-pop': { request: { workingDir: string; index?: number }; response: GitStatus }
+  'git:stash-list': { request: { workingDir: string }; response: StashEntry[] }
+  'git:stage': { request: { workingDir: string; files: string[] }; response: GitStatus }
+  'git:unstage': { request: { workingDir: string; files: string[] }; response: GitStatus }
+  'git:discard': {
+    request: { workingDir: string; files: string[]; recursive?: boolean }
+    response: GitStatus
+  }
+  'git:merge-worktree': {
+    request: { projectPath: string; branch: string; into: string; strategy?: MergeStrategy }
+    response: MergeResult
+  }
+  'git:fetch': { request: { workingDir: string; remote?: string }; response: GitStatus }
+  'git:pull': { request: { workingDir: string; remote?: string; rebase?: boolean }; response: GitStatus }
+  'git:push': {
+    request: { workingDir: string; remote?: string; setUpstream?: boolean }
+    response: PushResult
+  }
+  'git:diff': {
+    request: { worktreePath: string; filePath?: string; includeContent?: boolean }
+    response: DiffResult
+  }
+  'git:create-branch': {
+    request: { workingDir: string; name: string; base?: string; checkout?: boolean; force?: boolean }
+    response: CreateBranchResult
+  }
   'git:rename-branch': {
     request: { worktreePath: string; newBranch: string }
     response: string
@@ -110,10 +250,15 @@ export interface IpcChannels {
   }
 
   // --- Terminal ---
-  'terminal:create': { request: { cwd: string; sessionId: string }; response: string }
+  'terminal:create': {
+    request: { cwd: string; sessionId: string; env?: Record<string, string>; cols?: number; rows?: number }
+    response: string
+  }
   'terminal:write': { request: { terminalId: string; data: string }; response: void }
   'terminal:resize': { request: { terminalId: string; cols: number; rows: number }; response: void }
   'terminal:kill': { request: { terminalId: string }; response: void }
+  'terminal:get-scrollback': { request: { terminalId: string }; response: string }
+  'terminal:list': { request: { sessionId?: string } | void; response: TerminalInfo[] }
 
   // --- Workspaces ---
   'workspace:list': { request: void; response: WorkspaceInfo[] }
@@ -126,6 +271,7 @@ export interface IpcChannels {
       }
     response: WorkspaceInfo
   }
+  'workspace:resume-sessions': { request: { workspaceId: string; max: number }; response: string[] }
   'workspace:select-directory': { request: void; response: string | null }
   'workspace:open-in-vscode': { request: { path: string }; response: void }
   'workspace:open-directory': { request: { path: string }; response: void }
@@ -139,6 +285,13 @@ export interface IpcChannels {
   'settings:get': { request: void; response: AppSettings }
   'settings:set': { request: Partial<AppSettings>; response: void }
   'settings:set-agent': { request: { agentId: string; settings: Record<string, unknown> }; response: void }
+  'mcp:add-server': {
+    request: Omit<McpServerConfig, 'id' | 'enabled'> & Partial<Pick<McpServerConfig, 'id' | 'enabled'>>
+    response: McpServerConfig
+  }
+  'mcp:remove-server': { request: { serverId: string }; response: McpServerConfig }
+  'mcp:update-server': { request: { serverId: string; updates: Partial<McpServerConfig> }; response: McpServerConfig }
+  'mcp:toggle-server': { request: { serverId: string; enabled: boolean }; response: McpServerConfig }
 
   // --- Permission Rules ---
   'permission:save-rule': {
@@ -165,6 +318,22 @@ export interface IpcChannels {
     request: void
     response: { available: boolean; distributions: string[] }
   }
+  'system:get-data-dir': {
+    request: void
+    response: { path: string; isFallback: boolean; error?: string }
+  }
+  'system:set-log-level': { request: { level: LogLevel }; response: void }
+  'system:get-app-log': { request: { maxLines?: number }; response: string[] }
+  'system:open-log-file': { request: void; response: void }
+  'system:prune-downloads': {
+    request: { maxAgeDays?: number }
+    response: { freedBytes: number; removedFiles: number }
+  }
+  'system:export-backup': {
+    request: { path: string; includeCache?: boolean }
+    response: BackupSummary
+  }
+  'system:import-backup': { request: { path: string }; response: BackupSummary }
 
   // --- Window ---
   'window:reload': { request: void; response: void }
@@ -172,6 +341,9 @@ export interface IpcChannels {
   'window:reset-zoom': { request: void; response: void }
   'window:zoom-in': { request: void; response: void }
   'window:zoom-out': { request: void; response: void }
+  'window:get-zoom': { request: void; response: number }
+  'window:set-zoom': { request: { level: number }; response: number }
+  'window:reset-geometry': { request: void; response: void }
   'window:toggle-fullscreen': { request: void; response: void }
   'window:minimize': { request: void; response: void }
   'window:close': { request: void; response: void }
@@ -186,8 +358,29 @@ export interface IpcEvents {
   'session:permission-request': PermissionRequestEvent
   'session:permission-resolved': PermissionResolvedEvent
   'session:hook-progress': WorktreeHookProgressEvent
+  'session:file-access': FileAccessEvent
+  'session:auto-commit': AutoCommitEvent
+  'session:prompt-queued': PromptQueuedEvent
+  'session:prompt-dequeued': PromptDequeuedEvent
+  'session:budget-warning': BudgetWarningEvent
+  'session:messages-changed': MessagesChangedEvent
   'terminal:data': { terminalId: string; data: string }
-  'agent:status-change': { connectionId: string; status: AgentConnection['status']; error?: string }
+  'terminal:exit': { terminalId: string; exitCode: number; signal?: number }
+  'file:search-result': FileSearchResultEvent
+  'file:search-done': FileSearchDoneEvent
+  'agent:status-change': {
+    agentId: string
+    /** Absent when the agent failed before a connection existed (missing-dependency) */
+    connectionId?: string
+    status: AgentConnection['status']
+    error?: string
+    /** Suggested fix when the agent process could not be spawned */
+    hint?: string
+  }
+  'agent:resource-usage': AgentResourceUsage
+  'agent:download-progress': AgentDownloadProgress
+  'workspace:resume-progress': WorkspaceResumeProgressEvent
+  'thread-cache:progress': ThreadCacheProgressEvent
 }
 
 // ============================================================
@@ -199,6 +392,12 @@ export interface ElectronAPI {
     data: IpcChannels[T]['request']
   ): Promise<IpcChannels[T]['response']>
 
+  /** Like invoke, but resolves with the structured error instead of rejecting */
+  invokeResult<T extends keyof IpcChannels>(
+    channel: T,
+    data: IpcChannels[T]['request']
+  ): Promise<IpcResult<IpcChannels[T]['response']>>
+
   on<T extends keyof IpcEvents>(
     channel: T,
     callback: (data: IpcEvents[T]) => void
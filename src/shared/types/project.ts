@@ -13,9 +13,49 @@ export interface FileTreeNode {
   name: string
   path: string
   type: 'file' | 'directory'
+  /** Undefined for directories a lazy read stopped at; load them with file:read-dir */
   children?: FileTreeNode[]
+  /** Set on lazily cut-off directories: whether expanding them will show anything */
+  hasChildren?: boolean
   extension?: string
+  /** Bytes; files only, and only when the tree was read with includeMetadata */
   size?: number
+  /** Last modification time (ISO 8601), only with includeMetadata */
+  modified?: string
+}
+
+export interface FileSearchOptions {
+  /** Treat the query as a regular expression instead of literal text */
+  regex?: boolean
+  caseSensitive?: boolean
+  /** Stop after this many matches (default 500) */
+  maxResults?: number
+  /** Only search files whose root-relative path matches, e.g. "src/**/*.ts" or "*.md" */
+  glob?: string
+}
+
+export interface FileSearchMatch {
+  /** Relative to the search root, '/'-separated */
+  path: string
+  /** 1-based */
+  line: number
+  /** 1-based column of the first match on the line */
+  column: number
+  text: string
+}
+
+/** Streamed while a file:search runs */
+export interface FileSearchResultEvent {
+  searchId: string
+  matches: FileSearchMatch[]
+}
+
+export interface FileSearchDoneEvent {
+  searchId: string
+  matchCount: number
+  /** maxResults was reached before every file was searched */
+  truncated: boolean
+  error?: string
 }
 
 export interface FileChange {
@@ -28,10 +68,21 @@ export interface FileChange {
 
 export interface DiffResult {
   files: FileDiff[]
+  totalAdditions: number
+  totalDeletions: number
 }
 
 export interface FileDiff {
   path: string
-  oldContent: string
-  newContent: string
+  changeKind: FileChange['status']
+  /** Previous path, set for renames */
+  oldPath?: string
+  /** Lines added/removed, counted from the unified diff hunks */
+  additions: number
+  deletions: number
+  /** Git reported "Binary files ... differ"; there are no hunks and no content is loaded */
+  isBinary: boolean
+  /** Full file contents, only present when the diff was requested with includeContent */
+  oldContent?: string
+  newContent?: string
 }
@@ -11,6 +11,8 @@ export interface SessionInfo {
   createdAt: string
   worktreePath?: string
   worktreeBranch?: string
+  /** Project/repository path the session was created for (the main repo for worktree sessions) */
+  sourcePath?: string
   workingDir: string
   status: SessionStatus
   messages: Message[]
@@ -21,6 +23,12 @@ export interface SessionInfo {
   workspaceId: string
   /** ID of the parent session this was forked from. Undefined for root sessions. */
   parentSessionId?: string
+  /** Session id assigned by the agent, needed for session/load after a restart. */
+  acpSessionId?: string
+  /** Integrator-defined key/value tags, e.g. an issue number or PR link. */
+  metadata?: Record<string, unknown>
+  /** Standing instructions sent to the agent ahead of every prompt */
+  systemPrompt?: string
   /** The first prompt to be sent once session creation completes. UI-only field. */
   pendingPrompt?: string
   /** Rich first prompt content (text/images) to send after initialization. UI-only field. */
@@ -33,9 +41,26 @@ export interface SessionInfo {
   initError?: string
   /** Error message from the most recent prompt failure. UI-only field. */
   lastError?: string
-}
-
-export type SessionStatus = 'initializing' | 'creating' | 'active' | 'prompting' | 'idle' | 'cancelled' | 'error'
+  /** Prompts waiting in the main process behind the running one, in delivery order. Not persisted. */
+  queuedPrompts?: QueuedPromptPreview[]
+  /** Latest context-window usage reported by the agent. Not persisted. */
+  usage?: SessionUsage
+  /** Cost accumulated over the thread's lifetime; persisted in the thread manifest */
+  totalCost?: UsageCost
+  /** Agent's last cost report, the baseline for its next one after an app restart; persisted */
+  reportedCost?: ReportedCost
+}
+
+export type SessionStatus =
+  | 'initializing'
+  | 'creating'
+  | 'active'
+  | 'prompting'
+  | 'idle'
+  | 'cancelled'
+  | 'error'
+  /** Persisted thread picked up by session:sync that has no agent connection yet */
+  | 'disconnected'
 
 export type InteractionMode = 'ask' | 'code' | 'plan' | 'act' | string
 
@@ -46,6 +71,8 @@ export interface Message {
   timestamp: string
   toolCalls?: ToolCallInfo[]
   isStreaming?: boolean
+  /** Why the agent ended the turn; set on the last agent message of a prompt */
+  stopReason?: StopReason
 }
 
 // ACP Content Block types (spec-aligned)
@@ -136,8 +163,14 @@ export type ToolCallStatus = 'pending' | 'in_progress' | 'running' | 'completed'
 
 /** Location affected by a tool call (for file-following) */
 export interface ToolCallLocation {
+  /** Absolute path (relative paths are resolved against the session working dir) */
   path: string
   line?: number
+  column?: number
+  endLine?: number
+  endColumn?: number
+  /** Unknown fields from the agent are kept for forward compatibility */
+  [key: string]: unknown
 }
 
 export interface ToolCallInfo {
@@ -167,6 +200,35 @@ export interface PromptResult {
   stopReason: StopReason
 }
 
+/** A prompt currently in flight on some session */
+export interface ActivePrompt {
+  sessionId: string
+  promptId: string
+  startedAt: string
+}
+
+/** A prompt waiting for the running one to finish */
+export interface QueuedPromptPreview {
+  promptId: string
+  /** Start of the prompt's text, for display */
+  preview: string
+  queuedAt: string
+}
+
+/** Emitted when a prompt is queued behind the running one */
+export interface PromptQueuedEvent {
+  sessionId: string
+  prompt: QueuedPromptPreview
+  /** 1-based place in the queue */
+  position: number
+}
+
+/** Emitted when a queued prompt is handed to the agent */
+export interface PromptDequeuedEvent {
+  sessionId: string
+  promptId: string
+}
+
 // Session update events streamed from main to renderer
 export interface SessionUpdateEvent {
   sessionId: string
@@ -231,6 +293,28 @@ export interface PermissionResolvedEvent {
   requestId: string
 }
 
+/** Emitted when the agent reads or writes a file through the client (fs/* methods) */
+export interface FileAccessEvent {
+  sessionId: string
+  path: string
+  operation: 'read' | 'write'
+}
+
+/** Emitted after a prompt's changes were committed because git.autoCommit is on */
+export interface AutoCommitEvent {
+  sessionId: string
+  hash: string
+  message: string
+  branch: string
+}
+
+/** A live terminal and the session that owns it */
+export interface TerminalInfo {
+  terminalId: string
+  sessionId: string
+  cwd: string
+}
+
 export interface CreateSessionRequest {
   connectionId: string
   workingDir: string
@@ -241,6 +325,13 @@ export interface CreateSessionRequest {
   title?: string
   baseBranch?: string
   branchName?: string
+  /**
+   * Without a worktree: switch the working directory to this branch before
+   * starting, creating it from `baseBranch` (or HEAD) when it doesn't exist
+   */
+  checkoutBranch?: string
+  /** Standing instructions sent to the agent ahead of every prompt */
+  systemPrompt?: string
 }
 
 export interface HookStep {
@@ -328,13 +419,51 @@ export interface TokenUsage {
   cached_write_tokens?: number
 }
 
+export interface UsageCost {
+  amount: number
+  currency: string
+}
+
+/** Running cost total an agent reported for one of its sessions */
+export interface ReportedCost extends UsageCost {
+  acpSessionId: string
+}
+
 export interface UsageUpdate {
   used: number
   size: number
-  cost?: {
-    amount: number
-    currency: string
-  }
+  cost?: UsageCost
+}
+
+/** Usage as tracked by the main process for one session */
+export interface SessionUsage extends UsageUpdate {
+  updatedAt: string
+}
+
+/** Emitted when a thread's messages were edited or deleted outside of a prompt */
+export interface MessagesChangedEvent {
+  sessionId: string
+  messages: Message[]
+}
+
+/** Emitted once when a session's context usage crosses the warning threshold */
+export interface BudgetWarningEvent {
+  sessionId: string
+  used: number
+  size: number
+  /** used / size at the time of the warning */
+  ratio: number
+  threshold: number
+}
+
+/** Progress of a thread cache rebuild, emitted per scanned workspace and once when done. */
+export interface ThreadCacheProgressEvent {
+  phase: 'scanning' | 'done'
+  /** Workspace that was just scanned (absent for the final event) */
+  workspaceId?: string
+  completed: number
+  total: number
+  threadCount: number
 }
 
 /**
@@ -349,6 +478,8 @@ export interface PersistedThread {
   createdAt: string
   worktreePath?: string
   worktreeBranch?: string
+  /** Project/repository path the session was created for (the main repo for worktree sessions) */
+  sourcePath?: string
   workingDir: string
   messages: Message[]
   interactionMode?: InteractionMode
@@ -356,4 +487,14 @@ export interface PersistedThread {
   workspaceId: string
   /** ID of the parent session this was forked from. */
   parentSessionId?: string
+  /** Session id assigned by the agent, needed for session/load after a restart. */
+  acpSessionId?: string
+  /** Integrator-defined key/value tags, e.g. an issue number or PR link. */
+  metadata?: Record<string, unknown>
+  /** Cost accumulated over the thread's lifetime */
+  totalCost?: UsageCost
+  /** Agent's last cost report and the agent session it belongs to */
+  reportedCost?: ReportedCost
+  /** Standing instructions sent to the agent ahead of every prompt */
+  systemPrompt?: string
 }
@@ -20,13 +20,47 @@ export interface GeneralSettings {
   summarizationModel?: string
   /** Terminal shell to use (auto-detected by default based on OS) */
   terminalShell?: string
+  /** Window zoom factor restored on startup (1 = 100%, clamped to 0.5-3) */
+  zoomLevel?: number
+  /** Lines of output kept per terminal for repainting after a reload. Defaults to 10000. */
+  terminalScrollbackLines?: number
   /** Whether the user has completed (or skipped) the onboarding wizard */
   completedOnboarding?: boolean
+  /** Reconnect a workspace's most recent threads when it is opened (opt-in) */
+  resumeSessionsOnOpen?: boolean
+  /** Maximum number of threads to reconnect when resumeSessionsOnOpen is enabled */
+  resumeSessionsMax?: number
+  /** Prune stale and partial agent downloads on startup */
+  autoPruneDownloads?: boolean
+  /** How long the registry cache stays fresh, in seconds (0 = always refetch). Defaults to 1 hour. */
+  registryCacheTtlSecs?: number
+  /** Registry URLs merged in order; later sources win on agent id collisions. Defaults to the ACP registry. */
+  registrySources?: string[]
+  /** Require an ed25519 signature on registry payloads (opt-in; needs registryPublicKey) */
+  verifyRegistrySignature?: boolean
+  /** Trusted ed25519 public key for registry signatures: PEM, or the raw 32 bytes as base64/hex */
+  registryPublicKey?: string
+  /** How often live sessions' unsaved messages are flushed to disk, in seconds. Defaults to 5. */
+  autoSaveIntervalSecs?: number
+  /** Automatically send "continue" when the agent stops with max_tokens (opt-in) */
+  autoContinueOnMaxTokens?: boolean
+  /** Maximum automatic continuations per prompt. Defaults to 3. */
+  autoContinueMaxTurns?: number
+  /** Context-window fill ratio (0-1) at which session:budget-warning fires. Defaults to 0.8. */
+  contextWarningThreshold?: number
+  /** Minimum level written to the main-process log. Defaults to info (debug when DEBUG is set). */
+  logLevel?: LogLevel
 }
 
+export type LogLevel = 'debug' | 'info' | 'warn' | 'error'
+
 export interface GitSettings {
   enableWorktrees: boolean
   worktreeBaseDir?: string
+  /** Worktree directory name; supports {session}, {workspace}, {date}, {title-slug} */
+  worktreeDirTemplate?: string
+  /** Worktree branch name; same placeholders as worktreeDirTemplate */
+  worktreeBranchTemplate?: string
   autoCommit: boolean
   commitPrefix: string
   cleanupWorktreesOnClose: boolean
@@ -45,6 +79,8 @@ export interface AgentSettings {
   customEnv?: Record<string, string>
   runInWsl?: boolean
   wslDistribution?: string
+  /** Ids of registry extensions enabled for this agent */
+  extensions?: string[]
 }
 
 export interface McpSettings {
@@ -79,3 +115,22 @@ export const DEFAULT_SETTINGS: AppSettings = {
     servers: []
   }
 }
+
+// ============================================================
+// Backup
+// ============================================================
+
+/** Result of exporting or importing a backup archive */
+export interface BackupSummary {
+  archivePath: string
+  sizeBytes: number
+  createdAt: string
+  /** Number of data files (settings, installed agents, workspaces, ...) */
+  dataFiles: number
+  includesCache: boolean
+  /** Number of .agent/threads folders in the archive */
+  threadFolders: number
+  threadCount: number
+  /** Restored data files are staged and only take effect after a restart */
+  restartRequired?: boolean
+}
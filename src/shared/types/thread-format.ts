@@ -81,6 +81,8 @@ export interface ThreadManifest {
     worktree?: {
       path: string
       branch: string
+      /** Repository the worktree was created from */
+      sourcePath?: string
     }
   }
   stats: {
@@ -133,5 +135,5 @@ export interface StoredToolCall {
     oldText: string
     newText: string
   }
-  locations?: Array<{ path: string; line?: number }>
+  locations?: Array<{ path: string; line?: number; column?: number; endLine?: number; endColumn?: number }>
 }
@@ -17,3 +17,12 @@ export interface WorkspaceInfo {
   createdAt: string
   lastAccessedAt: string
 }
+
+/** Progress of a `workspace:resume-sessions` run, emitted once per thread. */
+export interface WorkspaceResumeProgressEvent {
+  workspaceId: string
+  sessionId: string
+  completed: number
+  total: number
+  error?: string
+}
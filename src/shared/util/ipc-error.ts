@@ -0,0 +1,32 @@
+import type { SerializedAppError } from '../types/errors'
+
+/**
+ * Electron only carries an error's message across invoke(), so the main
+ * process sends structured errors as this prefix followed by JSON.
+ */
+export const APP_ERROR_PREFIX = 'APP_ERROR:'
+
+export function encodeAppErrorMessage(error: SerializedAppError): string {
+  return `${APP_ERROR_PREFIX}${JSON.stringify(error)}`
+}
+
+/**
+ * Recover the structured error from a rejected invoke(). Anything that was
+ * not encoded by the main process becomes an INTERNAL error with its message.
+ */
+export function decodeIpcError(error: unknown): SerializedAppError {
+  const message = error instanceof Error ? error.message : String(error)
+  const start = message.indexOf(APP_ERROR_PREFIX)
+  if (start !== -1) {
+    try {
+      const parsed = JSON.parse(message.slice(start + APP_ERROR_PREFIX.length)) as SerializedAppError
+      if (parsed && typeof parsed.code === 'string' && typeof parsed.message === 'string') return parsed
+    } catch {
+      // Fall through to the plain message
+    }
+  }
+  return {
+    code: 'INTERNAL',
+    message: message.replace(/^Error invoking remote method '[^']+':\s*/i, '').replace(/^Error:\s*/i, '')
+  }
+}
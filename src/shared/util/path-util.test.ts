@@ -0,0 +1,43 @@
+import { describe, expect, it } from 'vitest'
+import { isSamePath, normalizePath } from './path-util'
+
+describe('normalizePath', () => {
+  it.each([
+    ['C:\\a\\', 'c:/a'],
+    ['c:/a', 'c:/a'],
+    ['/a/b/', '/a/b'],
+    ['/a//b', '/a/b'],
+    ['\\\\srv\\share\\', '//srv/share'],
+    ['//srv/share', '//srv/share'],
+    ['/', '/'],
+    ['C:\\', 'c:/'],
+    ['C:', 'c:/'],
+    ['', '']
+  ])('normalizes %j to %j', (input, expected) => {
+    expect(normalizePath(input)).toBe(expected)
+  })
+
+  it('keeps the case of Unix paths', () => {
+    expect(normalizePath('/Users/Me/')).toBe('/Users/Me')
+  })
+})
+
+describe('isSamePath', () => {
+  it('matches paths that differ only in separators, trailing slashes or drive case', () => {
+    expect(isSamePath('C:\\a\\', 'c:/a')).toBe(true)
+    expect(isSamePath('/a/b/', '/a/b')).toBe(true)
+    expect(isSamePath('\\\\srv\\share\\', '//SRV/share')).toBe(true)
+    expect(isSamePath('C:\\', 'c:/')).toBe(true)
+  })
+
+  it('tells different paths apart', () => {
+    expect(isSamePath('/a/b', '/a/B')).toBe(false)
+    expect(isSamePath('/a', '/a/b')).toBe(false)
+    expect(isSamePath('//srv/share', '/srv/share')).toBe(false)
+  })
+
+  it('never matches a missing path', () => {
+    expect(isSamePath(undefined, '/a')).toBe(false)
+    expect(isSamePath('', '')).toBe(false)
+  })
+})
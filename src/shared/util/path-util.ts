@@ -0,0 +1,27 @@
+/**
+ * Normalize a filesystem path for comparison or use as a map key, so the same
+ * directory always yields the same string regardless of separator style or
+ * trailing slashes. Windows paths (drive letter or UNC) compare case-insensitively.
+ * Pure string logic so it can be used from both main and renderer.
+ */
+export function normalizePath(p: string): string {
+  if (!p) return p
+  const isUnc = /^[\\/]{2}[^\\/]/.test(p)
+  let normalized = p.replace(/\\/g, '/').replace(/\/{2,}/g, '/')
+  if (isUnc) normalized = '/' + normalized
+
+  // Strip trailing slashes, but keep roots like "/" and "C:/"
+  while (normalized.length > 1 && normalized.endsWith('/') && !/^[a-zA-Z]:\/$/.test(normalized)) {
+    normalized = normalized.slice(0, -1)
+  }
+  if (/^[a-zA-Z]:$/.test(normalized)) normalized += '/'
+
+  const isWindows = isUnc || /^[a-zA-Z]:/.test(normalized)
+  return isWindows ? normalized.toLowerCase() : normalized
+}
+
+/** True if both paths refer to the same location after normalization */
+export function isSamePath(a: string | undefined, b: string | undefined): boolean {
+  if (!a || !b) return false
+  return normalizePath(a) === normalizePath(b)
+}
@@ -1,4 +1,4 @@
-import type { ContentBlock, Message, SessionUpdate, ToolCallStatus } from '@shared/types/session'
+import type { ContentBlock, Message, SessionUpdate, StopReason, ToolCallStatus } from '@shared/types/session'
 import { v4 as uuid } from 'uuid'
 
 /**
@@ -150,6 +150,30 @@ export function applyUpdateToMessages(messages: Message[], update: SessionUpdate
   }
 }
 
+/**
+ * Close out a finished turn: stop streaming, settle tool calls the agent left
+ * open (as failed when the turn was cancelled, so partial work isn't recorded
+ * as done), and record the stop reason on the turn's final agent message.
+ */
+export function applyStopReason(messages: Message[], stopReason: StopReason): Message[] {
+  const settledStatus: ToolCallStatus = stopReason === 'cancelled' ? 'failed' : 'completed'
+  const lastIndex = messages.length - 1
+  return messages.map((m, i) => {
+    const hasOpenToolCalls = m.toolCalls?.some((tc) => isOpenToolCallStatus(tc.status)) ?? false
+    const isTurnEnd = i === lastIndex && m.role === 'agent'
+    if (!m.isStreaming && !hasOpenToolCalls && !isTurnEnd) return m
+    return {
+      ...m,
+      isStreaming: false,
+      ...(isTurnEnd ? { stopReason } : {}),
+      toolCalls: m.toolCalls?.map((tc) => ({
+        ...tc,
+        status: isOpenToolCallStatus(tc.status) ? settledStatus : tc.status
+      }))
+    }
+  })
+}
+
 function findLastIndex<T>(arr: T[], pred: (item: T) => boolean): number {
   for (let i = arr.length - 1; i >= 0; i--) {
     if (pred(arr[i])) return i
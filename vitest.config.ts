@@ -0,0 +1,19 @@
+import { defineConfig } from 'vitest/config'
+import { resolve } from 'path'
+
+export default defineConfig({
+  resolve: {
+    alias: {
+      '@shared': resolve('src/shared'),
+      // Main-process services run under plain Node in tests
+      electron: resolve('src/main/test/electron.ts'),
+      'electron-store': resolve('src/main/test/electron-store.ts')
+    }
+  },
+  test: {
+    environment: 'node',
+    include: ['src/**/*.test.ts'],
+    // Tests spawn scripts/mock-acp-agent.mjs as a real child process
+    testTimeout: 15000
+  }
+})